@@ -11,6 +11,8 @@ pub struct CommandPart {
 pub struct Command {
     pub parts: Vec<CommandPart>,
     query: bool,
+    /// The canonical name of the command as it was defined.
+    pub name: String,
 }
 
 pub type CommandPath = Vec<String>;
@@ -19,6 +21,7 @@ impl TryFrom<&str> for Command {
     type Error = Box<dyn std::error::Error>;
 
     fn try_from(mut value: &str) -> Result<Self, Self::Error> {
+        let name = value.trim().to_string();
         let mut parts = Vec::new();
         let mut query = false;
 
@@ -49,7 +52,7 @@ impl TryFrom<&str> for Command {
             });
         }
 
-        Ok(Command { parts, query })
+        Ok(Command { parts, query, name })
     }
 }
 
@@ -58,6 +61,33 @@ impl Command {
         self.query
     }
 
+    /// Returns whether this is a common command, e.g. `*RST`.
+    pub fn is_common(&self) -> bool {
+        self.name.starts_with('*')
+    }
+
+    /// Returns the long form of the header starting at the root, e.g.
+    /// `:SOURCE:VOLTAGE`.
+    pub fn header(&self) -> String {
+        self.parts
+            .iter()
+            .map(|part| format!(":{}", part.long))
+            .collect()
+    }
+
+    /// Prepends the parts of the `prefix` to this command.
+    pub fn with_prefix(mut self, prefix: &Command) -> Command {
+        let mut parts = prefix.parts.clone();
+        parts.append(&mut self.parts);
+        self.parts = parts;
+        self.name = format!(
+            "{}:{}",
+            prefix.name.trim_end_matches(':'),
+            self.name.trim_start_matches(':')
+        );
+        self
+    }
+
     pub fn paths(&self) -> Vec<CommandPath> {
         let mut paths: Vec<CommandPath> = vec![vec![]];
 
@@ -85,6 +115,54 @@ impl Command {
 
         paths
     }
+
+    /// Describes the form in which a path is derived from this command, e.g.
+    /// `short form` or `long form with optional parts omitted`.
+    ///
+    /// Returns [None] if it is not a path of this command.
+    pub fn path_form(&self, path: &[String]) -> Option<String> {
+        let (short, long, omitted) = path_forms(&self.parts, path)?;
+        let form = match (short, long) {
+            (true, true) => "mixed short and long form",
+            (true, false) => "short form",
+            (false, _) => "long form",
+        };
+        if omitted {
+            Some(format!("{form} with optional parts omitted"))
+        }
+        else {
+            Some(form.to_string())
+        }
+    }
+}
+
+/// Matches a path against the parts of a command and returns whether it
+/// contains short forms, long forms that differ from the short forms and
+/// omitted optional parts.
+fn path_forms(parts: &[CommandPart], path: &[String]) -> Option<(bool, bool, bool)> {
+    let Some((part, rest)) = parts.split_first()
+    else {
+        return path.is_empty().then_some((false, false, false));
+    };
+
+    let first = path.first();
+    let long = (first == Some(&part.long))
+        .then(|| path_forms(rest, &path[1..]))
+        .flatten()
+        .map(|(short, long, omitted)| (short, long || part.short != part.long, omitted));
+    let short = || {
+        (first == Some(&part.short))
+            .then(|| path_forms(rest, &path[1..]))
+            .flatten()
+            .map(|(_, long, omitted)| (true, long, omitted))
+    };
+    let omitted = || {
+        part.optional
+            .then(|| path_forms(rest, path))
+            .flatten()
+            .map(|(short, long, _)| (short, long, true))
+    };
+    long.or_else(short).or_else(omitted)
 }
 
 #[test]
@@ -99,3 +177,52 @@ pub fn test_all_paths() {
     assert!(paths.iter().any(|p| p.as_ref() == vec!["TIM"]));
     assert!(paths.iter().any(|p| p.as_ref() == vec!["TIME"]));
 }
+
+#[test]
+pub fn test_header() {
+    let cmd = Command::try_from("[SOURce]:VOLTage?").unwrap();
+    assert_eq!(cmd.header(), ":SOURCE:VOLTAGE");
+}
+
+#[test]
+pub fn test_prefix() {
+    let prefix = Command::try_from("[SENSe]:VOLTage").unwrap();
+    let cmd = Command::try_from("RANGe?").unwrap().with_prefix(&prefix);
+    assert_eq!(cmd.name, "[SENSe]:VOLTage:RANGe?");
+    assert!(cmd.is_query());
+
+    let paths = cmd.paths();
+    assert!(paths
+        .iter()
+        .any(|p| p.as_ref() == vec!["SENS", "VOLT", "RANG"]));
+    assert!(paths.iter().any(|p| p.as_ref() == vec!["VOLTAGE", "RANGE"]));
+}
+
+#[test]
+pub fn test_path_form() {
+    let cmd = Command::try_from("[SYSTem]:TEST").unwrap();
+    let path = |path: &[&str]| {
+        path.iter()
+            .map(|part| part.to_string())
+            .collect::<CommandPath>()
+    };
+    assert_eq!(
+        cmd.path_form(&path(&["SYSTEM", "TEST"])).unwrap(),
+        "long form"
+    );
+    assert_eq!(
+        cmd.path_form(&path(&["SYST", "TEST"])).unwrap(),
+        "short form"
+    );
+    assert_eq!(
+        cmd.path_form(&path(&["TEST"])).unwrap(),
+        "long form with optional parts omitted"
+    );
+    assert_eq!(cmd.path_form(&path(&["SYST"])), None);
+
+    let cmd = Command::try_from("SOURce:VOLTage").unwrap();
+    assert_eq!(
+        cmd.path_form(&path(&["SOUR", "VOLTAGE"])).unwrap(),
+        "mixed short and long form"
+    );
+}
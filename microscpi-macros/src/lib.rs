@@ -1,11 +1,12 @@
 use std::rc::Rc;
 
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::parse::{Parse, ParseStream, Parser};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::token::Comma;
-use syn::{parse_macro_input, Attribute, Expr, Ident, ImplItemFn, ItemImpl, Lit, Path, Type};
+use syn::{parse_macro_input, Attribute, Expr, Ident, ImplItemFn, ItemImpl, Lit, Meta, Path, Type};
 
 mod command;
 mod tree;
@@ -13,42 +14,725 @@ mod tree;
 use command::Command;
 use tree::Tree;
 
+/// The maximum number of arguments of a command, which has to match
+/// `microscpi::MAX_ARGS`. The generated interfaces assert that their handlers
+/// don't take more arguments than the parser can hold.
+const MAX_ARGS: usize = 10;
+
 enum CommandHandler {
     UserFunction(Ident),
     StandardFunction(&'static str),
+    /// A method of a trait marked with `#[command_set]`, given by the path of
+    /// the trait and the name of the method.
+    SetMethod(Path, Ident),
 }
 
 #[derive(Default)]
 struct Config {
     pub error_commands: bool,
+    pub dropped_errors: bool,
     pub standard_commands: bool,
+    pub identity_commands: bool,
+    pub reset_commands: bool,
+    pub self_test_commands: bool,
+    pub trigger_commands: bool,
+    pub state_commands: bool,
+    pub clock_commands: bool,
+    pub remote_commands: bool,
+    pub macro_commands: bool,
+    pub learn: bool,
+    pub help: bool,
+    pub format_commands: bool,
+    pub status_commands: bool,
+    pub operation_status_commands: bool,
+    pub questionable_status_commands: bool,
+    pub statistics_commands: bool,
+    pub context: Option<Type>,
+    pub on_device_clear: Option<Ident>,
+    pub protection_unlocked: Option<Ident>,
+    pub timeout: Option<Ident>,
+    /// The path prefix of all commands defined in the `impl` block.
+    pub prefix: Option<Command>,
+    /// The `impl` blocks marked with `#[interface_part]`, whose commands are
+    /// merged into the command tree.
+    pub parts: Vec<Path>,
+    /// The traits marked with `#[command_set]`, whose commands are registered.
+    pub command_sets: Vec<Path>,
 }
 
 struct CommandDefinition {
     pub id: usize,
+    /// The span of the definition of the command, at which its errors are
+    /// reported.
+    pub span: proc_macro2::Span,
     pub command: Command,
     pub handler: CommandHandler,
     pub args: Vec<Type>,
     pub future: bool,
+    /// Whether the handler takes the interface context as first parameter.
+    pub context: bool,
+    /// Whether the handler takes the response writer as last parameter.
+    pub writer: bool,
+    /// The time after which the execution of the handler is aborted.
+    pub timeout_ms: Option<u32>,
+    /// Whether the command is part of the `*LRN?` response.
+    pub learn: bool,
+    /// The first line of the documentation of the handler.
+    pub doc: Option<String>,
+    /// Whether the command is rejected while the device is in local
+    /// operation.
+    pub local_locked: bool,
+    /// Whether the command is rejected while the protection of the interface
+    /// is locked.
+    pub protected: bool,
+    /// Further paths of the command, which share its handler and id.
+    pub aliases: Vec<Command>,
+    /// The ranges of the arguments, given by their index, that are checked
+    /// before the handler is called.
+    pub ranges: Vec<(usize, Expr)>,
+    /// The names of the parameters of the handler receiving the arguments, if
+    /// they are bound to an identifier.
+    pub arg_names: Vec<Option<String>>,
+    /// The `cfg` attributes of the handler, which are applied to the generated
+    /// code referring to it.
+    pub cfg: Vec<Attribute>,
+    /// Whether the handler returns a `Result`, whose error is propagated.
+    pub fallible: bool,
+    /// The return type of the handler.
+    pub output: syn::ReturnType,
 }
 
 impl CommandDefinition {
-    fn args(&self) -> Punctuated<Expr, Comma> {
-        self.args
+    /// Returns the definition of a command that is implemented by a method of
+    /// a trait of the crate, e.g. `StandardCommands::system_version`. The
+    /// handler is synchronous and returns a `Result`, other handlers change
+    /// the fields with the struct update syntax.
+    fn standard(id: usize, cmd: &str, handler: &'static str, args: Vec<Type>) -> CommandDefinition {
+        CommandDefinition {
+            id,
+            span: proc_macro2::Span::call_site(),
+            command: Command::try_from(cmd).unwrap(),
+            handler: CommandHandler::StandardFunction(handler),
+            args,
+            future: false,
+            context: false,
+            writer: false,
+            timeout_ms: None,
+            learn: false,
+            doc: None,
+            local_locked: false,
+            protected: false,
+            aliases: Vec::new(),
+            ranges: Vec::new(),
+            arg_names: Vec::new(),
+            cfg: Vec::new(),
+            fallible: true,
+            output: syn::ReturnType::Default,
+        }
+    }
+}
+
+/// An `impl` block marked with `#[interface_part]`, whose commands are merged
+/// into the command tree of an interface.
+struct PartDefinition {
+    /// The name of the part, from which the names of the methods generated for
+    /// it are derived.
+    pub name: Ident,
+    /// The path prefix of the commands.
+    pub prefix: Option<Command>,
+    /// The `impl` block without the bodies of the functions.
+    pub item: ItemImpl,
+}
+
+impl PartDefinition {
+    /// Parses the arguments of the `interface_part` attribute, i.e. the name
+    /// of the part and an optional prefix.
+    fn parse_attr(attr: proc_macro2::TokenStream) -> syn::Result<(Ident, Option<Command>)> {
+        let span = attr.span();
+        let mut name = None;
+        let mut prefix = None;
+        for meta in Punctuated::<Meta, Comma>::parse_terminated.parse2(attr)? {
+            match meta {
+                Meta::Path(path) if name.is_none() && path.get_ident().is_some() => {
+                    name = path.get_ident().cloned();
+                }
+                Meta::NameValue(name_value) if name_value.path.is_ident("prefix") => {
+                    prefix = Some(parse_prefix(&name_value.value)?);
+                }
+                meta => {
+                    return Err(syn::Error::new(
+                        meta.span(),
+                        "Unknown interface part option",
+                    ))
+                }
+            }
+        }
+        let name =
+            name.ok_or_else(|| syn::Error::new(span, "Missing name of the interface part"))?;
+        Ok((name, prefix))
+    }
+}
+
+impl Parse for PartDefinition {
+    /// Parses the metadata of a part, i.e. the arguments of its attribute in
+    /// parentheses followed by the `impl` block.
+    fn parse(input: ParseStream) -> syn::Result<PartDefinition> {
+        let attr;
+        syn::parenthesized!(attr in input);
+        let (name, prefix) = PartDefinition::parse_attr(attr.parse()?)?;
+        Ok(PartDefinition {
+            name,
+            prefix,
+            item: input.parse()?,
+        })
+    }
+}
+
+/// The metadata appended to the input of `__interface_parts` by the macro of a
+/// part or a command set.
+enum Metadata {
+    Part(PartDefinition),
+    /// The functions of a command set in an `impl` block.
+    CommandSet(ItemImpl),
+}
+
+impl Parse for Metadata {
+    fn parse(input: ParseStream) -> syn::Result<Metadata> {
+        if input.peek(syn::token::Paren) {
+            return Ok(Metadata::Part(input.parse()?));
+        }
+        let keyword: Ident = input.parse()?;
+        if keyword != "command_set" {
+            return Err(syn::Error::new(
+                keyword.span(),
+                "Invalid interface metadata",
+            ));
+        }
+        Ok(Metadata::CommandSet(input.parse()?))
+    }
+}
+
+/// Parses the arguments of the `interface` attribute.
+///
+/// The `use` option is a keyword, which is not accepted as the path of a
+/// [Meta] by `syn`, so it is converted to a list with an identifier.
+fn parse_interface_attrs(input: ParseStream) -> syn::Result<Punctuated<Meta, Comma>> {
+    let mut attrs = Punctuated::new();
+    while !input.is_empty() {
+        if input.peek(syn::Token![use]) {
+            let keyword: syn::Token![use] = input.parse()?;
+            let content;
+            let paren = syn::parenthesized!(content in input);
+            attrs.push_value(Meta::List(syn::MetaList {
+                path: Ident::new("use", keyword.span).into(),
+                delimiter: syn::MacroDelimiter::Paren(paren),
+                tokens: content.parse()?,
+            }));
+        }
+        else {
+            attrs.push_value(input.parse()?);
+        }
+        if input.is_empty() {
+            break;
+        }
+        attrs.push_punct(input.parse()?);
+    }
+    Ok(attrs)
+}
+
+/// Parses the path prefix of the commands of an `impl` block.
+fn parse_prefix(value: &Expr) -> syn::Result<Command> {
+    let prefix = match value {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Str(prefix),
+            ..
+        }) => Command::try_from(prefix.value().as_str())
+            .ok()
+            .filter(|prefix| !prefix.is_query() && !prefix.is_common()),
+        _ => None,
+    };
+    prefix.ok_or_else(|| syn::Error::new(value.span(), "Invalid SCPI path prefix"))
+}
+
+/// A nested interface that is mounted below a path of the command tree.
+struct SubtreeDefinition {
+    pub path: Command,
+    /// The method returning a mutable reference to the nested interface.
+    pub func: Ident,
+    /// The type of the nested interface.
+    pub ty: Type,
+}
+
+impl SubtreeDefinition {
+    /// Parses a `#[scpi(subtree = "...")]` attribute of a function returning
+    /// `&mut T`.
+    fn parse(func: &ImplItemFn, path: &str) -> syn::Result<SubtreeDefinition> {
+        let ty = match &func.sig.output {
+            syn::ReturnType::Type(_, ty) => match ty.as_ref() {
+                Type::Reference(reference) if reference.mutability.is_some() => {
+                    Some(*reference.elem.clone())
+                }
+                _ => None,
+            },
+            syn::ReturnType::Default => None,
+        };
+
+        let ty = ty.ok_or_else(|| {
+            syn::Error::new(
+                func.sig.span(),
+                "A subtree function must return a mutable reference to an interface",
+            )
+        })?;
+
+        let path = Command::try_from(path)
+            .ok()
+            .filter(|path| !path.is_query() && !path.parts.is_empty())
+            .ok_or_else(|| syn::Error::new(func.sig.span(), "Invalid SCPI subtree path"))?;
+
+        Ok(SubtreeDefinition {
+            path,
+            func: func.sig.ident.clone(),
+            ty,
+        })
+    }
+}
+
+/// Extracts the subtree path from a `scpi` attribute, if present.
+fn subtree_path(attr: &Attribute) -> syn::Result<Option<String>> {
+    let mut subtree = None;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("subtree") {
+            if let Lit::Str(path) = meta.value()?.parse()? {
+                subtree = Some(path.value());
+                Ok(())
+            }
+            else {
+                Err(meta.error("Invalid SCPI subtree path"))
+            }
+        }
+        else {
+            // Skip the values of the other options.
+            if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            }
+            else if meta.input.peek(syn::token::Paren) {
+                meta.parse_nested_meta(|meta| {
+                    meta.value()?.parse::<Expr>()?;
+                    Ok(())
+                })?;
+            }
+            Ok(())
+        }
+    })?;
+    Ok(subtree)
+}
+
+/// Checks whether the type of an argument is a mutable reference to an `impl`
+/// type, i.e. the response writer.
+fn is_writer(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => {
+            reference.mutability.is_some() && matches!(*reference.elem, Type::ImplTrait(_))
+        }
+        _ => false,
+    }
+}
+
+/// Checks whether the type of an argument is an `Option`.
+fn is_optional(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Checks whether a return type is a `Result`, judged by its name.
+fn is_result(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
+
+/// Returns the generic type arguments of the last segment of a type path, e.g.
+/// `T` and `E` of `Result<T, E>`.
+fn type_args(ty: &Type) -> Vec<&Type> {
+    let Type::Path(path) = ty
+    else {
+        return Vec::new();
+    };
+    match path.path.segments.last().map(|segment| &segment.arguments) {
+        Some(syn::PathArguments::AngleBracketed(generics)) => generics
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Checks whether a type refers to `Self` or one of the given generic
+/// parameters.
+fn mentions_generics(ty: &Type, params: &[Ident]) -> bool {
+    fn mentions(tokens: proc_macro2::TokenStream, params: &[Ident]) -> bool {
+        tokens.into_iter().any(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) => ident == "Self" || params.contains(&ident),
+            proc_macro2::TokenTree::Group(group) => mentions(group.stream(), params),
+            _ => false,
+        })
+    }
+    mentions(quote!(#ty), params)
+}
+
+/// Returns the names of the type and const parameters of an `impl` block.
+fn generic_params(generics: &syn::Generics) -> Vec<Ident> {
+    generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Type(param) => Some(param.ident.clone()),
+            syn::GenericParam::Const(param) => Some(param.ident.clone()),
+            syn::GenericParam::Lifetime(_) => None,
+        })
+        .collect()
+}
+
+/// Adds the bounds of the commands for the types depending on the generic
+/// parameters to the where-clause, unless they are already present.
+fn add_generic_bounds<'a>(
+    where_clause: &mut syn::WhereClause,
+    commands: impl IntoIterator<Item = &'a Rc<CommandDefinition>>, params: &[Ident],
+) {
+    for bound in commands
+        .into_iter()
+        .flat_map(|cmd| cmd.generic_bounds(params))
+    {
+        let tokens = bound.to_token_stream().to_string();
+        if !where_clause
+            .predicates
             .iter()
-            .enumerate()
-            .map(|(id, _arg)| -> Expr {
-                syn::parse_quote! {
-                    args.get(#id).unwrap().try_into()?
+            .any(|other| other.to_token_stream().to_string() == tokens)
+        {
+            where_clause.predicates.push(bound);
+        }
+    }
+}
+
+/// Replaces the lifetimes of a type, except `'static`, by the given lifetime.
+fn with_lifetime(ty: &Type, lifetime: &syn::Lifetime) -> Type {
+    fn replace(
+        tokens: proc_macro2::TokenStream, lifetime: &syn::Lifetime,
+    ) -> proc_macro2::TokenStream {
+        let mut output = proc_macro2::TokenStream::new();
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            match token {
+                proc_macro2::TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                    match tokens.next() {
+                        Some(proc_macro2::TokenTree::Ident(ident)) if ident != "static" => {
+                            lifetime.to_tokens(&mut output);
+                        }
+                        next => output.extend(
+                            [proc_macro2::TokenTree::Punct(punct)]
+                                .into_iter()
+                                .chain(next),
+                        ),
+                    }
+                }
+                proc_macro2::TokenTree::Group(group) => {
+                    let mut replaced = proc_macro2::Group::new(
+                        group.delimiter(),
+                        replace(group.stream(), lifetime),
+                    );
+                    replaced.set_span(group.span());
+                    output.extend([proc_macro2::TokenTree::Group(replaced)]);
+                }
+                token => output.extend([token]),
+            }
+        }
+        output
+    }
+    syn::parse2(replace(quote!(#ty), lifetime)).unwrap()
+}
+
+/// Returns the name of the function of a command set that converts the
+/// argument `id` of a method.
+fn set_conversion(func: &Ident, id: usize) -> Ident {
+    format_ident!("__scpi_{}_arg_{}", func, id)
+}
+
+/// Returns the first non-empty line of the documentation of an item.
+fn first_doc_line(attrs: &[Attribute]) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(syn::MetaNameValue {
+                value:
+                    Expr::Lit(syn::ExprLit {
+                        lit: Lit::Str(doc), ..
+                    }),
+                ..
+            }) => Some(doc.value().trim().to_string()),
+            _ => None,
+        })
+        .find(|line| !line.is_empty())
+}
+
+/// Returns the syntax of an argument, e.g. `<f64>` or `[<u8>]` if it is
+/// optional.
+fn arg_syntax(ty: &Type) -> String {
+    match ty {
+        Type::Reference(reference) => arg_syntax(&reference.elem),
+        Type::Path(path) => {
+            let Some(segment) = path.path.segments.last()
+            else {
+                return String::from("<>");
+            };
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(generics) if segment.ident == "Option" => {
+                    match generics.args.first() {
+                        Some(syn::GenericArgument::Type(inner)) => {
+                            format!("[{}]", arg_syntax(inner))
+                        }
+                        _ => String::from("[<>]"),
+                    }
+                }
+                _ => format!("<{}>", segment.ident),
+            }
+        }
+        _ => format!("<{}>", quote!(#ty).to_string().replace(' ', "")),
+    }
+}
+
+impl CommandDefinition {
+    /// Returns the syntax of the command followed by the first line of its
+    /// documentation, e.g. `SOURce:VOLTage <f64> - Sets the output voltage.`.
+    /// The range of an argument follows its type, e.g. `<f64 0.0..=30.0>`.
+    fn help(&self) -> String {
+        let mut help = self.command.name.clone();
+        for (index, arg) in self.args.iter().enumerate() {
+            help.push(if index == 0 { ' ' } else { ',' });
+            let mut syntax = arg_syntax(arg);
+            if let Some((_, range)) = self.ranges.iter().find(|(arg, _)| *arg == index) {
+                let range = quote!(#range).to_string().replace(' ', "");
+                syntax.insert_str(
+                    syntax.rfind('>').unwrap_or(syntax.len()),
+                    &format!(" {range}"),
+                );
+            }
+            help.push_str(&syntax);
+        }
+        if self.protected {
+            help.push_str(" (protected)");
+        }
+        for (index, alias) in self.aliases.iter().enumerate() {
+            help.push_str(if index == 0 { " (alias " } else { ", " });
+            help.push_str(&alias.name);
+        }
+        if !self.aliases.is_empty() {
+            help.push(')');
+        }
+        if let Some(doc) = &self.doc {
+            help.push_str(" - ");
+            help.push_str(doc);
+        }
+        help
+    }
+
+    /// Returns the bounds, which are required to convert the arguments and to
+    /// write the response of the handler, for the types that depend on the
+    /// generic parameters of the interface.
+    fn generic_bounds(&self, params: &[Ident]) -> Vec<syn::WherePredicate> {
+        let mut bounds: Vec<syn::WherePredicate> = Vec::new();
+        for ty in &self.args {
+            let ty = if is_optional(ty) {
+                type_args(ty).first().copied().unwrap_or(ty)
+            }
+            else {
+                ty
+            };
+            if mentions_generics(ty, params) {
+                bounds.push(syn::parse_quote! {
+                    for<'r, 'v> &'r ::microscpi::Value<'v>:
+                        ::core::convert::TryInto<#ty, Error = ::microscpi::Error>
+                });
+            }
+        }
+
+        if let syn::ReturnType::Type(_, ty) = &self.output {
+            let (value, error) = if self.fallible {
+                let args = type_args(ty);
+                (args.first().copied(), args.get(1).copied())
+            }
+            else {
+                (Some(ty.as_ref()), None)
+            };
+            // Handlers taking the response writer write the response themselves.
+            if let Some(value) = value.filter(|ty| !self.writer && mentions_generics(ty, params)) {
+                bounds.push(syn::parse_quote!(#value: ::microscpi::Response));
+            }
+            if let Some(error) = error.filter(|ty| mentions_generics(ty, params)) {
+                bounds.push(syn::parse_quote! {
+                    ::microscpi::Error: ::core::convert::From<#error>
+                });
+            }
+        }
+        bounds
+    }
+
+    /// The number of arguments that are not optional.
+    fn required_args(&self) -> usize {
+        self.args.iter().take_while(|arg| !is_optional(arg)).count()
+    }
+
+    /// Returns the device-dependent info identifying the parameter `id` in an
+    /// error, e.g. `parameter 1 (voltage)`.
+    fn arg_info(&self, id: usize) -> String {
+        match self.arg_names.get(id).cloned().flatten() {
+            Some(name) => format!("parameter {} ({name})", id + 1),
+            None => format!("parameter {}", id + 1),
+        }
+    }
+
+    /// Returns the conversion of the argument `id` from its value. A failed
+    /// conversion is reported with the info of the parameter.
+    ///
+    /// The target type is stated explicitly, as the bounds added for generic
+    /// argument types would otherwise take precedence in the inference. The
+    /// argument types of a command set may not be in scope, so its arguments
+    /// are converted by the functions generated for the trait.
+    fn convert_arg(&self, id: usize) -> Expr {
+        let info = self.arg_info(id);
+        let with_info = quote! {
+            map_err(|error| ::microscpi::Error::from(error).with_info(#info))
+        };
+        let ty = &self.args[id];
+        let value_ty = if is_optional(ty) {
+            type_args(ty).first().copied().unwrap_or(ty)
+        }
+        else {
+            ty
+        };
+        let convert = match &self.handler {
+            CommandHandler::SetMethod(set, func) => {
+                let conversion = set_conversion(func, id);
+                quote! { <Self as #set>::#conversion }
+            }
+            _ => quote! { ::core::convert::TryInto::<#value_ty>::try_into },
+        };
+        if is_optional(ty) {
+            syn::parse_quote! {
+                args.get(#id)
+                    .map(#convert)
+                    .transpose()
+                    .#with_info?
+            }
+        }
+        else {
+            syn::parse_quote! {
+                #convert(args.get(#id).unwrap()).#with_info?
+            }
+        }
+    }
+
+    /// Returns the arguments of the handler call. Arguments with a range are
+    /// converted in advance by [CommandDefinition::check_ranges] and passed by
+    /// their variable.
+    ///
+    /// With `byte_order`, the byte order of the interface is applied to the
+    /// arguments implementing `WithByteOrder`, all others are passed unchanged.
+    fn args(&self, byte_order: bool) -> Punctuated<Expr, Comma> {
+        (0..self.args.len())
+            .map(|id| -> Expr {
+                if self.ranges.iter().any(|(arg, _)| *arg == id) {
+                    let var = format_ident!("arg_{}", id);
+                    syn::parse_quote!(#var)
+                }
+                else if byte_order {
+                    let conversion = self.convert_arg(id);
+                    syn::parse_quote! {
+                        ::microscpi::Argument(#conversion).apply_byte_order(byte_order)
+                    }
+                }
+                else {
+                    self.convert_arg(id)
                 }
             })
             .collect()
     }
 
-    fn call(&self) -> proc_macro2::TokenStream {
+    /// Wraps the execution of the handler, so the arguments with a range are
+    /// converted and rejected with `DataOutOfRange` and the info of the
+    /// parameter if they are out of range.
+    fn check_ranges(&self, execution: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        self.ranges
+            .iter()
+            .rev()
+            .fold(execution, |execution, (id, range)| {
+                let var = format_ident!("arg_{}", id);
+                let ty = &self.args[*id];
+                let conversion = self.convert_arg(*id);
+                let info = self.arg_info(*id);
+                let out_of_range = if is_optional(ty) {
+                    quote! { #var.as_ref().is_some_and(|value| !(#range).contains(value)) }
+                }
+                else {
+                    quote! { !(#range).contains(&#var) }
+                };
+                quote! {
+                    let #var = #conversion;
+                    if #out_of_range {
+                        Err(::microscpi::Error::DataOutOfRange.with_info(#info))
+                    }
+                    else {
+                        #execution
+                    }
+                }
+            })
+    }
+
+    /// Returns an expression that evaluates to whether the handler is
+    /// compiled, according to its `cfg` attributes.
+    fn cfg_enabled(&self) -> proc_macro2::TokenStream {
+        let predicates = self.cfg.iter().filter_map(|attr| match &attr.meta {
+            Meta::List(list) => Some(&list.tokens),
+            _ => None,
+        });
+        quote! { cfg!(all(#(#predicates),*)) }
+    }
+
+    /// Returns the match arm executing the command. With `byte_order`, the
+    /// arguments are decoded in the byte order of the interface, see
+    /// [CommandDefinition::args].
+    fn call(&self, byte_order: bool) -> proc_macro2::TokenStream {
         let command_id = self.id;
         let arg_count = self.args.len();
-        let args = self.args();
+        let required_args = self.required_args();
+        let byte_order = byte_order
+            && (0..self.args.len()).any(|id| self.ranges.iter().all(|(arg, _)| *arg != id));
+
+        let mut args = self.args(byte_order);
+        if self.context {
+            args.insert(0, syn::parse_quote!(context));
+        }
+        if self.writer {
+            args.push(syn::parse_quote!(response));
+        }
 
         let fn_call = match &self.handler {
             CommandHandler::UserFunction(ident) => {
@@ -59,24 +743,97 @@ impl CommandDefinition {
                 let path: Path = syn::parse(path.parse().unwrap()).unwrap();
                 quote! { ::microscpi::#path(self, #args) }
             }
+            CommandHandler::SetMethod(set, func) => quote! { #set::#func(self, #args) },
         };
 
-        let fn_call = if self.future {
-            quote! { #fn_call.await? }
+        let fn_call = if let Some(timeout_ms) = self.timeout_ms {
+            quote! {
+                ::microscpi::with_timeout(
+                    #fn_call,
+                    <Self as ::microscpi::Interface>::timeout(#timeout_ms)
+                ).await?
+            }
+        }
+        else if self.future {
+            quote! { #fn_call.await }
         }
         else {
+            fn_call
+        };
+
+        let fn_call = if self.fallible {
             quote! { #fn_call? }
+        }
+        else {
+            fn_call
+        };
+
+        // Handlers taking the response writer write the response themselves.
+        let write_result = if self.writer {
+            quote! {
+                #fn_call;
+                Ok(())
+            }
+        }
+        else {
+            quote! {
+                let result = #fn_call;
+                result.write_response(response).await?;
+                Ok(())
+            }
+        };
+
+        let write_result = self.check_ranges(write_result);
+
+        let write_result = if byte_order {
+            quote! {
+                #[allow(unused_imports)]
+                use ::microscpi::UnorderedArgument as _;
+                let byte_order = ::microscpi::Interface::byte_order(self);
+                #write_result
+            }
+        }
+        else {
+            write_result
+        };
+
+        let write_result = if self.protected {
+            quote! {
+                if !::microscpi::Interface::protection_unlocked(self) {
+                    Err(::microscpi::Error::CommandProtected)
+                }
+                else {
+                    #write_result
+                }
+            }
+        }
+        else {
+            write_result
+        };
+
+        let write_result = if self.local_locked {
+            quote! {
+                if ::microscpi::Interface::is_local(self) {
+                    Err(::microscpi::Error::InvalidWhileInLocal)
+                }
+                else {
+                    #write_result
+                }
+            }
+        }
+        else {
+            write_result
         };
 
+        let cfg = &self.cfg;
         quote! {
+            #(#cfg)*
             #command_id => {
-                if args.len() != #arg_count {
+                if args.len() < #required_args || args.len() > #arg_count {
                     Err(::microscpi::Error::UnexpectedNumberOfParameters)
                 }
                 else {
-                    let result = #fn_call;
-                    result.write_response(response).await.unwrap();
-                    Ok(())
+                    #write_result
                 }
             }
         }
@@ -93,144 +850,1391 @@ impl CommandDefinition {
     /// # Errors
     /// Returns an error if the attribute contains an invalid SCPI command name.
     fn parse(func: &ImplItemFn, attr: &Attribute) -> syn::Result<CommandDefinition> {
-        let mut cmd: Option<String> = None;
+        let mut cmd: Option<syn::LitStr> = None;
+        let mut timeout_ms: Option<u32> = None;
+        let mut learn = true;
+        let mut infallible = false;
+        let mut local_locked = false;
+        let mut protected = false;
+        let mut protect_query = true;
+        let mut ranges: Vec<(Ident, Expr)> = Vec::new();
 
         attr.parse_nested_meta(|meta| {
             if meta.path.is_ident("cmd") {
                 if let Lit::Str(name) = meta.value()?.parse()? {
-                    cmd = Some(name.value());
+                    cmd = Some(name);
                     Ok(())
                 }
                 else {
                     Err(meta.error("Invalid SCPI command name"))
                 }
             }
+            else if meta.path.is_ident("timeout_ms") {
+                if let Lit::Int(value) = meta.value()?.parse()? {
+                    timeout_ms = Some(value.base10_parse()?);
+                    Ok(())
+                }
+                else {
+                    Err(meta.error("Invalid timeout"))
+                }
+            }
+            else if meta.path.is_ident("no_learn") {
+                learn = false;
+                Ok(())
+            }
+            else if meta.path.is_ident("infallible") {
+                infallible = true;
+                Ok(())
+            }
+            else if meta.path.is_ident("local_locked") {
+                local_locked = true;
+                Ok(())
+            }
+            else if meta.path.is_ident("protected") {
+                protected = true;
+                if meta.input.peek(syn::token::Paren) {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("query") {
+                            let value: syn::LitBool = meta.value()?.parse()?;
+                            protect_query = value.value;
+                            Ok(())
+                        }
+                        else {
+                            Err(meta.error("Unknown protection option"))
+                        }
+                    })?;
+                }
+                Ok(())
+            }
+            else if meta.path.is_ident("range") {
+                meta.parse_nested_meta(|meta| {
+                    let name = meta
+                        .path
+                        .get_ident()
+                        .cloned()
+                        .ok_or_else(|| meta.error("Expected a parameter name"))?;
+                    let range: syn::LitStr = meta.value()?.parse()?;
+                    ranges.push((name, range.parse()?));
+                    Ok(())
+                })
+            }
             else {
                 Ok(())
             }
         })?;
 
-        let args = func
+        let mut params: Vec<&syn::PatType> = func
             .sig
             .inputs
             .iter()
             .filter_map(|arg| match arg {
-                syn::FnArg::Typed(arg_type) => Some(*arg_type.ty.clone()),
+                syn::FnArg::Typed(arg_type) => Some(arg_type),
                 syn::FnArg::Receiver(_) => None,
             })
             .collect();
 
+        // A trailing `&mut impl Write` parameter receives the response writer.
+        let writer = params.last().is_some_and(|param| is_writer(&param.ty));
+        if writer {
+            params.pop();
+        }
+
+        // A mutable reference cannot be converted from an argument value, so a
+        // leading `&mut` parameter receives the interface context.
+        let context = matches!(
+            params.first().map(|param| param.ty.as_ref()),
+            Some(Type::Reference(reference)) if reference.mutability.is_some()
+        );
+        if context {
+            params.remove(0);
+        }
+
+        if let Some(param) = params.get(MAX_ARGS) {
+            return Err(syn::Error::new(
+                param.span(),
+                format!(
+                    "A handler can take at most {MAX_ARGS} arguments, the capacity given by \
+                     `microscpi::MAX_ARGS`, but `{}` takes {}",
+                    func.sig.ident,
+                    params.len()
+                ),
+            ));
+        }
+
+        let args: Vec<Type> = params.iter().map(|param| *param.ty.clone()).collect();
+        let arg_names = params
+            .iter()
+            .map(|param| match param.pat.as_ref() {
+                syn::Pat::Ident(pat) => {
+                    Some(pat.ident.to_string().trim_start_matches('_').to_string())
+                        .filter(|name| !name.is_empty())
+                }
+                _ => None,
+            })
+            .collect();
+
+        let ranges = ranges
+            .into_iter()
+            .map(|(name, range)| {
+                let index = params.iter().position(
+                    |param| matches!(param.pat.as_ref(), syn::Pat::Ident(pat) if pat.ident == name),
+                );
+                match index {
+                    Some(index) => Ok((index, range)),
+                    None => Err(syn::Error::new(
+                        name.span(),
+                        format!("Unknown parameter `{name}`"),
+                    )),
+                }
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        // Optional arguments can only be omitted at the end of the argument list.
+        if let Some(arg) = args
+            .iter()
+            .skip_while(|arg| !is_optional(arg))
+            .find(|arg| !is_optional(arg))
+        {
+            return Err(syn::Error::new(
+                arg.span(),
+                "Required arguments must not follow optional arguments",
+            ));
+        }
+
+        if timeout_ms.is_some() && func.sig.asyncness.is_none() {
+            return Err(syn::Error::new(
+                attr.span(),
+                "A timeout requires an async handler",
+            ));
+        }
+
         if let Some(cmd) = &cmd {
+            let command = Command::try_from(cmd.value().as_str())
+                .map_err(|_| syn::Error::new(attr.span(), "Invalid SCPI command syntax"))?;
+            let protected = protected && (protect_query || !command.is_query());
             Ok(CommandDefinition {
                 id: 0,
-                command: Command::try_from(cmd.as_str())
-                    .map_err(|_| syn::Error::new(attr.span(), "Invalid SCPI command syntax"))?,
+                span: cmd.span(),
+                command,
                 handler: CommandHandler::UserFunction(func.sig.ident.to_owned()),
                 args,
                 future: func.sig.asyncness.is_some(),
+                context,
+                writer,
+                timeout_ms,
+                learn,
+                doc: first_doc_line(&func.attrs),
+                local_locked,
+                protected,
+                aliases: Vec::new(),
+                ranges,
+                arg_names,
+                cfg: func
+                    .attrs
+                    .iter()
+                    .filter(|attr| attr.path().is_ident("cfg"))
+                    .cloned()
+                    .collect(),
+                fallible: !infallible && is_result(&func.sig.output),
+                output: func.sig.output.clone(),
             })
         }
         else {
             Err(syn::Error::new(attr.span(), "Missing SCPI command path"))
         }
     }
-}
 
-/// Extracts all SCPI command functions from an `impl` block.
-///
-/// # Arguments
-/// * `input` - The implementation item of the struct where to extract the SCPI
-///   commands from.
-///
-/// # Returns
-/// A vector containing all command definitions.
-fn extract_commands(input: &mut ItemImpl) -> Result<Vec<Rc<CommandDefinition>>, syn::Error> {
-    let mut commands = Vec::new();
-    for item in input.items.iter_mut() {
-        if let syn::ImplItem::Fn(ref mut item_fn) = item {
-            // Find the first SCPI attribute for this function, parse it and then remove
-            // it from the list attributes, so the compiler does not complain about an
-            // unknown attribute.
-            if let Some(idx) = item_fn
-                .attrs
-                .iter()
-                .position(|attr| attr.path().is_ident("scpi"))
-            {
-                let attr = item_fn.attrs.remove(idx);
-                let mut cmd = CommandDefinition::parse(item_fn, &attr)?;
-                cmd.id = commands.len();
-                commands.push(Rc::new(cmd).clone());
-            }
-        }
+    /// Parses a further `scpi` attribute of a handler, which defines an alias
+    /// of the command.
+    fn parse_alias(&self, attr: &Attribute) -> syn::Result<Command> {
+        let mut cmd: Option<String> = None;
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("cmd") {
+                if let Lit::Str(name) = meta.value()?.parse()? {
+                    cmd = Some(name.value());
+                    Ok(())
+                }
+                else {
+                    Err(meta.error("Invalid SCPI command name"))
+                }
+            }
+            else {
+                Err(meta.error("An alias only accepts the `cmd` option"))
+            }
+        })?;
+
+        let cmd = cmd.ok_or_else(|| syn::Error::new(attr.span(), "Missing SCPI command path"))?;
+        let alias = Command::try_from(cmd.as_str())
+            .map_err(|_| syn::Error::new(attr.span(), "Invalid SCPI command syntax"))?;
+        if alias.is_query() != self.command.is_query() {
+            return Err(syn::Error::new(
+                attr.span(),
+                "An alias of a query must be a query and vice versa",
+            ));
+        }
+        Ok(alias)
+    }
+}
+
+/// Returns the node table entry of a command.
+///
+/// The nodes of commands with `cfg` attributes are part of the tree in every
+/// configuration. If the handler is not compiled, the entry is `None`, so the
+/// command is rejected as an undefined header.
+fn node_entry(cmd: Option<&CommandDefinition>) -> proc_macro2::TokenStream {
+    match cmd {
+        Some(cmd) if cmd.cfg.is_empty() => {
+            let command_id = cmd.id;
+            quote! { Some(#command_id) }
+        }
+        Some(cmd) => {
+            let command_id = cmd.id;
+            let enabled = cmd.cfg_enabled();
+            quote! { if #enabled { Some(#command_id) } else { None } }
+        }
+        None => quote! { None },
     }
-    Ok(commands)
+}
+
+/// Generates the statements that write the `*LRN?` response.
+///
+/// For every command with a query on the same node, the response contains the
+/// command with the current value returned by the query, e.g.
+/// `:SOURCE:VOLTAGE 5`. Only queries without arguments are considered.
+fn learn_statements(commands: &[Rc<CommandDefinition>]) -> Vec<proc_macro2::TokenStream> {
+    commands
+        .iter()
+        .filter(|cmd| {
+            cmd.learn && !cmd.command.is_query() && !cmd.command.is_common() && !cmd.args.is_empty()
+        })
+        .filter_map(|cmd| {
+            let query = commands.iter().find(|query| {
+                query.learn
+                    && query.command.is_query()
+                    && query.command.parts == cmd.command.parts
+                    && query.args.is_empty()
+                    && !query.context
+                    && !query.writer
+            })?;
+            match &query.handler {
+                CommandHandler::UserFunction(func) => Some((cmd, query, quote! { self.#func() })),
+                CommandHandler::SetMethod(set, func) => {
+                    Some((cmd, query, quote! { #set::#func(self) }))
+                }
+                CommandHandler::StandardFunction(_) => None,
+            }
+        })
+        .map(|(cmd, query, call)| {
+            let header = format!("{} ", cmd.command.header());
+            let call = if query.future {
+                quote! { #call.await }
+            }
+            else {
+                call
+            };
+            let call = if query.fallible {
+                quote! { #call? }
+            }
+            else {
+                call
+            };
+            // The separator is chosen at runtime, as the preceding commands
+            // may be disabled by `cfg` attributes.
+            let cfg = cmd.cfg.iter().chain(&query.cfg);
+            quote! {
+                #(#cfg)*
+                {
+                    if !::core::mem::take(first) {
+                        response.write_str(";").await?;
+                    }
+                    response.write_str(#header).await?;
+                    #call.write_response(response).await?;
+                }
+            }
+        })
+        .collect()
+}
+
+/// Extracts all SCPI command functions from an `impl` block.
+///
+/// # Arguments
+/// * `input` - The implementation item of the struct where to extract the SCPI
+///   commands from.
+///
+/// * `prefix` - The path prefix of the commands and nested interfaces.
+///
+/// * `first_id` - The id of the first command.
+///
+/// # Returns
+/// A vector containing all command definitions and a vector containing all
+/// nested interfaces.
+#[allow(clippy::type_complexity)]
+fn extract_commands(
+    input: &mut ItemImpl, prefix: Option<&Command>, first_id: usize,
+) -> Result<(Vec<Rc<CommandDefinition>>, Vec<Rc<SubtreeDefinition>>), syn::Error> {
+    let mut commands = Vec::new();
+    let mut subtrees = Vec::new();
+    for item in input.items.iter_mut() {
+        if let syn::ImplItem::Fn(ref mut item_fn) = item {
+            // Take the SCPI attributes of this function out of the list of attributes, so
+            // the compiler does not complain about an unknown attribute. The first one
+            // defines the command, further ones define aliases.
+            let (attrs, other_attrs) = std::mem::take(&mut item_fn.attrs)
+                .into_iter()
+                .partition::<Vec<_>, _>(|attr| attr.path().is_ident("scpi"));
+            item_fn.attrs = other_attrs;
+
+            let mut attrs = attrs.into_iter();
+            if let Some(attr) = attrs.next() {
+                if let Some(path) = subtree_path(&attr)? {
+                    if let Some(alias) = attrs.next() {
+                        return Err(syn::Error::new(
+                            alias.span(),
+                            "A subtree can't have aliases",
+                        ));
+                    }
+                    let mut subtree = SubtreeDefinition::parse(item_fn, &path)?;
+                    if let Some(prefix) = prefix {
+                        subtree.path = subtree.path.with_prefix(prefix);
+                    }
+                    subtrees.push(Rc::new(subtree));
+                    continue;
+                }
+                let mut cmd = CommandDefinition::parse(item_fn, &attr)?;
+                cmd.id = first_id + commands.len();
+                for attr in attrs {
+                    let alias = cmd.parse_alias(&attr)?;
+                    cmd.aliases.push(alias);
+                }
+                // Common commands are always located at the root.
+                if let Some(prefix) = prefix {
+                    if !cmd.command.is_common() {
+                        cmd.command = cmd.command.with_prefix(prefix);
+                    }
+                    for alias in cmd.aliases.iter_mut().filter(|alias| !alias.is_common()) {
+                        *alias = alias.clone().with_prefix(prefix);
+                    }
+                }
+                commands.push(Rc::new(cmd).clone());
+            }
+        }
+    }
+    Ok((commands, subtrees))
 }
 
 /// Macro attribute to define an SCPI interface.
 ///
 /// This attribute will process an `impl` block and register the SCPI commands
 /// defined within it.
+///
+/// A handler either returns a `Result`, whose error is pushed to the error
+/// handler, or a plain value implementing `Response`, e.g. `fn value(&mut self)
+/// -> u64`. The return type is recognized as a `Result` by its name, so a
+/// plain value type named `Result` is marked with `#[scpi(infallible)]`.
+///
+/// The `Ieee4882Device` option registers the commands of all traits bundled by
+/// `Ieee4882Device`, i.e. the mandatory common commands of IEEE 488.2.
+///
+/// The `dropped_errors` option registers the `SYSTem:ERRor:DROPped?` query of
+/// `ErrorCommands`, which responds with the number of errors lost due to
+/// overflows of the error queue and resets it.
+///
+/// The `learn` option registers the `*LRN?` query, which responds with the
+/// commands restoring the current settings. It contains every command with a
+/// query on the same node, unless one of them is marked with
+/// `#[scpi(no_learn)]`. The commands of parts and command sets are included,
+/// but the commands of nested interfaces are not, as their headers depend on
+/// the path the interface is mounted at.
+///
+/// The `help` option registers the `SYSTem:HELP:SYNTax? <header>` query,
+/// which responds with the syntax of a command and the first line of its
+/// documentation as a block. The help texts are embedded into the binary, so
+/// the option is disabled by default.
+///
+/// Commands marked with `#[scpi(local_locked)]` are rejected with
+/// `Error::InvalidWhileInLocal` while the interface is in local operation,
+/// which requires the `RemoteCommands` option. These commands of a nested
+/// interface are also rejected while the interface it is mounted in is in
+/// local operation.
+///
+/// Commands marked with `#[scpi(protected)]` are rejected with
+/// `Error::CommandProtected` while the protection of the interface is locked.
+/// The `protection_unlocked` option selects a method of the interface that
+/// returns whether the protection is unlocked, e.g.
+/// `#[interface(protection_unlocked = calibration_unlocked)]`. Queries are
+/// excluded from the protection with `#[scpi(protected(query = false))]`.
+///
+/// The `range` option of a command declares the valid ranges of its
+/// parameters by name, e.g. `#[scpi(cmd = "SOURce:VOLTage", range(voltage =
+/// "0.0..=30.0"))]`. Values outside of the range are rejected with
+/// `Error::DataOutOfRange` before the handler is called.
+///
+/// Errors of the conversion or the range of an argument carry the position and
+/// name of the parameter as device-dependent info, e.g. `-222,"Data out of
+/// range;parameter 1 (voltage)"`. The info doesn't change the kind of the
+/// error: it compares equal to the error without info, and `Error::kind`
+/// returns the original variant for matching.
+///
+/// Further `#[scpi(cmd = "...")]` attributes of a handler define aliases of its
+/// command, e.g. a legacy `SOURce:AMPLitude` for `SOURce:VOLTage:[LEVel]`.
+/// The aliases share the handler and the command id, and are listed in the
+/// help of the command.
+///
+/// `cfg` attributes of a handler, e.g. `#[cfg(feature = "dac")]`, also apply
+/// to the generated code calling it. The command tree is the same in every
+/// configuration: if the handler is not compiled, the nodes of its command
+/// remain without a handler and the command is rejected with
+/// `Error::UndefinedHeader`. Therefore each command may only be defined once,
+/// even by handlers with mutually exclusive `cfg` attributes.
+///
+/// The `prefix` option registers all commands of the `impl` block below a
+/// path, e.g. `#[interface(prefix = "SENSe:VOLTage")]`. Common commands like
+/// `*RST` are not affected by the prefix.
+///
+/// The `parts` option merges the commands of `impl` blocks marked with
+/// `#[interface_part]` into the command tree, e.g. `#[interface(parts(lan::Lan,
+/// source::Source))]`. Their command ids follow the ids of the own commands.
+///
+/// The `use` option registers the commands of traits marked with
+/// `#[command_set]`, which are implemented by the interface, e.g.
+/// `#[interface(use(lan::LanCommands))]`.
+///
+/// A generic `impl` block, e.g. `impl<D: Dac, const CH: usize> Psu<D, CH>
+/// where D::Error: Into<Error>`, implements `Interface` with the same generics
+/// and where-clause. Arguments and responses whose types depend on the generic
+/// parameters are supported by bounds that are added to the implementation,
+/// i.e. that the type can be converted from a `&Value` and implements
+/// `Response`. The types of nested interfaces must not depend on the generic
+/// parameters.
 #[proc_macro_attribute]
 pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let attrs: Punctuated<Path, Comma> = parse_macro_input!(attr with Punctuated::parse_terminated);
-    let mut input_impl = parse_macro_input!(item as ItemImpl);
+    let input_impl = parse_macro_input!(item as ItemImpl);
+    expand_interface(attr.into(), input_impl, None)
+}
 
-    let mut config = Config::default();
+/// Expands an interface whose parts and command sets have been collected.
+///
+/// The input is the arguments of the `interface` attribute in parentheses,
+/// followed by the `impl` block of the interface and the metadata of the parts
+/// and command sets in braces.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __interface_parts(input: TokenStream) -> TokenStream {
+    let parser = |input: ParseStream| {
+        let attr;
+        syn::parenthesized!(attr in input);
+        let attr: proc_macro2::TokenStream = attr.parse()?;
+        let input_impl: ItemImpl = input.parse()?;
+        let mut metadata = Vec::new();
+        while !input.is_empty() {
+            let content;
+            syn::braced!(content in input);
+            metadata.push(content.parse()?);
+        }
+        Ok((attr, input_impl, metadata))
+    };
+    let (attr, input_impl, metadata) = parse_macro_input!(input with parser);
+    expand_interface(attr, input_impl, Some(metadata))
+}
+
+/// Generates the implementation of the `Interface` trait.
+///
+/// If the expansion fails, the `impl` block without the `scpi` attributes and
+/// an empty implementation of the trait are emitted along with the error, so
+/// that the uses of the handlers and of the interface don't report further
+/// errors.
+fn expand_interface(
+    attr: proc_macro2::TokenStream, input_impl: ItemImpl, metadata: Option<Vec<Metadata>>,
+) -> TokenStream {
+    let mut recovered = input_impl.clone();
+    match expand_interface_impl(attr.clone(), input_impl, metadata) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => {
+            for item in recovered.items.iter_mut() {
+                if let syn::ImplItem::Fn(item_fn) = item {
+                    item_fn.attrs.retain(|attr| !attr.path().is_ident("scpi"));
+                }
+            }
+            let error = err.to_compile_error();
+            let self_ty = &recovered.self_ty;
+            let context_ty = context_option(attr).unwrap_or_else(|| syn::parse_quote!(()));
+            let (impl_generics, _, where_clause) = recovered.generics.split_for_impl();
+            quote! {
+                #error
+                #recovered
+                impl #impl_generics ::microscpi::Interface for #self_ty #where_clause {
+                    type Context = #context_ty;
+
+                    const ROOT_NODE: &'static ::microscpi::Node = &::microscpi::Node {
+                        children: &[],
+                        command: None,
+                        query: None,
+                        subtree: None,
+                    };
+                    const COMMAND_COUNT: ::microscpi::CommandId = 0;
 
-    for path in attrs {
-        if path.is_ident("ErrorCommands") {
-            config.error_commands = true;
+                    async fn execute_command<'a>(
+                        &'a mut self,
+                        _context: &mut Self::Context,
+                        _command_id: ::microscpi::CommandId,
+                        _args: &[::microscpi::Value<'a>],
+                        _response: &mut impl ::microscpi::Write
+                    ) -> Result<(), ::microscpi::Error> {
+                        Err(::microscpi::Error::UndefinedHeader)
+                    }
+                }
+            }
+            .into()
         }
-        else if path.is_ident("StandardCommands") {
-            config.standard_commands = true;
+    }
+}
+
+/// Returns the type given by the `context` option of an interface, if the
+/// options can be parsed.
+fn context_option(attr: proc_macro2::TokenStream) -> Option<Type> {
+    parse_interface_attrs
+        .parse2(attr)
+        .ok()?
+        .into_iter()
+        .find_map(|meta| match meta {
+            Meta::NameValue(name_value) if name_value.path.is_ident("context") => {
+                let value = name_value.value;
+                syn::parse2(quote!(#value)).ok()
+            }
+            _ => None,
+        })
+}
+
+/// Expands the `impl` block of an interface.
+///
+/// If the interface consists of parts or command sets and their metadata has
+/// not been collected yet, the expansion is passed through the macros
+/// generated for them, which append their metadata and eventually invoke
+/// `__interface_parts`.
+fn expand_interface_impl(
+    attr: proc_macro2::TokenStream, mut input_impl: ItemImpl, metadata: Option<Vec<Metadata>>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let attrs = parse_interface_attrs.parse2(attr.clone())?;
+
+    let mut config = Config::default();
+
+    for meta in attrs {
+        match meta {
+            Meta::Path(path) if path.is_ident("ErrorCommands") => {
+                config.error_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("StandardCommands") => {
+                config.standard_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("IdentityCommands") => {
+                config.identity_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("ResetCommands") => {
+                config.reset_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("SelfTestCommands") => {
+                config.self_test_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("TriggerCommands") => {
+                config.trigger_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("StateCommands") => {
+                config.state_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("ClockCommands") => {
+                config.clock_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("RemoteCommands") => {
+                config.remote_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("MacroCommands") => {
+                config.macro_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("Ieee4882Device") => {
+                config.error_commands = true;
+                config.standard_commands = true;
+                config.identity_commands = true;
+                config.reset_commands = true;
+                config.self_test_commands = true;
+                config.status_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("dropped_errors") => {
+                config.dropped_errors = true;
+            }
+            Meta::Path(path) if path.is_ident("learn") => {
+                config.learn = true;
+            }
+            Meta::Path(path) if path.is_ident("help") => {
+                config.help = true;
+            }
+            Meta::Path(path) if path.is_ident("FormatCommands") => {
+                config.format_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("StatusCommands") => {
+                config.status_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("OperationStatusCommands") => {
+                config.operation_status_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("QuestionableStatusCommands") => {
+                config.questionable_status_commands = true;
+            }
+            Meta::Path(path) if path.is_ident("StatisticsCommands") => {
+                config.statistics_commands = true;
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("timeout") => {
+                let value = name_value.value;
+                config.timeout = Some(syn::parse2(quote!(#value))?);
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("on_device_clear") => {
+                let value = name_value.value;
+                config.on_device_clear = Some(syn::parse2(quote!(#value))?);
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("protection_unlocked") => {
+                let value = name_value.value;
+                config.protection_unlocked = Some(syn::parse2(quote!(#value))?);
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("prefix") => {
+                config.prefix = Some(parse_prefix(&name_value.value)?);
+            }
+            Meta::List(list) if list.path.is_ident("parts") => {
+                let parts = list.parse_args_with(Punctuated::<Path, Comma>::parse_terminated)?;
+                config.parts = parts.into_iter().collect();
+            }
+            Meta::List(list) if list.path.is_ident("use") => {
+                let sets = list.parse_args_with(Punctuated::<Path, Comma>::parse_terminated)?;
+                config.command_sets = sets.into_iter().collect();
+            }
+            Meta::NameValue(name_value) if name_value.path.is_ident("context") => {
+                let value = name_value.value;
+                config.context = Some(syn::parse2(quote!(#value))?);
+            }
+            _ => {}
         }
     }
 
+    let collected = config.parts.iter().chain(&config.command_sets);
+    let Some(metadata) = metadata.or_else(|| collected.clone().next().is_none().then(Vec::new))
+    else {
+        let collected: Vec<&Path> = collected.collect();
+        let (first, next) = collected.split_first().unwrap();
+        return Ok(quote! {
+            #first! {
+                #({ #next })*
+                { ::microscpi::__interface_parts }
+                (#attr)
+                #input_impl
+            }
+        });
+    };
+
+    let context_ty = config
+        .context
+        .clone()
+        .unwrap_or_else(|| syn::parse_quote!(()));
+
     let impl_ty = input_impl.self_ty.clone();
 
-    let mut commands = match extract_commands(&mut input_impl) {
-        Ok(commands) => commands,
-        Err(err) => {
-            return err.to_compile_error().into();
+    let (mut commands, subtrees) = extract_commands(&mut input_impl, config.prefix.as_ref(), 0)?;
+
+    let mut parts = Vec::new();
+    let mut set_items = Vec::new();
+    for metadata in metadata {
+        match metadata {
+            Metadata::Part(part) => parts.push(part),
+            Metadata::CommandSet(item) => set_items.push(item),
         }
-    };
+    }
+
+    // The commands of a command set call the methods of its trait.
+    for (path, mut item) in config.command_sets.iter().zip(set_items) {
+        let (set_commands, _) = extract_commands(&mut item, None, commands.len())?;
+        for mut cmd in set_commands {
+            let definition = Rc::get_mut(&mut cmd).unwrap();
+            if let CommandHandler::UserFunction(func) = &definition.handler {
+                definition.handler = CommandHandler::SetMethod(path.clone(), func.clone());
+            }
+            commands.push(cmd);
+        }
+    }
+
+    let mut learn_statements = learn_statements(&commands);
+    let mut part_names = Vec::new();
+
+    if config.learn {
+        commands.push(Rc::new(CommandDefinition {
+            future: true,
+            writer: true,
+            ..CommandDefinition::standard(commands.len(), "*LRN?", "Interface::learn", Vec::new())
+        }));
+    }
+
+    if config.help {
+        commands.push(Rc::new(CommandDefinition {
+            future: true,
+            writer: true,
+            ..CommandDefinition::standard(
+                commands.len(),
+                "SYSTem:HELP:SYNTax?",
+                "Interface::help_syntax",
+                vec![syn::parse_quote!(&str)],
+            )
+        }));
+    }
 
     if config.standard_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:VERSion?",
+            "StandardCommands::system_version",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:CAPability?",
+            "StandardCommands::system_capability",
+            Vec::new(),
+        )));
+    }
+
+    if config.identity_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*IDN?",
+            "IdentityCommands::identify",
+            Vec::new(),
+        )));
+    }
+
+    let reset_command = config.reset_commands.then(|| {
+        let id = commands.len();
+        commands.push(Rc::new(CommandDefinition::standard(
+            id,
+            "*RST",
+            "ResetCommands::reset_device",
+            Vec::new(),
+        )));
+        id
+    });
+
+    if config.self_test_commands {
         commands.push(Rc::new(CommandDefinition {
-            id: commands.len(),
-            args: Vec::new(),
-            command: Command::try_from("SYSTem:VERSion?").unwrap(),
-            handler: CommandHandler::StandardFunction("StandardCommands::system_version"),
-            future: false,
+            future: true,
+            ..CommandDefinition::standard(
+                commands.len(),
+                "*TST?",
+                "SelfTestCommands::self_test_query",
+                Vec::new(),
+            )
         }));
     }
 
+    if config.trigger_commands {
+        commands.push(Rc::new(CommandDefinition {
+            future: true,
+            ..CommandDefinition::standard(
+                commands.len(),
+                "*TRG",
+                "TriggerCommands::trigger_command",
+                Vec::new(),
+            )
+        }));
+    }
+
+    if config.state_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*SAV",
+            "StateCommands::save_command",
+            vec![syn::parse_quote!(u8)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*RCL",
+            "StateCommands::recall_command",
+            vec![syn::parse_quote!(u8)],
+        )));
+    }
+
+    if config.clock_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:DATE",
+            "ClockCommands::system_date",
+            vec![
+                syn::parse_quote!(u16),
+                syn::parse_quote!(u8),
+                syn::parse_quote!(u8),
+            ],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:DATE?",
+            "ClockCommands::system_date_query",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:TIME",
+            "ClockCommands::system_time",
+            vec![
+                syn::parse_quote!(u8),
+                syn::parse_quote!(u8),
+                syn::parse_quote!(u8),
+            ],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:TIME?",
+            "ClockCommands::system_time_query",
+            Vec::new(),
+        )));
+    }
+
+    if config.remote_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:LOCal",
+            "RemoteCommands::system_local",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:REMote",
+            "RemoteCommands::system_remote",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:RWLock",
+            "RemoteCommands::system_rwlock",
+            Vec::new(),
+        )));
+    }
+
+    if config.macro_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*DMC",
+            "MacroCommands::define_macro",
+            vec![syn::parse_quote!(&str), syn::parse_quote!(&[u8])],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*EMC",
+            "MacroCommands::enable_macros",
+            vec![syn::parse_quote!(i32)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*EMC?",
+            "MacroCommands::macros_enabled",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*GMC?",
+            "MacroCommands::get_macro",
+            vec![syn::parse_quote!(&str)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition {
+            future: true,
+            writer: true,
+            ..CommandDefinition::standard(
+                commands.len(),
+                "*LMC?",
+                "MacroCommands::list_macros",
+                Vec::new(),
+            )
+        }));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*PMC",
+            "MacroCommands::purge_macros",
+            Vec::new(),
+        )));
+    }
+
     if config.error_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:ERRor:[NEXT]?",
+            "ErrorCommands::system_error_next",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:ERRor:COUNt?",
+            "ErrorCommands::system_error_count",
+            Vec::new(),
+        )));
+
         commands.push(Rc::new(CommandDefinition {
-            id: commands.len(),
-            args: Vec::new(),
-            command: Command::try_from("SYSTem:ERRor:[NEXT]?").unwrap(),
-            handler: CommandHandler::StandardFunction("ErrorCommands::system_error_next"),
-            future: false,
+            future: true,
+            writer: true,
+            ..CommandDefinition::standard(
+                commands.len(),
+                "SYSTem:ERRor:ALL?",
+                "ErrorCommands::system_error_all",
+                Vec::new(),
+            )
+        }));
+    }
+
+    if config.dropped_errors {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:ERRor:DROPped?",
+            "ErrorCommands::system_error_dropped",
+            Vec::new(),
+        )));
+    }
+
+    if config.format_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "FORMat:[DATA]",
+            "FormatCommands::format_data",
+            vec![
+                syn::parse_quote!(::microscpi::DataType),
+                syn::parse_quote!(Option<u8>),
+            ],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "FORMat:[DATA]?",
+            "FormatCommands::format_data_query",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "FORMat:BORDer",
+            "FormatCommands::format_border",
+            vec![syn::parse_quote!(::microscpi::ByteOrder)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "FORMat:BORDer?",
+            "FormatCommands::format_border_query",
+            Vec::new(),
+        )));
+    }
+
+    if config.status_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*STB?",
+            "StatusCommands::status_byte",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*SRE",
+            "StatusCommands::set_status_byte_enable",
+            vec![syn::parse_quote!(u8)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*SRE?",
+            "StatusCommands::status_byte_enable",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*CLS",
+            "Interface::clear_status",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*ESR?",
+            "StatusCommands::event_status_register",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*ESE",
+            "StatusCommands::set_event_status_enable",
+            vec![syn::parse_quote!(u8)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*ESE?",
+            "StatusCommands::event_status_enable",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "*OPC",
+            "StatusCommands::operation_complete",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition {
+            future: true,
+            ..CommandDefinition::standard(
+                commands.len(),
+                "*OPC?",
+                "StatusCommands::operation_complete_query",
+                Vec::new(),
+            )
         }));
 
         commands.push(Rc::new(CommandDefinition {
-            id: commands.len(),
-            args: Vec::new(),
-            command: Command::try_from("SYSTem:ERRor:COUNt?").unwrap(),
-            handler: CommandHandler::StandardFunction("ErrorCommands::system_error_count"),
-            future: false,
+            future: true,
+            ..CommandDefinition::standard(
+                commands.len(),
+                "*WAI",
+                "StatusCommands::wait",
+                Vec::new(),
+            )
         }));
     }
 
+    if config.operation_status_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:OPERation:CONDition?",
+            "OperationStatusCommands::operation_condition",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:OPERation:[EVENt]?",
+            "OperationStatusCommands::operation_event",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:OPERation:ENABle",
+            "OperationStatusCommands::set_operation_enable",
+            vec![syn::parse_quote!(u16)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:OPERation:ENABle?",
+            "OperationStatusCommands::operation_enable",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:OPERation:PTRansition",
+            "OperationStatusCommands::set_operation_positive_transition",
+            vec![syn::parse_quote!(u16)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:OPERation:PTRansition?",
+            "OperationStatusCommands::operation_positive_transition",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:OPERation:NTRansition",
+            "OperationStatusCommands::set_operation_negative_transition",
+            vec![syn::parse_quote!(u16)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:OPERation:NTRansition?",
+            "OperationStatusCommands::operation_negative_transition",
+            Vec::new(),
+        )));
+    }
+
+    if config.questionable_status_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:QUEStionable:CONDition?",
+            "QuestionableStatusCommands::questionable_condition",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:QUEStionable:[EVENt]?",
+            "QuestionableStatusCommands::questionable_event",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:QUEStionable:ENABle",
+            "QuestionableStatusCommands::set_questionable_enable",
+            vec![syn::parse_quote!(u16)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:QUEStionable:ENABle?",
+            "QuestionableStatusCommands::questionable_enable",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:QUEStionable:PTRansition",
+            "QuestionableStatusCommands::set_questionable_positive_transition",
+            vec![syn::parse_quote!(u16)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:QUEStionable:PTRansition?",
+            "QuestionableStatusCommands::questionable_positive_transition",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:QUEStionable:NTRansition",
+            "QuestionableStatusCommands::set_questionable_negative_transition",
+            vec![syn::parse_quote!(u16)],
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "STATus:QUEStionable:NTRansition?",
+            "QuestionableStatusCommands::questionable_negative_transition",
+            Vec::new(),
+        )));
+    }
+
+    if config.statistics_commands {
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:STATistics:COMMands?",
+            "StatisticsCommands::statistics_commands",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:STATistics:ERRors?",
+            "StatisticsCommands::statistics_errors",
+            Vec::new(),
+        )));
+
+        commands.push(Rc::new(CommandDefinition::standard(
+            commands.len(),
+            "SYSTem:STATistics:LAST?",
+            "StatisticsCommands::statistics_last",
+            Vec::new(),
+        )));
+    }
+
+    // The command ids of the parts follow the ids of the own commands. The
+    // bounds of the parts are added to the interface, so they have to name
+    // their generic parameters alike.
+    let mut part_commands = Vec::new();
+    for mut part in parts {
+        if generic_params(&part.item.generics) != generic_params(&input_impl.generics) {
+            return Err(syn::Error::new(
+                part.item.self_ty.span(),
+                "An interface part has to declare the same generic parameters as the interface",
+            ));
+        }
+        let first_id = commands.len() + part_commands.iter().map(Vec::len).sum::<usize>();
+        let (cmds, _) = extract_commands(&mut part.item, part.prefix.as_ref(), first_id)?;
+        part_commands.push(cmds);
+        part_names.push(part.name);
+    }
+
+    // Without a timer the timeout of a handler would never elapse.
+    if let Some(cmd) = commands
+        .iter()
+        .chain(part_commands.iter().flatten())
+        .find(|cmd| cmd.timeout_ms.is_some())
+        .filter(|_| config.timeout.is_none())
+    {
+        return Err(syn::Error::new(
+            cmd.span,
+            "A handler with a timeout requires the `timeout` option of the interface, e.g. \
+             `#[interface(timeout = sleep)]`",
+        ));
+    }
+
+    // Without a remote state the interface is never in local operation.
+    if let Some(cmd) = commands
+        .iter()
+        .chain(part_commands.iter().flatten())
+        .find(|cmd| cmd.local_locked)
+        .filter(|_| !config.remote_commands)
+    {
+        return Err(syn::Error::new(
+            cmd.span,
+            "A command marked with `local_locked` requires the `RemoteCommands` option of the \
+             interface",
+        ));
+    }
+
+    let generic_params = generic_params(&input_impl.generics);
+
+    // The nodes of the command tree are statics, which can't refer to the
+    // generic parameters.
+    if let Some(subtree) = subtrees
+        .iter()
+        .find(|subtree| mentions_generics(&subtree.ty, &generic_params))
+    {
+        return Err(syn::Error::new(
+            subtree.ty.span(),
+            "The type of a nested interface can't depend on the generic parameters of the \
+             interface",
+        ));
+    }
+
     let mut tree = Tree::new();
-    commands
+    for cmd in commands.iter().chain(part_commands.iter().flatten()) {
+        if let Err(err) = tree.insert(cmd.clone()) {
+            return Err(err.to_syn_error(cmd));
+        }
+    }
+
+    for subtree in &subtrees {
+        if let Err(err) = tree.insert_subtree(subtree.clone()) {
+            return Err(syn::Error::new(subtree.func.span(), err));
+        }
+    }
+
+    // The command ids of the nested interfaces follow the ids of the own
+    // commands. Their number is only known to the compiler, so the offsets are
+    // constant expressions.
+    let own_count = commands.len() + part_commands.iter().map(Vec::len).sum::<usize>();
+    let mut subtree_offsets: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut command_count = quote! { #own_count };
+    for subtree in &subtrees {
+        let ty = &subtree.ty;
+        subtree_offsets.push(command_count.clone());
+        command_count = quote! { #command_count + <#ty as ::microscpi::Interface>::COMMAND_COUNT };
+    }
+
+    let mut command_items: Vec<proc_macro2::TokenStream> = commands
         .iter()
-        .try_for_each(|cmd| tree.insert(cmd.clone()))
-        .unwrap();
+        .map(|cmd| cmd.call(config.format_commands))
+        .collect();
 
-    let command_items: Vec<proc_macro2::TokenStream> =
-        commands.iter().map(|cmd| cmd.call()).collect();
+    let mut command_names: Vec<proc_macro2::TokenStream> = commands
+        .iter()
+        .chain(part_commands.iter().flatten())
+        .map(|cmd| {
+            let command_id = cmd.id;
+            let name = &cmd.command.name;
+            let cfg = &cmd.cfg;
+            quote! { #(#cfg)* #command_id => Some(#name) }
+        })
+        .collect();
+
+    let local_locked_ids: Vec<usize> = commands
+        .iter()
+        .chain(part_commands.iter().flatten())
+        .filter(|cmd| cmd.local_locked)
+        .map(|cmd| cmd.id)
+        .collect();
+    let mut local_locked_items: Vec<proc_macro2::TokenStream> = Vec::new();
+    if !local_locked_ids.is_empty() {
+        local_locked_items.push(quote! { #(#local_locked_ids)|* => true });
+    }
+
+    let mut command_helps: Vec<proc_macro2::TokenStream> = commands
+        .iter()
+        .chain(part_commands.iter().flatten())
+        .map(|cmd| {
+            let command_id = cmd.id;
+            let help = cmd.help();
+            let cfg = &cmd.cfg;
+            quote! { #(#cfg)* #command_id => Some(#help) }
+        })
+        .collect();
+
+    // The commands of a part are executed by the method generated for it.
+    for (name, cmds) in part_names.iter().zip(&part_commands) {
+        let Some(start) = cmds.first().map(|cmd| cmd.id)
+        else {
+            continue;
+        };
+        let end = start + cmds.len();
+        let execute = format_ident!("__scpi_{}_execute", name);
+        command_items.push(quote! {
+            command_id if (#start..#end).contains(&command_id) => {
+                self.#execute(context, command_id - #start, args, response).await
+            }
+        });
+    }
+
+    for (subtree, offset) in subtrees.iter().zip(&subtree_offsets) {
+        let ty = &subtree.ty;
+        let func = &subtree.func;
+        let range = quote! {
+            command_id if ((#offset)..(#offset) + <#ty as ::microscpi::Interface>::COMMAND_COUNT)
+                .contains(&command_id)
+        };
+        let execute = quote! {
+            ::microscpi::Interface::execute_command(
+                self.#func(), context, command_id - (#offset), args, response
+            ).await
+        };
+        // The local operation of the interface also locks the commands of its
+        // nested interfaces.
+        let execute = if config.remote_commands {
+            quote! {
+                if <#ty as ::microscpi::Interface>::is_local_locked(command_id - (#offset))
+                    && ::microscpi::Interface::is_local(self)
+                {
+                    Err(::microscpi::Error::InvalidWhileInLocal)
+                }
+                else {
+                    #execute
+                }
+            }
+        }
+        else {
+            execute
+        };
+        command_items.push(quote! {
+            #range => {
+                #execute
+            }
+        });
+        local_locked_items.push(quote! {
+            #range => <#ty as ::microscpi::Interface>::is_local_locked(command_id - (#offset))
+        });
+        command_names.push(quote! {
+            #range => <#ty as ::microscpi::Interface>::command_name(command_id - (#offset))
+        });
+        command_helps.push(quote! {
+            #range => <#ty as ::microscpi::Interface>::command_help(command_id - (#offset))
+        });
+    }
 
     let mut nodes: Vec<proc_macro2::TokenStream> = Vec::new();
 
@@ -242,14 +2246,22 @@ pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
             quote!((#name, &#reference))
         });
 
-        let command = if let Some(command_id) = cmd_node.command.map(|cmd_def| cmd_def.id) {
-            quote! { Some(#command_id) }
-        }
-        else {
-            quote! { None }
-        };
-        let query = if let Some(command_id) = cmd_node.query.map(|cmd_def| cmd_def.id) {
-            quote! { Some(#command_id) }
+        let command = node_entry(cmd_node.command.as_deref());
+        let query = node_entry(cmd_node.query.as_deref());
+
+        let subtree = if let Some(subtree) = &cmd_node.subtree {
+            let ty = &subtree.ty;
+            let offset = subtrees
+                .iter()
+                .position(|other| Rc::ptr_eq(other, subtree))
+                .map(|idx| &subtree_offsets[idx])
+                .unwrap();
+            quote! {
+                Some(::microscpi::tree::Subtree {
+                    root: <#ty as ::microscpi::Interface>::ROOT_NODE,
+                    offset: #offset
+                })
+            }
         }
         else {
             quote! { None }
@@ -261,20 +2273,53 @@ pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
                     #(#entries),*
                 ],
                 command: #command,
-                query: #query
+                query: #query,
+                subtree: #subtree
             };
         };
 
         nodes.push(node_item);
+
+        // The nested tree is only known to the compiler, so commands of the
+        // interface shadowing commands of the subtree are rejected by a
+        // constant assertion.
+        if let Some(subtree) = &cmd_node.subtree {
+            let ty = &subtree.ty;
+            let message = format!(
+                "The nested interface mounted at `{}` is shadowed by commands of the interface \
+                 with the same path",
+                subtree.path.name
+            );
+            nodes.push(quote_spanned! { subtree.func.span() =>
+                const _: () = ::core::assert!(
+                    !#node_name.shadows(<#ty as ::microscpi::Interface>::ROOT_NODE),
+                    #message
+                );
+            });
+        }
     }
 
     let mut interface_impl: ItemImpl = syn::parse_quote! {
         impl ::microscpi::Interface for #impl_ty {
-            fn root_node(&self) -> &'static ::microscpi::Node {
+            type Context = #context_ty;
+
+            // The nodes are scoped to the initializer, so several interfaces
+            // can be defined in the same module.
+            const ROOT_NODE: &'static ::microscpi::Node = {
+                #(#nodes)*
                 &SCPI_NODE_0
+            };
+            const COMMAND_COUNT: ::microscpi::CommandId = #command_count;
+
+            fn command_name(command_id: ::microscpi::CommandId) -> Option<&'static str> {
+                match command_id {
+                    #(#command_names,)*
+                    _ => None
+                }
             }
             async fn execute_command<'a>(
                 &'a mut self,
+                context: &mut Self::Context,
                 command_id: ::microscpi::CommandId,
                 args: &[::microscpi::Value<'a>],
                 response: &mut impl ::microscpi::Write
@@ -288,13 +2333,478 @@ pub fn interface(attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     };
 
-    // Copy the generics from the main implementation
+    if config.learn {
+        learn_statements.extend(part_names.iter().map(|name| {
+            let learn = format_ident!("__scpi_{}_learn", name);
+            quote! { self.#learn(first, response).await?; }
+        }));
+        interface_impl.items.push(syn::parse_quote! {
+            async fn learn(
+                &mut self,
+                response: &mut impl ::microscpi::Write
+            ) -> Result<(), ::microscpi::Error> {
+                use ::microscpi::Response;
+                #[allow(unused_variables)]
+                let first = &mut true;
+                #(#learn_statements)*
+                Ok(())
+            }
+        });
+    }
+
+    if config.help {
+        interface_impl.items.push(syn::parse_quote! {
+            fn command_help(command_id: ::microscpi::CommandId) -> Option<&'static str> {
+                match command_id {
+                    #(#command_helps,)*
+                    _ => None
+                }
+            }
+        });
+    }
+
+    if config.format_commands {
+        interface_impl.items.push(syn::parse_quote! {
+            fn byte_order(&mut self) -> Option<::microscpi::ByteOrder> {
+                Some(::microscpi::FormatCommands::data_format(self).byte_order())
+            }
+        });
+    }
+
+    if !local_locked_items.is_empty() {
+        interface_impl.items.push(syn::parse_quote! {
+            fn is_local_locked(command_id: ::microscpi::CommandId) -> bool {
+                match command_id {
+                    #(#local_locked_items,)*
+                    _ => false
+                }
+            }
+        });
+    }
+
+    if config.remote_commands {
+        interface_impl.items.push(syn::parse_quote! {
+            fn is_local(&mut self) -> bool {
+                *::microscpi::RemoteCommands::remote_state(self) == ::microscpi::RemoteState::Local
+            }
+        });
+    }
+
+    if config.macro_commands {
+        interface_impl.items.push(syn::parse_quote! {
+            fn macro_body(&mut self, label: &[u8]) -> Option<&[u8]> {
+                let store = ::microscpi::MacroCommands::macro_store(self);
+                if ::microscpi::MacroStore::enabled(store) {
+                    ::microscpi::MacroStore::body(store, label)
+                }
+                else {
+                    None
+                }
+            }
+        });
+    }
+
+    if let Some(reset_command) = reset_command {
+        interface_impl.items.push(syn::parse_quote! {
+            const RESET_COMMAND: Option<::microscpi::CommandId> = Some(#reset_command);
+        });
+    }
+
+    if config.status_commands {
+        let clear_errors = config.error_commands.then(|| {
+            quote! {
+                ::microscpi::ErrorQueue::clear(::microscpi::ErrorCommands::error_queue(self));
+            }
+        });
+        let error_available = config.error_commands.then(|| {
+            quote! {
+                let error_count = ::microscpi::ErrorQueue::error_count(
+                    ::microscpi::ErrorCommands::error_queue(self)
+                );
+                ::microscpi::StatusCommands::status_registers(self)
+                    .set_error_available(error_count > 0);
+            }
+        });
+        interface_impl.items.push(syn::parse_quote! {
+            fn update_status(&mut self) {
+                #error_available
+                ::microscpi::StatusCommands::check_service_request(self);
+            }
+        });
+        interface_impl.items.push(syn::parse_quote! {
+            fn clear_status(&mut self) -> Result<(), ::microscpi::Error> {
+                ::microscpi::StatusCommands::clear_event_status(self);
+                #clear_errors
+                Ok(())
+            }
+        });
+        interface_impl.items.push(syn::parse_quote! {
+            fn set_output_pending(&mut self, pending: bool) {
+                ::microscpi::StatusCommands::status_registers(self).set_output_pending(pending);
+            }
+        });
+    }
+
+    let cancel_operation_complete =
+        (config.status_commands && reset_command.is_some()).then(|| {
+            quote! {
+                if Some(command_id) == <Self as ::microscpi::Interface>::RESET_COMMAND {
+                    ::microscpi::StatusCommands::status_registers(self).disarm_operation_complete();
+                }
+            }
+        });
+    if config.statistics_commands || cancel_operation_complete.is_some() {
+        let record_statistics = config.statistics_commands.then(|| {
+            quote! {
+                let name = <Self as ::microscpi::Interface>::command_name(command_id);
+                ::microscpi::StatisticsCommands::statistics(self).record_command(name);
+            }
+        });
+        interface_impl.items.push(syn::parse_quote! {
+            fn record_command(&mut self, command_id: ::microscpi::CommandId) {
+                #record_statistics
+                #cancel_operation_complete
+            }
+        });
+    }
+
+    if config.statistics_commands || config.status_commands {
+        let record_statistics = config.statistics_commands.then(|| {
+            quote! { ::microscpi::StatisticsCommands::statistics(self).record_error(); }
+        });
+        let record_event = config.status_commands.then(|| {
+            quote! {
+                ::microscpi::StatusCommands::status_registers(self)
+                    .set_event(::microscpi::EventStatus::from_error(&error));
+            }
+        });
+        interface_impl.items.push(syn::parse_quote! {
+            fn record_error(&mut self, error: ::microscpi::Error) {
+                #record_statistics
+                #record_event
+            }
+        });
+    }
+
+    if let Some(timeout) = &config.timeout {
+        interface_impl.items.push(syn::parse_quote! {
+            fn timeout(duration_ms: u32) -> impl ::core::future::Future<Output = ()> {
+                Self::#timeout(duration_ms)
+            }
+        });
+    }
+
+    if let Some(on_device_clear) = &config.on_device_clear {
+        interface_impl.items.push(syn::parse_quote! {
+            fn on_device_clear(&mut self) {
+                self.#on_device_clear();
+            }
+        });
+    }
+
+    if let Some(protection_unlocked) = &config.protection_unlocked {
+        interface_impl.items.push(syn::parse_quote! {
+            fn protection_unlocked(&self) -> bool {
+                self.#protection_unlocked()
+            }
+        });
+    }
+
+    // Copy the generics from the main implementation and add the bounds of the
+    // argument and response types depending on them, including those of the
+    // parts, whose methods require them as well.
     interface_impl.generics = input_impl.generics.clone();
+    add_generic_bounds(
+        interface_impl.generics.make_where_clause(),
+        commands.iter().chain(part_commands.iter().flatten()),
+        &generic_params,
+    );
 
-    quote! {
-        #(#nodes)*
+    // The arguments are checked against the local copy of the capacity, so the
+    // generated code asserts that it still fits the parser.
+    let max_args = commands
+        .iter()
+        .chain(part_commands.iter().flatten())
+        .map(|cmd| cmd.args.len())
+        .max()
+        .unwrap_or(0);
+
+    Ok(quote! {
         #input_impl
         #interface_impl
+
+        const _: () = ::core::assert!(
+            #max_args <= ::microscpi::MAX_ARGS,
+            "A handler takes more arguments than `microscpi::MAX_ARGS`"
+        );
+    })
+}
+
+/// Macro attribute to define a part of an SCPI interface.
+///
+/// The commands of an `impl` block marked with `#[interface_part(Name)]` are
+/// merged into the command tree of the interface that lists the part with
+/// `#[interface(parts(path::to::Name))]`, so the commands of an interface can
+/// be split across several modules. The part has to be defined in the same
+/// crate as the interface and its name has to be unique among the parts of
+/// the interface.
+///
+/// The `prefix` option registers all commands of the part below a path, e.g.
+/// `#[interface_part(Voltage, prefix = "SENSe:VOLTage")]`. The commands of
+/// a part can't mount nested interfaces, and `*LRN?` only contains the
+/// commands whose query is defined in the same part.
+#[proc_macro_attribute]
+pub fn interface_part(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = proc_macro2::TokenStream::from(attr);
+    let mut input_impl = parse_macro_input!(item as ItemImpl);
+
+    let (name, prefix) = match PartDefinition::parse_attr(attr.clone()) {
+        Ok(parsed) => parsed,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    // The interface parses the commands of the part again from its metadata,
+    // which only requires the signatures of the functions.
+    let mut metadata = input_impl.clone();
+    metadata.items.retain_mut(|item| match item {
+        syn::ImplItem::Fn(item_fn) => {
+            item_fn.block = syn::parse_quote!({});
+            true
+        }
+        _ => false,
+    });
+
+    let commands = match extract_commands(&mut input_impl, prefix.as_ref(), 0) {
+        Ok((_, subtrees)) if !subtrees.is_empty() => {
+            return syn::Error::new(
+                subtrees[0].func.span(),
+                "An interface part can't mount a subtree",
+            )
+            .to_compile_error()
+            .into();
+        }
+        Ok((commands, _)) => commands,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    // A part does not know whether its interface selects a byte order, it is
+    // queried at runtime instead.
+    let command_items = commands.iter().map(|cmd| cmd.call(true));
+    let learn_statements = learn_statements(&commands);
+    let execute = format_ident!("__scpi_{}_execute", name);
+    let learn = format_ident!("__scpi_{}_learn", name);
+
+    // The methods of the part require the bounds of the argument and response
+    // types depending on the generic parameters, like the interface itself.
+    let mut where_clause: syn::WhereClause = syn::parse_quote! {
+        where Self: ::microscpi::Interface
+    };
+    add_generic_bounds(
+        &mut where_clause,
+        &commands,
+        &generic_params(&input_impl.generics),
+    );
+
+    input_impl.items.push(syn::parse_quote! {
+        #[doc(hidden)]
+        #[allow(dead_code, non_snake_case, unused_variables)]
+        pub async fn #execute<'a>(
+            &'a mut self,
+            context: &mut <Self as ::microscpi::Interface>::Context,
+            command_id: ::microscpi::CommandId,
+            args: &[::microscpi::Value<'a>],
+            response: &mut impl ::microscpi::Write
+        ) -> Result<(), ::microscpi::Error> #where_clause {
+            use ::microscpi::Response;
+            match command_id {
+                #(#command_items,)*
+                _ => Err(::microscpi::Error::UndefinedHeader)
+            }
+        }
+    });
+
+    input_impl.items.push(syn::parse_quote! {
+        #[doc(hidden)]
+        #[allow(dead_code, non_snake_case, unused_variables)]
+        pub async fn #learn(
+            &mut self,
+            first: &mut bool,
+            response: &mut impl ::microscpi::Write
+        ) -> Result<(), ::microscpi::Error> #where_clause {
+            use ::microscpi::Response;
+            #(#learn_statements)*
+            Ok(())
+        }
+    });
+
+    // The macro of the part appends its metadata to the input and passes it to
+    // the macro of the next part, or eventually to `__interface_parts`.
+    let macro_name = format_ident!("__scpi_part_{}", name);
+    quote! {
+        #input_impl
+
+        #[doc(hidden)]
+        macro_rules! #macro_name {
+            ({ $($next:tt)* } $($rest:tt)*) => {
+                $($next)*! { $($rest)* { (#attr) #metadata } }
+            };
+        }
+
+        #[allow(unused_imports)]
+        pub(crate) use #macro_name as #name;
+    }
+    .into()
+}
+
+/// Macro attribute to define a reusable set of SCPI commands.
+///
+/// The methods of a trait marked with `#[command_set]` define commands with
+/// the `scpi` attribute, like the handlers of an interface. An interface that
+/// implements the trait registers these commands with the `use` option, e.g.
+/// `#[interface(use(lan::LanCommands))]`, so a command set can be shared
+/// between interfaces and crates. The names of the command sets have to be
+/// unique within a crate. The arguments of the commands are converted by
+/// hidden functions that are added to the trait, so the argument types only
+/// have to be in scope where the trait is defined.
+///
+/// The commands of a command set can't mount nested interfaces.
+#[proc_macro_attribute]
+pub fn command_set(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut input_trait = parse_macro_input!(item as syn::ItemTrait);
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::TokenStream::from(attr).span(),
+            "A command set has no options",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    // The interface parses the commands of the set from its metadata, which
+    // contains the signatures of the methods in an `impl` block.
+    let functions = input_trait.items.iter().filter_map(|item| match item {
+        syn::TraitItem::Fn(item_fn) => Some(ImplItemFn {
+            attrs: item_fn.attrs.clone(),
+            vis: syn::Visibility::Inherited,
+            defaultness: None,
+            sig: item_fn.sig.clone(),
+            block: syn::parse_quote!({}),
+        }),
+        _ => None,
+    });
+    let metadata: ItemImpl = syn::parse_quote! {
+        impl CommandSet {
+            #(#functions)*
+        }
+    };
+
+    let commands = match extract_commands(&mut metadata.clone(), None, 0) {
+        Ok((_, subtrees)) if !subtrees.is_empty() => {
+            return syn::Error::new(
+                subtrees[0].func.span(),
+                "A command set can't mount a subtree",
+            )
+            .to_compile_error()
+            .into();
+        }
+        Ok((commands, _)) => commands,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    for item in input_trait.items.iter_mut() {
+        if let syn::TraitItem::Fn(item_fn) = item {
+            item_fn.attrs.retain(|attr| !attr.path().is_ident("scpi"));
+        }
+    }
+
+    // The argument types may not be in scope in the crate of the interface,
+    // so the trait provides the conversions of the arguments.
+    let lifetime: syn::Lifetime = syn::parse_quote!('value);
+    let mut conversions: Vec<Ident> = Vec::new();
+    for cmd in &commands {
+        let CommandHandler::UserFunction(func) = &cmd.handler
+        else {
+            continue;
+        };
+        for (id, ty) in cmd.args.iter().enumerate() {
+            let conversion = set_conversion(func, id);
+            if conversions.contains(&conversion) {
+                continue;
+            }
+            let ty = if is_optional(ty) {
+                type_args(ty).first().copied().unwrap_or(ty)
+            }
+            else {
+                ty
+            };
+            let ty = with_lifetime(ty, &lifetime);
+            input_trait.items.push(syn::parse_quote! {
+                #[doc(hidden)]
+                fn #conversion<#lifetime>(
+                    value: &#lifetime ::microscpi::Value<#lifetime>
+                ) -> Result<#ty, ::microscpi::Error>
+                where
+                    Self: Sized
+                {
+                    ::core::convert::TryInto::<#ty>::try_into(value).map_err(::microscpi::Error::from)
+                }
+            });
+            conversions.push(conversion);
+        }
+    }
+
+    // The macro is exported, so the command set can be used by other crates.
+    // It is re-exported next to the trait under the name of the trait.
+    let name = &input_trait.ident;
+    let vis = &input_trait.vis;
+    let macro_name = format_ident!("__scpi_command_set_{}", name);
+    quote! {
+        #input_trait
+
+        #[doc(hidden)]
+        #[macro_export]
+        macro_rules! #macro_name {
+            ({ $($next:tt)* } $($rest:tt)*) => {
+                $($next)*! { $($rest)* { command_set #metadata } }
+            };
+        }
+
+        #[doc(hidden)]
+        #[allow(unused_imports)]
+        #vis use #macro_name as #name;
+    }
+    .into()
+}
+
+/// Derives an `ErrorHandler` that ignores all errors.
+#[proc_macro_derive(IgnoreErrors)]
+pub fn derive_ignore_errors(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::microscpi::ErrorHandler for #ident #ty_generics #where_clause {
+            fn handle_error(&mut self, _error: ::microscpi::Error) {}
+        }
+    }
+    .into()
+}
+
+/// Derives an `ErrorHandler` that emits all errors through the logging facade
+/// enabled by the `log` or `defmt` feature.
+#[proc_macro_derive(LogErrors)]
+pub fn derive_log_errors(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as syn::DeriveInput);
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::microscpi::ErrorHandler for #ident #ty_generics #where_clause {
+            fn handle_error(&mut self, error: ::microscpi::Error) {
+                ::microscpi::log_error(error);
+            }
+        }
     }
     .into()
 }
@@ -1,25 +1,81 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::CommandDefinition;
+use crate::command::{Command, CommandPath};
+use crate::{CommandDefinition, SubtreeDefinition};
 
-#[derive(Debug)]
 pub enum Error {
-    CommandExists,
-    QueryExists,
+    /// A path is already defined by another command or query.
+    Duplicate {
+        existing: Rc<CommandDefinition>,
+        path: CommandPath,
+    },
+    SubtreeConflict,
 }
 
-impl std::error::Error for Error {}
-
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match *self {
-            Error::CommandExists => write!(f, "Command already exists"),
-            Error::QueryExists => write!(f, "Query already exists"),
+        match self {
+            Error::Duplicate { existing, .. } if existing.command.is_query() => {
+                write!(f, "Query already exists")
+            }
+            Error::Duplicate { .. } => write!(f, "Command already exists"),
+            Error::SubtreeConflict => write!(f, "Subtree already mounted at this path"),
         }
     }
 }
 
+impl Error {
+    /// Converts the error of inserting a command into an error at its
+    /// definition. A duplicate path is also reported at the definition of the
+    /// existing command.
+    pub fn to_syn_error(&self, cmd: &CommandDefinition) -> syn::Error {
+        let Error::Duplicate { existing, path } = self
+        else {
+            return syn::Error::new(cmd.span, self);
+        };
+
+        let mut header = path.join(":");
+        if cmd.command.is_query() {
+            header.push('?');
+        }
+        // Describes the name or alias of a command that contains the path.
+        let describe = |cmd: &CommandDefinition| {
+            std::iter::once(&cmd.command)
+                .chain(&cmd.aliases)
+                .find_map(|command: &Command| {
+                    let form = command.path_form(path)?;
+                    Some(format!("`{}` ({form})", command.name))
+                })
+                .unwrap_or_else(|| format!("`{}`", cmd.command.name))
+        };
+
+        if std::ptr::eq(existing.as_ref(), cmd) {
+            return syn::Error::new(
+                cmd.span,
+                format!(
+                    "{self}: `{header}` is defined twice by {} and its aliases",
+                    describe(cmd)
+                ),
+            );
+        }
+
+        let mut error = syn::Error::new(
+            cmd.span,
+            format!(
+                "{self}: `{header}` of {} is already defined by {}",
+                describe(cmd),
+                describe(existing)
+            ),
+        );
+        error.combine(syn::Error::new(
+            existing.span,
+            format!("`{header}` is first defined here"),
+        ));
+        error
+    }
+}
+
 type NodeId = usize;
 
 pub struct Tree {
@@ -31,6 +87,7 @@ pub struct TreeNode {
     pub children: HashMap<String, NodeId>,
     pub command: Option<Rc<CommandDefinition>>,
     pub query: Option<Rc<CommandDefinition>>,
+    pub subtree: Option<Rc<SubtreeDefinition>>,
 }
 
 impl Tree {
@@ -40,60 +97,65 @@ impl Tree {
         }
     }
 
+    /// Inserts a command at the paths of its name and its aliases.
     pub fn insert(&mut self, cmd: Rc<CommandDefinition>) -> Result<(), Error> {
-        cmd.command
-            .paths()
-            .iter()
-            .try_for_each(|path| self.insert_at(0, path, cmd.clone()))
+        std::iter::once(&cmd.command)
+            .chain(&cmd.aliases)
+            .flat_map(|command| command.paths())
+            .try_for_each(|path| self.insert_at(0, &path, cmd.clone()))
     }
 
-    fn insert_at(
-        &mut self, id: NodeId, path: &[String], cmd: Rc<CommandDefinition>,
-    ) -> Result<(), Error> {
-        use std::collections::hash_map::Entry;
+    pub fn insert_subtree(&mut self, subtree: Rc<SubtreeDefinition>) -> Result<(), Error> {
+        subtree.path.paths().iter().try_for_each(|path| {
+            let node_id = self.node_at(0, path);
+            let node = self.items.get_mut(&node_id).unwrap();
+            if node.subtree.is_some() {
+                return Err(Error::SubtreeConflict);
+            }
+            node.subtree = Some(subtree.clone());
+            Ok(())
+        })
+    }
 
-        if let Some(part) = path.first() {
+    /// Returns the id of the node at the path below the node `id`, inserting
+    /// the missing nodes.
+    fn node_at(&mut self, id: NodeId, path: &[String]) -> NodeId {
+        path.iter().fold(id, |id, part| {
             let next_id = self.items.len();
-
-            let entry = self
+            let node_id = *self
                 .items
                 .get_mut(&id)
                 .unwrap()
                 .children
-                .entry(part.clone());
-
-            let node_id = match entry {
-                Entry::Occupied(o) => *o.get(),
-                Entry::Vacant(v) => {
-                    v.insert(next_id);
-                    next_id
-                }
-            };
+                .entry(part.clone())
+                .or_insert(next_id);
 
             // A new node has to be inserted.
             if node_id == next_id {
                 self.items.insert(next_id, TreeNode::default());
             }
+            node_id
+        })
+    }
 
-            self.insert_at(node_id, &path[1..], cmd)?;
+    fn insert_at(
+        &mut self, id: NodeId, path: &[String], cmd: Rc<CommandDefinition>,
+    ) -> Result<(), Error> {
+        let node_id = self.node_at(id, path);
+        let node = self.items.get_mut(&node_id).unwrap();
+        let entry = if cmd.command.is_query() {
+            &mut node.query
         }
         else {
-            let node = self.items.get_mut(&id).unwrap();
-            if cmd.command.is_query() {
-                if let Some(_existing) = &node.query {
-                    return Err(Error::QueryExists);
-                }
-                else {
-                    node.query = Some(cmd)
-                }
-            }
-            else if let Some(_existing) = &node.command {
-                return Err(Error::CommandExists);
-            }
-            else {
-                node.command = Some(cmd)
-            }
+            &mut node.command
+        };
+        if let Some(existing) = entry {
+            return Err(Error::Duplicate {
+                existing: existing.clone(),
+                path: path.to_vec(),
+            });
         }
+        *entry = Some(cmd);
         Ok(())
     }
 }
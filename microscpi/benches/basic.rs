@@ -21,11 +21,13 @@ impl ExampleInterface {
 }
 
 fn criterion_benchmark(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
     let mut interface = ExampleInterface { value: 42 };
     let mut output = Vec::new();
     c.bench_function("system_value", |b| {
         b.iter(|| {
-            let _ = black_box(interface.run(b"SYSTEM:VAL?\n", &mut output));
+            output.clear();
+            black_box(runtime.block_on(interface.run(b"SYSTEM:VAL?\n", &mut output)));
         })
     });
 }
@@ -7,26 +7,30 @@ static ROOT_NODE: Node = Node {
     children: &[("*IDN", &IDN_NODE), ("SYST", &SYST_NODE)],
     command: None,
     query: None,
+    subtree: None,
 };
 
 static IDN_NODE: Node = Node {
     children: &[],
     command: None,
     query: None,
+    subtree: None,
 };
 
 static SYST_NODE: Node = Node {
     children: &[("ERR", &ERR_NODE)],
     command: None,
     query: None,
+    subtree: None,
 };
 
 static ERR_NODE: Node = Node {
     children: &[],
     command: None,
     query: None,
+    subtree: None,
 };
 
 fuzz_target!(|data: &[u8]| {
-    let _ = microscpi::parser::parse(&ROOT_NODE, &ROOT_NODE, data);
+    let _ = microscpi::parser::parse(&ROOT_NODE, &ROOT_NODE, 0, data);
 });
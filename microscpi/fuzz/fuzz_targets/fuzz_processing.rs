@@ -5,7 +5,8 @@ use std::sync::OnceLock;
 
 use libfuzzer_sys::fuzz_target;
 use microscpi::{
-    self as scpi, Adapter, ErrorCommands, ErrorQueue, Interface, StandardCommands, StaticErrorQueue,
+    self as scpi, AdapterErrorType, AdapterRead, AdapterWrite, ErrorCommands, ErrorQueue,
+    Interface, StandardCommands, StaticErrorQueue,
 };
 use tokio::runtime::Runtime;
 
@@ -19,9 +20,11 @@ impl<'a> FuzzAdapter<'a> {
     }
 }
 
-impl<'a> Adapter for FuzzAdapter<'a> {
+impl AdapterErrorType for FuzzAdapter<'_> {
     type Error = ();
+}
 
+impl AdapterRead for FuzzAdapter<'_> {
     async fn read(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error> {
         match self.0.read(dst) {
             Ok(0) => Err(()),
@@ -29,7 +32,9 @@ impl<'a> Adapter for FuzzAdapter<'a> {
             Err(_) => Err(()),
         }
     }
+}
 
+impl AdapterWrite for FuzzAdapter<'_> {
     async fn write(&mut self, _src: &[u8]) -> Result<(), Self::Error> {
         // Ignore
         Ok(())
@@ -0,0 +1,137 @@
+//! This module contains implementations of the [Adapter](crate::Adapter)
+//! trait for common I/O types.
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{AdapterErrorType, AdapterRead, AdapterWrite};
+
+/// Adapter for a tokio byte stream, e.g. a [tokio::net::TcpStream].
+///
+/// The end of the stream ends [crate::Interface::process] without an error.
+///
+/// ```no_run
+/// # use microscpi::{self as scpi, Interface};
+/// # struct ExampleInterface;
+/// # impl scpi::ErrorHandler for ExampleInterface {
+/// #     fn handle_error(&mut self, _error: scpi::Error) {}
+/// # }
+/// # #[scpi::interface]
+/// # impl ExampleInterface {
+/// #     #[scpi(cmd = "*IDN?")]
+/// #     async fn idn(&mut self) -> Result<&str, scpi::Error> {
+/// #         Ok("EXAMPLE")
+/// #     }
+/// # }
+/// # async fn example() -> std::io::Result<()> {
+/// let listener = tokio::net::TcpListener::bind("0.0.0.0:5025").await?;
+/// let (stream, _) = listener.accept().await?;
+///
+/// let mut interface = ExampleInterface;
+/// interface
+///     .process::<256, _>(&mut scpi::TokioAdapter::new(stream))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+pub struct TokioAdapter<T> {
+    stream: T,
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncRead + AsyncWrite + Unpin> TokioAdapter<T> {
+    pub fn new(stream: T) -> Self {
+        TokioAdapter { stream }
+    }
+
+    /// Returns the underlying stream.
+    pub fn into_inner(self) -> T {
+        self.stream
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncRead + AsyncWrite + Unpin> AdapterErrorType for TokioAdapter<T> {
+    type Error = std::io::Error;
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncRead + AsyncWrite + Unpin> AdapterRead for TokioAdapter<T> {
+    async fn read(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.stream.read(dst).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: AsyncRead + AsyncWrite + Unpin> AdapterWrite for TokioAdapter<T> {
+    async fn write(&mut self, src: &[u8]) -> Result<(), Self::Error> {
+        self.stream.write_all(src).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.stream.flush().await
+    }
+}
+
+/// Adapter for blocking I/O types, e.g. a serial port.
+///
+/// The input is read from `reader` and the responses are written to `writer`.
+/// The end of the input ends [crate::Interface::process] without an error.
+/// Use it with [crate::Interface::process_blocking] to process commands
+/// without an async runtime.
+pub struct BlockingAdapter<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: std::io::Read, W: std::io::Write> BlockingAdapter<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        BlockingAdapter { reader, writer }
+    }
+
+    /// Returns the underlying reader and writer.
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+}
+
+impl<R, W> AdapterErrorType for BlockingAdapter<R, W> {
+    type Error = std::io::Error;
+}
+
+impl<R: std::io::Read, W> AdapterRead for BlockingAdapter<R, W> {
+    async fn read(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            match self.reader.read(dst) {
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<R, W: std::io::Write> AdapterWrite for BlockingAdapter<R, W> {
+    async fn write(&mut self, src: &[u8]) -> Result<(), Self::Error> {
+        self.writer.write_all(src)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.writer.flush()
+    }
+}
+
+/// Runs a future to completion on the current thread.
+///
+/// The future is polled with a waker that does nothing, so a pending future is
+/// polled again immediately. This only suits futures that do not wait for
+/// external events.
+pub(crate) fn block_on<F: core::future::Future>(future: F) -> F::Output {
+    let mut future = core::pin::pin!(future);
+    let mut context = core::task::Context::from_waker(core::task::Waker::noop());
+
+    loop {
+        if let core::task::Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+    }
+}
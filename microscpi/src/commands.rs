@@ -1,5 +1,10 @@
 //! This module containts implementations of SCPI standard commands.
-use crate::{Characters, Error, ErrorHandler, ErrorQueue, SCPI_STD_VERSION};
+#[cfg(feature = "command-macros")]
+use crate::{Arbitrary, MacroStore};
+use crate::{
+    ByteOrder, Characters, DataFormat, DataType, Error, ErrorHandler, ErrorQueue, Identity,
+    Interface, Response, Statistics, StatusRegisters, Write, SCPI_STD_VERSION,
+};
 
 /// Error Commands
 ///
@@ -13,6 +18,13 @@ use crate::{Characters, Error, ErrorHandler, ErrorQueue, SCPI_STD_VERSION};
 ///
 /// * `SYSTem:ERRor:[NEXT]?`
 /// * `SYSTem:ERRor:[COUNt]?`
+/// * `SYSTem:ERRor:ALL?`
+/// * `SYSTem:ERRor:DROPped?` (with the `dropped_errors` option)
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `ErrorCommands`",
+    note = "the `ErrorCommands`, `Ieee4882Device` and `dropped_errors` options of the interface \
+            macro require an implementation of `ErrorCommands`"
+)]
 pub trait ErrorCommands {
     fn error_queue(&mut self) -> &mut impl ErrorQueue;
 
@@ -20,13 +32,33 @@ pub trait ErrorCommands {
         Ok(self.error_queue().error_count())
     }
 
-    fn system_error_next(&mut self) -> Result<(i16, &'static str), Error> {
-        if let Some(error) = self.error_queue().pop_error() {
-            Ok((error.number(), error.into()))
-        }
+    fn system_error_next(&mut self) -> Result<Option<Error>, Error> {
+        Ok(self.error_queue().pop_error())
+    }
+
+    /// Writes all queued errors as a comma-separated list and clears the
+    /// queue. If the queue is empty, `0,"No error"` is written.
+    async fn system_error_all(&mut self, response: &mut impl Write) -> Result<(), Error> {
+        let Some(first) = self.error_queue().pop_error()
         else {
-            Ok((0, ""))
+            return response.write_str("0,\"No error\"").await;
+        };
+
+        first.write_response(response).await?;
+        while let Some(error) = self.error_queue().pop_error() {
+            response.write_char(',').await?;
+            error.write_response(response).await?;
         }
+        Ok(())
+    }
+
+    /// Returns the number of errors lost due to queue overflows and resets
+    /// it.
+    fn system_error_dropped(&mut self) -> Result<usize, Error> {
+        let queue = self.error_queue();
+        let dropped = queue.dropped_count();
+        queue.reset_dropped_count();
+        Ok(dropped)
     }
 }
 
@@ -44,8 +76,826 @@ where
 /// # Implemented commands
 ///
 /// * `SYSTem:VERSion?`
+/// * `SYSTem:CAPability?`
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `StandardCommands`",
+    note = "the `StandardCommands` and `Ieee4882Device` options of the interface macro require an \
+            implementation of `StandardCommands`"
+)]
 pub trait StandardCommands {
-    fn system_version(&mut self) -> Result<Characters, Error> {
-        Ok(Characters(SCPI_STD_VERSION))
+    /// The response of the `SYSTem:VERSion?` query, i.e. the version of the
+    /// SCPI standard the device complies with.
+    const SCPI_VERSION: &'static str = SCPI_STD_VERSION;
+
+    /// The instrument class of the device, e.g. `DCSUPPLY WITH MEASURE`.
+    fn capability(&self) -> &str {
+        "SCPI-99"
+    }
+
+    fn system_version(&mut self) -> Result<Characters<'static>, Error> {
+        Ok(Characters(Self::SCPI_VERSION))
+    }
+
+    fn system_capability(&mut self) -> Result<&str, Error> {
+        Ok(self.capability())
+    }
+}
+
+/// Identity Commands
+///
+/// The [IdentityCommands] trait implements the `*IDN?` query. The only
+/// requirement to implement this trait is to provide the four fields of the
+/// identification. The response is formatted as
+/// `<manufacturer>,<model>,<serial number>,<firmware version>`.
+///
+/// # Implemented commands
+///
+/// * `*IDN?`
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `IdentityCommands`",
+    note = "the `IdentityCommands` and `Ieee4882Device` options of the interface macro require an \
+            implementation of `IdentityCommands`"
+)]
+pub trait IdentityCommands {
+    fn manufacturer(&self) -> &str;
+
+    fn model(&self) -> &str;
+
+    /// The serial number of the device, or `0` if it is not available.
+    fn serial_number(&self) -> &str;
+
+    fn firmware_version(&self) -> &str;
+
+    fn identify(&mut self) -> Result<Identity<'_>, Error> {
+        Ok(Identity {
+            manufacturer: self.manufacturer(),
+            model: self.model(),
+            serial_number: self.serial_number(),
+            firmware_version: self.firmware_version(),
+        })
+    }
+}
+
+/// Reset Commands
+///
+/// The [ResetCommands] trait implements the `*RST` command. The only
+/// requirement to implement this trait is to provide the device specific
+/// reset via the [ResetCommands::reset] method. The error queue and the
+/// status registers are not affected by a reset, except that a pending `*OPC`
+/// is cancelled. A compound header set by a
+/// preceding command of the program message is cleared, so the commands
+/// following `*RST` start at the root of the command tree again.
+///
+/// # Implemented commands
+///
+/// * `*RST`
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `ResetCommands`",
+    note = "the `ResetCommands` and `Ieee4882Device` options of the interface macro require an \
+            implementation of `ResetCommands`"
+)]
+pub trait ResetCommands {
+    /// Sets the device specific functions to a known state.
+    fn reset(&mut self) -> Result<(), Error>;
+
+    /// Executes the `*RST` command.
+    fn reset_device(&mut self) -> Result<(), Error> {
+        self.reset()
+    }
+}
+
+/// Self-Test Commands
+///
+/// The [SelfTestCommands] trait implements the `*TST?` query. The only
+/// requirement to implement this trait is to provide the self-test via the
+/// [SelfTestCommands::self_test] method, which returns `0` if the self-test
+/// passed or a device specific error code otherwise. A failed self-test is
+/// additionally reported as [Error::SelfTestFailed] to the error handler.
+///
+/// # Implemented commands
+///
+/// * `*TST?`
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `SelfTestCommands`",
+    note = "the `SelfTestCommands` and `Ieee4882Device` options of the interface macro require an \
+            implementation of `SelfTestCommands`"
+)]
+pub trait SelfTestCommands: Interface {
+    async fn self_test(&mut self) -> Result<i16, Error>;
+
+    /// Executes the `*TST?` query.
+    async fn self_test_query(&mut self) -> Result<i16, Error> {
+        let result = self.self_test().await?;
+        if result != 0 {
+            self.handle_error(Error::SelfTestFailed);
+            self.record_error(Error::SelfTestFailed);
+        }
+        Ok(result)
+    }
+}
+
+/// Trigger Commands
+///
+/// The [TriggerCommands] trait implements the `*TRG` command. The only
+/// requirement to implement this trait is to provide the trigger action via
+/// the [TriggerCommands::trigger] method. A trigger received while the device
+/// is not armed, as reported by [TriggerCommands::armed], fails with
+/// [Error::TriggerError].
+///
+/// # Implemented commands
+///
+/// * `*TRG`
+pub trait TriggerCommands {
+    async fn trigger(&mut self) -> Result<(), Error>;
+
+    /// Returns whether the device is armed and ready to accept a trigger.
+    fn armed(&mut self) -> bool {
+        true
+    }
+
+    /// Executes the `*TRG` command.
+    async fn trigger_command(&mut self) -> Result<(), Error> {
+        if !self.armed() {
+            return Err(Error::TriggerError);
+        }
+        self.trigger().await
+    }
+}
+
+/// State Commands
+///
+/// The [StateCommands] trait implements the commands to save and recall the
+/// device state. The requirements to implement this trait are the number of
+/// available memory slots [StateCommands::MAX_SLOTS] and the methods to save
+/// and recall the state of a slot. The slot numbers are checked before these
+/// methods are called, so they are always less than
+/// [StateCommands::MAX_SLOTS].
+///
+/// # Implemented commands
+///
+/// * `*SAV <slot>`
+/// * `*RCL <slot>`
+pub trait StateCommands {
+    /// The number of memory slots.
+    const MAX_SLOTS: u8;
+
+    fn save_state(&mut self, slot: u8) -> Result<(), Error>;
+
+    fn recall_state(&mut self, slot: u8) -> Result<(), Error>;
+
+    /// Executes the `*SAV` command.
+    fn save_command(&mut self, slot: u8) -> Result<(), Error> {
+        if slot >= Self::MAX_SLOTS {
+            return Err(Error::DataOutOfRange);
+        }
+        self.save_state(slot)
+    }
+
+    /// Executes the `*RCL` command.
+    fn recall_command(&mut self, slot: u8) -> Result<(), Error> {
+        if slot >= Self::MAX_SLOTS {
+            return Err(Error::DataOutOfRange);
+        }
+        self.recall_state(slot)
+    }
+}
+
+/// Clock Commands
+///
+/// The [ClockCommands] trait implements the commands to set and query the
+/// date and time of the device clock. The requirements to implement this trait
+/// are the methods to read the clock and to set its date and time. The values
+/// are checked before these methods are called, so the date is always a valid
+/// calendar date and the time is always a valid time of day.
+///
+/// # Implemented commands
+///
+/// * `SYSTem:DATE <year>,<month>,<day>`
+/// * `SYSTem:DATE?`
+/// * `SYSTem:TIME <hour>,<minute>,<second>`
+/// * `SYSTem:TIME?`
+pub trait ClockCommands {
+    /// Returns the current date and time as
+    /// `(year, month, day, hour, minute, second)`.
+    fn get_datetime(&mut self) -> Result<(u16, u8, u8, u8, u8, u8), Error>;
+
+    fn set_date(&mut self, year: u16, month: u8, day: u8) -> Result<(), Error>;
+
+    fn set_time(&mut self, hour: u8, minute: u8, second: u8) -> Result<(), Error>;
+
+    /// Executes the `SYSTem:DATE` command.
+    fn system_date(&mut self, year: u16, month: u8, day: u8) -> Result<(), Error> {
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if year.is_multiple_of(4)
+                && (!year.is_multiple_of(100) || year.is_multiple_of(400)) =>
+            {
+                29
+            }
+            2 => 28,
+            _ => return Err(Error::DataOutOfRange),
+        };
+        if !(1..=days).contains(&day) {
+            return Err(Error::DataOutOfRange);
+        }
+        self.set_date(year, month, day)
+    }
+
+    /// Executes the `SYSTem:DATE?` query.
+    fn system_date_query(&mut self) -> Result<(u16, u8, u8), Error> {
+        let (year, month, day, ..) = self.get_datetime()?;
+        Ok((year, month, day))
+    }
+
+    /// Executes the `SYSTem:TIME` command.
+    fn system_time(&mut self, hour: u8, minute: u8, second: u8) -> Result<(), Error> {
+        if hour > 23 || minute > 59 || second > 59 {
+            return Err(Error::DataOutOfRange);
+        }
+        self.set_time(hour, minute, second)
+    }
+
+    /// Executes the `SYSTem:TIME?` query.
+    fn system_time_query(&mut self) -> Result<(u8, u8, u8), Error> {
+        let (.., hour, minute, second) = self.get_datetime()?;
+        Ok((hour, minute, second))
+    }
+}
+
+/// The remote state of a device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RemoteState {
+    /// The device is operated from the front panel. Commands marked with
+    /// `#[scpi(local_locked)]` are rejected.
+    #[default]
+    Local,
+    /// The device is operated by the controller.
+    Remote,
+    /// The device is operated by the controller and the front panel is locked.
+    RemoteWithLockout,
+}
+
+/// Remote Commands
+///
+/// The [RemoteCommands] trait implements the commands to switch between local
+/// and remote operation. The only requirement to implement this trait is to
+/// provide a [RemoteState] via the [RemoteCommands::remote_state] method. The
+/// device starts in [RemoteState::Local], so the commands marked with
+/// `#[scpi(local_locked)]` are rejected with [Error::InvalidWhileInLocal] until
+/// `SYSTem:REMote` or `SYSTem:RWLock` is executed. Queries should not be
+/// marked, as they don't change the state of the device.
+///
+/// # Implemented commands
+///
+/// * `SYSTem:LOCal`
+/// * `SYSTem:REMote`
+/// * `SYSTem:RWLock`
+pub trait RemoteCommands {
+    fn remote_state(&mut self) -> &mut RemoteState;
+
+    /// Executes the `SYSTem:LOCal` command.
+    fn system_local(&mut self) -> Result<(), Error> {
+        *self.remote_state() = RemoteState::Local;
+        Ok(())
+    }
+
+    /// Executes the `SYSTem:REMote` command.
+    fn system_remote(&mut self) -> Result<(), Error> {
+        *self.remote_state() = RemoteState::Remote;
+        Ok(())
+    }
+
+    /// Executes the `SYSTem:RWLock` command.
+    fn system_rwlock(&mut self) -> Result<(), Error> {
+        *self.remote_state() = RemoteState::RemoteWithLockout;
+        Ok(())
+    }
+}
+
+/// Macro Commands
+///
+/// The [MacroCommands] trait implements the IEEE 488.2 commands to define
+/// macros, which expand to a sequence of commands when their label is received
+/// as a header. The only requirement to implement this trait is to provide a
+/// [MacroStore] via the [MacroCommands::macro_store]
+/// method. This crate contains an implementation based on a statically
+/// allocated data structure: [crate::StaticMacroStore].
+///
+/// Macros are expanded only while they are enabled with `*EMC 1`. A macro
+/// label takes precedence over a command with the same header, but labels of
+/// common commands are rejected. Errors while parsing the expanded commands
+/// are reported as [Error::MacroExecutionError], as are expansions exceeding
+/// [MACRO_EXPANSION_SIZE](crate::MACRO_EXPANSION_SIZE) bytes. Macros nested
+/// deeper than [MAX_MACRO_DEPTH](crate::MAX_MACRO_DEPTH), e.g. macros
+/// expanding each other endlessly, are reported as
+/// [Error::MacroRecursionError]. Macro parameters are not supported.
+///
+/// This trait is only available with the `command-macros` feature.
+///
+/// # Implemented commands
+///
+/// * `*DMC <label>,<block>`
+/// * `*EMC <enable>`
+/// * `*EMC?`
+/// * `*GMC? <label>`
+/// * `*LMC?`
+/// * `*PMC`
+#[cfg(feature = "command-macros")]
+pub trait MacroCommands {
+    fn macro_store(&mut self) -> &mut impl MacroStore;
+
+    /// Executes the `*DMC` command.
+    fn define_macro(&mut self, label: &str, body: &[u8]) -> Result<(), Error> {
+        self.macro_store().define(label, body)
+    }
+
+    /// Executes the `*EMC` command.
+    fn enable_macros(&mut self, enable: i32) -> Result<(), Error> {
+        self.macro_store().set_enabled(enable != 0);
+        Ok(())
+    }
+
+    /// Executes the `*EMC?` query.
+    fn macros_enabled(&mut self) -> Result<bool, Error> {
+        Ok(self.macro_store().enabled())
+    }
+
+    /// Executes the `*GMC?` query.
+    fn get_macro(&mut self, label: &str) -> Result<Arbitrary<'_>, Error> {
+        self.macro_store()
+            .body(label.as_bytes())
+            .map(Arbitrary)
+            .ok_or(Error::MacroHeaderNotFound)
+    }
+
+    /// Executes the `*LMC?` query, which responds with the labels of all
+    /// macros as strings.
+    async fn list_macros(&mut self, response: &mut impl Write) -> Result<(), Error> {
+        let mut labels = self.macro_store().labels().peekable();
+        if labels.peek().is_none() {
+            return response.write_str("\"\"").await;
+        }
+        for (index, label) in labels.enumerate() {
+            if index > 0 {
+                response.write_char(',').await?;
+            }
+            label.write_response(response).await?;
+        }
+        Ok(())
+    }
+
+    /// Executes the `*PMC` command.
+    fn purge_macros(&mut self) -> Result<(), Error> {
+        self.macro_store().purge();
+        Ok(())
+    }
+}
+
+/// Format Commands
+///
+/// The [FormatCommands] trait implements the commands to select the data
+/// format of array responses. The only requirement to implement this trait is
+/// to provide a [DataFormat] via the [FormatCommands::data_format] method. The
+/// selected format can then be passed to a [crate::DataArray] response. Block
+/// data arguments of the handlers, i.e. [crate::BlockData], are decoded in the
+/// byte order selected by `FORMat:BORDer`.
+///
+/// # Implemented commands
+///
+/// * `FORMat[:DATA] ASCii|REAL|INTeger[,<length>]`
+/// * `FORMat[:DATA]?`
+/// * `FORMat:BORDer NORMal|SWAPped`
+/// * `FORMat:BORDer?`
+pub trait FormatCommands {
+    fn data_format(&mut self) -> &mut DataFormat;
+
+    fn format_data(&mut self, data_type: DataType, length: Option<u8>) -> Result<(), Error> {
+        let format = self.data_format();
+        *format = DataFormat::new(data_type, length)?.with_byte_order(format.byte_order());
+        Ok(())
+    }
+
+    fn format_data_query(&mut self) -> Result<DataFormat, Error> {
+        Ok(*self.data_format())
+    }
+
+    fn format_border(&mut self, byte_order: ByteOrder) -> Result<(), Error> {
+        let format = self.data_format();
+        *format = format.with_byte_order(byte_order);
+        Ok(())
+    }
+
+    fn format_border_query(&mut self) -> Result<ByteOrder, Error> {
+        Ok(self.data_format().byte_order())
+    }
+}
+
+/// Status Commands
+///
+/// The [StatusCommands] trait implements the IEEE 488.2 status reporting
+/// commands. The only requirement to implement this trait is to provide the
+/// [StatusRegisters] via the [StatusCommands::status_registers] method. The
+/// interface keeps the registers informed about pending response data, so the
+/// MAV bit of the status byte is set while a response has not been written to
+/// the controller, and errors set the corresponding bits of the standard event
+/// status register. `*CLS` clears the standard event status register, the
+/// event registers of the operation and questionable status, the MAV bit and,
+/// if the interface has [ErrorCommands], the error queue. A response
+/// that has not been read by the controller before `*CLS` is received is
+/// discarded by [Interface::process]. Whenever the MSS bit of
+/// the status byte is set, [StatusCommands::on_service_request] is called.
+///
+/// Overlapped operations, which continue after their command has been
+/// executed, are registered with [StatusRegisters::begin_operation]. `*WAI`
+/// and `*OPC?` wait until all of them have completed by calling
+/// [StatusCommands::wait_operations], while `*OPC` sets the OPC bit of the
+/// standard event status register once they have completed. `*RST` and `*CLS`
+/// cancel a pending `*OPC`.
+///
+/// # Implemented commands
+///
+/// * `*STB?`
+/// * `*SRE <mask>`
+/// * `*SRE?`
+/// * `*CLS`
+/// * `*ESR?`
+/// * `*ESE <mask>`
+/// * `*ESE?`
+/// * `*OPC`
+/// * `*OPC?`
+/// * `*WAI`
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `StatusCommands`",
+    note = "the `StatusCommands` and `Ieee4882Device` options of the interface macro require an \
+            implementation of `StatusCommands`"
+)]
+pub trait StatusCommands {
+    fn status_registers(&mut self) -> &mut StatusRegisters;
+
+    /// Waits for the progress of the pending operations.
+    ///
+    /// This is called repeatedly while operations are pending. An
+    /// implementation awaits the completion of an operation, e.g. by receiving
+    /// from a channel, and registers it with
+    /// [OperationToken::complete_on](crate::OperationToken::complete_on). The
+    /// default implementation yields to the executor, which is sufficient
+    /// if the registers are updated by an interrupt handler. Devices
+    /// without overlapped operations never call it.
+    async fn wait_operations(&mut self) {
+        let mut yielded = false;
+        core::future::poll_fn(|cx| {
+            if yielded {
+                core::task::Poll::Ready(())
+            }
+            else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                core::task::Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Executes the `*WAI` command.
+    async fn wait(&mut self) -> Result<(), Error> {
+        while self.status_registers().pending_operations() > 0 {
+            self.wait_operations().await;
+        }
+        Ok(())
+    }
+
+    /// Executes the `*OPC` command.
+    fn operation_complete(&mut self) -> Result<(), Error> {
+        self.status_registers().arm_operation_complete();
+        Ok(())
+    }
+
+    /// Executes the `*OPC?` query.
+    async fn operation_complete_query(&mut self) -> Result<u8, Error> {
+        self.wait().await?;
+        Ok(1)
+    }
+
+    /// Called when the MSS bit of the status byte is set, e.g. to assert the
+    /// service request line of the transport.
+    fn on_service_request(&mut self) {}
+
+    /// Checks the status byte for a service request and calls
+    /// [StatusCommands::on_service_request].
+    ///
+    /// This is called by the interface after each command and whenever the
+    /// status has changed otherwise.
+    fn check_service_request(&mut self) {
+        if self.status_registers().poll_service_request() {
+            self.on_service_request();
+        }
+    }
+
+    /// Returns whether a response is pending in the output queue.
+    fn output_pending(&mut self) -> bool {
+        self.status_registers().output_pending()
+    }
+
+    fn status_byte(&mut self) -> Result<u8, Error> {
+        let output_pending = self.output_pending();
+        let registers = self.status_registers();
+        let mut status = registers.status_byte();
+        status.set(crate::StatusByte::MESSAGE_AVAILABLE, output_pending);
+        Ok(status
+            .with_master_summary(registers.status_byte_enable())
+            .bits())
+    }
+
+    fn status_byte_enable(&mut self) -> Result<u8, Error> {
+        Ok(self.status_registers().status_byte_enable())
+    }
+
+    fn set_status_byte_enable(&mut self, enable: u8) -> Result<(), Error> {
+        self.status_registers().set_status_byte_enable(enable);
+        Ok(())
+    }
+
+    /// Clears the standard event status register, the event registers of the
+    /// operation and questionable status, the pending output and a pending
+    /// `*OPC`.
+    fn clear_event_status(&mut self) {
+        let registers = self.status_registers();
+        registers.take_event_status();
+        registers.operation_mut().take_event();
+        registers.questionable_mut().take_event();
+        registers.set_output_pending(false);
+        registers.disarm_operation_complete();
+    }
+
+    fn event_status_register(&mut self) -> Result<u8, Error> {
+        Ok(self.status_registers().take_event_status().bits())
+    }
+
+    fn event_status_enable(&mut self) -> Result<u8, Error> {
+        Ok(self.status_registers().event_status_enable())
+    }
+
+    fn set_event_status_enable(&mut self, enable: u8) -> Result<(), Error> {
+        self.status_registers().set_event_status_enable(enable);
+        Ok(())
+    }
+}
+
+/// IEEE 488.2 Device
+///
+/// The [Ieee4882Device] trait bundles the traits that implement the common
+/// commands mandatory for IEEE 488.2 devices together with the mandatory SCPI
+/// system commands. It is implemented for every type implementing
+/// [ErrorCommands], [StandardCommands], [IdentityCommands], [ResetCommands],
+/// [SelfTestCommands] and [StatusCommands], so the `Ieee4882Device` option of
+/// the interface macro registers the commands of all of them at once.
+///
+/// ```
+/// use microscpi::{self as scpi, ErrorQueue, StaticErrorQueue, StatusRegisters};
+///
+/// #[derive(Default)]
+/// pub struct ExampleDevice {
+///     errors: StaticErrorQueue<10>,
+///     status: StatusRegisters,
+/// }
+///
+/// impl scpi::ErrorCommands for ExampleDevice {
+///     fn error_queue(&mut self) -> &mut impl ErrorQueue {
+///         &mut self.errors
+///     }
+/// }
+///
+/// impl scpi::StandardCommands for ExampleDevice {}
+///
+/// impl scpi::IdentityCommands for ExampleDevice {
+///     fn manufacturer(&self) -> &str {
+///         "EXAMPLE"
+///     }
+///
+///     fn model(&self) -> &str {
+///         "DEVICE"
+///     }
+///
+///     fn serial_number(&self) -> &str {
+///         "0"
+///     }
+///
+///     fn firmware_version(&self) -> &str {
+///         "1.0"
+///     }
+/// }
+///
+/// impl scpi::ResetCommands for ExampleDevice {
+///     fn reset(&mut self) -> Result<(), scpi::Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// impl scpi::SelfTestCommands for ExampleDevice {
+///     async fn self_test(&mut self) -> Result<i16, scpi::Error> {
+///         Ok(0)
+///     }
+/// }
+///
+/// impl scpi::StatusCommands for ExampleDevice {
+///     fn status_registers(&mut self) -> &mut StatusRegisters {
+///         &mut self.status
+///     }
+/// }
+///
+/// #[scpi::interface(Ieee4882Device)]
+/// impl ExampleDevice {}
+///
+/// let mut device = ExampleDevice::default();
+/// scpi::assert_scpi!(device, "*RST;*OPC?;*TST?;*IDN?", "1;0;EXAMPLE,DEVICE,0,1.0");
+/// ```
+///
+/// # Implemented commands
+///
+/// * `*IDN?`
+/// * `*RST`
+/// * `*TST?`
+/// * `*OPC`
+/// * `*OPC?`
+/// * `*WAI`
+/// * `*CLS`
+/// * `*ESE <mask>`
+/// * `*ESE?`
+/// * `*ESR?`
+/// * `*SRE <mask>`
+/// * `*SRE?`
+/// * `*STB?`
+/// * `SYSTem:ERRor:[NEXT]?`, `SYSTem:VERSion?` and the other commands of the
+///   bundled traits.
+pub trait Ieee4882Device:
+    ErrorCommands
+    + StandardCommands
+    + IdentityCommands
+    + ResetCommands
+    + SelfTestCommands
+    + StatusCommands
+{
+}
+
+impl<T> Ieee4882Device for T where
+    T: ErrorCommands
+        + StandardCommands
+        + IdentityCommands
+        + ResetCommands
+        + SelfTestCommands
+        + StatusCommands
+{
+}
+
+/// Operation Status Commands
+///
+/// The [OperationStatusCommands] trait implements the commands to read the
+/// operation status register of the [StatusRegisters]. The only requirement
+/// to implement this trait is to implement [StatusCommands]. The device
+/// updates the condition of the register with
+/// [ScpiRegister16::set_condition](crate::ScpiRegister16::set_condition), using
+/// the bits defined by [OperationStatus](crate::OperationStatus).
+///
+/// # Implemented commands
+///
+/// * `STATus:OPERation:CONDition?`
+/// * `STATus:OPERation:[EVENt]?`
+/// * `STATus:OPERation:ENABle <mask>`
+/// * `STATus:OPERation:ENABle?`
+/// * `STATus:OPERation:PTRansition <mask>`
+/// * `STATus:OPERation:PTRansition?`
+/// * `STATus:OPERation:NTRansition <mask>`
+/// * `STATus:OPERation:NTRansition?`
+pub trait OperationStatusCommands: StatusCommands {
+    fn operation_condition(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().operation().condition())
+    }
+
+    fn operation_event(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().operation_mut().take_event())
+    }
+
+    fn operation_enable(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().operation().enable())
+    }
+
+    fn set_operation_enable(&mut self, enable: u16) -> Result<(), Error> {
+        self.status_registers().operation_mut().set_enable(enable);
+        Ok(())
+    }
+
+    fn operation_positive_transition(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().operation().positive_transition())
+    }
+
+    fn set_operation_positive_transition(&mut self, filter: u16) -> Result<(), Error> {
+        self.status_registers()
+            .operation_mut()
+            .set_positive_transition(filter);
+        Ok(())
+    }
+
+    fn operation_negative_transition(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().operation().negative_transition())
+    }
+
+    fn set_operation_negative_transition(&mut self, filter: u16) -> Result<(), Error> {
+        self.status_registers()
+            .operation_mut()
+            .set_negative_transition(filter);
+        Ok(())
+    }
+}
+
+/// Questionable Status Commands
+///
+/// The [QuestionableStatusCommands] trait implements the commands to read the
+/// questionable status register of the [StatusRegisters]. The only
+/// requirement to implement this trait is to implement [StatusCommands]. The
+/// device updates the condition of the register with
+/// [ScpiRegister16::set_condition](crate::ScpiRegister16::set_condition), using
+/// the bits defined by [QuestionableStatus](crate::QuestionableStatus).
+///
+/// # Implemented commands
+///
+/// * `STATus:QUEStionable:CONDition?`
+/// * `STATus:QUEStionable:[EVENt]?`
+/// * `STATus:QUEStionable:ENABle <mask>`
+/// * `STATus:QUEStionable:ENABle?`
+/// * `STATus:QUEStionable:PTRansition <mask>`
+/// * `STATus:QUEStionable:PTRansition?`
+/// * `STATus:QUEStionable:NTRansition <mask>`
+/// * `STATus:QUEStionable:NTRansition?`
+pub trait QuestionableStatusCommands: StatusCommands {
+    fn questionable_condition(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().questionable().condition())
+    }
+
+    fn questionable_event(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().questionable_mut().take_event())
+    }
+
+    fn questionable_enable(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().questionable().enable())
+    }
+
+    fn set_questionable_enable(&mut self, enable: u16) -> Result<(), Error> {
+        self.status_registers()
+            .questionable_mut()
+            .set_enable(enable);
+        Ok(())
+    }
+
+    fn questionable_positive_transition(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().questionable().positive_transition())
+    }
+
+    fn set_questionable_positive_transition(&mut self, filter: u16) -> Result<(), Error> {
+        self.status_registers()
+            .questionable_mut()
+            .set_positive_transition(filter);
+        Ok(())
+    }
+
+    fn questionable_negative_transition(&mut self) -> Result<u16, Error> {
+        Ok(self.status_registers().questionable().negative_transition())
+    }
+
+    fn set_questionable_negative_transition(&mut self, filter: u16) -> Result<(), Error> {
+        self.status_registers()
+            .questionable_mut()
+            .set_negative_transition(filter);
+        Ok(())
+    }
+}
+
+/// Statistics Commands
+///
+/// The [StatisticsCommands] trait implements queries for the command execution
+/// statistics, e.g. for field diagnostics. The only requirement to implement
+/// this trait is to provide the [Statistics] via the
+/// [StatisticsCommands::statistics] method. The interface updates the
+/// statistics while executing commands.
+///
+/// # Implemented commands
+///
+/// * `SYSTem:STATistics:COMMands?`
+/// * `SYSTem:STATistics:ERRors?`
+/// * `SYSTem:STATistics:LAST?`
+pub trait StatisticsCommands {
+    fn statistics(&mut self) -> &mut Statistics;
+
+    fn statistics_commands(&mut self) -> Result<u32, Error> {
+        Ok(self.statistics().commands())
+    }
+
+    fn statistics_errors(&mut self) -> Result<u32, Error> {
+        Ok(self.statistics().errors())
+    }
+
+    fn statistics_last(&mut self) -> Result<&'static str, Error> {
+        Ok(self.statistics().last_command().unwrap_or(""))
     }
 }
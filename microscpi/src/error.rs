@@ -1,12 +1,77 @@
 use crate::parser::ParseError;
 
+/// The maximum length of the device-dependent info of an error in bytes.
+pub const MAX_ERROR_INFO_LENGTH: usize = 32;
+
+/// Device-dependent info of an error, e.g. `value clipped to 10V`.
+///
+/// The info is stored inline, so an [Error] carrying it can still be copied.
+/// Longer texts are truncated to [MAX_ERROR_INFO_LENGTH] bytes.
+///
+/// The inline storage determines the size of every [Error], whether it
+/// carries info or not: on 64-bit targets an error takes 56 bytes instead of
+/// the 24 bytes of an [Error::Custom]. An error queue holding `N` errors, e.g.
+/// a [StaticErrorQueue](crate::StaticErrorQueue), takes this size for each
+/// entry.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ErrorInfo {
+    len: u8,
+    data: [u8; MAX_ERROR_INFO_LENGTH],
+}
+
+impl ErrorInfo {
+    /// Creates the info from a text, which is truncated to
+    /// [MAX_ERROR_INFO_LENGTH] bytes at a character boundary.
+    pub fn new(info: &str) -> ErrorInfo {
+        let mut len = info.len().min(MAX_ERROR_INFO_LENGTH);
+        while !info.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let mut data = [0; MAX_ERROR_INFO_LENGTH];
+        data[..len].copy_from_slice(&info.as_bytes()[..len]);
+        ErrorInfo {
+            len: len as u8,
+            data,
+        }
+    }
+
+    /// Returns the text of the info.
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.data[..self.len as usize]).unwrap_or_default()
+    }
+}
+
+impl core::fmt::Debug for ErrorInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ErrorInfo {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=str}", self.as_str())
+    }
+}
+
 /// SCPI error
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// Errors are compared by their [kind](Error::kind), so an error with
+/// device-dependent info equals the error it was created from.
+#[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
 pub enum Error {
     /// A custom error, consisting of an error number and a name.
     Custom(i16, &'static str),
 
+    /// An error with device-dependent info, consisting of the number and the
+    /// name of the error it was created from and the info. It is created with
+    /// [Error::with_info] and the original error is returned by
+    /// [Error::kind].
+    WithInfo(i16, &'static str, ErrorInfo),
+
     /// Command error (-100)
     ///
     /// This is the generic syntax error for devices that cannot detect more
@@ -241,6 +306,31 @@ pub enum Error {
     /// device at this point in parsing.
     ExpressionDataNotAllowed,
 
+    /// Macro error (-180)
+    ///
+    /// This error, as well as errors -181 through -189, are generated when
+    /// defining a macro or executing a macro. This particular error message
+    /// should be used when the device cannot detect a more specific error.
+    MacroCommandError,
+
+    /// Invalid outside macro definition (-181)
+    ///
+    /// Indicates that a macro parameter placeholder (`$<number>`) was
+    /// encountered outside of a macro definition.
+    InvalidOutsideMacroDefinition,
+
+    /// Invalid inside macro definition (-183)
+    ///
+    /// Indicates that the program message unit sequence, sent with a `*DDT` or
+    /// `*DMC` command, is syntactically invalid (see IEEE 488.2, 10.7.6.3).
+    InvalidInsideMacroDefinition,
+
+    /// Macro parameter error (-184)
+    ///
+    /// Indicates that a command inside the macro definition had the wrong
+    /// number or type of parameters.
+    MacroCommandParameterError,
+
     /// Execution error (-200)
     ///
     /// This is the generic syntax error for devices that cannot detect more
@@ -257,6 +347,13 @@ pub enum Error {
     /// not be executed.
     InvalidWhileInLocal,
 
+    /// Settings lost due to rtl (-202)
+    ///
+    /// Indicates that a setting associated with a hard local control (see IEEE
+    /// 488.2, 5.6.1.6) was lost when the device changed to LOCS from REMS or to
+    /// LWLS from RWLS.
+    SettingsLostDueToRtl,
+
     /// Command protected (-203)
     ///
     /// Indicates that a legal password-protected program command or query could
@@ -272,6 +369,41 @@ pub enum Error {
     /// Error.
     TriggerError,
 
+    /// Trigger ignored (-211)
+    ///
+    /// Indicates that a GET, `*TRG`, or triggering signal was received and
+    /// recognized by the device but was ignored because of device timing
+    /// considerations; for example, the device was not ready to respond.
+    TriggerIgnored,
+
+    /// Arm ignored (-212)
+    ///
+    /// Indicates that an arming signal was received and recognized by the
+    /// device but was ignored.
+    ArmIgnored,
+
+    /// Init ignored (-213)
+    ///
+    /// Indicates that a request for a measurement initiation was ignored as
+    /// another measurement was already in progress.
+    InitIgnored,
+
+    /// Trigger deadlock (-214)
+    ///
+    /// Indicates that the trigger source for the initiation of a measurement is
+    /// set to GET and subsequent measurement query is received. The measurement
+    /// cannot be started until a GET is received, but the GET would cause an
+    /// INTERRUPTED error.
+    TriggerDeadlock,
+
+    /// Arm deadlock (-215)
+    ///
+    /// Indicates that the arm source for the initiation of a measurement is set
+    /// to GET and subsequent measurement query is received. The measurement
+    /// cannot be started until a GET is received, but the GET would cause
+    /// an INTERRUPTED error.
+    ArmDeadlock,
+
     /// Parameter error (-220)
     ///
     /// Indicates that a program data element related error occurred. This error
@@ -322,6 +454,26 @@ pub enum Error {
     /// access.
     DataCorruptOrStale,
 
+    /// Data questionable (-231)
+    ///
+    /// Indicates that measurement accuracy is suspect.
+    DataQuestionable,
+
+    /// Invalid format (-232)
+    ///
+    /// Indicates that a legal program data element was parsed but could not be
+    /// executed because the data format or structure is inappropriate; for
+    /// example, when loading memory tables or when sending a SYSTem:SET
+    /// parameter from an unknown instrument.
+    InvalidFormat,
+
+    /// Invalid version (-233)
+    ///
+    /// Indicates that a legal program data element was parsed but could not be
+    /// executed because the version of the data is incorrect to the device; for
+    /// example, a settings file created by a newer firmware version.
+    InvalidVersion,
+
     /// Hardware Error (-240)
     ///
     /// Indicates that a legal program command or query could not be executed
@@ -331,6 +483,218 @@ pub enum Error {
     /// specific errors described for errors -241 through -249.
     HardwareError,
 
+    /// Hardware missing (-241)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because of missing device hardware; for example, an option was not
+    /// installed.
+    HardwareMissing,
+
+    /// Mass storage error (-250)
+    ///
+    /// Indicates that a mass storage error occurred. This error message should
+    /// be used when the device cannot detect the more specific errors
+    /// described for errors -251 through -259.
+    MassStorageError,
+
+    /// Missing mass storage (-251)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because of missing mass storage; for example, an option that was not
+    /// installed.
+    MissingMassStorage,
+
+    /// Missing media (-252)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because of a missing media; for example, no disk.
+    MissingMedia,
+
+    /// Corrupt media (-253)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because of corrupt media; for example, bad disk or wrong format.
+    CorruptMedia,
+
+    /// Media full (-254)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because the media was full; for example, there is no room on the disk.
+    MediaFull,
+
+    /// Directory full (-255)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because the media directory was full.
+    DirectoryFull,
+
+    /// File name not found (-256)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because the file name on the device media was not found; for example, an
+    /// attempt was made to read or copy a nonexistent file.
+    FileNameNotFound,
+
+    /// File name error (-257)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because the file name on the device media was in error; for example, an
+    /// attempt was made to copy to a duplicate file name.
+    FileNameError,
+
+    /// Media protected (-258)
+    ///
+    /// Indicates that a legal program command or query could not be executed
+    /// because the media was protected; for example, the write-protect tab on a
+    /// disk was present.
+    MediaProtected,
+
+    /// Expression error (-260)
+    ///
+    /// Indicates that an expression program data element related error
+    /// occurred. This error message should be used when the device cannot
+    /// detect the more specific errors described for errors -261 through
+    /// -269.
+    ExecutionExpressionError,
+
+    /// Math error in expression (-261)
+    ///
+    /// Indicates that a syntactically legal expression program data element
+    /// could not be executed due to a math error; for example, a
+    /// divide-by-zero was attempted.
+    MathErrorInExpression,
+
+    /// Macro error (-270)
+    ///
+    /// This is the generic macro error for devices that cannot detect more
+    /// specific errors. This code indicates that a macro-related execution
+    /// error occurred.
+    MacroError,
+
+    /// Macro syntax error (-271)
+    ///
+    /// Indicates that a syntactically legal macro program data sequence,
+    /// written in accordance with IEEE 488.2, 10.7.2, could not be executed
+    /// due to a syntax error within the macro definition (see IEEE 488.2,
+    /// 10.7.6.3).
+    MacroSyntaxError,
+
+    /// Macro execution error (-272)
+    ///
+    /// Indicates that a syntactically legal macro program data sequence could
+    /// not be executed due to some error in the macro definition.
+    MacroExecutionError,
+
+    /// Illegal macro label (-273)
+    ///
+    /// Indicates that the macro label defined in the `*DMC` command was a
+    /// legal string syntax, but could not be accepted by the device; for
+    /// example, the label was too long or the same as a common command.
+    IllegalMacroLabel,
+
+    /// Macro parameter error (-274)
+    ///
+    /// Indicates that the macro definition improperly used a macro parameter
+    /// placeholder or that a macro was invoked with parameters.
+    MacroParameterError,
+
+    /// Macro definition too long (-275)
+    ///
+    /// Indicates that a syntactically legal macro program data sequence could
+    /// not be executed because the string or block contents were too long for
+    /// the device to handle.
+    MacroDefinitionTooLong,
+
+    /// Macro recursion error (-276)
+    ///
+    /// Indicates that a syntactically legal macro program data sequence could
+    /// not be executed because the device found it to be recursive.
+    MacroRecursionError,
+
+    /// Macro redefinition not allowed (-277)
+    ///
+    /// Indicates that a syntactically legal macro label in the `*DMC` command
+    /// could not be executed because the macro label was already defined.
+    MacroRedefinitionNotAllowed,
+
+    /// Macro header not found (-278)
+    ///
+    /// Indicates that a syntactically legal macro label in the `*GMC?` query
+    /// could not be executed because the header was not previously defined.
+    MacroHeaderNotFound,
+
+    /// Program error (-280)
+    ///
+    /// Indicates that a downloaded program-related execution error occurred.
+    /// This error message should be used when the device cannot detect the
+    /// more specific errors described for errors -281 through -289.
+    ProgramError,
+
+    /// Cannot create program (-281)
+    ///
+    /// Indicates that an attempt to create a program was unsuccessful. A reason
+    /// for the failure might include not enough memory.
+    CannotCreateProgram,
+
+    /// Illegal program name (-282)
+    ///
+    /// The name used to reference a program was invalid; for example,
+    /// redefining an existing program, deleting a nonexistent program, or
+    /// in general, referencing a nonexistent program.
+    IllegalProgramName,
+
+    /// Illegal variable name (-283)
+    ///
+    /// An attempt was made to reference a nonexistent variable in a program.
+    IllegalVariableName,
+
+    /// Program currently running (-284)
+    ///
+    /// Certain operations dealing with programs may be illegal while the
+    /// program is running; for example, deleting a running program might
+    /// not be possible.
+    ProgramCurrentlyRunning,
+
+    /// Program syntax error (-285)
+    ///
+    /// Indicates that a syntax error appears in a downloaded program. The
+    /// syntax used when parsing the downloaded program is device-specific.
+    ProgramSyntaxError,
+
+    /// Program runtime error (-286)
+    ///
+    /// Indicates that an error occurred while a downloaded program was
+    /// executed.
+    ProgramRuntimeError,
+
+    /// Memory use error (-290)
+    ///
+    /// Indicates that a user request has directly or indirectly caused an error
+    /// related to memory or `<data_handle>`s, this is not the same as "bad"
+    /// memory.
+    MemoryUseError,
+
+    /// Out of memory (-291)
+    ///
+    /// Indicates that a user request has directly or indirectly caused the
+    /// device to run out of memory for the requested operation.
+    ExecutionOutOfMemory,
+
+    /// Referenced name does not exist (-292)
+    ///
+    /// Indicates that a referenced `<data_handle>` does not exist.
+    ReferencedNameDoesNotExist,
+
+    /// Referenced name already exists (-293)
+    ///
+    /// Indicates that a `<data_handle>` to be created already exists.
+    ReferencedNameAlreadyExists,
+
+    /// Incompatible type (-294)
+    ///
+    /// Indicates that the type or structure of a memory item is inadequate.
+    IncompatibleType,
+
     /// Device specific error (-300)
     ///
     /// This is the generic device-dependent error for devices that cannot
@@ -345,6 +709,35 @@ pub enum Error {
     /// occurred. This code is device-dependent.
     SystemError,
 
+    /// Memory error (-311)
+    ///
+    /// Indicates that an error was detected in the device's memory.
+    MemoryError,
+
+    /// PUD memory lost (-312)
+    ///
+    /// Indicates that the protected user data saved by the `*PUD` command has
+    /// been lost.
+    PudMemoryLost,
+
+    /// Calibration memory lost (-313)
+    ///
+    /// Indicates that nonvolatile calibration data used by the `*CAL?` command
+    /// has been lost.
+    CalibrationMemoryLost,
+
+    /// Save/recall memory lost (-314)
+    ///
+    /// Indicates that the nonvolatile data saved by the `*SAV` command has been
+    /// lost.
+    SaveRecallMemoryLost,
+
+    /// Configuration memory lost (-315)
+    ///
+    /// Indicates that nonvolatile configuration data saved by the device has
+    /// been lost. The meaning of this error is device-specific.
+    ConfigurationMemoryLost,
+
     /// Storage fault (-320)
     ///
     /// Indicates that the firmware detected a fault when using data storage.
@@ -352,6 +745,11 @@ pub enum Error {
     /// mass storage element.
     StorageFault,
 
+    /// Out of memory (-321)
+    ///
+    /// An internal operation needed more memory than was available.
+    StorageOutOfMemory,
+
     /// Self-test failed (-330)
     SelfTestFailed,
 
@@ -371,6 +769,17 @@ pub enum Error {
     /// the more specific errors described for errors -361 through -363.
     CommunicationError,
 
+    /// Parity error in program message (-361)
+    ///
+    /// Parity bit not correct when data received for example, on a serial port.
+    ParityError,
+
+    /// Framing error in program message (-362)
+    ///
+    /// A stop bit was not detected when data was received for example, on a
+    /// serial port (for example, a baud rate mismatch).
+    FramingError,
+
     /// Input buffer overrun (-363)
     ///
     /// Software or hardware input buffer on serial port overflows with data
@@ -388,13 +797,253 @@ pub enum Error {
     /// specific errors. This code indicates only that a Query Error as
     /// defined in IEEE 488.2, 11.5.1.1.7 and 6.3 has occurred.
     QueryError,
+
+    /// Query interrupted (-410)
+    ///
+    /// Indicates that a condition causing an INTERRUPTED Query error occurred
+    /// (see IEEE 488.2, 6.3.2.3); for example, a query followed by DAB or GET
+    /// before a response was completely sent.
+    QueryInterrupted,
+
+    /// Query UNTERMINATED (-420)
+    ///
+    /// Indicates that a condition causing an UNTERMINATED Query error occurred
+    /// (see IEEE 488.2, 6.3.2.2); for example, the device was addressed to talk
+    /// and an incomplete program message was received.
+    QueryUnterminated,
+
+    /// Query DEADLOCKED (-430)
+    ///
+    /// Indicates that a condition causing a DEADLOCKED Query error occurred
+    /// (see IEEE 488.2, 6.3.1.7); for example, both input buffer and output
+    /// buffer are full and the device cannot continue.
+    QueryDeadlocked,
+
+    /// Query UNTERMINATED after indefinite response (-440)
+    ///
+    /// Indicates that a query was received in the same program message after a
+    /// query requesting an indefinite response was executed (see IEEE 488.2,
+    /// 6.5.7.5.7).
+    QueryUnterminatedAfterIndefinite,
+}
+
+/// Defines [STANDARD_ERRORS] together with an exhaustive match of the
+/// variants, so a standard error missing in the list fails to compile in the
+/// tests.
+macro_rules! standard_errors {
+    ($($error:ident),* $(,)?) => {
+        /// All standard errors in the descending order of their numbers.
+        const STANDARD_ERRORS: &[Error] = &[$(Error::$error),*];
+
+        #[cfg(test)]
+        fn is_standard_error(error: &Error) -> bool {
+            match error {
+                Error::Custom(..) | Error::WithInfo(..) => false,
+                $(Error::$error)|* => true,
+            }
+        }
+    };
 }
 
+standard_errors![
+    CommandError,
+    InvalidCharacter,
+    SyntaxError,
+    InvalidSeparator,
+    DataTypeError,
+    GetNotAllowed,
+    ParameterNotAllowed,
+    MissingParameter,
+    CommandHeaderError,
+    HeaderSeparatorError,
+    ProgramMnemonicTooLong,
+    UndefinedHeader,
+    HeaderSuffixOutOfRange,
+    UnexpectedNumberOfParameters,
+    NumericDataError,
+    InvalidCharacterInNumber,
+    ExponentTooLarge,
+    TooManyDigits,
+    NumericDataNotAllowed,
+    SuffixError,
+    InvalidSuffix,
+    SuffixTooLong,
+    SuffixNotAllowed,
+    CharacterDataError,
+    InvalidCharacterData,
+    CharacterDataTooLong,
+    CharacterNotAllowed,
+    StringDataError,
+    InvalidStringData,
+    StringDataNotAllowed,
+    BlockDataError,
+    InvalidBlockData,
+    BlockDataNotAllowed,
+    ExpressionError,
+    InvalidExpression,
+    ExpressionDataNotAllowed,
+    MacroCommandError,
+    InvalidOutsideMacroDefinition,
+    InvalidInsideMacroDefinition,
+    MacroCommandParameterError,
+    ExecutionError,
+    InvalidWhileInLocal,
+    SettingsLostDueToRtl,
+    CommandProtected,
+    TriggerError,
+    TriggerIgnored,
+    ArmIgnored,
+    InitIgnored,
+    TriggerDeadlock,
+    ArmDeadlock,
+    ParameterError,
+    SettingsConflict,
+    DataOutOfRange,
+    TooMuchData,
+    IllegalParameterValue,
+    OutOfMemory,
+    ListsNotSameLength,
+    DataCorruptOrStale,
+    DataQuestionable,
+    InvalidFormat,
+    InvalidVersion,
+    HardwareError,
+    HardwareMissing,
+    MassStorageError,
+    MissingMassStorage,
+    MissingMedia,
+    CorruptMedia,
+    MediaFull,
+    DirectoryFull,
+    FileNameNotFound,
+    FileNameError,
+    MediaProtected,
+    ExecutionExpressionError,
+    MathErrorInExpression,
+    MacroError,
+    MacroSyntaxError,
+    MacroExecutionError,
+    IllegalMacroLabel,
+    MacroParameterError,
+    MacroDefinitionTooLong,
+    MacroRecursionError,
+    MacroRedefinitionNotAllowed,
+    MacroHeaderNotFound,
+    ProgramError,
+    CannotCreateProgram,
+    IllegalProgramName,
+    IllegalVariableName,
+    ProgramCurrentlyRunning,
+    ProgramSyntaxError,
+    ProgramRuntimeError,
+    MemoryUseError,
+    ExecutionOutOfMemory,
+    ReferencedNameDoesNotExist,
+    ReferencedNameAlreadyExists,
+    IncompatibleType,
+    DeviceSpecificError,
+    SystemError,
+    MemoryError,
+    PudMemoryLost,
+    CalibrationMemoryLost,
+    SaveRecallMemoryLost,
+    ConfigurationMemoryLost,
+    StorageFault,
+    StorageOutOfMemory,
+    SelfTestFailed,
+    CalibrationFailed,
+    QueueOverflow,
+    CommunicationError,
+    ParityError,
+    FramingError,
+    InputBufferOverrun,
+    TimeoutError,
+    QueryError,
+    QueryInterrupted,
+    QueryUnterminated,
+    QueryDeadlocked,
+    QueryUnterminatedAfterIndefinite,
+];
+
 impl Error {
+    /// Attaches device-dependent info to the error, which is reported after
+    /// its name, e.g. `-222,"Data out of range;clipped to 10V"`. The info
+    /// replaces a previously attached info and is truncated to
+    /// [MAX_ERROR_INFO_LENGTH] bytes.
+    pub fn with_info(self, info: &str) -> Error {
+        Error::WithInfo(self.number(), self.into(), ErrorInfo::new(info))
+    }
+
+    /// Returns the error without its device-dependent info, e.g.
+    /// [Error::DataOutOfRange] for an [Error::DataOutOfRange] with info.
+    /// Errors without info are returned unchanged.
+    ///
+    /// This allows matching the variant of an error regardless of its info:
+    ///
+    /// ```
+    /// use microscpi::Error;
+    ///
+    /// let error = Error::DataOutOfRange.with_info("parameter 1 (voltage)");
+    /// assert!(matches!(error.kind(), Error::DataOutOfRange));
+    /// ```
+    pub fn kind(&self) -> Error {
+        match *self {
+            Error::WithInfo(number, name, _) => Error::from_code(number)
+                .filter(|error| Into::<&str>::into(*error) == name)
+                .unwrap_or(Error::Custom(number, name)),
+            error => error,
+        }
+    }
+
+    /// Returns the device-dependent info of the error, if any.
+    pub fn info(&self) -> Option<&str> {
+        match self {
+            Error::WithInfo(_, _, info) => Some(info.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the error with the given number.
+    ///
+    /// Negative numbers are looked up among the standard errors and positive
+    /// numbers, which are device-dependent, result in an [Error::Custom] with
+    /// an empty name. Returns [None] for unknown negative numbers and zero.
+    pub fn from_code(number: i16) -> Option<Error> {
+        match number {
+            1.. => Some(Error::Custom(number, "")),
+            _ => STANDARD_ERRORS
+                .iter()
+                .find(|error| error.number() == number)
+                .copied(),
+        }
+    }
+
+    /// Returns whether the error is a command error (-100 to -199).
+    pub fn is_command_error(&self) -> bool {
+        matches!(self.number(), -199..=-100)
+    }
+
+    /// Returns whether the error is an execution error (-200 to -299).
+    pub fn is_execution_error(&self) -> bool {
+        matches!(self.number(), -299..=-200)
+    }
+
+    /// Returns whether the error is a device-specific error (-300 to -399) or
+    /// a device-dependent error with a positive number.
+    pub fn is_device_specific_error(&self) -> bool {
+        matches!(self.number(), -399..=-300 | 1..)
+    }
+
+    /// Returns whether the error is a query error (-400 to -499).
+    pub fn is_query_error(&self) -> bool {
+        matches!(self.number(), -499..=-400)
+    }
+
     /// Get the error number as defined in IEEE 488.2.
     pub fn number(&self) -> i16 {
         match self {
             Error::Custom(number, _name) => *number,
+            Error::WithInfo(number, _name, _info) => *number,
             Error::CommandError => -100,
             Error::InvalidCharacter => -101,
             Error::SyntaxError => -102,
@@ -431,11 +1080,21 @@ impl Error {
             Error::ExpressionError => -170,
             Error::InvalidExpression => -171,
             Error::ExpressionDataNotAllowed => -178,
+            Error::MacroCommandError => -180,
+            Error::InvalidOutsideMacroDefinition => -181,
+            Error::InvalidInsideMacroDefinition => -183,
+            Error::MacroCommandParameterError => -184,
             Error::ExecutionError => -200,
             Error::InvalidWhileInLocal => -201,
+            Error::SettingsLostDueToRtl => -202,
             Error::CommandProtected => -203,
             Error::ParameterError => -220,
             Error::TriggerError => -210,
+            Error::TriggerIgnored => -211,
+            Error::ArmIgnored => -212,
+            Error::InitIgnored => -213,
+            Error::TriggerDeadlock => -214,
+            Error::ArmDeadlock => -215,
             Error::SettingsConflict => -221,
             Error::DataOutOfRange => -222,
             Error::TooMuchData => -223,
@@ -443,17 +1102,78 @@ impl Error {
             Error::OutOfMemory => -225,
             Error::ListsNotSameLength => -226,
             Error::DataCorruptOrStale => -230,
+            Error::DataQuestionable => -231,
+            Error::InvalidFormat => -232,
+            Error::InvalidVersion => -233,
             Error::HardwareError => -240,
+            Error::HardwareMissing => -241,
+            Error::MassStorageError => -250,
+            Error::MissingMassStorage => -251,
+            Error::MissingMedia => -252,
+            Error::CorruptMedia => -253,
+            Error::MediaFull => -254,
+            Error::DirectoryFull => -255,
+            Error::FileNameNotFound => -256,
+            Error::FileNameError => -257,
+            Error::MediaProtected => -258,
+            Error::ExecutionExpressionError => -260,
+            Error::MathErrorInExpression => -261,
+            Error::MacroError => -270,
+            Error::MacroSyntaxError => -271,
+            Error::MacroExecutionError => -272,
+            Error::IllegalMacroLabel => -273,
+            Error::MacroParameterError => -274,
+            Error::MacroDefinitionTooLong => -275,
+            Error::MacroRecursionError => -276,
+            Error::MacroRedefinitionNotAllowed => -277,
+            Error::MacroHeaderNotFound => -278,
+            Error::ProgramError => -280,
+            Error::CannotCreateProgram => -281,
+            Error::IllegalProgramName => -282,
+            Error::IllegalVariableName => -283,
+            Error::ProgramCurrentlyRunning => -284,
+            Error::ProgramSyntaxError => -285,
+            Error::ProgramRuntimeError => -286,
+            Error::MemoryUseError => -290,
+            Error::ExecutionOutOfMemory => -291,
+            Error::ReferencedNameDoesNotExist => -292,
+            Error::ReferencedNameAlreadyExists => -293,
+            Error::IncompatibleType => -294,
             Error::DeviceSpecificError => -300,
             Error::SystemError => -310,
+            Error::MemoryError => -311,
+            Error::PudMemoryLost => -312,
+            Error::CalibrationMemoryLost => -313,
+            Error::SaveRecallMemoryLost => -314,
+            Error::ConfigurationMemoryLost => -315,
             Error::StorageFault => -320,
+            Error::StorageOutOfMemory => -321,
             Error::SelfTestFailed => -330,
             Error::CalibrationFailed => -340,
             Error::QueueOverflow => -350,
             Error::CommunicationError => -360,
+            Error::ParityError => -361,
+            Error::FramingError => -362,
             Error::InputBufferOverrun => -363,
             Error::TimeoutError => -365,
             Error::QueryError => -400,
+            Error::QueryInterrupted => -410,
+            Error::QueryUnterminated => -420,
+            Error::QueryDeadlocked => -430,
+            Error::QueryUnterminatedAfterIndefinite => -440,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self.kind(), other.kind()) {
+            (Error::Custom(number, name), Error::Custom(other_number, other_name)) => {
+                number == other_number && name == other_name
+            }
+            (kind, other_kind) => {
+                core::mem::discriminant(&kind) == core::mem::discriminant(&other_kind)
+            }
         }
     }
 }
@@ -462,6 +1182,7 @@ impl From<Error> for &str {
     fn from(error: Error) -> &'static str {
         match error {
             Error::Custom(_, name) => name,
+            Error::WithInfo(_, name, _) => name,
             Error::CommandError => "Command error",
             Error::InvalidCharacter => "Invalid character",
             Error::SyntaxError => "Syntax Error",
@@ -471,12 +1192,23 @@ impl From<Error> for &str {
             Error::InvalidCharacterData => "Invalid character data",
             Error::ExecutionError => "Execution error",
             Error::QueryError => "Query error",
+            Error::QueryInterrupted => "Query INTERRUPTED",
+            Error::QueryUnterminated => "Query UNTERMINATED",
+            Error::QueryDeadlocked => "Query DEADLOCKED",
+            Error::QueryUnterminatedAfterIndefinite => {
+                "Query UNTERMINATED after indefinite response"
+            }
             Error::UnexpectedNumberOfParameters => "Unexpected number of parameters",
             Error::InvalidSeparator => "Invalid separator",
             Error::DataTypeError => "Data type error",
             Error::ParameterNotAllowed => "Parameter not allowed",
             Error::MissingParameter => "Missing parameter",
             Error::SystemError => "System error",
+            Error::MemoryError => "Memory error",
+            Error::PudMemoryLost => "PUD memory lost",
+            Error::CalibrationMemoryLost => "Calibration memory lost",
+            Error::SaveRecallMemoryLost => "Save/recall memory lost",
+            Error::ConfigurationMemoryLost => "Configuration memory lost",
             Error::QueueOverflow => "Queue overflow",
             Error::CommandHeaderError => "Command header error",
             Error::HeaderSeparatorError => "Header separator error",
@@ -486,19 +1218,61 @@ impl From<Error> for &str {
             Error::TooManyDigits => "Too many digits",
             Error::NumericDataNotAllowed => "Numeric data not allowed",
             Error::InvalidWhileInLocal => "Invalid while in local",
+            Error::SettingsLostDueToRtl => "Settings lost due to rtl",
             Error::CommandProtected => "Command protected",
             Error::TriggerError => "Trigger error",
+            Error::TriggerIgnored => "Trigger ignored",
+            Error::ArmIgnored => "Arm ignored",
+            Error::InitIgnored => "Init ignored",
+            Error::TriggerDeadlock => "Trigger deadlock",
+            Error::ArmDeadlock => "Arm deadlock",
             Error::ParameterError => "Parameter error",
             Error::SettingsConflict => "Settings conflict",
             Error::DataOutOfRange => "Data out of range",
             Error::TooMuchData => "Too much data",
             Error::IllegalParameterValue => "Illegal parameter value",
             Error::HardwareError => "Hardware error",
+            Error::HardwareMissing => "Hardware missing",
+            Error::MassStorageError => "Mass storage error",
+            Error::MissingMassStorage => "Missing mass storage",
+            Error::MissingMedia => "Missing media",
+            Error::CorruptMedia => "Corrupt media",
+            Error::MediaFull => "Media full",
+            Error::DirectoryFull => "Directory full",
+            Error::FileNameNotFound => "File name not found",
+            Error::FileNameError => "File name error",
+            Error::MediaProtected => "Media protected",
+            Error::ExecutionExpressionError => "Expression error",
+            Error::MathErrorInExpression => "Math error in expression",
+            Error::MacroError => "Macro error",
+            Error::MacroSyntaxError => "Macro syntax error",
+            Error::MacroExecutionError => "Macro execution error",
+            Error::IllegalMacroLabel => "Illegal macro label",
+            Error::MacroParameterError => "Macro parameter error",
+            Error::MacroDefinitionTooLong => "Macro definition too long",
+            Error::MacroRecursionError => "Macro recursion error",
+            Error::MacroRedefinitionNotAllowed => "Macro redefinition not allowed",
+            Error::MacroHeaderNotFound => "Macro header not found",
+            Error::ProgramError => "Program error",
+            Error::CannotCreateProgram => "Cannot create program",
+            Error::IllegalProgramName => "Illegal program name",
+            Error::IllegalVariableName => "Illegal variable name",
+            Error::ProgramCurrentlyRunning => "Program currently running",
+            Error::ProgramSyntaxError => "Program syntax error",
+            Error::ProgramRuntimeError => "Program runtime error",
+            Error::MemoryUseError => "Memory use error",
+            Error::ExecutionOutOfMemory => "Out of memory",
+            Error::ReferencedNameDoesNotExist => "Referenced name does not exist",
+            Error::ReferencedNameAlreadyExists => "Referenced name already exists",
+            Error::IncompatibleType => "Incompatible type",
             Error::DeviceSpecificError => "Device specific error",
             Error::StorageFault => "Storage fault",
+            Error::StorageOutOfMemory => "Out of memory",
             Error::SelfTestFailed => "Self test failed",
             Error::CalibrationFailed => "Calibration failed",
             Error::CommunicationError => "Communication error",
+            Error::ParityError => "Parity error in program message",
+            Error::FramingError => "Framing error in program message",
             Error::InputBufferOverrun => "Input buffer overrun",
             Error::TimeoutError => "Timeout error",
             Error::GetNotAllowed => "Get not allowed",
@@ -518,16 +1292,27 @@ impl From<Error> for &str {
             Error::ExpressionError => "Expression error",
             Error::InvalidExpression => "Invalid expression",
             Error::ExpressionDataNotAllowed => "Expression data not allowed",
+            Error::MacroCommandError => "Macro error",
+            Error::InvalidOutsideMacroDefinition => "Invalid outside macro definition",
+            Error::InvalidInsideMacroDefinition => "Invalid inside macro definition",
+            Error::MacroCommandParameterError => "Macro parameter error",
             Error::OutOfMemory => "Out of memory",
             Error::ListsNotSameLength => "Lists not same length",
             Error::DataCorruptOrStale => "Data corrupt or stale",
+            Error::DataQuestionable => "Data questionable",
+            Error::InvalidFormat => "Invalid format",
+            Error::InvalidVersion => "Invalid version",
         }
     }
 }
 
 impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}", Into::<&str>::into(*self))
+        write!(f, "{}", Into::<&str>::into(*self))?;
+        if let Some(info) = self.info() {
+            write!(f, ";{info}")?;
+        }
+        Ok(())
     }
 }
 
@@ -549,15 +1334,239 @@ impl From<ParseError> for Error {
     }
 }
 
+/// A registry of device-specific errors with positive numbers, which is
+/// declared with the [device_errors](crate::device_errors) macro.
+pub trait ErrorRegistry {
+    /// All errors of the registry.
+    const ERRORS: &'static [Error];
+
+    /// Returns the error with the given number.
+    ///
+    /// The errors of the registry are looked up first, any other number is
+    /// resolved with [Error::from_code].
+    fn from_code(number: i16) -> Option<Error> {
+        Self::ERRORS
+            .iter()
+            .find(|error| error.number() == number)
+            .copied()
+            .or_else(|| Error::from_code(number))
+    }
+}
+
+/// Checks the numbers of the errors declared with
+/// [device_errors](crate::device_errors) at compile time.
+#[doc(hidden)]
+pub const fn check_device_error_numbers(numbers: &[i16]) {
+    let mut i = 0;
+    while i < numbers.len() {
+        if numbers[i] <= 0 {
+            panic!("device-specific error numbers must be positive");
+        }
+        let mut j = i + 1;
+        while j < numbers.len() {
+            if numbers[i] == numbers[j] {
+                panic!("duplicate device-specific error number");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+/// Declares a registry of device-specific errors.
+///
+/// Each error is declared as a constant [Error::Custom] of the registry type,
+/// which implements [ErrorRegistry]. The error numbers have to be positive and
+/// unique, otherwise the declaration fails to compile.
+///
+/// ```
+/// use microscpi::{self as scpi, ErrorRegistry};
+///
+/// scpi::device_errors! {
+///     /// The errors of the oven controller.
+///     pub struct OvenErrors {
+///         /// The oven has not reached its temperature yet.
+///         OVEN_COLD = (373, "Oven cold"),
+///         DOOR_OPEN = (374, "Door open"),
+///     }
+/// }
+///
+/// assert_eq!(OvenErrors::OVEN_COLD, scpi::Error::Custom(373, "Oven cold"));
+/// assert_eq!(OvenErrors::from_code(374), Some(OvenErrors::DOOR_OPEN));
+/// assert_eq!(OvenErrors::from_code(-102), Some(scpi::Error::SyntaxError));
+/// ```
+///
+/// Negative numbers are reserved for the standard errors:
+///
+/// ```compile_fail
+/// microscpi::device_errors! {
+///     pub struct OvenErrors {
+///         OVEN_COLD = (-373, "Oven cold"),
+///     }
+/// }
+/// ```
+///
+/// Every number may only be used once:
+///
+/// ```compile_fail
+/// microscpi::device_errors! {
+///     pub struct OvenErrors {
+///         OVEN_COLD = (373, "Oven cold"),
+///         DOOR_OPEN = (373, "Door open"),
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! device_errors {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$error_meta:meta])*
+                $error:ident = ($number:literal, $error_name:literal)
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name;
+
+        impl $name {
+            $(
+                $(#[$error_meta])*
+                pub const $error: $crate::Error = $crate::Error::Custom($number, $error_name);
+            )*
+        }
+
+        impl $crate::ErrorRegistry for $name {
+            const ERRORS: &'static [$crate::Error] = &[$($name::$error),*];
+        }
+
+        const _: () = $crate::check_device_error_numbers(&[$($number),*]);
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_standard_errors() {
+        for pair in STANDARD_ERRORS.windows(2) {
+            assert!(
+                pair[0].number() > pair[1].number(),
+                "{:?} and {:?} are not ordered by their numbers",
+                pair[0],
+                pair[1]
+            );
+        }
+        assert!(!is_standard_error(&Error::Custom(-100, "Command error")));
+        for error in STANDARD_ERRORS {
+            assert!(is_standard_error(error), "{error:?}");
+            assert!((-499..=-100).contains(&error.number()), "{error:?}");
+            assert!(!Into::<&str>::into(*error).is_empty(), "{error:?}");
+        }
+    }
+
+    #[test]
+    fn test_error_from_code() {
+        for error in STANDARD_ERRORS {
+            assert_eq!(Error::from_code(error.number()), Some(*error));
+        }
+        assert_eq!(Error::from_code(999), Some(Error::Custom(999, "")));
+        assert_eq!(Error::from_code(-499), None);
+        assert_eq!(Error::from_code(-1), None);
+        assert_eq!(Error::from_code(0), None);
+    }
+
+    #[test]
+    fn test_device_errors() {
+        crate::device_errors! {
+            struct DeviceErrors {
+                OVEN_COLD = (373, "Oven cold"),
+                DOOR_OPEN = (374, "Door open"),
+            }
+        }
+
+        assert_eq!(DeviceErrors::ERRORS.len(), 2);
+        assert_eq!(DeviceErrors::OVEN_COLD.number(), 373);
+        assert!(DeviceErrors::DOOR_OPEN.is_device_specific_error());
+        assert_eq!(
+            DeviceErrors::from_code(373),
+            Some(Error::Custom(373, "Oven cold"))
+        );
+        assert_eq!(DeviceErrors::from_code(375), Some(Error::Custom(375, "")));
+        assert_eq!(DeviceErrors::from_code(-350), Some(Error::QueueOverflow));
+        assert_eq!(DeviceErrors::from_code(-1), None);
+    }
+
+    #[test]
+    fn test_error_classes() {
+        for error in STANDARD_ERRORS {
+            let classes = [
+                error.is_command_error(),
+                error.is_execution_error(),
+                error.is_device_specific_error(),
+                error.is_query_error(),
+            ];
+            assert_eq!(classes.iter().filter(|c| **c).count(), 1, "{error:?}");
+        }
+        assert!(Error::SyntaxError.is_command_error());
+        assert!(Error::DataOutOfRange.is_execution_error());
+        assert!(Error::QueueOverflow.is_device_specific_error());
+        assert!(Error::Custom(1, "").is_device_specific_error());
+        assert!(Error::QueryDeadlocked.is_query_error());
+        assert!(!Error::Custom(-1000, "").is_command_error());
+    }
+
+    #[test]
+    fn test_error_info() {
+        let error = Error::DataOutOfRange.with_info("clipped to 10V");
+        assert_eq!(error.number(), -222);
+        assert_eq!(error.info(), Some("clipped to 10V"));
+        assert_eq!(Into::<&str>::into(error), "Data out of range");
+        assert_eq!(error.to_string(), "Data out of range;clipped to 10V");
+        assert_eq!(Error::DataOutOfRange.info(), None);
+        assert!(matches!(error.kind(), Error::DataOutOfRange));
+        assert!(matches!(Error::HardwareError.kind(), Error::HardwareError));
+        let custom = Error::Custom(373, "Oven cold");
+        assert!(matches!(
+            custom.with_info("zone 2").kind(),
+            Error::Custom(373, "Oven cold")
+        ));
+        // A custom error reusing a standard number keeps its name.
+        assert!(matches!(
+            Error::Custom(-222, "Clipped").with_info("10V").kind(),
+            Error::Custom(-222, "Clipped")
+        ));
+
+        // Attaching info replaces the previous info.
+        let error = error.with_info("clipped to 5V");
+        assert_eq!(error.info(), Some("clipped to 5V"));
+
+        // The info is ignored when comparing errors.
+        assert_eq!(error, Error::DataOutOfRange);
+        assert_eq!(error, Error::DataOutOfRange.with_info("clipped to 10V"));
+        assert_ne!(error, Error::TooMuchData);
+        assert_ne!(
+            Error::Custom(-222, "Clipped").with_info("10V"),
+            Error::DataOutOfRange
+        );
+
+        // Long info is truncated at a character boundary.
+        let error = Error::HardwareError.with_info("temperature of channel 1 above 85 °C");
+        assert_eq!(error.info(), Some("temperature of channel 1 above 8"));
+        let error = Error::HardwareError.with_info("temperature of channel 1 at 10 °C");
+        assert_eq!(error.info(), Some("temperature of channel 1 at 10 "));
+    }
+
     #[test]
     fn test_error_number() {
         assert_eq!(Error::CommandError.number(), -100);
         assert_eq!(Error::InvalidCharacter.number(), -101);
         assert_eq!(Error::SyntaxError.number(), -102);
+        assert_eq!(Error::TriggerDeadlock.number(), -214);
+        assert_eq!(Error::MediaProtected.number(), -258);
+        assert_eq!(Error::ConfigurationMemoryLost.number(), -315);
         assert_eq!(Error::Custom(999, "Custom Error").number(), 999);
     }
 
@@ -656,6 +1665,14 @@ mod tests {
         assert_eq!(Error::ListsNotSameLength.number(), -226);
         assert_eq!(Error::DataCorruptOrStale.number(), -230);
         assert_eq!(Error::HardwareError.number(), -240);
+        assert_eq!(Error::MacroError.number(), -270);
+        assert_eq!(Error::MacroExecutionError.number(), -272);
+        assert_eq!(Error::IllegalMacroLabel.number(), -273);
+        assert_eq!(Error::MacroParameterError.number(), -274);
+        assert_eq!(Error::MacroDefinitionTooLong.number(), -275);
+        assert_eq!(Error::MacroRecursionError.number(), -276);
+        assert_eq!(Error::MacroRedefinitionNotAllowed.number(), -277);
+        assert_eq!(Error::MacroHeaderNotFound.number(), -278);
         assert_eq!(Error::DeviceSpecificError.number(), -300);
         assert_eq!(Error::SystemError.number(), -310);
         assert_eq!(Error::StorageFault.number(), -320);
@@ -666,5 +1683,6 @@ mod tests {
         assert_eq!(Error::InputBufferOverrun.number(), -363);
         assert_eq!(Error::TimeoutError.number(), -365);
         assert_eq!(Error::QueryError.number(), -400);
+        assert_eq!(Error::QueryInterrupted.number(), -410);
     }
 }
@@ -2,9 +2,16 @@ use crate::Error;
 
 /// An error queue stores the occurred errors until they are queried by the
 /// user. It should behave according to the SCPI standard.
-pub trait ErrorQueue: Default {
+///
+/// How a queue is constructed is up to the implementation, so it may e.g.
+/// borrow its storage or take the capacity as a parameter.
+pub trait ErrorQueue {
     /// The number of errors currently stored in the error queue.
     fn error_count(&self) -> usize;
+    /// Returns whether the error queue is empty.
+    fn is_empty(&self) -> bool {
+        self.error_count() == 0
+    }
     /// Append a new error to the end of the error queue.
     ///
     /// How a queue overflow is handled depends on the implementation of the
@@ -15,47 +22,170 @@ pub trait ErrorQueue: Default {
     /// Get and remove the error in the front of the error queue. If the queue
     /// is empty, [None] is returned.
     fn pop_error(&mut self) -> Option<Error>;
+    /// Remove all errors from the error queue.
+    fn clear(&mut self) {
+        while self.pop_error().is_some() {}
+    }
+    /// The number of errors lost due to queue overflows since the count was
+    /// last reset, including the errors replaced by [Error::QueueOverflow].
+    ///
+    /// Queues that don't keep track of lost errors return 0.
+    fn dropped_count(&self) -> usize {
+        0
+    }
+    /// Reset the number of lost errors.
+    fn reset_dropped_count(&mut self) {}
+}
+
+/// Replaces the most recent error of a full queue by [Error::QueueOverflow]
+/// and returns the number of errors lost by it.
+fn overflow(most_recent: Option<&mut Error>) -> usize {
+    // If the queue is full, change the most recent added item to an *Queue
+    // Overflow* error, as specified in IEEE 488.2, 21.8.1.
+    match most_recent {
+        Some(value) if *value != Error::QueueOverflow => {
+            *value = Error::QueueOverflow;
+            2
+        }
+        _ => 1,
+    }
 }
 
 /// An implementation of an [ErrorQueue] utilizing a statically allocated
 /// queue holding a maximum of `N` errors.
+///
+/// The number of errors lost due to overflows is counted, saturating at
+/// [usize::MAX].
 #[derive(Default)]
-pub struct StaticErrorQueue<const N: usize>(heapless::Deque<Error, N>);
+pub struct StaticErrorQueue<const N: usize> {
+    errors: heapless::Deque<Error, N>,
+    dropped: usize,
+    on_error: Option<fn(Error)>,
+}
 
 impl<const N: usize> StaticErrorQueue<N> {
     pub fn new() -> StaticErrorQueue<N> {
         StaticErrorQueue::default()
     }
+
+    /// Sets a callback, which is called with every pushed error after it was
+    /// queued or replaced by [Error::QueueOverflow], e.g. to signal a fault.
+    pub fn on_error(mut self, callback: fn(Error)) -> StaticErrorQueue<N> {
+        self.on_error = Some(callback);
+        self
+    }
 }
 
 impl<const N: usize> ErrorQueue for StaticErrorQueue<N> {
     fn push_error(&mut self, error: Error) {
-        #[cfg(feature = "defmt")]
-        defmt::trace!("Push Error: {}", error);
-        if self.0.push_back(error).is_err() {
-            // If the queue is full, change the most recent added item to an *Queue
-            // Overflow* error, as specified in IEEE 488.2, 21.8.1.
-            if let Some(value) = self.0.back_mut() {
-                *value = Error::QueueOverflow;
-            }
+        warn!("Push Error: {}", error);
+        if self.errors.push_back(error).is_err() {
+            let dropped = overflow(self.errors.back_mut());
+            self.dropped = self.dropped.saturating_add(dropped);
+        }
+        if let Some(on_error) = self.on_error {
+            on_error(error);
+        }
+    }
+
+    fn pop_error(&mut self) -> Option<Error> {
+        self.errors.pop_front()
+    }
+
+    fn error_count(&self) -> usize {
+        self.errors.len()
+    }
+
+    fn dropped_count(&self) -> usize {
+        self.dropped
+    }
+
+    fn reset_dropped_count(&mut self) {
+        self.dropped = 0;
+    }
+}
+
+/// An implementation of an [ErrorQueue] utilizing a heap allocated queue,
+/// which is either unlimited or holds a maximum number of errors.
+///
+/// The number of errors lost due to overflows is counted, saturating at
+/// [usize::MAX].
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct DynamicErrorQueue {
+    errors: std::collections::VecDeque<Error>,
+    max_len: Option<usize>,
+    dropped: usize,
+    on_error: Option<fn(Error)>,
+}
+
+#[cfg(feature = "std")]
+impl DynamicErrorQueue {
+    /// Creates an unlimited error queue.
+    pub fn new() -> DynamicErrorQueue {
+        DynamicErrorQueue::default()
+    }
+
+    /// Creates an error queue holding a maximum of `max_len` errors.
+    ///
+    /// The queue holds at least one error, so it can report an overflow with
+    /// [Error::QueueOverflow]. A `max_len` of zero is treated as one.
+    pub fn with_max_len(max_len: usize) -> DynamicErrorQueue {
+        DynamicErrorQueue {
+            max_len: Some(max_len.max(1)),
+            ..Default::default()
+        }
+    }
+
+    /// Sets a callback, which is called with every pushed error after it was
+    /// queued or replaced by [Error::QueueOverflow], e.g. to signal a fault.
+    pub fn on_error(mut self, callback: fn(Error)) -> DynamicErrorQueue {
+        self.on_error = Some(callback);
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl ErrorQueue for DynamicErrorQueue {
+    fn push_error(&mut self, error: Error) {
+        warn!("Push Error: {}", error);
+        if self
+            .max_len
+            .is_some_and(|max_len| self.errors.len() >= max_len)
+        {
+            let dropped = overflow(self.errors.back_mut());
+            self.dropped = self.dropped.saturating_add(dropped);
+        }
+        else {
+            self.errors.push_back(error);
+        }
+        if let Some(on_error) = self.on_error {
+            on_error(error);
         }
     }
 
     fn pop_error(&mut self) -> Option<Error> {
-        self.0.pop_front()
+        self.errors.pop_front()
     }
 
     fn error_count(&self) -> usize {
-        self.0.len()
+        self.errors.len()
+    }
+
+    fn dropped_count(&self) -> usize {
+        self.dropped
+    }
+
+    fn reset_dropped_count(&mut self) {
+        self.dropped = 0;
     }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_push_and_pop_error() {
-        let mut queue: StaticErrorQueue<3> = StaticErrorQueue::new();
+    fn check_push_and_pop_error(mut queue: impl ErrorQueue) {
         assert_eq!(queue.error_count(), 0);
 
         queue.push_error(Error::ExpressionError);
@@ -66,9 +196,8 @@ mod tests {
         assert_eq!(queue.error_count(), 0);
     }
 
-    #[test]
-    fn test_queue_overflow() {
-        let mut queue: StaticErrorQueue<2> = StaticErrorQueue::new();
+    /// Expects a queue holding a maximum of two errors.
+    fn check_queue_overflow(mut queue: impl ErrorQueue) {
         queue.push_error(Error::CalibrationFailed);
         queue.push_error(Error::HardwareError);
         assert_eq!(queue.error_count(), 2);
@@ -84,16 +213,37 @@ mod tests {
         assert_eq!(error, Some(Error::QueueOverflow));
     }
 
-    #[test]
-    fn test_pop_empty_queue() {
-        let mut queue: StaticErrorQueue<2> = StaticErrorQueue::new();
+    /// Expects a queue holding a maximum of two errors.
+    fn check_dropped_count(mut queue: impl ErrorQueue) {
+        assert_eq!(queue.dropped_count(), 0);
+        for error in [
+            Error::CalibrationFailed,
+            Error::HardwareError,
+            Error::DataTypeError,
+            Error::SyntaxError,
+            Error::OutOfMemory,
+        ] {
+            queue.push_error(error);
+        }
+
+        // The replaced HardwareError and the three errors pushed to the full
+        // queue are lost.
+        assert_eq!(queue.dropped_count(), 4);
+        assert_eq!(queue.pop_error(), Some(Error::CalibrationFailed));
+        assert_eq!(queue.pop_error(), Some(Error::QueueOverflow));
+
+        queue.reset_dropped_count();
+        assert_eq!(queue.dropped_count(), 0);
+        queue.push_error(Error::HardwareError);
+        assert_eq!(queue.dropped_count(), 0);
+    }
+
+    fn check_pop_empty_queue(mut queue: impl ErrorQueue) {
         let error = queue.pop_error();
         assert_eq!(error, None);
     }
 
-    #[test]
-    fn test_error_count() {
-        let mut queue: StaticErrorQueue<3> = StaticErrorQueue::new();
+    fn check_error_count(mut queue: impl ErrorQueue) {
         assert_eq!(queue.error_count(), 0);
 
         queue.push_error(Error::BlockDataNotAllowed);
@@ -108,4 +258,123 @@ mod tests {
         queue.pop_error();
         assert_eq!(queue.error_count(), 0);
     }
+
+    #[test]
+    fn test_push_and_pop_error() {
+        check_push_and_pop_error(StaticErrorQueue::<3>::new());
+        #[cfg(feature = "std")]
+        check_push_and_pop_error(DynamicErrorQueue::new());
+    }
+
+    #[test]
+    fn test_queue_overflow() {
+        check_queue_overflow(StaticErrorQueue::<2>::new());
+        #[cfg(feature = "std")]
+        check_queue_overflow(DynamicErrorQueue::with_max_len(2));
+    }
+
+    #[test]
+    fn test_dropped_count() {
+        check_dropped_count(StaticErrorQueue::<2>::new());
+        #[cfg(feature = "std")]
+        check_dropped_count(DynamicErrorQueue::with_max_len(2));
+    }
+
+    #[test]
+    fn test_on_error() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static PUSHED: AtomicUsize = AtomicUsize::new(0);
+        fn count_error(_error: Error) {
+            PUSHED.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut queue = StaticErrorQueue::<2>::new().on_error(count_error);
+        queue.push_error(Error::SyntaxError);
+        assert_eq!(PUSHED.load(Ordering::Relaxed), 1);
+        // An overflow is notified once as well.
+        queue.push_error(Error::HardwareError);
+        queue.push_error(Error::OutOfMemory);
+        assert_eq!(PUSHED.load(Ordering::Relaxed), 3);
+        queue.pop_error();
+        queue.clear();
+        assert_eq!(PUSHED.load(Ordering::Relaxed), 3);
+
+        #[cfg(feature = "std")]
+        {
+            let mut queue = DynamicErrorQueue::with_max_len(1).on_error(count_error);
+            queue.push_error(Error::SyntaxError);
+            queue.push_error(Error::HardwareError);
+            assert_eq!(PUSHED.load(Ordering::Relaxed), 5);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_zero_max_len() {
+        let mut queue = DynamicErrorQueue::with_max_len(0);
+        queue.push_error(Error::SyntaxError);
+        queue.push_error(Error::HardwareError);
+        assert_eq!(queue.pop_error(), Some(Error::QueueOverflow));
+        assert_eq!(queue.dropped_count(), 2);
+    }
+
+    #[test]
+    fn test_pop_empty_queue() {
+        check_pop_empty_queue(StaticErrorQueue::<2>::new());
+        #[cfg(feature = "std")]
+        check_pop_empty_queue(DynamicErrorQueue::new());
+    }
+
+    #[test]
+    fn test_error_count() {
+        check_error_count(StaticErrorQueue::<3>::new());
+        #[cfg(feature = "std")]
+        check_error_count(DynamicErrorQueue::new());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_unlimited_queue() {
+        let mut queue = DynamicErrorQueue::new();
+        for _ in 0..1000 {
+            queue.push_error(Error::HardwareError);
+        }
+        assert_eq!(queue.error_count(), 1000);
+        assert!(std::iter::from_fn(|| queue.pop_error()).all(|e| e == Error::HardwareError));
+    }
+
+    /// A queue borrowing its storage, which can't implement [Default] and
+    /// doesn't count lost errors.
+    struct BorrowedErrorQueue<'a>(&'a mut std::vec::Vec<Error>);
+
+    impl ErrorQueue for BorrowedErrorQueue<'_> {
+        fn error_count(&self) -> usize {
+            self.0.len()
+        }
+
+        fn push_error(&mut self, error: Error) {
+            self.0.push(error);
+        }
+
+        fn pop_error(&mut self) -> Option<Error> {
+            (!self.0.is_empty()).then(|| self.0.remove(0))
+        }
+    }
+
+    #[test]
+    fn test_borrowed_queue() {
+        let mut storage = std::vec::Vec::new();
+        let mut queue = BorrowedErrorQueue(&mut storage);
+        assert!(queue.is_empty());
+
+        queue.push_error(Error::SyntaxError);
+        queue.push_error(Error::HardwareError);
+        assert!(!queue.is_empty());
+        assert_eq!(queue.pop_error(), Some(Error::SyntaxError));
+
+        queue.clear();
+        assert!(queue.is_empty());
+        assert_eq!(queue.dropped_count(), 0);
+    }
 }
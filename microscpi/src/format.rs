@@ -0,0 +1,435 @@
+//! This module contains the data format selection for array responses.
+use crate::response::write_block_header;
+use crate::value::mnemonic_matches;
+use crate::{ByteOrder, Error, Response, Value, Write};
+
+/// Data type used for array responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DataType {
+    /// Comma-separated ASCII numbers.
+    #[default]
+    Ascii,
+    /// Definite length block of IEEE 754 floating point numbers.
+    Real,
+    /// Definite length block of signed integers.
+    Integer,
+}
+
+impl DataType {
+    /// The default length in bits, if no length is specified.
+    fn default_length(&self) -> u8 {
+        match self {
+            DataType::Ascii => 0,
+            DataType::Real => 32,
+            DataType::Integer => 16,
+        }
+    }
+
+    /// Checks whether the length in bits is supported for this data type.
+    fn supports_length(&self, length: u8) -> bool {
+        match self {
+            DataType::Ascii => length == 0,
+            DataType::Real => matches!(length, 32 | 64),
+            DataType::Integer => matches!(length, 8 | 16 | 32 | 64),
+        }
+    }
+}
+
+impl TryInto<DataType> for &Value<'_> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<DataType, Self::Error> {
+        match self {
+            Value::Characters(data) if mnemonic_matches("ASCii", data) => Ok(DataType::Ascii),
+            Value::Characters(data) if mnemonic_matches("REAL", data) => Ok(DataType::Real),
+            Value::Characters(data) if mnemonic_matches("INTeger", data) => Ok(DataType::Integer),
+            Value::Characters(_) => Err(Error::IllegalParameterValue),
+            _ => Err(Error::DataTypeError),
+        }
+    }
+}
+
+impl TryInto<DataType> for Value<'_> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<DataType, Self::Error> {
+        (&self).try_into()
+    }
+}
+
+impl Response for DataType {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        match self {
+            DataType::Ascii => f.write_str("ASC").await,
+            DataType::Real => f.write_str("REAL").await,
+            DataType::Integer => f.write_str("INT").await,
+        }
+    }
+}
+
+impl TryInto<ByteOrder> for &Value<'_> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ByteOrder, Self::Error> {
+        match self {
+            Value::Characters(data) if mnemonic_matches("NORMal", data) => Ok(ByteOrder::BigEndian),
+            Value::Characters(data) if mnemonic_matches("SWAPped", data) => {
+                Ok(ByteOrder::LittleEndian)
+            }
+            Value::Characters(_) => Err(Error::IllegalParameterValue),
+            _ => Err(Error::DataTypeError),
+        }
+    }
+}
+
+impl TryInto<ByteOrder> for Value<'_> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ByteOrder, Self::Error> {
+        (&self).try_into()
+    }
+}
+
+impl Response for ByteOrder {
+    /// Writes the byte order as `NORM` (big-endian) or `SWAP` (little-endian).
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        match self {
+            ByteOrder::BigEndian => f.write_str("NORM").await,
+            ByteOrder::LittleEndian => f.write_str("SWAP").await,
+        }
+    }
+}
+
+/// Data format
+///
+/// The data format selected with the `FORMat[:DATA]` and `FORMat:BORDer`
+/// commands. It determines how a [DataArray] is written. The default format is
+/// ASCII with big-endian (NORMal) byte order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DataFormat {
+    data_type: DataType,
+    length: u8,
+    byte_order: ByteOrder,
+}
+
+impl Default for DataFormat {
+    fn default() -> Self {
+        DataFormat {
+            data_type: DataType::Ascii,
+            length: 0,
+            byte_order: ByteOrder::BigEndian,
+        }
+    }
+}
+
+impl DataFormat {
+    /// Creates a new data format.
+    ///
+    /// The length is specified in bits. If no length is given, the default
+    /// length of the data type is used: 32 for [DataType::Real] and 16 for
+    /// [DataType::Integer]. Returns [Error::IllegalParameterValue] if the
+    /// length is not supported for the data type.
+    pub fn new(data_type: DataType, length: Option<u8>) -> Result<DataFormat, Error> {
+        let length = length.unwrap_or(data_type.default_length());
+
+        if !data_type.supports_length(length) {
+            return Err(Error::IllegalParameterValue);
+        }
+
+        Ok(DataFormat {
+            data_type,
+            length,
+            byte_order: ByteOrder::BigEndian,
+        })
+    }
+
+    /// Returns the format with the specified byte order.
+    pub fn with_byte_order(self, byte_order: ByteOrder) -> Self {
+        DataFormat { byte_order, ..self }
+    }
+
+    /// The selected data type.
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+
+    /// The selected length in bits.
+    pub fn length(&self) -> u8 {
+        self.length
+    }
+
+    /// The selected byte order of multi-byte elements.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// Encodes a single element in the binary representation of this format.
+    ///
+    /// Returns a buffer and the number of valid bytes in this buffer.
+    fn encode<T: DataElement>(&self, value: T) -> ([u8; 8], usize) {
+        let mut buf = [0u8; 8];
+        let len = (self.length / 8) as usize;
+
+        match (self.data_type, self.length) {
+            (DataType::Real, 32) => {
+                buf[..4].copy_from_slice(&(value.to_f64() as f32).to_be_bytes());
+            }
+            (DataType::Real, _) => {
+                buf.copy_from_slice(&value.to_f64().to_be_bytes());
+            }
+            (_, 8) => {
+                buf[..1].copy_from_slice(
+                    &(value.to_i64().clamp(i8::MIN.into(), i8::MAX.into()) as i8).to_be_bytes(),
+                );
+            }
+            (_, 16) => {
+                buf[..2].copy_from_slice(
+                    &(value.to_i64().clamp(i16::MIN.into(), i16::MAX.into()) as i16).to_be_bytes(),
+                );
+            }
+            (_, 32) => {
+                buf[..4].copy_from_slice(
+                    &(value.to_i64().clamp(i32::MIN.into(), i32::MAX.into()) as i32).to_be_bytes(),
+                );
+            }
+            (_, _) => {
+                buf.copy_from_slice(&value.to_i64().to_be_bytes());
+            }
+        }
+
+        if self.byte_order == ByteOrder::LittleEndian {
+            buf[..len].reverse();
+        }
+
+        (buf, len)
+    }
+}
+
+impl Response for DataFormat {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        (self.data_type, self.length).write_response(f).await
+    }
+}
+
+/// A numeric type that can be written as part of a [DataArray].
+pub trait DataElement: Copy + Response {
+    /// Converts the value into a float.
+    fn to_f64(self) -> f64;
+    /// Converts the value into an integer, saturating at the bounds.
+    fn to_i64(self) -> i64;
+}
+
+macro_rules! impl_data_element {
+    ($type:ty) => {
+        impl DataElement for $type {
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn to_i64(self) -> i64 {
+                self as i64
+            }
+        }
+    };
+}
+
+impl_data_element!(u8);
+impl_data_element!(i8);
+impl_data_element!(u16);
+impl_data_element!(i16);
+impl_data_element!(u32);
+impl_data_element!(i32);
+impl_data_element!(u64);
+impl_data_element!(i64);
+impl_data_element!(f32);
+impl_data_element!(f64);
+
+/// Data array
+///
+/// An array of numbers that is written according to a [DataFormat]. With the
+/// ASCII format, the numbers are written comma-separated. With the REAL or
+/// INTeger format, the numbers are converted to the selected type and written
+/// as a definite length block.
+pub struct DataArray<'a, T> {
+    data: &'a [T],
+    format: DataFormat,
+}
+
+impl<'a, T: DataElement> DataArray<'a, T> {
+    /// Creates a new data array that is written in the specified format.
+    pub fn new(data: &'a [T], format: DataFormat) -> DataArray<'a, T> {
+        DataArray { data, format }
+    }
+}
+
+impl<T: DataElement> Response for DataArray<'_, T> {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        if self.format.data_type == DataType::Ascii {
+            return self.data.write_response(f).await;
+        }
+
+        let len = self
+            .data
+            .len()
+            .checked_mul((self.format.length / 8) as usize)
+            .ok_or(Error::TooMuchData)?;
+
+        write_block_header(f, len).await?;
+
+        for value in self.data {
+            let (buf, len) = self.format.encode(*value);
+            f.write_bytes(&buf[..len]).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_type() {
+        assert_eq!(Value::Characters("ASC").try_into(), Ok(DataType::Ascii));
+        assert_eq!(Value::Characters("real").try_into(), Ok(DataType::Real));
+        assert_eq!(
+            Value::Characters("INTEGER").try_into(),
+            Ok(DataType::Integer)
+        );
+        assert_eq!(
+            Value::Characters("BIN").try_into(),
+            Err::<DataType, Error>(Error::IllegalParameterValue)
+        );
+        assert_eq!(
+            Value::String("REAL").try_into(),
+            Err::<DataType, Error>(Error::DataTypeError)
+        );
+    }
+
+    #[test]
+    fn test_data_format() {
+        assert_eq!(
+            DataFormat::new(DataType::Real, None).map(|f| f.length()),
+            Ok(32)
+        );
+        assert_eq!(
+            DataFormat::new(DataType::Integer, Some(8)).map(|f| f.length()),
+            Ok(8)
+        );
+        assert_eq!(
+            DataFormat::new(DataType::Real, Some(16)),
+            Err(Error::IllegalParameterValue)
+        );
+        assert_eq!(
+            DataFormat::new(DataType::Ascii, Some(8)),
+            Err(Error::IllegalParameterValue)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_data_format_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        DataFormat::default()
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"ASC,0");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        DataFormat::new(DataType::Integer, Some(32))
+            .unwrap()
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"INT,32");
+    }
+
+    #[tokio::test]
+    async fn test_data_array_response() {
+        let data = [1.5f32, -2.0];
+
+        let mut buffer: Vec<u8> = Vec::new();
+        DataArray::new(&data, DataFormat::default())
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"1.5,-2");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        DataArray::new(&data, DataFormat::new(DataType::Real, None).unwrap())
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"#18\x3f\xc0\x00\x00\xc0\x00\x00\x00");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        DataArray::new(
+            &[300i32, -1],
+            DataFormat::new(DataType::Integer, Some(8)).unwrap(),
+        )
+        .write_response(&mut buffer)
+        .await
+        .unwrap();
+        assert_eq!(buffer, b"#12\x7f\xff");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        DataArray::new(
+            &[0x1234u16],
+            DataFormat::new(DataType::Real, Some(64)).unwrap(),
+        )
+        .write_response(&mut buffer)
+        .await
+        .unwrap();
+        assert_eq!(buffer, b"#18\x40\xb2\x34\x00\x00\x00\x00\x00");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        DataArray::<f64>::new(&[], DataFormat::new(DataType::Real, None).unwrap())
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"#10");
+    }
+
+    #[test]
+    fn test_byte_order() {
+        assert_eq!(
+            Value::Characters("NORM").try_into(),
+            Ok(ByteOrder::BigEndian)
+        );
+        assert_eq!(
+            Value::Characters("swapped").try_into(),
+            Ok(ByteOrder::LittleEndian)
+        );
+        assert_eq!(
+            Value::Characters("BIG").try_into(),
+            Err::<ByteOrder, Error>(Error::IllegalParameterValue)
+        );
+        assert_eq!(DataFormat::default().byte_order(), ByteOrder::BigEndian);
+    }
+
+    #[tokio::test]
+    async fn test_data_array_swapped() {
+        let format = DataFormat::new(DataType::Real, None)
+            .unwrap()
+            .with_byte_order(ByteOrder::LittleEndian);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        DataArray::new(&[1.5f32, -2.0], format)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"#18\x00\x00\xc0\x3f\x00\x00\x00\xc0");
+
+        let format = format.with_byte_order(ByteOrder::BigEndian);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        DataArray::new(&[1.5f32, -2.0], format)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"#18\x3f\xc0\x00\x00\xc0\x00\x00\x00");
+    }
+}
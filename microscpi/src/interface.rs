@@ -1,48 +1,642 @@
+#[cfg(feature = "command-macros")]
+use crate::macro_store::{macro_call, MacroCall, MACRO_EXPANSION_SIZE};
 use crate::parser::{self, CommandCall, ParseError};
-use crate::{tree, CommandId, Error, Value};
+use crate::response::DynWriter;
+use crate::{tree, ByteOrder, CommandId, Error, SyncWrite, Value, MAX_EXECUTE_STR_LENGTH};
 
+/// Error Handler
+///
+/// Every interface needs an error handler, which receives the errors that
+/// occur while parsing and executing commands. Interfaces implementing
+/// [ErrorCommands](crate::ErrorCommands) push the errors into their error
+/// queue. For prototypes, the errors can be ignored with the
+/// [IgnoreErrors](crate::IgnoreErrors) derive:
+///
+/// ```
+/// use microscpi::{self as scpi, Interface};
+///
+/// #[derive(scpi::IgnoreErrors)]
+/// pub struct ExampleInterface;
+///
+/// #[scpi::interface]
+/// impl ExampleInterface {
+///     #[scpi(cmd = "VALue?")]
+///     fn value(&mut self) -> Result<u32, scpi::Error> {
+///         Ok(42)
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut output = Vec::new();
+/// ExampleInterface.run(b"UNKNOWN\n", &mut output).await;
+/// ExampleInterface.run(b"VAL?\n", &mut output).await;
+/// assert_eq!(output, b"42\n");
+/// # }
+/// ```
+///
+/// With the `log` or `defmt` feature, the [LogErrors](crate::LogErrors) derive
+/// emits the errors through the logging facade instead.
 pub trait ErrorHandler {
     fn handle_error(&mut self, _error: Error);
 }
 
-pub trait Adapter {
+/// Runs a future to completion, unless the timeout future completes first.
+#[doc(hidden)]
+pub async fn with_timeout<F: core::future::Future>(
+    future: F, timeout: impl core::future::Future<Output = ()>,
+) -> Result<F::Output, Error> {
+    let mut future = core::pin::pin!(future);
+    let mut timeout = core::pin::pin!(timeout);
+
+    core::future::poll_fn(|cx| {
+        if let core::task::Poll::Ready(output) = future.as_mut().poll(cx) {
+            core::task::Poll::Ready(Ok(output))
+        }
+        else if timeout.as_mut().poll(cx).is_ready() {
+            core::task::Poll::Ready(Err(Error::TimeoutError))
+        }
+        else {
+            core::task::Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Emits an error through the enabled logging facades.
+#[doc(hidden)]
+#[cfg(any(feature = "log", feature = "defmt"))]
+pub fn log_error(error: Error) {
+    error!("SCPI error: {}", error);
+}
+
+/// Writes the body of a macro followed by its separator and the remaining
+/// commands to the buffer. The first command of the body is resolved from the
+/// root node.
+///
+/// # Returns
+/// Whether the label belongs to a macro.
+#[cfg(feature = "command-macros")]
+fn expand_macro<I: Interface + ?Sized>(
+    interface: &mut I, call: &MacroCall<'_>, rest: &[u8],
+    buf: &mut heapless::Vec<u8, MACRO_EXPANSION_SIZE>,
+) -> Result<bool, Error> {
+    let Some(body) = interface.macro_body(call.label)
+    else {
+        return Ok(false);
+    };
+    if call.parameters {
+        return Err(Error::MacroParameterError);
+    }
+
+    // Resolve the first command of the macro from the root node.
+    let root = body
+        .iter()
+        .find(|c| !c.is_ascii_whitespace())
+        .is_some_and(u8::is_ascii_alphabetic);
+    let prefix: &[u8] = if root { b":" } else { b"" };
+
+    buf.extend_from_slice(prefix)
+        .and_then(|_| buf.extend_from_slice(body))
+        .and_then(|_| buf.push(call.separator).or(Err(())))
+        .and_then(|_| buf.extend_from_slice(rest))
+        .or(Err(Error::MacroExecutionError))?;
+    Ok(true)
+}
+
+/// Error type of an adapter
+///
+/// The error type is shared by the receiving and the transmitting half of an
+/// adapter, see [AdapterRead] and [AdapterWrite].
+pub trait AdapterErrorType {
     type Error;
+}
 
+/// Receiving half of an adapter
+///
+/// Transports with independent receive and transmit objects implement
+/// [AdapterRead] and [AdapterWrite] on separate types and are processed with
+/// [Interface::process_split].
+pub trait AdapterRead: AdapterErrorType {
+    /// Reads input into `dst` and returns the number of bytes read.
+    ///
+    /// A return value of `0` indicates the end of the input.
     async fn read(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+/// Transmitting half of an adapter
+///
+/// See [AdapterRead].
+pub trait AdapterWrite: AdapterErrorType {
     async fn write(&mut self, src: &[u8]) -> Result<(), Self::Error>;
     async fn flush(&mut self) -> Result<(), Self::Error>;
+
+    /// Returns whether a response has been written, but not yet been read by
+    /// the controller.
+    ///
+    /// If a new program message is received while a response is pending, the
+    /// response is discarded and a [Error::QueryInterrupted] error is reported.
+    /// The default implementation assumes that responses are always read
+    /// immediately.
+    fn response_pending(&self) -> bool {
+        false
+    }
+
+    /// Discards a pending response.
+    async fn discard_response(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Adapter
+///
+/// An adapter connects an interface to a transport, which is processed with
+/// [Interface::process]. It is implemented for every type that implements
+/// both [AdapterRead] and [AdapterWrite].
+pub trait Adapter: AdapterRead + AdapterWrite {}
+
+impl<A: AdapterRead + AdapterWrite> Adapter for A {}
+
+/// Adapter combining a receiving and a transmitting half.
+struct SplitAdapter<'a, R, W> {
+    rx: &'a mut R,
+    tx: &'a mut W,
+}
+
+impl<R: AdapterRead, W: AdapterWrite<Error = R::Error>> AdapterErrorType
+    for SplitAdapter<'_, R, W>
+{
+    type Error = R::Error;
+}
+
+impl<R: AdapterRead, W: AdapterWrite<Error = R::Error>> AdapterRead for SplitAdapter<'_, R, W> {
+    async fn read(&mut self, dst: &mut [u8]) -> Result<usize, Self::Error> {
+        self.rx.read(dst).await
+    }
+}
+
+impl<R: AdapterRead, W: AdapterWrite<Error = R::Error>> AdapterWrite for SplitAdapter<'_, R, W> {
+    async fn write(&mut self, src: &[u8]) -> Result<(), Self::Error> {
+        self.tx.write(src).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.tx.flush().await
+    }
+
+    fn response_pending(&self) -> bool {
+        self.tx.response_pending()
+    }
+
+    async fn discard_response(&mut self) -> Result<(), Self::Error> {
+        self.tx.discard_response().await
+    }
+}
+
+/// Writer that streams responses to an [Adapter].
+///
+/// The response is collected in a chunk buffer of `N` bytes, which is written
+/// to the adapter whenever it is full. Thus responses of any size can be
+/// written. An error of the adapter is stored and reported as
+/// [Error::SystemError] to the response writer.
+pub(crate) struct AdapterWriter<'a, A: Adapter, const N: usize> {
+    adapter: &'a mut A,
+    buf: heapless::Vec<u8, N>,
+    error: Option<A::Error>,
+}
+
+impl<'a, A: Adapter, const N: usize> AdapterWriter<'a, A, N> {
+    pub(crate) fn new(adapter: &'a mut A) -> Self {
+        AdapterWriter {
+            adapter,
+            buf: heapless::Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Writes the buffered data to the adapter.
+    async fn write_buf(&mut self) -> Result<(), Error> {
+        if self.error.is_some() {
+            return Err(Error::SystemError);
+        }
+
+        if !self.buf.is_empty() {
+            let result = self.adapter.write(&self.buf).await;
+            self.buf.clear();
+
+            if let Err(error) = result {
+                self.error = Some(error);
+                return Err(Error::SystemError);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes any remaining buffered data to the adapter and returns the first
+    /// error of the adapter, if any.
+    pub(crate) async fn finish(mut self) -> Result<(), A::Error> {
+        // An error of the adapter is stored and returned below.
+        let _ = self.write_buf().await;
+
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Formatter output that only keeps the bytes of a window of the formatted
+/// string.
+struct WindowWriter<'a, const N: usize> {
+    buf: &'a mut heapless::Vec<u8, N>,
+    skip: usize,
+    len: usize,
+}
+
+impl<const N: usize> core::fmt::Write for WindowWriter<'_, N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for b in s.as_bytes() {
+            if self.len >= self.skip {
+                // Bytes beyond the window are only counted.
+                let _ = self.buf.push(*b);
+            }
+            self.len += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<A: Adapter, const N: usize> crate::Write for AdapterWriter<'_, A, N> {
+    async fn write_bytes(&mut self, mut bytes: &[u8]) -> Result<(), Error> {
+        while !bytes.is_empty() {
+            let count = bytes.len().min(N - self.buf.len());
+            // The count is limited to the available capacity.
+            let _ = self.buf.extend_from_slice(&bytes[..count]);
+            bytes = &bytes[count..];
+
+            if self.buf.is_full() {
+                self.write_buf().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_char(&mut self, c: char) -> Result<(), Error> {
+        self.write_bytes(c.encode_utf8(&mut [0; 4]).as_bytes())
+            .await
+    }
+
+    async fn write_str(&mut self, s: &str) -> Result<(), Error> {
+        self.write_bytes(s.as_bytes()).await
+    }
+
+    async fn write_fmt(&mut self, args: core::fmt::Arguments<'_>) -> Result<(), Error> {
+        // The formatted output may be larger than the free space in the buffer. As
+        // formatting cannot be suspended, the arguments are formatted repeatedly and
+        // each time the next part of the output is kept, until all of it is written.
+        let mut written = 0;
+
+        loop {
+            let start = self.buf.len();
+            let mut window = WindowWriter {
+                buf: &mut self.buf,
+                skip: written,
+                len: 0,
+            };
+            core::fmt::Write::write_fmt(&mut window, args).or(Err(Error::SystemError))?;
+
+            let len = window.len;
+            written += self.buf.len() - start;
+
+            if self.buf.is_full() {
+                self.write_buf().await?;
+            }
+
+            if written >= len {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn flush(&mut self) -> Result<(), Error> {
+        self.write_buf().await?;
+
+        if let Err(error) = self.adapter.flush().await {
+            self.error = Some(error);
+            return Err(Error::SystemError);
+        }
+        Ok(())
+    }
+}
+
+/// Options for the interactive use of [Interface::process_console] on a
+/// terminal
+///
+/// With all options disabled, the processing behaves like
+/// [Interface::process], which is suitable if the peer is a program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConsoleOptions {
+    /// Echo the received bytes back to the terminal.
+    pub echo: bool,
+    /// Prompt written at the start and after each program message. No prompt
+    /// is written if it is empty.
+    pub prompt: &'static str,
+    /// Remove the last byte of the unterminated input when a backspace (0x08)
+    /// or delete (0x7f) character is received.
+    pub backspace: bool,
+}
+
+impl ConsoleOptions {
+    /// Whether the received input has to be edited or echoed.
+    fn edit_input(&self) -> bool {
+        self.echo || self.backspace
+    }
+}
+
+/// State of the command processing with [Interface::process_step]
+///
+/// Holds the command buffer of `N` bytes and the position of the buffered
+/// input between the calls.
+pub struct ProcessState<const N: usize> {
+    buf: [u8; N],
+    /// Start of the input that has not been executed yet.
+    proc_offset: usize,
+    /// Position up to which the input has been searched for a terminator.
+    read_offset: usize,
+    /// End of the buffered input.
+    read_end: usize,
+    /// Whether the remainder of an oversized command is being discarded.
+    discarding: bool,
+    /// Whether the adapter has reported the end of the input.
+    closed: bool,
+    console: ConsoleOptions,
+}
+
+impl<const N: usize> ProcessState<N> {
+    pub const fn new() -> Self {
+        ProcessState {
+            buf: [0; N],
+            proc_offset: 0,
+            read_offset: 0,
+            read_end: 0,
+            discarding: false,
+            closed: false,
+            console: ConsoleOptions {
+                echo: false,
+                prompt: "",
+                backspace: false,
+            },
+        }
+    }
+
+    /// Enables the console features of `options`.
+    pub const fn with_console(mut self, options: ConsoleOptions) -> Self {
+        self.console = options;
+        self
+    }
+
+    /// Returns whether the adapter has reported the end of the input.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Discards the buffered input.
+    ///
+    /// This is intended to be called when no input has been received for some
+    /// time, so a partial command is not prefixed to the next command. Use it
+    /// with [Interface::process_step] and a timer, e.g. in a `select`.
+    ///
+    /// # Returns
+    /// Whether an unterminated command has been discarded. The caller may then
+    /// report an error to the error handler.
+    pub fn reset_input(&mut self) -> bool {
+        let discarded = self.read_end > 0 || self.discarding;
+        self.proc_offset = 0;
+        self.read_offset = 0;
+        self.read_end = 0;
+        self.discarding = false;
+        discarded
+    }
+
+    /// Applies the console options to `count` bytes of input read into the
+    /// buffer at the end of the buffered input.
+    async fn edit_input<A: Adapter>(
+        &mut self, adapter: &mut A, count: usize,
+    ) -> Result<(), A::Error> {
+        let mut end = self.read_end;
+
+        for i in self.read_end..self.read_end + count {
+            let byte = self.buf[i];
+
+            if self.console.backspace && (byte == 0x08 || byte == 0x7f) {
+                // Only the input following the last terminator can be edited.
+                let line_start = self.buf[..end]
+                    .iter()
+                    .rposition(|b| *b == b'\n')
+                    .map_or(0, |position| position + 1);
+
+                if end > line_start {
+                    end -= 1;
+                    if self.console.echo {
+                        adapter.write(b"\x08 \x08").await?;
+                    }
+                }
+            }
+            else {
+                self.buf[end] = byte;
+                end += 1;
+                if self.console.echo {
+                    adapter.write(&[byte]).await?;
+                }
+            }
+        }
+
+        self.read_end = end;
+        self.read_offset = self.read_offset.min(end);
+
+        if self.console.echo {
+            adapter.flush().await?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for ProcessState<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state of a partially executed program message.
+#[doc(hidden)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MessageState {
+    /// The current header and its command id offset, or `None` for the root.
+    header: Option<(&'static tree::Node, CommandId)>,
+    /// Whether a query response has been written for the program message.
+    responded: bool,
+}
+
+impl MessageState {
+    pub(crate) const fn new() -> Self {
+        MessageState {
+            header: None,
+            responded: false,
+        }
+    }
+
+    /// Whether the response message has not been terminated yet.
+    pub(crate) fn responded(&self) -> bool {
+        self.responded
+    }
+}
+
+/// Summary of a call to [Interface::run_report]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunReport<'a> {
+    commands: usize,
+    queries: usize,
+    errors: usize,
+    incomplete: bool,
+    remaining: &'a [u8],
+    /// The input starting at the command that could not be parsed.
+    rejected: Option<&'a [u8]>,
+}
+
+impl<'a> RunReport<'a> {
+    /// The number of commands and queries that were executed successfully.
+    pub fn commands(&self) -> usize {
+        self.commands
+    }
+
+    /// The number of queries that were answered.
+    pub fn queries(&self) -> usize {
+        self.queries
+    }
+
+    /// The number of errors passed to the error handler.
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// Whether the input ended with an incomplete command.
+    pub fn incomplete(&self) -> bool {
+        self.incomplete
+    }
+
+    /// Whether a response was written.
+    pub fn responded(&self) -> bool {
+        self.queries > 0
+    }
+
+    /// Adds the counters of a subsequent report to this report.
+    pub(crate) fn add(&mut self, other: &RunReport<'_>) {
+        self.commands += other.commands;
+        self.queries += other.queries;
+        self.errors += other.errors;
+        self.incomplete = other.incomplete;
+    }
+
+    /// Counts an error that occurred outside of the execution of commands.
+    pub(crate) fn add_error(&mut self) {
+        self.errors += 1;
+    }
+
+    /// The input starting at the command that could not be parsed, if parsing
+    /// failed.
+    pub(crate) fn rejected(&self) -> Option<&'a [u8]> {
+        self.rejected
+    }
+
+    /// The remaining input that was not parsed.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.remaining
+    }
 }
 
 pub trait Interface: ErrorHandler {
+    /// External context passed to the command handlers
+    ///
+    /// The context is lent to the interface for the duration of a call to
+    /// [Interface::run_with_context] and passed to all command handlers which
+    /// take a `&mut` reference to it as their first parameter. It is `()` for
+    /// interfaces without a context.
+    type Context;
+
+    /// The root node of the SCPI command tree of this interface.
+    #[doc(hidden)]
+    const ROOT_NODE: &'static tree::Node;
+
+    /// The number of command ids used by this interface, including the
+    /// commands of nested interfaces.
+    #[doc(hidden)]
+    const COMMAND_COUNT: CommandId;
+
+    /// The id of the `*RST` command, which resets the header of the program
+    /// message.
+    #[doc(hidden)]
+    const RESET_COMMAND: Option<CommandId> = None;
+
     /// Returns the root node of the SCPI command tree of this interface.
     #[doc(hidden)]
-    fn root_node(&self) -> &'static tree::Node;
+    fn root_node(&self) -> &'static tree::Node {
+        Self::ROOT_NODE
+    }
+
+    /// Looks up a command or query by its path, e.g. `SYSTem:ERRor?`.
+    ///
+    /// The path components may be given in the long or short form and are
+    /// matched *case-insensitive*. A trailing `?` looks up the query.
+    ///
+    /// # Returns
+    /// The id of the command and whether it is a query, if the interface
+    /// implements it.
+    fn lookup(&self, path: &str) -> Option<(CommandId, bool)> {
+        let (path, query) = match path.strip_suffix('?') {
+            Some(path) => (path, true),
+            None => (path, false),
+        };
+
+        let (node, offset) = self.lookup_node(path)?;
+        if query {
+            node.query.map(|id| (id + offset, true))
+        }
+        else {
+            node.command.map(|id| (id + offset, false))
+        }
+    }
+
+    /// Looks up the node of a path without a trailing `?` and returns it
+    /// together with its command id offset.
+    #[doc(hidden)]
+    fn lookup_node(&self, path: &str) -> Option<(&'static tree::Node, CommandId)> {
+        let path = path.strip_prefix(':').unwrap_or(path);
+
+        let mut node = self.root_node();
+        let mut offset = 0;
+        for name in path.split(':') {
+            (node, offset) = node.descend(name, offset)?;
+        }
+        Some((node, offset))
+    }
 
     /// Executes the command with the specified command id and the supplied
     /// arguments.
     #[doc(hidden)]
     async fn execute_command<'a>(
-        &'a mut self, command_id: CommandId, args: &[Value<'a>], response: &mut impl crate::Write,
+        &'a mut self, context: &mut Self::Context, command_id: CommandId, args: &[Value<'a>],
+        response: &mut impl crate::Write,
     ) -> Result<(), Error>;
 
     #[doc(hidden)]
     async fn execute(
-        &mut self, call: &CommandCall<'_>, response: &mut impl crate::Write,
+        &mut self, context: &mut Self::Context, call: &CommandCall<'_>,
+        response: &mut impl crate::Write,
     ) -> Result<(), Error> {
-        let command = if call.query {
-            call.node.query
-        }
-        else {
-            call.node.command
-        };
-
-        if let Some(command) = command {
-            self.execute_command(command, &call.args, response).await?;
-
-            if call.query {
-                response.write_char('\n').await?;
-                response.flush().await?;
-            }
+        if let Some(command) = call.command_id() {
+            self.execute_command(context, command, &call.args, response)
+                .await?;
         }
         else {
             return Err(Error::UndefinedHeader);
@@ -53,109 +647,875 @@ pub trait Interface: ErrorHandler {
 
     /// Parses and executes the commands in the input buffer.
     ///
-    /// The result is written to the response buffer. Any remaining input that
-    /// was not parsed is returned. If an error occurs, the remaining input
+    /// The result is written to the response buffer. The responses of all
+    /// queries within one program message are separated by `;` and the
+    /// response message is terminated by a single newline. Any remaining input
+    /// that was not parsed is returned. If an error occurs, the remaining input
     /// is returned and the error is passed to the error handler.
-    async fn run<'a>(&mut self, mut input: &'a [u8], response: &mut impl crate::Write) -> &'a [u8] {
-        let mut header = self.root_node();
+    ///
+    /// Use [Interface::run_report] to get a summary of what was executed.
+    async fn run<'a>(&mut self, input: &'a [u8], response: &mut impl crate::Write) -> &'a [u8]
+    where
+        Self: Interface<Context = ()>,
+    {
+        self.run_report(input, response).await.remaining()
+    }
+
+    /// Parses and executes the commands in the input buffer, passing the
+    /// context to the command handlers.
+    ///
+    /// This behaves exactly like [Interface::run], but is used for interfaces
+    /// with a [context](Interface::Context).
+    async fn run_with_context<'a>(
+        &mut self, context: &mut Self::Context, input: &'a [u8], response: &mut impl crate::Write,
+    ) -> &'a [u8] {
+        self.run_report_with_context(context, input, response)
+            .await
+            .remaining()
+    }
+
+    /// Parses and executes the commands in the input buffer and returns a
+    /// summary of the execution.
+    ///
+    /// This behaves exactly like [Interface::run], but the returned
+    /// [RunReport] additionally contains the number of executed commands,
+    /// answered queries and errors.
+    async fn run_report<'a>(
+        &mut self, input: &'a [u8], response: &mut impl crate::Write,
+    ) -> RunReport<'a>
+    where
+        Self: Interface<Context = ()>,
+    {
+        self.run_report_with_context(&mut (), input, response).await
+    }
+
+    /// Parses and executes the commands in the input buffer, passing the
+    /// context to the command handlers, and returns a summary of the
+    /// execution.
+    async fn run_report_with_context<'a>(
+        &mut self, context: &mut Self::Context, input: &'a [u8], response: &mut impl crate::Write,
+    ) -> RunReport<'a> {
+        let mut state = MessageState::default();
+        let mut report = self
+            .run_message_state(context, &mut state, input, response)
+            .await;
+
+        if report.incomplete && state.responded {
+            self.end_response(response, &mut report).await;
+        }
+        report
+    }
+
+    /// Parses and executes the commands in the input buffer, starting with the
+    /// header and response state of a previous call.
+    ///
+    /// In contrast to [Interface::run_report_with_context], the response
+    /// message is not terminated if the input ends with an incomplete command.
+    #[doc(hidden)]
+    async fn run_message_state<'a>(
+        &mut self, context: &mut Self::Context, state: &mut MessageState, mut input: &'a [u8],
+        response: &mut impl crate::Write,
+    ) -> RunReport<'a> {
+        let mut report = RunReport::default();
 
         while !input.is_empty() {
-            let result = parser::parse(self.root_node(), header, input);
+            #[cfg(feature = "command-macros")]
+            match self
+                .run_macro(context, state, input, response, &mut report)
+                .await
+            {
+                Ok(Some(rest)) => {
+                    input = rest;
+                    continue;
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    self.reject_message(error, state, input, response, &mut report)
+                        .await;
+                    return report;
+                }
+            }
 
-            #[cfg(feature = "defmt")]
-            defmt::trace!("Run: {:?}", input);
+            let (header, header_offset) = state.header.unwrap_or((self.root_node(), 0));
+            let result = parser::parse(self.root_node(), header, header_offset, input);
+
+            trace!("Run: {:?}", input);
 
             if let Err(ParseError::Incomplete) = result {
-                #[cfg(feature = "defmt")]
-                defmt::trace!("Incomplete Input");
-                return input;
+                trace!("Incomplete Input");
+                report.incomplete = true;
+                report.remaining = input;
+                return report;
             }
             else if let Err(error) = result {
-                #[cfg(feature = "defmt")]
-                defmt::trace!("Parse error");
-                self.handle_error(error.into());
-                return &[];
+                trace!("Parse error");
+                self.reject_message(error.into(), state, input, response, &mut report)
+                    .await;
+                return report;
             }
 
             let (i, call) = result.unwrap();
 
             if let Some(call) = call {
-                if let Err(error) = self.execute(&call, response).await {
-                    #[cfg(feature = "defmt")]
-                    defmt::trace!("Execution error");
+                self.execute_call(context, state, &call, response, &mut report)
+                    .await;
+            }
+
+            input = i;
+        }
+        report
+    }
+
+    /// Executes a parsed command of a program message and updates the state
+    /// of the message.
+    #[doc(hidden)]
+    async fn execute_call(
+        &mut self, context: &mut Self::Context, state: &mut MessageState, call: &CommandCall<'_>,
+        response: &mut impl crate::Write, report: &mut RunReport<'_>,
+    ) {
+        let separator = if call.query && state.responded {
+            response.write_char(';').await
+        }
+        else {
+            Ok(())
+        };
+
+        match separator.and(self.execute(context, call, response).await) {
+            Ok(()) => {
+                report.commands += 1;
+                if let Some(command_id) = call.command_id() {
+                    self.record_command(command_id);
+                }
+                if call.query {
+                    report.queries += 1;
+                    state.responded = true;
+                    self.set_output_pending(true);
+                }
+            }
+            Err(error) => {
+                trace!("Execution error");
+                self.handle_error(error);
+                self.record_error(error);
+                report.errors += 1;
+            }
+        }
+
+        if call.terminated && state.responded {
+            self.end_response(response, report).await;
+            state.responded = false;
+        }
+        self.update_status();
+
+        if call.terminated || Self::RESET_COMMAND.is_some_and(|id| call.command_id() == Some(id)) {
+            // Reset the header to the root node if a call is ended with a terminator.
+            state.header = None;
+        }
+        else if let Some(call_header) = call.header {
+            // Update the current header, if the current command is not a common command.
+            state.header = Some((call_header, call.header_offset));
+        }
+    }
+
+    /// Reports an error that aborts the execution of a program message.
+    #[doc(hidden)]
+    async fn reject_message<'a>(
+        &mut self, error: Error, state: &mut MessageState, input: &'a [u8],
+        response: &mut impl crate::Write, report: &mut RunReport<'a>,
+    ) {
+        self.handle_error(error);
+        self.record_error(error);
+        report.errors += 1;
+        report.rejected = Some(input);
+        if state.responded {
+            self.end_response(response, report).await;
+        }
+        self.update_status();
+        *state = MessageState::default();
+    }
+
+    /// Expands and executes a macro, if the input starts with the label of a
+    /// macro.
+    ///
+    /// The commands of the macro are executed like the commands of the
+    /// program message, starting at the root node. Labels of other macros
+    /// within the macro are expanded as well, up to a nesting depth of
+    /// [MAX_MACRO_DEPTH](crate::MAX_MACRO_DEPTH).
+    ///
+    /// # Returns
+    /// The input following the label, or [None] if the input does not start
+    /// with the label of a macro.
+    #[cfg(feature = "command-macros")]
+    #[doc(hidden)]
+    async fn run_macro<'a>(
+        &mut self, context: &mut Self::Context, state: &mut MessageState, input: &'a [u8],
+        response: &mut impl crate::Write, report: &mut RunReport<'_>,
+    ) -> Result<Option<&'a [u8]>, Error> {
+        let Some(call) = macro_call(input)
+        else {
+            return Ok(None);
+        };
+
+        let mut buf = heapless::Vec::new();
+        if !expand_macro(self, &call, &[], &mut buf)? {
+            return Ok(None);
+        }
+
+        // The commands of the macro do not change the header of the following
+        // commands.
+        let header = state.header;
+        // The expanded macros that have not been executed completely, each by
+        // the length of the input following its commands.
+        let mut nesting: heapless::Vec<usize, { crate::MAX_MACRO_DEPTH }> = heapless::Vec::new();
+        nesting.push(0).or(Err(Error::MacroRecursionError))?;
+        let mut position = 0;
+        while position < buf.len() {
+            while nesting
+                .last()
+                .is_some_and(|&rest| buf.len() - position <= rest)
+            {
+                nesting.pop();
+            }
+
+            if let Some(nested) = macro_call(&buf[position..]) {
+                let mut expanded = heapless::Vec::new();
+                let rest = &buf[position + nested.len..];
+                if expand_macro(self, &nested, rest, &mut expanded)? {
+                    nesting
+                        .push(rest.len())
+                        .or(Err(Error::MacroRecursionError))?;
+                    buf = expanded;
+                    position = 0;
+                    continue;
+                }
+            }
+
+            let (header, header_offset) = state.header.unwrap_or((self.root_node(), 0));
+            let (i, call) =
+                parser::parse(self.root_node(), header, header_offset, &buf[position..])
+                    .or(Err(Error::MacroExecutionError))?;
+            position = buf.len() - i.len();
+
+            if let Some(call) = call {
+                self.execute_call(context, state, &call, response, report)
+                    .await;
+            }
+        }
+
+        if call.separator == b';' {
+            state.header = header;
+        }
+        Ok(Some(&input[call.len..]))
+    }
+
+    /// Parses and executes exactly one program message.
+    ///
+    /// In contrast to [Interface::run], the input following the terminator of
+    /// the first program message is not executed, but returned untouched. If
+    /// the input does not contain a complete program message, [None] is
+    /// returned and nothing is executed. Errors during execution are passed
+    /// to the error handler like in [Interface::run].
+    ///
+    /// The program message is parsed completely before it is executed. If it
+    /// cannot be parsed, e.g. because of an undefined header, none of its
+    /// commands are executed. The error is passed to the error handler and
+    /// returned, and the input is discarded like in [Interface::run].
+    async fn run_single<'a>(
+        &mut self, input: &'a [u8], response: &mut impl crate::Write,
+    ) -> Result<Option<&'a [u8]>, Error>
+    where
+        Self: Interface<Context = ()>,
+    {
+        let mut header = (self.root_node(), 0);
+        let mut rest = input;
+
+        // Find the end of the first program message before executing anything.
+        loop {
+            if rest.is_empty() {
+                return Ok(None);
+            }
+
+            #[cfg(feature = "command-macros")]
+            if let Some(call) =
+                macro_call(rest).filter(|call| self.macro_body(call.label).is_some())
+            {
+                rest = &rest[call.len..];
+                if call.separator == b'\n' {
+                    break;
+                }
+                continue;
+            }
+
+            match parser::parse(self.root_node(), header.0, header.1, rest) {
+                Ok((i, call)) => {
+                    rest = i;
+                    match call {
+                        Some(call) if call.terminated => break,
+                        Some(call) => {
+                            if let Some(call_header) = call.header {
+                                header = (call_header, call.header_offset);
+                            }
+                        }
+                        // Skip empty lines preceding the program message.
+                        None => {}
+                    }
+                }
+                Err(ParseError::Incomplete) => return Ok(None),
+                Err(error) => {
+                    let error = Error::from(error);
                     self.handle_error(error);
+                    self.record_error(error);
+                    self.update_status();
+                    return Err(error);
+                }
+            }
+        }
+
+        let message = &input[..input.len() - rest.len()];
+        self.run(message, response).await;
+
+        Ok(Some(rest))
+    }
+
+    /// Parses and executes a single program message from a string.
+    ///
+    /// This is useful to invoke other commands from within a command handler,
+    /// e.g. to apply a stored setup. The responses of queries are written to
+    /// the response without a terminator. In contrast to [Interface::run],
+    /// execution stops at the first error, which is returned to the caller
+    /// instead of being passed to the error handler.
+    ///
+    /// A missing terminator is appended to a copy of the command on the
+    /// stack, so commands without a terminator are limited to
+    /// [MAX_EXECUTE_STR_LENGTH](crate::MAX_EXECUTE_STR_LENGTH) bytes. Longer
+    /// commands are rejected with [Error::InputBufferOverrun] without being
+    /// executed. Commands ending with a terminator are not copied and not
+    /// limited.
+    async fn execute_str(
+        &mut self, command: &str, response: &mut impl crate::Write,
+    ) -> Result<(), Error>
+    where
+        Self: Interface<Context = ()>,
+    {
+        let mut buf: heapless::Vec<u8, { MAX_EXECUTE_STR_LENGTH + 1 }> = heapless::Vec::new();
+
+        let mut input = if command.ends_with('\n') {
+            command.as_bytes()
+        }
+        else {
+            buf.extend_from_slice(command.as_bytes())
+                .or(Err(Error::InputBufferOverrun))?;
+            buf.push(b'\n').or(Err(Error::InputBufferOverrun))?;
+            &buf
+        };
+
+        let mut header = (self.root_node(), 0);
+        let mut responded = false;
+
+        while !input.is_empty() {
+            let (i, call) = parser::parse(self.root_node(), header.0, header.1, input)?;
+
+            if let Some(call) = call {
+                if call.query && responded {
+                    response.write_char(';').await?;
                 }
+                self.execute(&mut (), &call, response).await?;
+                responded |= call.query;
 
                 if call.terminated {
-                    // Reset the header to the root node if a call is ended with a terminator.
-                    header = self.root_node();
+                    break;
+                }
+                else if Self::RESET_COMMAND.is_some_and(|id| call.command_id() == Some(id)) {
+                    // The header is reset by `*RST`.
+                    header = (self.root_node(), 0);
                 }
                 else if let Some(call_header) = call.header {
-                    // Update the current header, if the current command is not a common command.
-                    header = call_header;
+                    header = (call_header, call.header_offset);
                 }
             }
 
             input = i;
         }
-        &[]
+
+        Ok(())
     }
 
-    async fn process<const N: usize, A: Adapter>(
-        &mut self, adapter: &mut A,
+    /// Updates the status byte after a command or a change of the status.
+    ///
+    /// This is implemented by the interface macro for interfaces with
+    /// [StatusCommands](crate::StatusCommands).
+    #[doc(hidden)]
+    fn update_status(&mut self) {}
+
+    /// Returns whether the device is in local operation, so commands marked
+    /// with `#[scpi(local_locked)]` are rejected.
+    ///
+    /// This is implemented by the interface macro for interfaces with
+    /// [RemoteCommands](crate::RemoteCommands).
+    #[doc(hidden)]
+    fn is_local(&mut self) -> bool {
+        false
+    }
+
+    /// Returns the byte order in which [WithByteOrder](crate::WithByteOrder)
+    /// arguments are decoded, or `None` if they keep their own default.
+    ///
+    /// This is implemented by the interface macro for interfaces with
+    /// [FormatCommands](crate::FormatCommands), which select the byte order
+    /// with `FORMat:BORDer`.
+    #[doc(hidden)]
+    fn byte_order(&mut self) -> Option<ByteOrder> {
+        None
+    }
+
+    /// Returns the body of the macro with the given label, if the expansion
+    /// of macros is enabled.
+    ///
+    /// This is implemented by the interface macro for interfaces with
+    /// [MacroCommands](crate::MacroCommands).
+    #[doc(hidden)]
+    fn macro_body(&mut self, _label: &[u8]) -> Option<&[u8]> {
+        None
+    }
+
+    /// Executes the `*CLS` command.
+    ///
+    /// This is implemented by the interface macro for interfaces with
+    /// [StatusCommands](crate::StatusCommands).
+    #[doc(hidden)]
+    fn clear_status(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Writes the response of the `*LRN?` query.
+    ///
+    /// This is implemented by the interface macro with the `learn` option.
+    #[doc(hidden)]
+    async fn learn(&mut self, _response: &mut impl crate::Write) -> Result<(), Error> {
+        Err(Error::UndefinedHeader)
+    }
+
+    /// Returns a future that completes after the duration has elapsed.
+    ///
+    /// Handlers with a `timeout_ms` attribute, e.g.
+    /// `#[scpi(cmd = "MEASure?", timeout_ms = 500)]`, are raced against this
+    /// future and fail with [Error::TimeoutError] if it completes first. The
+    /// `timeout` option of the interface macro selects an associated function
+    /// of the interface that implements the timer of the executor:
+    /// `#[scpi::interface(timeout = sleep)]`. Without it, handlers with a
+    /// `timeout_ms` attribute are rejected by the interface macro.
+    fn timeout(_duration_ms: u32) -> impl core::future::Future<Output = ()>
+    where
+        Self: Sized,
+    {
+        core::future::pending()
+    }
+
+    /// Called when a device clear is performed with [Interface::device_clear].
+    ///
+    /// The `on_device_clear` option of the interface macro selects a method of
+    /// the interface that is called, e.g. to abort long-running operations:
+    /// `#[scpi::interface(on_device_clear = abort_operations)]`.
+    fn on_device_clear(&mut self) {}
+
+    /// Returns whether commands marked with `#[scpi(protected)]` may be
+    /// executed. Otherwise they are rejected with [Error::CommandProtected].
+    ///
+    /// The `protection_unlocked` option of the interface macro selects a
+    /// method of the interface that returns the state of the protection:
+    /// `#[scpi::interface(protection_unlocked = calibration_unlocked)]`.
+    /// Without it, protected commands are always rejected.
+    fn protection_unlocked(&self) -> bool {
+        false
+    }
+
+    /// Performs a device clear as defined by IEEE 488.2, 5.8.
+    ///
+    /// This is intended to be called by transports that support a device clear
+    /// event, e.g. USBTMC or GPIB. The partial input buffered in `state` is
+    /// discarded, a pending response of the adapter is discarded and
+    /// [Interface::on_device_clear] is called. The settings of the instrument
+    /// and the error queue are not changed.
+    async fn device_clear<const N: usize, A: Adapter>(
+        &mut self, adapter: &mut A, state: &mut ProcessState<N>,
     ) -> Result<(), A::Error> {
-        let mut cmd_buf = [0u8; N];
-        let mut res_buf: heapless::Vec<u8, N> = heapless::Vec::new();
+        state.reset_input();
 
-        let mut proc_offset = 0;
-        let mut read_offset = 0;
+        if adapter.response_pending() {
+            adapter.discard_response().await?;
+        }
+        self.set_output_pending(false);
+        self.on_device_clear();
+        self.update_status();
 
-        loop {
-            let count = adapter.read(&mut cmd_buf[read_offset..]).await?;
-            let read_end = read_offset + count;
+        Ok(())
+    }
 
-            // Find the first terminator in the buffer starting from the last read position.
-            while let Some(position) = cmd_buf[read_offset..read_end]
-                .iter()
-                .position(|b| *b == b'\n')
-            {
-                let terminator_pos = read_offset + position;
-                let data = &cmd_buf[proc_offset..=terminator_pos];
+    /// Returns the canonical name of a command, e.g. `SYSTem:ERRor:COUNt?`.
+    #[doc(hidden)]
+    fn command_name(_command_id: CommandId) -> Option<&'static str>
+    where
+        Self: Sized,
+    {
+        None
+    }
 
-                let remaining = self.run(data, &mut res_buf).await;
+    /// Returns the syntax of a command, e.g. `SOURce:VOLTage <f64>`, followed
+    /// by the first line of its documentation.
+    ///
+    /// This is implemented by the interface macro with the `help` option.
+    #[doc(hidden)]
+    fn command_help(_command_id: CommandId) -> Option<&'static str>
+    where
+        Self: Sized,
+    {
+        None
+    }
 
-                if !res_buf.is_empty() {
-                    adapter.write(&res_buf).await?;
-                    adapter.flush().await?;
-                    res_buf.clear();
-                }
+    /// Returns whether a command is marked with `#[scpi(local_locked)]`.
+    ///
+    /// An interface with [RemoteCommands](crate::RemoteCommands) uses this to
+    /// reject these commands of its nested interfaces while it is in local
+    /// operation.
+    #[doc(hidden)]
+    fn is_local_locked(_command_id: CommandId) -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Writes the response of the `SYSTem:HELP:SYNTax?` query.
+    ///
+    /// The response is a block containing the syntax of the command and of the
+    /// query of the header, one per line. A header with a trailing `?` only
+    /// selects the query. An unknown header is reported as
+    /// [Error::UndefinedHeader] and responded with an empty block.
+    #[doc(hidden)]
+    async fn help_syntax(
+        &mut self, header: &str, response: &mut impl crate::Write,
+    ) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        use crate::{Arbitrary, ArbitraryParts, Response};
+
+        let help = |command_id: CommandId| {
+            Self::command_help(command_id)
+                .or_else(|| Self::command_name(command_id))
+                .unwrap_or_default()
+                .as_bytes()
+        };
+
+        let (path, query_only) = match header.strip_suffix('?') {
+            Some(path) => (path, true),
+            None => (header, false),
+        };
+        let node = self.lookup_node(path);
+        let command = node
+            .and_then(|(node, offset)| node.command.map(|id| id + offset))
+            .filter(|_| !query_only);
+        let query = node.and_then(|(node, offset)| node.query.map(|id| id + offset));
+
+        match (command, query) {
+            (Some(command), Some(query)) => {
+                ArbitraryParts(&[help(command), b"\n", help(query)])
+                    .write_response(response)
+                    .await
+            }
+            (Some(command_id), None) | (None, Some(command_id)) => {
+                Arbitrary(help(command_id)).write_response(response).await
+            }
+            (None, None) => {
+                self.handle_error(Error::UndefinedHeader);
+                self.record_error(Error::UndefinedHeader);
+                Arbitrary(&[]).write_response(response).await
+            }
+        }
+    }
+
+    /// Records the successful execution of a command in the statistics and
+    /// cancels a pending `*OPC` after `*RST`.
+    ///
+    /// This is implemented by the interface macro for interfaces with
+    /// [StatisticsCommands](crate::StatisticsCommands) or with both
+    /// [StatusCommands](crate::StatusCommands) and
+    /// [ResetCommands](crate::ResetCommands).
+    #[doc(hidden)]
+    fn record_command(&mut self, _command_id: CommandId) {}
+
+    /// Records an error in the statistics and the standard event status
+    /// register.
+    ///
+    /// This is implemented by the interface macro for interfaces with
+    /// [StatisticsCommands](crate::StatisticsCommands) or
+    /// [StatusCommands](crate::StatusCommands).
+    #[doc(hidden)]
+    fn record_error(&mut self, _error: Error) {}
+
+    /// Informs the status registers whether a response is pending.
+    ///
+    /// This is implemented by the interface macro for interfaces with
+    /// [StatusCommands](crate::StatusCommands).
+    #[doc(hidden)]
+    fn set_output_pending(&mut self, _pending: bool) {}
+
+    /// Terminates a response message with a newline and flushes it.
+    #[doc(hidden)]
+    async fn end_response(&mut self, response: &mut impl crate::Write, report: &mut RunReport<'_>) {
+        let result = match response.write_char('\n').await {
+            Ok(()) => response.flush().await,
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(()) => self.set_output_pending(false),
+            Err(error) => {
+                self.handle_error(error);
+                self.record_error(error);
+                report.errors += 1;
+            }
+        }
+    }
+
+    /// Parses and executes the commands in the input buffer, writing the
+    /// result to a [SyncWrite] trait object.
+    ///
+    /// This behaves exactly like [Interface::run], but the command dispatch
+    /// code is only instantiated once, regardless of the number of different
+    /// writer types used. This reduces the code size if an interface writes
+    /// responses to several different sinks.
+    async fn run_dyn<'a>(&mut self, input: &'a [u8], response: &mut dyn SyncWrite) -> &'a [u8]
+    where
+        Self: Interface<Context = ()>,
+    {
+        self.run(input, &mut DynWriter::new(response)).await
+    }
+
+    /// Parses and executes the commands in the input buffer without an async
+    /// runtime.
+    ///
+    /// This behaves exactly like [Interface::run], but blocks the current
+    /// thread until the execution has finished. Command handlers that wait for
+    /// external events, e.g. timers or channels of an async runtime, are not
+    /// supported and never finish.
+    #[cfg(feature = "std")]
+    fn run_blocking<'a>(&mut self, input: &'a [u8], response: &mut impl crate::Write) -> &'a [u8]
+    where
+        Self: Interface<Context = ()>,
+    {
+        crate::adapters::block_on(self.run(input, response))
+    }
+
+    /// Reads commands from the adapter and executes them without an async
+    /// runtime.
+    ///
+    /// This behaves exactly like [Interface::process], but blocks the current
+    /// thread. It is intended to be used with a
+    /// [BlockingAdapter](crate::BlockingAdapter). The same restrictions as for
+    /// [Interface::run_blocking] apply.
+    #[cfg(feature = "std")]
+    fn process_blocking<const N: usize, A: Adapter>(
+        &mut self, adapter: &mut A,
+    ) -> Result<(), A::Error>
+    where
+        Self: Interface<Context = ()>,
+    {
+        crate::adapters::block_on(self.process::<N, A>(adapter))
+    }
+
+    /// Reads commands from the adapter and executes them.
+    ///
+    /// The input is buffered in a command buffer of `N` bytes. Responses are
+    /// written to the adapter in chunks of up to `N` bytes, so they are not
+    /// limited in size. A command that does not fit into the command buffer is
+    /// discarded up to its terminator and [Error::InputBufferOverrun] is
+    /// reported, or [Error::QueryDeadlocked] if a response is pending at the
+    /// same time, which is discarded as well. Returns when the adapter reports
+    /// an error or the end of the input. If the input ends with an unterminated
+    /// query, [Error::QueryUnterminated] is reported.
+    ///
+    /// Use [Interface::process_step] to combine the processing with other
+    /// tasks, and [Interface::process_split] for transports with separate
+    /// receiving and transmitting halves.
+    async fn process<const N: usize, A: Adapter>(&mut self, adapter: &mut A) -> Result<(), A::Error>
+    where
+        Self: Interface<Context = ()>,
+    {
+        let mut state = ProcessState::<N>::new();
+
+        while !state.is_closed() {
+            self.process_step(adapter, &mut state).await?;
+        }
+        Ok(())
+    }
+
+    /// Reads commands from the receiving half `rx` and writes the responses
+    /// to the transmitting half `tx`.
+    ///
+    /// This behaves exactly like [Interface::process], but the halves can be
+    /// separate objects, e.g. owned by different drivers. Both halves must use
+    /// the same error type.
+    async fn process_split<const N: usize, R: AdapterRead, W: AdapterWrite<Error = R::Error>>(
+        &mut self, rx: &mut R, tx: &mut W,
+    ) -> Result<(), R::Error>
+    where
+        Self: Interface<Context = ()>,
+    {
+        self.process::<N, _>(&mut SplitAdapter { rx, tx }).await
+    }
+
+    /// Reads commands from the adapter and executes them, providing the
+    /// console features of `options` for the interactive use on a terminal.
+    ///
+    /// This behaves like [Interface::process], but the received input is
+    /// echoed, can be edited with backspace and a prompt is written after each
+    /// program message, as enabled in `options`.
+    async fn process_console<const N: usize, A: Adapter>(
+        &mut self, adapter: &mut A, options: ConsoleOptions,
+    ) -> Result<(), A::Error>
+    where
+        Self: Interface<Context = ()>,
+    {
+        let mut state = ProcessState::<N>::new().with_console(options);
+
+        if !options.prompt.is_empty() {
+            adapter.write(options.prompt.as_bytes()).await?;
+            adapter.flush().await?;
+        }
 
-                // Update the offset to the position up to where the data has been processed.
-                if !remaining.is_empty() {
-                    proc_offset = proc_offset + data.len() - remaining.len();
-                    read_offset = terminator_pos + 1;
+        while !state.is_closed() {
+            self.process_step(adapter, &mut state).await?;
+        }
+        Ok(())
+    }
+
+    /// Performs a single round of [Interface::process].
+    ///
+    /// If the command buffer in `state` does not contain a complete program
+    /// message, the adapter is read once. Then all complete program messages
+    /// are executed and the function returns.
+    ///
+    /// # Cancellation
+    /// The future may be dropped while it waits for the adapter to read, e.g.
+    /// when it is used in a `select`. The buffered input is kept in `state`
+    /// and processing continues with the next call. If the future is dropped
+    /// while a program message is executed, that message is considered
+    /// processed and its response may be incomplete.
+    async fn process_step<const N: usize, A: Adapter>(
+        &mut self, adapter: &mut A, state: &mut ProcessState<N>,
+    ) -> Result<(), A::Error>
+    where
+        Self: Interface<Context = ()>,
+    {
+        if !state.buf[state.read_offset..state.read_end].contains(&b'\n') {
+            let count = adapter.read(&mut state.buf[state.read_end..]).await?;
+
+            if count == 0 {
+                // A query that is not terminated before the end of the input is never
+                // answered.
+                if !state.discarding && parser::contains_query(&state.buf[..state.read_end]) {
+                    self.handle_error(Error::QueryUnterminated);
+                    self.record_error(Error::QueryUnterminated);
+                    self.update_status();
                 }
-                else {
-                    proc_offset = terminator_pos + 1;
-                    read_offset = proc_offset;
+                state.closed = true;
+                return Ok(());
+            }
+
+            if state.console.edit_input() {
+                state.edit_input(adapter, count).await?;
+            }
+            else {
+                state.read_end += count;
+            }
+
+            // Skip the input up to the terminator of the discarded command.
+            if state.discarding {
+                match state.buf[..state.read_end].iter().position(|b| *b == b'\n') {
+                    Some(position) => {
+                        state.buf.copy_within(position + 1..state.read_end, 0);
+                        state.read_end -= position + 1;
+                        state.discarding = false;
+                    }
+                    None => {
+                        state.read_end = 0;
+                        return Ok(());
+                    }
                 }
             }
+        }
+
+        // Find the first terminator in the buffer starting from the last read position.
+        while let Some(position) = state.buf[state.read_offset..state.read_end]
+            .iter()
+            .position(|b| *b == b'\n')
+        {
+            let terminator_pos = state.read_offset + position;
+            let data_start = state.proc_offset;
+
+            // The message is marked as processed before it is executed, so it is not
+            // executed again if the future is dropped.
+            state.proc_offset = terminator_pos + 1;
+            state.read_offset = terminator_pos + 1;
+
+            if adapter.response_pending() {
+                adapter.discard_response().await?;
+                self.set_output_pending(false);
+                self.handle_error(Error::QueryInterrupted);
+                self.record_error(Error::QueryInterrupted);
+            }
 
-            read_offset = read_end;
+            let mut writer = AdapterWriter::<A, N>::new(adapter);
+            let remaining = self
+                .run(&state.buf[data_start..=terminator_pos], &mut writer)
+                .await
+                .len();
+            writer.finish().await?;
 
-            // Ensure `read_from` does not exceed the buffer length
-            if read_offset >= cmd_buf.len() {
-                #[cfg(feature = "defmt")]
-                defmt::warn!("SCPI buffer overflow, resetting buffer");
-                read_offset = 0;
-                proc_offset = 0;
+            // The response remains available until the controller has read it.
+            self.set_output_pending(adapter.response_pending());
+            self.update_status();
+
+            // Keep the remaining data, if the message has not been processed completely.
+            state.proc_offset -= remaining;
+
+            if !state.console.prompt.is_empty() {
+                adapter.write(state.console.prompt.as_bytes()).await?;
+                adapter.flush().await?;
             }
-            // If there is unprocessed data, shift it to the beginning of the buffer.
-            else if proc_offset > 0 {
-                cmd_buf.copy_within(proc_offset..read_end, 0);
-                read_offset -= proc_offset;
-                proc_offset = 0;
+        }
+
+        // If there is unprocessed data, shift it to the beginning of the buffer.
+        if state.proc_offset > 0 {
+            state.buf.copy_within(state.proc_offset..state.read_end, 0);
+            state.read_end -= state.proc_offset;
+            state.proc_offset = 0;
+        }
+        state.read_offset = state.read_end;
+
+        // The buffer is full without containing a complete command. The command is
+        // discarded up to its terminator, so its tail is not executed as a new command.
+        if state.read_end >= N {
+            warn!("SCPI buffer overflow, discarding command");
+
+            // If the controller does not read the pending response either, neither
+            // side can continue, so the response is discarded as well.
+            let error = if adapter.response_pending() {
+                adapter.discard_response().await?;
+                self.set_output_pending(false);
+                Error::QueryDeadlocked
             }
+            else {
+                Error::InputBufferOverrun
+            };
+            self.handle_error(error);
+            self.record_error(error);
+            self.update_status();
+            state.read_end = 0;
+            state.read_offset = 0;
+            state.discarding = true;
         }
+
+        Ok(())
     }
 }
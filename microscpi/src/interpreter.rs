@@ -0,0 +1,182 @@
+//! This module contains an interpreter that keeps the parser state between
+//! calls.
+
+use crate::interface::MessageState;
+use crate::{Error, Interface, RunReport};
+
+/// SCPI Interpreter
+///
+/// The interpreter executes the input of an interface as it arrives, e.g. one
+/// byte at a time. In contrast to [Interface::run], the current command header
+/// and the state of the response message are kept between calls, so a
+/// program message can be split at any position. Only the incomplete command
+/// at the end of the input is buffered, in a buffer of `N` bytes.
+///
+/// ```
+/// use microscpi::{self as scpi, Interpreter};
+///
+/// #[derive(scpi::IgnoreErrors)]
+/// pub struct ExampleInterface {
+///     frequency: f64,
+///     amplitude: f64,
+/// }
+///
+/// #[scpi::interface]
+/// impl ExampleInterface {
+///     #[scpi(cmd = "SOURce:FREQuency")]
+///     fn set_frequency(&mut self, frequency: f64) -> Result<(), scpi::Error> {
+///         self.frequency = frequency;
+///         Ok(())
+///     }
+///
+///     #[scpi(cmd = "SOURce:AMPLitude")]
+///     fn set_amplitude(&mut self, amplitude: f64) -> Result<(), scpi::Error> {
+///         self.amplitude = amplitude;
+///         Ok(())
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut interface = ExampleInterface { frequency: 0.0, amplitude: 0.0 };
+/// let mut interpreter = Interpreter::<64>::new();
+/// let mut output = Vec::new();
+///
+/// interpreter.feed(&mut interface, &mut (), b"SOUR:FREQ 5", &mut output).await;
+/// interpreter.feed(&mut interface, &mut (), b"0;AMPL", &mut output).await;
+/// assert_eq!(interface.frequency, 50.0);
+///
+/// interpreter.feed(&mut interface, &mut (), b" 2\n", &mut output).await;
+/// assert_eq!(interface.amplitude, 2.0);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Interpreter<const N: usize> {
+    buf: heapless::Vec<u8, N>,
+    state: MessageState,
+    discarding: bool,
+}
+
+impl<const N: usize> Interpreter<N> {
+    pub const fn new() -> Self {
+        Interpreter {
+            buf: heapless::Vec::new(),
+            state: MessageState::new(),
+            discarding: false,
+        }
+    }
+
+    /// Returns whether an incomplete command is buffered.
+    pub fn pending(&self) -> bool {
+        !self.buf.is_empty() || self.discarding
+    }
+
+    /// Discards the buffered input and resets the header to the root node.
+    ///
+    /// This is intended to be used when the transport is reset, e.g. on a
+    /// device clear.
+    pub fn reset(&mut self) {
+        self.buf.clear();
+        self.state = MessageState::new();
+        self.discarding = false;
+    }
+
+    /// Parses and executes the input, continuing the program message of the
+    /// previous call.
+    ///
+    /// All complete commands are executed immediately and an incomplete
+    /// command at the end of the input is buffered until the next call. The
+    /// responses of queries are written to the response and the response
+    /// message is terminated with the program message. Errors are passed to
+    /// the error handler of the interface and the rest of the program message
+    /// is discarded. If an incomplete command exceeds the buffer,
+    /// [Error::InputBufferOverrun] is reported.
+    ///
+    /// # Returns
+    /// A summary of the execution. Its remaining input is always empty, as
+    /// the interpreter buffers incomplete commands.
+    pub async fn feed<I: Interface>(
+        &mut self, interface: &mut I, context: &mut I::Context, mut input: &[u8],
+        response: &mut impl crate::Write,
+    ) -> RunReport<'static> {
+        let mut report = RunReport::default();
+
+        while !input.is_empty() {
+            // Skip the input up to the terminator of the discarded program message.
+            if self.discarding {
+                match input.iter().position(|b| *b == b'\n') {
+                    Some(position) => {
+                        input = &input[position + 1..];
+                        self.discarding = false;
+                        continue;
+                    }
+                    None => break,
+                }
+            }
+
+            let count = input.len().min(N - self.buf.len());
+            // The length has been limited to the free capacity of the buffer.
+            let _ = self.buf.extend_from_slice(&input[..count]);
+            input = &input[count..];
+
+            // Only the input up to the last separator is parsed, as the parser cannot tell
+            // whether a command at the end of the input is complete, e.g. a number.
+            let end = self
+                .buf
+                .iter()
+                .rposition(|b| *b == b';' || *b == b'\n')
+                .map_or(0, |position| position + 1);
+
+            let consumed = if end > 0 {
+                let run = interface
+                    .run_message_state(context, &mut self.state, &self.buf[..end], response)
+                    .await;
+                report.add(&run);
+
+                if run.incomplete() {
+                    end - run.remaining().len()
+                }
+                else if let Some(rejected) = run.rejected() {
+                    let start = end - rejected.len();
+                    match self.buf[start..].iter().position(|b| *b == b'\n') {
+                        Some(position) => start + position + 1,
+                        None => {
+                            self.discarding = true;
+                            self.buf.len()
+                        }
+                    }
+                }
+                else {
+                    end
+                }
+            }
+            else {
+                0
+            };
+
+            self.buf.copy_within(consumed.., 0);
+            self.buf.truncate(self.buf.len() - consumed);
+
+            // The buffer is full without containing a complete command.
+            if self.buf.is_full() {
+                interface.handle_error(Error::InputBufferOverrun);
+                interface.record_error(Error::InputBufferOverrun);
+                interface.update_status();
+                report.add_error();
+                if self.state.responded() {
+                    interface.end_response(response, &mut report).await;
+                }
+                self.reset();
+                self.discarding = true;
+            }
+        }
+
+        report
+    }
+}
+
+impl<const N: usize> Default for Interpreter<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
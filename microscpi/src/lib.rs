@@ -101,6 +101,321 @@
 //!     assert_eq!(output, b"-113,\"Undefined header\"\n");
 //! }
 //! ```
+//!
+//! ## Passing a context to command handlers
+//!
+//! Peripherals that are owned elsewhere can be lent to the command handlers
+//! for the duration of a call. The context type is declared with the
+//! `context` option of the `#[interface]` attribute. Command handlers that
+//! need the context take a `&mut` reference to it as their first parameter;
+//! all other handlers omit it. The interface is then run with
+//! [Interface::run_with_context].
+//!
+//! ```
+//! use microscpi::{self as scpi, Interface};
+//!
+//! pub struct Gpio {
+//!     high: bool,
+//! }
+//!
+//! pub struct ExampleInterface;
+//!
+//! impl scpi::ErrorHandler for ExampleInterface {
+//!     fn handle_error(&mut self, error: scpi::Error) {
+//!         println!("Error: {error}");
+//!     }
+//! }
+//!
+//! #[scpi::interface(context = Gpio)]
+//! impl ExampleInterface {
+//!     #[scpi(cmd = "OUTPut:STATe")]
+//!     async fn output_state(&mut self, gpio: &mut Gpio, state: bool) -> Result<(), scpi::Error> {
+//!         gpio.high = state;
+//!         Ok(())
+//!     }
+//!
+//!     #[scpi(cmd = "SYSTem:VALue?")]
+//!     async fn system_value(&mut self) -> Result<u64, scpi::Error> {
+//!         Ok(42)
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! pub async fn main() {
+//!     let mut output = Vec::new();
+//!     let mut interface = ExampleInterface;
+//!     let mut gpio = Gpio { high: false };
+//!
+//!     interface
+//!         .run_with_context(&mut gpio, b"OUTP:STAT 1;:SYST:VAL?\n", &mut output)
+//!         .await;
+//!
+//!     assert!(gpio.high);
+//!     assert_eq!(output, b"42\n");
+//! }
+//! ```
+//!
+//! ## Nested interfaces
+//!
+//! The commands of a module can be defined in a separate interface and mounted
+//! below a path of another interface. A method marked with the `subtree`
+//! attribute returns a mutable reference to the nested interface. Errors of
+//! the nested interface are passed to the error handler of the outer
+//! interface, and both interfaces must use the same context type.
+//!
+//! ```
+//! use microscpi::{self as scpi, Interface};
+//!
+//! mod power_supply {
+//!     use microscpi as scpi;
+//!
+//!     #[derive(Default)]
+//!     pub struct PowerSupply {
+//!         pub voltage: f64,
+//!     }
+//!
+//!     impl scpi::ErrorHandler for PowerSupply {
+//!         fn handle_error(&mut self, _error: scpi::Error) {}
+//!     }
+//!
+//!     #[scpi::interface]
+//!     impl PowerSupply {
+//!         #[scpi(cmd = "VOLTage")]
+//!         async fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+//!             self.voltage = voltage;
+//!             Ok(())
+//!         }
+//!     }
+//! }
+//!
+//! use power_supply::PowerSupply;
+//!
+//! #[derive(Default)]
+//! pub struct ExampleInterface {
+//!     supply: PowerSupply,
+//! }
+//!
+//! impl scpi::ErrorHandler for ExampleInterface {
+//!     fn handle_error(&mut self, error: scpi::Error) {
+//!         println!("Error: {error}");
+//!     }
+//! }
+//!
+//! #[scpi::interface]
+//! impl ExampleInterface {
+//!     #[scpi(subtree = "SOURce")]
+//!     fn source(&mut self) -> &mut PowerSupply {
+//!         &mut self.supply
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! pub async fn main() {
+//!     let mut output = Vec::new();
+//!     let mut interface = ExampleInterface::default();
+//!
+//!     interface.run(b"SOUR:VOLT 12\n", &mut output).await;
+//!
+//!     assert_eq!(interface.supply.voltage, 12.0);
+//! }
+//! ```
+//!
+//! ## Splitting an interface into parts
+//!
+//! The commands of a large interface can be split across several `impl`
+//! blocks in different modules. Each block is marked with
+//! `#[scpi::interface_part(Name)]` and the interface lists the parts with the
+//! `parts` option, which merges their commands into a single command tree.
+//!
+//! ```
+//! use microscpi::{self as scpi, Interface};
+//!
+//! #[derive(Default, scpi::IgnoreErrors)]
+//! pub struct ExampleInterface {
+//!     voltage: f64,
+//!     current: f64,
+//! }
+//!
+//! mod voltage {
+//!     use microscpi as scpi;
+//!
+//!     #[scpi::interface_part(Voltage, prefix = "SOURce")]
+//!     impl super::ExampleInterface {
+//!         #[scpi(cmd = "VOLTage")]
+//!         fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+//!             self.voltage = voltage;
+//!             Ok(())
+//!         }
+//!     }
+//! }
+//!
+//! mod current {
+//!     use microscpi as scpi;
+//!
+//!     #[scpi::interface_part(Current, prefix = "SOURce")]
+//!     impl super::ExampleInterface {
+//!         #[scpi(cmd = "CURRent")]
+//!         fn set_current(&mut self, current: f64) -> Result<(), scpi::Error> {
+//!             self.current = current;
+//!             Ok(())
+//!         }
+//!     }
+//! }
+//!
+//! #[scpi::interface(parts(voltage::Voltage, current::Current))]
+//! impl ExampleInterface {}
+//!
+//! #[tokio::main]
+//! pub async fn main() {
+//!     let mut output = Vec::new();
+//!     let mut interface = ExampleInterface::default();
+//!
+//!     interface.run(b"SOUR:VOLT 12;CURR 0.5\n", &mut output).await;
+//!
+//!     assert_eq!(interface.voltage, 12.0);
+//!     assert_eq!(interface.current, 0.5);
+//! }
+//! ```
+//!
+//! ## Reusable command sets
+//!
+//! Commands shared by several interfaces, e.g. in a library crate, can be
+//! defined by the methods of a trait marked with `#[scpi::command_set]`. An
+//! interface implementing the trait registers its commands with the `use`
+//! option.
+//!
+//! ```
+//! use microscpi::{self as scpi, Interface};
+//!
+//! // The command set, which could be defined in another crate as well.
+//! mod lan {
+//!     use microscpi as scpi;
+//!
+//!     #[scpi::command_set]
+//!     pub trait LanCommands {
+//!         fn dhcp_enabled(&mut self) -> &mut bool;
+//!
+//!         #[scpi(cmd = "SYSTem:COMMunicate:LAN:DHCP")]
+//!         fn set_dhcp(&mut self, enabled: bool) -> Result<(), scpi::Error> {
+//!             *self.dhcp_enabled() = enabled;
+//!             Ok(())
+//!         }
+//!
+//!         #[scpi(cmd = "SYSTem:COMMunicate:LAN:DHCP?")]
+//!         fn dhcp(&mut self) -> bool {
+//!             *self.dhcp_enabled()
+//!         }
+//!     }
+//! }
+//!
+//! mod power_supply {
+//!     use microscpi as scpi;
+//!
+//!     #[derive(Default, scpi::IgnoreErrors)]
+//!     pub struct PowerSupply {
+//!         pub dhcp: bool,
+//!     }
+//!
+//!     impl super::lan::LanCommands for PowerSupply {
+//!         fn dhcp_enabled(&mut self) -> &mut bool {
+//!             &mut self.dhcp
+//!         }
+//!     }
+//!
+//!     #[scpi::interface(use(super::lan::LanCommands))]
+//!     impl PowerSupply {}
+//! }
+//!
+//! mod multimeter {
+//!     use microscpi as scpi;
+//!
+//!     use super::lan::LanCommands;
+//!
+//!     #[derive(Default, scpi::IgnoreErrors)]
+//!     pub struct Multimeter {
+//!         pub dhcp: bool,
+//!     }
+//!
+//!     impl LanCommands for Multimeter {
+//!         fn dhcp_enabled(&mut self) -> &mut bool {
+//!             &mut self.dhcp
+//!         }
+//!     }
+//!
+//!     #[scpi::interface(use(LanCommands))]
+//!     impl Multimeter {
+//!         #[scpi(cmd = "MEASure:VOLTage?")]
+//!         fn voltage(&mut self) -> f64 {
+//!             1.5
+//!         }
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! pub async fn main() {
+//!     let mut output = Vec::new();
+//!     let mut power_supply = power_supply::PowerSupply::default();
+//!     let mut multimeter = multimeter::Multimeter::default();
+//!
+//!     power_supply.run(b"SYST:COMM:LAN:DHCP ON\n", &mut output).await;
+//!     multimeter.run(b"SYST:COMM:LAN:DHCP?;:MEAS:VOLT?\n", &mut output).await;
+//!
+//!     assert!(power_supply.dhcp);
+//!     assert_eq!(output, b"0;1.5\n");
+//! }
+//! ```
+//!
+//! ## Parameter ranges
+//!
+//! The valid range of a parameter can be declared with the `range` option of
+//! the `scpi` attribute, which maps parameter names to range expressions. A
+//! value outside of its range is rejected with [Error::DataOutOfRange] before
+//! the handler is called. The ranges are included in the help of the commands.
+//!
+//! ```
+//! use microscpi::{self as scpi, Interface};
+//!
+//! #[derive(Default, scpi::IgnoreErrors)]
+//! pub struct ExampleInterface {
+//!     voltage: f64,
+//! }
+//!
+//! #[scpi::interface]
+//! impl ExampleInterface {
+//!     #[scpi(cmd = "SOURce:VOLTage", range(voltage = "0.0..=30.0"))]
+//!     fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+//!         self.voltage = voltage;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! pub async fn main() {
+//!     let mut output = Vec::new();
+//!     let mut interface = ExampleInterface::default();
+//!
+//!     interface.run(b"SOUR:VOLT 12;VOLT 42\n", &mut output).await;
+//!
+//!     assert_eq!(interface.voltage, 12.0);
+//! }
+//! ```
+//!
+//! A range of an unknown parameter fails to compile:
+//!
+//! ```compile_fail
+//! use microscpi as scpi;
+//!
+//! #[derive(scpi::IgnoreErrors)]
+//! pub struct ExampleInterface;
+//!
+//! #[scpi::interface]
+//! impl ExampleInterface {
+//!     #[scpi(cmd = "SOURce:VOLTage", range(volts = "0.0..=30.0"))]
+//!     fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+//!         Ok(())
+//!     }
+//! }
+//! ```
 #![cfg_attr(not(any(test, feature = "std")), no_std)]
 #![allow(async_fn_in_trait)]
 #![allow(clippy::from_str_radix_10)]
@@ -108,26 +423,87 @@
 #[cfg(feature = "std")]
 extern crate std as core;
 
+#[macro_use]
+mod logging;
+
+#[cfg(feature = "std")]
+mod adapters;
 mod commands;
 mod error;
 mod error_queue;
+mod format;
 mod interface;
+mod interpreter;
+#[cfg(feature = "command-macros")]
+mod macro_store;
+#[cfg(feature = "tokio")]
+pub mod net;
 #[doc(hidden)]
 pub mod parser;
+mod registers;
 mod response;
+#[cfg(feature = "embassy-sync")]
+mod shared;
+mod statistics;
+#[cfg(feature = "std")]
+pub mod testing;
 #[doc(hidden)]
 pub mod tree;
 mod value;
 
-pub use commands::{ErrorCommands, StandardCommands};
-pub use error::Error;
+#[cfg(feature = "std")]
+pub use adapters::BlockingAdapter;
+#[cfg(feature = "tokio")]
+pub use adapters::TokioAdapter;
+#[cfg(feature = "command-macros")]
+pub use commands::MacroCommands;
+pub use commands::{
+    ClockCommands, ErrorCommands, FormatCommands, IdentityCommands, Ieee4882Device,
+    OperationStatusCommands, QuestionableStatusCommands, RemoteCommands, RemoteState,
+    ResetCommands, SelfTestCommands, StandardCommands, StateCommands, StatisticsCommands,
+    StatusCommands, TriggerCommands,
+};
+#[doc(hidden)]
+pub use error::check_device_error_numbers;
+pub use error::{Error, ErrorInfo, ErrorRegistry, MAX_ERROR_INFO_LENGTH};
+#[cfg(feature = "std")]
+pub use error_queue::DynamicErrorQueue;
 pub use error_queue::{ErrorQueue, StaticErrorQueue};
-pub use interface::{Adapter, ErrorHandler, Interface};
-pub use microscpi_macros::interface;
-pub use response::{Arbitrary, Characters, Response, Write};
+pub use format::{DataArray, DataElement, DataFormat, DataType};
+#[cfg(any(feature = "log", feature = "defmt"))]
+pub use interface::log_error;
+#[doc(hidden)]
+pub use interface::with_timeout;
+pub use interface::{
+    Adapter, AdapterErrorType, AdapterRead, AdapterWrite, ConsoleOptions, ErrorHandler, Interface,
+    ProcessState, RunReport,
+};
+pub use interpreter::Interpreter;
+#[cfg(feature = "command-macros")]
+pub use macro_store::{
+    MacroStore, StaticMacroStore, MACRO_EXPANSION_SIZE, MAX_MACRO_DEPTH, MAX_MACRO_LABEL_LENGTH,
+};
+#[doc(hidden)]
+pub use microscpi_macros::__interface_parts;
+#[cfg(any(feature = "log", feature = "defmt"))]
+pub use microscpi_macros::LogErrors;
+pub use microscpi_macros::{command_set, interface, interface_part, IgnoreErrors};
+pub use registers::{
+    EventStatus, OperationStatus, OperationToken, QuestionableStatus, ScpiRegister16, StatusByte,
+    StatusRegisters,
+};
+pub use response::{
+    Arbitrary, ArbitraryParts, Bin, ChannelList, Characters, FmtWriter, Hex, Identity, Nr2, Nr3,
+    Oct, OnOff, Response, ResponseIter, SliceWriter, SyncWrite, Write, WriteAsFmt,
+};
+#[cfg(feature = "embassy-sync")]
+pub use shared::{SharedGuard, SharedHandle, SharedInterface};
+pub use statistics::Statistics;
 #[doc(hidden)]
 pub use tree::Node;
-pub use value::Value;
+#[doc(hidden)]
+pub use value::{Argument, UnorderedArgument};
+pub use value::{BlockData, BlockElement, Bounded, ByteOrder, Scaled, Value, WithByteOrder};
 
 /// Reference identifier of a command or query
 ///
@@ -141,9 +517,16 @@ pub type Result<T> = core::result::Result<T, Error>;
 /// The version of the SCPI standard this crate implements.
 pub const SCPI_STD_VERSION: &str = "1999.0";
 
-/// The maximum number of arguments that can be passed to a command.
+/// The maximum number of arguments that can be passed to a command. Handlers
+/// taking more arguments are rejected by the [interface] macro.
 pub const MAX_ARGS: usize = 10;
 
+/// The maximum length of a command without a terminator passed to
+/// [Interface::execute_str]. These commands are copied into a buffer on the
+/// stack to append the terminator, longer commands are rejected with
+/// [Error::InputBufferOverrun].
+pub const MAX_EXECUTE_STR_LENGTH: usize = 255;
+
 #[cfg(doctest)]
 #[doc = include_str!("../../README.md")]
 struct ReadmeDoctests;
@@ -0,0 +1,34 @@
+//! This module contains macros emitting messages through the enabled logging
+//! facades, i.e. `log` and `defmt`. Without any of them, the messages are
+//! omitted.
+
+/// Emits a message at the trace level.
+macro_rules! trace {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::trace!($($arg)*);
+        #[cfg(feature = "defmt")]
+        ::defmt::trace!($($arg)*);
+    }};
+}
+
+/// Emits a message at the warn level.
+macro_rules! warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::warn!($($arg)*);
+        #[cfg(feature = "defmt")]
+        ::defmt::warn!($($arg)*);
+    }};
+}
+
+/// Emits a message at the error level.
+#[cfg(any(feature = "log", feature = "defmt"))]
+macro_rules! error {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        ::log::error!($($arg)*);
+        #[cfg(feature = "defmt")]
+        ::defmt::error!($($arg)*);
+    }};
+}
@@ -0,0 +1,246 @@
+//! This module contains the storage of the macros defined with `*DMC`.
+
+use crate::Error;
+
+/// The maximum length of a macro label, as defined in IEEE 488.2, 10.7.6.1.
+pub const MAX_MACRO_LABEL_LENGTH: usize = 12;
+
+/// The maximum nesting depth of macros, i.e. of macros expanded within other
+/// macros. Exceeding it is reported as [Error::MacroRecursionError].
+pub const MAX_MACRO_DEPTH: usize = 8;
+
+/// The maximum size in bytes of the expanded commands of a macro, including
+/// the expansions of nested macros and the remaining commands of the program
+/// message. Exceeding it is reported as [Error::MacroExecutionError].
+pub const MACRO_EXPANSION_SIZE: usize = 256;
+
+/// A macro store keeps the macros defined with `*DMC` and whether their
+/// expansion is enabled by `*EMC`.
+///
+/// Labels are compared *case-insensitive*.
+pub trait MacroStore {
+    /// Defines a macro, which is expanded to `body` when its label is
+    /// received as a header.
+    fn define(&mut self, label: &str, body: &[u8]) -> Result<(), Error>;
+
+    /// Returns the body of a macro.
+    fn body(&self, label: &[u8]) -> Option<&[u8]>;
+
+    /// Returns the labels of all macros in the order of their definition.
+    fn labels(&self) -> impl Iterator<Item = &str>;
+
+    /// Removes all macros.
+    fn purge(&mut self);
+
+    /// Returns whether the expansion of macros is enabled.
+    fn enabled(&self) -> bool;
+
+    /// Enables or disables the expansion of macros.
+    fn set_enabled(&mut self, enabled: bool);
+}
+
+/// A statically allocated [MacroStore] holding a maximum of `N` macros with a
+/// body of at most `SZ` bytes each.
+///
+/// The expansion of macros is disabled initially and enabled with `*EMC 1`.
+#[derive(Debug)]
+pub struct StaticMacroStore<const N: usize, const SZ: usize> {
+    macros: heapless::Vec<
+        (
+            heapless::String<MAX_MACRO_LABEL_LENGTH>,
+            heapless::Vec<u8, SZ>,
+        ),
+        N,
+    >,
+    enabled: bool,
+}
+
+impl<const N: usize, const SZ: usize> StaticMacroStore<N, SZ> {
+    pub fn new() -> StaticMacroStore<N, SZ> {
+        StaticMacroStore {
+            macros: heapless::Vec::new(),
+            enabled: false,
+        }
+    }
+}
+
+impl<const N: usize, const SZ: usize> Default for StaticMacroStore<N, SZ> {
+    fn default() -> Self {
+        StaticMacroStore::new()
+    }
+}
+
+impl<const N: usize, const SZ: usize> MacroStore for StaticMacroStore<N, SZ> {
+    fn define(&mut self, label: &str, body: &[u8]) -> Result<(), Error> {
+        if !is_valid_label(label.as_bytes()) {
+            return Err(Error::IllegalMacroLabel);
+        }
+        if self.body(label.as_bytes()).is_some() {
+            return Err(Error::MacroRedefinitionNotAllowed);
+        }
+        if body.contains(&b'\n') {
+            return Err(Error::MacroExecutionError);
+        }
+
+        let label = heapless::String::try_from(label).or(Err(Error::IllegalMacroLabel))?;
+        let body = heapless::Vec::from_slice(body).or(Err(Error::MacroDefinitionTooLong))?;
+        self.macros.push((label, body)).or(Err(Error::OutOfMemory))
+    }
+
+    fn body(&self, label: &[u8]) -> Option<&[u8]> {
+        self.macros
+            .iter()
+            .find(|(name, _)| name.as_bytes().eq_ignore_ascii_case(label))
+            .map(|(_, body)| body.as_slice())
+    }
+
+    fn labels(&self) -> impl Iterator<Item = &str> {
+        self.macros.iter().map(|(label, _)| label.as_str())
+    }
+
+    fn purge(&mut self) {
+        self.macros.clear();
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+}
+
+/// Checks whether a label can be used as the header of a macro.
+///
+/// A label consists of program mnemonics separated by `:` and may end with
+/// `?`. Labels of common commands, which start with `*`, are rejected as
+/// defined in IEEE 488.2, 10.7.6.1.
+fn is_valid_label(label: &[u8]) -> bool {
+    let inner = label.strip_suffix(b"?").unwrap_or(label);
+
+    (1..=MAX_MACRO_LABEL_LENGTH).contains(&label.len())
+        && inner.split(|c| *c == b':').all(|mnemonic| {
+            mnemonic.first().is_some_and(u8::is_ascii_alphabetic)
+                && mnemonic
+                    .iter()
+                    .all(|c| c.is_ascii_alphanumeric() || *c == b'_')
+        })
+}
+
+/// A macro label at the beginning of a program message unit.
+#[derive(Debug, PartialEq)]
+pub(crate) struct MacroCall<'a> {
+    /// The label of the macro without a leading `:`.
+    pub label: &'a [u8],
+    /// Whether the label is followed by parameters.
+    pub parameters: bool,
+    /// The separator following the label, i.e. `;` or `\n`.
+    pub separator: u8,
+    /// The length of the program message unit including the separator.
+    pub len: usize,
+}
+
+/// Extracts a possible macro label from the beginning of the input.
+///
+/// Returns [None] if the input does not contain a complete program message
+/// unit.
+pub(crate) fn macro_call(input: &[u8]) -> Option<MacroCall<'_>> {
+    let start = input.iter().position(|c| !matches!(c, b' ' | b'\t'))?;
+    let end = start
+        + input[start..]
+            .iter()
+            .position(|c| matches!(c, b' ' | b'\t' | b'\r' | b';' | b'\n'))?;
+
+    // Strings and blocks of the parameters may contain separators.
+    let mut quote = None;
+    let separator = end
+        + input[end..].iter().position(|c| match quote {
+            Some(q) if *c == q => {
+                quote = None;
+                false
+            }
+            Some(_) => false,
+            None if matches!(c, b'"' | b'\'') => {
+                quote = Some(*c);
+                false
+            }
+            None => matches!(c, b';' | b'\n'),
+        })?;
+
+    Some(MacroCall {
+        label: input[start..end]
+            .strip_prefix(b":")
+            .unwrap_or(&input[start..end]),
+        parameters: input[end..separator]
+            .iter()
+            .any(|c| !c.is_ascii_whitespace()),
+        separator: input[separator],
+        len: separator + 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_define_macros() {
+        let mut store = StaticMacroStore::<2, 16>::new();
+        assert_eq!(store.define("LIMITS", b"VOLT 1"), Ok(()));
+        assert_eq!(store.body(b"limits"), Some(&b"VOLT 1"[..]));
+        assert_eq!(
+            store.define("Limits", b"VOLT 2"),
+            Err(Error::MacroRedefinitionNotAllowed)
+        );
+        assert_eq!(
+            store.define("SETUP", b"VOLT 1;CURR 2;OUTP ON"),
+            Err(Error::MacroDefinitionTooLong)
+        );
+        assert_eq!(store.define("MEAS?", b"MEAS:VOLT?"), Ok(()));
+        assert_eq!(store.define("SETUP", b"VOLT 1"), Err(Error::OutOfMemory));
+        assert_eq!(store.labels().collect::<Vec<_>>(), ["LIMITS", "MEAS?"]);
+
+        store.purge();
+        assert_eq!(store.labels().count(), 0);
+        assert_eq!(store.body(b"LIMITS"), None);
+    }
+
+    #[test]
+    fn test_macro_labels() {
+        assert!(is_valid_label(b"LIMITS"));
+        assert!(!is_valid_label(b"*SETUP"));
+        assert!(!is_valid_label(b"*RST"));
+        assert!(is_valid_label(b"SOUR:SETUP?"));
+        assert!(!is_valid_label(b":SETUP"));
+        assert!(!is_valid_label(b""));
+        assert!(!is_valid_label(b"1SETUP"));
+        assert!(!is_valid_label(b"SET UP"));
+        assert!(!is_valid_label(b"SOUR::SETUP"));
+        assert!(!is_valid_label(b"VERYLONGLABEL"));
+    }
+
+    #[test]
+    fn test_macro_call() {
+        assert_eq!(
+            macro_call(b" :LIMITS;VOLT 1\n"),
+            Some(MacroCall {
+                label: b"LIMITS",
+                parameters: false,
+                separator: b';',
+                len: 9,
+            })
+        );
+        assert_eq!(
+            macro_call(b"LABEL \"a;b\"\n"),
+            Some(MacroCall {
+                label: b"LABEL",
+                parameters: true,
+                separator: b'\n',
+                len: 12,
+            })
+        );
+        assert_eq!(macro_call(b"LIMITS"), None);
+        assert_eq!(macro_call(b"LIMITS 1"), None);
+    }
+}
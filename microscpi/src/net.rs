@@ -0,0 +1,86 @@
+//! This module contains a server for SCPI over raw TCP sockets.
+//!
+//! Instruments commonly accept SCPI commands on TCP port [SCPI_PORT]. Each
+//! connection is served by its own interface, so the error queue and all other
+//! state of an interface are scoped to the connection.
+use core::future::Future;
+use core::pin::Pin;
+use core::task::Poll;
+
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{Error, Interface, TokioAdapter};
+
+/// The TCP port commonly used for SCPI over raw sockets.
+pub const SCPI_PORT: u16 = 5025;
+
+/// Accepts connections on the listener and processes the commands of each
+/// connection.
+///
+/// A new interface is created by `make_interface` for every connection and
+/// processes the commands with a command buffer of `N` bytes until the
+/// connection is closed. An I/O error of a connection is passed to the error
+/// handler of its interface as [Error::CommunicationError] and ends the
+/// connection.
+///
+/// The connections are served concurrently within the returned future, so
+/// neither the interface nor its command handlers need to be [Send]. Returns
+/// when accepting a connection fails.
+///
+/// ```no_run
+/// # use microscpi::{self as scpi, Interface};
+/// # struct ExampleInterface;
+/// # impl scpi::ErrorHandler for ExampleInterface {
+/// #     fn handle_error(&mut self, _error: scpi::Error) {}
+/// # }
+/// # #[scpi::interface]
+/// # impl ExampleInterface {
+/// #     #[scpi(cmd = "*IDN?")]
+/// #     async fn idn(&mut self) -> Result<&str, scpi::Error> {
+/// #         Ok("EXAMPLE")
+/// #     }
+/// # }
+/// # async fn example() -> std::io::Result<()> {
+/// let listener = tokio::net::TcpListener::bind(("0.0.0.0", scpi::net::SCPI_PORT)).await?;
+/// scpi::net::serve::<256, _, _>(listener, || ExampleInterface).await
+/// # }
+/// ```
+pub async fn serve<const N: usize, I, F>(
+    listener: TcpListener, mut make_interface: F,
+) -> std::io::Result<()>
+where
+    I: Interface<Context = ()>,
+    F: FnMut() -> I,
+{
+    let mut connections: Vec<Pin<Box<dyn Future<Output = ()>>>> = Vec::new();
+
+    core::future::poll_fn(|cx| {
+        loop {
+            match listener.poll_accept(cx) {
+                Poll::Ready(Ok((stream, _))) => {
+                    connections.push(Box::pin(serve_connection::<N, I>(make_interface(), stream)));
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => break,
+            }
+        }
+
+        connections.retain_mut(|connection| connection.as_mut().poll(cx).is_pending());
+        Poll::Pending
+    })
+    .await
+}
+
+/// Processes the commands of a single connection until it is closed.
+async fn serve_connection<const N: usize, I>(mut interface: I, stream: TcpStream)
+where
+    I: Interface<Context = ()>,
+{
+    let mut adapter = TokioAdapter::new(stream);
+
+    if interface.process::<N, _>(&mut adapter).await.is_err() {
+        warn!("SCPI connection error");
+        interface.handle_error(Error::CommunicationError);
+        interface.record_error(Error::CommunicationError);
+    }
+}
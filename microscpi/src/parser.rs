@@ -4,7 +4,7 @@ use core::str::{self, Utf8Error};
 use heapless::Vec;
 
 use crate::tree::Node;
-use crate::{Error, Value, MAX_ARGS};
+use crate::{CommandId, Error, Value, MAX_ARGS};
 
 /// Enum to handle both recoverable and fatal errors.
 #[derive(Debug, PartialEq)]
@@ -57,9 +57,15 @@ type ParseResult<'a, T> = Result<(&'a [u8], T), ParseError>;
 pub struct CommandCall<'a> {
     /// The node in the SCPI command tree that the command corresponds to.
     pub node: &'static Node,
+    /// The offset of the command ids of the (nested) command tree the node
+    /// belongs to.
+    pub offset: CommandId,
     /// The parent node of this SCPI command. When the command has no header
     /// it must be a _common command_ (starting with an asterisk).
     pub header: Option<&'static Node>,
+    /// The offset of the command ids of the command tree the header belongs
+    /// to.
+    pub header_offset: CommandId,
     /// The command is a query (ends with a question mark).
     pub query: bool,
     /// The arguments of the command.
@@ -69,6 +75,19 @@ pub struct CommandCall<'a> {
     pub terminated: bool,
 }
 
+impl CommandCall<'_> {
+    /// Returns the id of the called command or query, if it is defined.
+    pub fn command_id(&self) -> Option<CommandId> {
+        let command = if self.query {
+            self.node.query
+        }
+        else {
+            self.node.command
+        };
+        command.map(|id| id + self.offset)
+    }
+}
+
 /// Takes bytes while the predicate function is true.
 ///
 /// Returns a tuple with the remaining input and the slice of bytes that were
@@ -116,7 +135,7 @@ fn is_whitespace(input: u8) -> bool {
 }
 
 /// Parses whitespace characters.
-fn whitespace(input: &[u8]) -> ParseResult<&[u8]> {
+fn whitespace(input: &[u8]) -> ParseResult<'_, &[u8]> {
     match take_while(is_whitespace)(input) {
         // If no input is remaning, the input is incomplete.
         Ok((&[], &[])) => Err(ParseError::Incomplete),
@@ -135,33 +154,33 @@ fn tag(tag: u8) -> impl Fn(&[u8]) -> ParseResult<u8> {
 }
 
 /// Parses a sequence of digits.
-fn digits(input: &[u8]) -> ParseResult<&[u8]> {
+fn digits(input: &[u8]) -> ParseResult<'_, &[u8]> {
     let (i1, _) = satisfy(|c| c.is_ascii_digit())(input)?;
     let (i2, res) = take_while(|c| c.is_ascii_digit())(i1)?;
     Ok((i2, &input[..res.len() + 1]))
 }
 
 /// Parses a program mnemonic (e.g., "SYSTEM").
-fn program_mnemonic(input: &[u8]) -> ParseResult<&[u8]> {
+fn program_mnemonic(input: &[u8]) -> ParseResult<'_, &[u8]> {
     let (i1, _) = satisfy(|c| c.is_ascii_alphabetic())(input)?;
     let (i2, res) = take_while(|c| c.is_ascii_alphanumeric() || c == b'_')(i1)?;
     Ok((i2, &input[..res.len() + 1]))
 }
 
 /// Parses a sign character (`+` or `-`).
-fn sign(input: &[u8]) -> ParseResult<u8> {
+fn sign(input: &[u8]) -> ParseResult<'_, u8> {
     tag(b'+')(input).or_else(|_| tag(b'-')(input))
 }
 
 /// Parses a label.
-fn characters(input: &[u8]) -> ParseResult<Value<'_>> {
+fn characters(input: &[u8]) -> ParseResult<'_, Value<'_>> {
     let (input, res) = program_mnemonic(input)?;
     let character_str = str::from_utf8(res)?;
     Ok((input, Value::Characters(character_str)))
 }
 
 /// Parses the mantissa part of a decimal number.
-fn mantissa(input: &[u8]) -> ParseResult<&[u8]> {
+fn mantissa(input: &[u8]) -> ParseResult<'_, &[u8]> {
     let (i1, _sign) = optional(sign)(input)?;
     let (i2, d1) = optional(digits)(i1)?;
     let (i3, _decimal) = optional(tag(b'.'))(i2)?;
@@ -175,23 +194,45 @@ fn mantissa(input: &[u8]) -> ParseResult<&[u8]> {
 }
 
 /// Parses the exponent part of a decimal number.
-fn exponent(input: &[u8]) -> ParseResult<&[u8]> {
+fn exponent(input: &[u8]) -> ParseResult<'_, &[u8]> {
     let (i1, _) = satisfy(|c| c == b'E' || c == b'e')(input)?;
     let (i2, _) = optional(sign)(i1)?;
     let (i3, _) = digits(i2)?;
     Ok((i3, &input[..input.len() - i3.len()]))
 }
 
-/// Parses a decimal number.
-fn decimal_numeric_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
+/// Parses a suffix unit (e.g., "ms" or "V/s") following a decimal number.
+///
+/// The suffix may be separated from the number by whitespace.
+fn suffix_program_data(input: &[u8]) -> ParseResult<'_, &str> {
+    let (i1, _) = optional(whitespace)(input)?;
+    let (i2, _) = optional(tag(b'/'))(i1)?;
+    let (i3, _) = satisfy(|c| c.is_ascii_alphabetic())(i2)?;
+    let (i4, _) = take_while(|c| c.is_ascii_alphanumeric() || c == b'/' || c == b'.')(i3)?;
+    let res = &i1[..i1.len() - i4.len()];
+
+    if res.len() > 12 {
+        return Err(ParseError::FatalError(Error::SuffixTooLong));
+    }
+
+    Ok((i4, str::from_utf8(res)?))
+}
+
+/// Parses a decimal number with an optional suffix.
+fn decimal_numeric_program_data(input: &[u8]) -> ParseResult<'_, Value<'_>> {
     let (i1, _) = mantissa(input)?;
     let (i2, _) = optional(exponent)(i1)?;
     let res = str::from_utf8(&input[..input.len() - i2.len()])?;
-    Ok((i2, Value::Decimal(res)))
+
+    match suffix_program_data(i2) {
+        Ok((i3, suffix)) => Ok((i3, Value::Suffixed(res, suffix))),
+        Err(ParseError::FatalError(error)) => Err(ParseError::FatalError(error)),
+        Err(_) => Ok((i2, Value::Decimal(res))),
+    }
 }
 
 /// Parses a hexadecimal number.
-fn hexadecimal_numeric_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
+fn hexadecimal_numeric_program_data(input: &[u8]) -> ParseResult<'_, Value<'_>> {
     let (i1, _) = tag(b'#')(input)?;
     let (i2, _) = satisfy(|c| c == b'H' || c == b'h')(i1)?;
     let (i3, _) = satisfy(|c| c.is_ascii_hexdigit())(i2)?;
@@ -201,7 +242,7 @@ fn hexadecimal_numeric_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
 }
 
 /// Parses a binary number.
-fn binary_numeric_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
+fn binary_numeric_program_data(input: &[u8]) -> ParseResult<'_, Value<'_>> {
     let (i1, _) = tag(b'#')(input)?;
     let (i2, _) = satisfy(|c| c == b'B' || c == b'b')(i1)?;
     let (i3, _) = satisfy(|c| c == b'0' || c == b'1')(i2)?;
@@ -211,7 +252,7 @@ fn binary_numeric_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
 }
 
 /// Parses an octal number.
-fn octal_numeric_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
+fn octal_numeric_program_data(input: &[u8]) -> ParseResult<'_, Value<'_>> {
     let (i1, _) = tag(b'#')(input)?;
     let (i2, _) = satisfy(|c| c == b'Q' || c == b'q')(i1)?;
     let (i3, _) = satisfy(|c| (b'0'..b'8').contains(&c))(i2)?;
@@ -220,26 +261,35 @@ fn octal_numeric_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
     Ok((i4, Value::Octal(res)))
 }
 
+/// Parses a string enclosed by the given quote. A quote is embedded into the
+/// string by doubling it and remains doubled in the value.
+fn quoted_string(quote: u8, input: &[u8]) -> ParseResult<'_, Value<'_>> {
+    let (mut rest, _) = tag(quote)(input)?;
+    loop {
+        let (i1, _) = take_while(|c| c != quote)(rest)?;
+        let (i2, _) = tag(quote)(i1)?;
+        match tag(quote)(i2) {
+            Ok((i3, _)) => rest = i3,
+            Err(_) => {
+                let res = str::from_utf8(&input[1..input.len() - i2.len() - 1])?;
+                return Ok((i2, Value::String(res)));
+            }
+        }
+    }
+}
+
 /// Parses a single quoted string.
-fn single_quoted_string_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
-    let (i1, _) = tag(b'\'')(input)?;
-    let (i2, res) = take_while(|c| c != b'\'')(i1)?;
-    let (i3, _) = tag(b'\'')(i2)?;
-    let res = str::from_utf8(res)?;
-    Ok((i3, Value::String(res)))
+fn single_quoted_string_program_data(input: &[u8]) -> ParseResult<'_, Value<'_>> {
+    quoted_string(b'\'', input)
 }
 
 /// Parses a double quoted string.
-fn double_quoted_string_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
-    let (i1, _) = tag(b'"')(input)?;
-    let (i2, res) = take_while(|c| c != b'"')(i1)?;
-    let (i3, _) = tag(b'"')(i2)?;
-    let res = str::from_utf8(res)?;
-    Ok((i3, Value::String(res)))
+fn double_quoted_string_program_data(input: &[u8]) -> ParseResult<'_, Value<'_>> {
+    quoted_string(b'"', input)
 }
 
 /// Parses arbitrary 8 bit binary data.
-fn arbitrary_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
+fn arbitrary_program_data(input: &[u8]) -> ParseResult<'_, Value<'_>> {
     let (i1, _) = tag(b'#')(input)?;
     let (i2, digits) = satisfy(|c| (b'1'..b'9').contains(&c))(i1)
         .map(|(i, value)| (i, (value - b'0') as usize))?;
@@ -263,17 +313,21 @@ fn arbitrary_program_data(input: &[u8]) -> ParseResult<Value<'_>> {
 }
 
 /// Parses a header separator (colon with optional whitespace).
-fn header_separator(input: &[u8]) -> ParseResult<()> {
+fn header_separator(input: &[u8]) -> ParseResult<'_, ()> {
     let (input, _) = optional(whitespace)(input)?;
     let (input, _) = tag(b':')(input).map_err(|_| Error::HeaderSeparatorError)?;
     let (input, _) = optional(whitespace)(input)?;
     Ok((input, ()))
 }
 
+/// A node of the command tree and the offset of the command ids of the tree it
+/// belongs to.
+type Position = (&'static Node, CommandId);
+
 /// Parses a common command program header (e.g., "*IDN").
 fn common_command_program_header(
     root: &'static Node,
-) -> impl Fn(&[u8]) -> ParseResult<(&'static Node, Option<&'static Node>)> {
+) -> impl Fn(&[u8]) -> ParseResult<(Position, Option<Position>)> {
     move |input: &[u8]| {
         let (i1, _) = tag(b'*')(input).map_err(|_| Error::UndefinedHeader)?;
         let (i2, res) = program_mnemonic(i1)?;
@@ -282,14 +336,14 @@ fn common_command_program_header(
             .child(str::from_utf8(name)?)
             .ok_or(Error::UndefinedHeader)?;
 
-        Ok((i2, (node, None)))
+        Ok((i2, ((node, 0), None)))
     }
 }
 
 /// Parses a compound command program header (e.g., "SYST:ERR").
 fn compound_command_program_header(
-    root: &'static Node, header: &'static Node,
-) -> impl Fn(&[u8]) -> ParseResult<(&'static Node, Option<&'static Node>)> {
+    root: &'static Node, header: Position,
+) -> impl Fn(&[u8]) -> ParseResult<(Position, Option<Position>)> {
     move |mut input: &[u8]| {
         let mut header = header;
 
@@ -297,11 +351,16 @@ fn compound_command_program_header(
         let (i1, root_command) = optional(header_separator)(input)?;
 
         // If true, we start with the root node.
-        let mut node = if root_command.is_some() { root } else { header };
+        let (mut node, mut offset) = if root_command.is_some() {
+            (root, 0)
+        }
+        else {
+            header
+        };
 
         let (i2, res) = program_mnemonic(i1)?;
         let name = str::from_utf8(res)?;
-        node = node.child(name).ok_or(Error::UndefinedHeader)?;
+        (node, offset) = node.descend(name, offset).ok_or(Error::UndefinedHeader)?;
         input = i2;
 
         loop {
@@ -313,19 +372,19 @@ fn compound_command_program_header(
 
             let (i, res) = program_mnemonic(i)?;
             let name = str::from_utf8(res)?;
-            header = node;
-            node = node.child(name).ok_or(Error::UndefinedHeader)?;
+            header = (node, offset);
+            (node, offset) = node.descend(name, offset).ok_or(Error::UndefinedHeader)?;
             input = i;
         }
 
-        Ok((input, (node, Some(header))))
+        Ok((input, ((node, offset), Some(header))))
     }
 }
 
 /// Parses the command program header (both common and compound).
 fn command_program_header(
-    root: &'static Node, header: &'static Node,
-) -> impl Fn(&[u8]) -> ParseResult<(&'static Node, Option<&'static Node>)> {
+    root: &'static Node, header: Position,
+) -> impl Fn(&[u8]) -> ParseResult<(Position, Option<Position>)> {
     move |input: &[u8]| {
         compound_command_program_header(root, header)(input)
             .or_else(|_| common_command_program_header(root)(input))
@@ -333,7 +392,7 @@ fn command_program_header(
 }
 
 /// Parses an argument separator (comma with optional whitespace).
-fn argument_separator(input: &[u8]) -> ParseResult<()> {
+fn argument_separator(input: &[u8]) -> ParseResult<'_, ()> {
     let (input, _) = optional(whitespace)(input)?;
     let (input, _) = tag(b',')(input).map_err(|_| Error::InvalidSeparator)?;
     let (input, _) = optional(whitespace)(input)?;
@@ -341,9 +400,16 @@ fn argument_separator(input: &[u8]) -> ParseResult<()> {
 }
 
 /// Parses an argument value.
-fn argument(input: &[u8]) -> ParseResult<Value<'_>> {
+fn argument(input: &[u8]) -> ParseResult<'_, Value<'_>> {
+    let decimal = decimal_numeric_program_data(input);
+
+    // A malformed suffix cannot be the start of any other data type.
+    if let Err(ParseError::FatalError(_)) = decimal {
+        return decimal;
+    }
+
     characters(input)
-        .or_else(|_| decimal_numeric_program_data(input))
+        .or(decimal)
         .or_else(|_| hexadecimal_numeric_program_data(input))
         .or_else(|_| binary_numeric_program_data(input))
         .or_else(|_| octal_numeric_program_data(input))
@@ -379,8 +445,11 @@ fn arguments<'a, 'b>(
 }
 
 /// Parses a SCPI command call.
+///
+/// The command header is resolved relative to the `header` node, whose command
+/// ids are offset by `header_offset`, unless it starts at the `root` node.
 pub fn parse<'a>(
-    root: &'static Node, header: &'static Node, input: &'a [u8],
+    root: &'static Node, header: &'static Node, header_offset: CommandId, input: &'a [u8],
 ) -> ParseResult<'a, Option<CommandCall<'a>>> {
     // Skip optional whitespace
     let (input, _) = optional(whitespace)(input)?;
@@ -390,7 +459,8 @@ pub fn parse<'a>(
         return Ok((input, None));
     }
 
-    let (input, (node, header)) = command_program_header(root, header)(input)?;
+    let (input, ((node, offset), header)) =
+        command_program_header(root, (header, header_offset))(input)?;
 
     let (input, query) = tag(b'?')(input)
         .map(|(i, _)| (i, true))
@@ -425,7 +495,9 @@ pub fn parse<'a>(
         input,
         Some(CommandCall {
             node,
-            header,
+            offset,
+            header: header.map(|(node, _)| node),
+            header_offset: header.map_or(0, |(_, offset)| offset),
             query,
             args,
             terminated,
@@ -433,6 +505,54 @@ pub fn parse<'a>(
     ))
 }
 
+/// Returns whether the unterminated program message units at the end of the
+/// input contain a query.
+///
+/// The headers are checked for a trailing `?` without resolving them, and the
+/// arguments are skipped, so a `?` within string or block data is not taken
+/// for a query.
+pub fn contains_query(mut input: &[u8]) -> bool {
+    let mut args = Vec::new();
+    loop {
+        let Ok((i, _)) = optional(whitespace)(input)
+        else {
+            return false;
+        };
+        let i = i.strip_prefix(b"*").unwrap_or(i);
+        let Ok((i, _)) = optional(header_separator)(i)
+        else {
+            return false;
+        };
+        let Ok((mut i, _)) = program_mnemonic(i)
+        else {
+            return false;
+        };
+        while let Ok((rest, _)) = header_separator(i) {
+            let Ok((rest, _)) = program_mnemonic(rest)
+            else {
+                return false;
+            };
+            i = rest;
+        }
+
+        if tag(b'?')(i).is_ok() {
+            return true;
+        }
+
+        if let Ok((rest, _)) = whitespace(i) {
+            args.clear();
+            if let Ok((rest, _)) = arguments(&mut args)(rest) {
+                i = rest;
+            }
+        }
+        let Ok((i, _)) = optional(whitespace)(i).and_then(|(i, _)| tag(b';')(i))
+        else {
+            return false;
+        };
+        input = i;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -441,24 +561,28 @@ mod tests {
         children: &[("*IDN", &IDN_NODE), ("SYST", &SYST_NODE)],
         command: None,
         query: None,
+        subtree: None,
     };
 
     static IDN_NODE: Node = Node {
         children: &[],
         command: None,
         query: None,
+        subtree: None,
     };
 
     static SYST_NODE: Node = Node {
         children: &[("ERR", &ERR_NODE)],
         command: None,
         query: None,
+        subtree: None,
     };
 
     static ERR_NODE: Node = Node {
         children: &[],
         command: None,
         query: None,
+        subtree: None,
     };
 
     #[test]
@@ -527,6 +651,16 @@ mod tests {
             single_quoted_string_program_data(b"''"),
             Ok((&b""[..], Value::String("")))
         );
+
+        assert_eq!(
+            double_quoted_string_program_data(b"\"a\"\"b\"\"\",1"),
+            Ok((&b",1"[..], Value::String("a\"\"b\"\"")))
+        );
+
+        assert_eq!(
+            single_quoted_string_program_data(b"'it''s'"),
+            Ok((&b""[..], Value::String("it''s")))
+        );
     }
 
     #[test]
@@ -547,6 +681,39 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_suffix() {
+        assert_eq!(
+            decimal_numeric_program_data(b"10ms"),
+            Ok((&b""[..], Value::Suffixed("10", "ms")))
+        );
+
+        assert_eq!(
+            decimal_numeric_program_data(b"2.5 S\n"),
+            Ok((&b"\n"[..], Value::Suffixed("2.5", "S")))
+        );
+
+        assert_eq!(
+            decimal_numeric_program_data(b"1.5E-3 V/s,"),
+            Ok((&b","[..], Value::Suffixed("1.5E-3", "V/s")))
+        );
+
+        assert_eq!(
+            decimal_numeric_program_data(b"123 456"),
+            Ok((&b" 456"[..], Value::Decimal("123")))
+        );
+
+        assert_eq!(
+            decimal_numeric_program_data(b"1 ABCDEFGHIJKLM"),
+            Err(ParseError::FatalError(Error::SuffixTooLong))
+        );
+
+        assert_eq!(
+            argument(b"1 ABCDEFGHIJKLM"),
+            Err(ParseError::FatalError(Error::SuffixTooLong))
+        );
+    }
+
     #[test]
     pub fn test_arbitrary() {
         assert_eq!(
@@ -560,7 +727,7 @@ mod tests {
         );
 
         assert_eq!(
-            arbitrary_program_data(&[b'#', b'1', b'0']),
+            arbitrary_program_data(b"#10"),
             Ok((&b""[..], Value::Arbitrary(&[])))
         );
     }
@@ -572,6 +739,18 @@ mod tests {
         assert_eq!(&args[..], &[Value::Decimal("123"), Value::Decimal("456")]);
     }
 
+    #[test]
+    pub fn test_contains_query() {
+        assert!(contains_query(b"SYST:ERR?"));
+        assert!(contains_query(b"*IDN?"));
+        assert!(contains_query(b":SYST:LAB \"A?\";:SYST:ERR?"));
+        assert!(!contains_query(b"*RST"));
+        assert!(!contains_query(b"SYST:LAB \"A?\""));
+        assert!(!contains_query(b"SYST:LAB \"A?"));
+        assert!(!contains_query(b"DATA #15AB?DE;SYST:LAB"));
+        assert!(!contains_query(b""));
+    }
+
     #[test]
     pub fn test_satisfy() {
         assert_eq!(satisfy(|c| c == b'a')(b"abc"), Ok((&b"bc"[..], b'a')));
@@ -633,7 +812,7 @@ mod tests {
     pub fn test_common_command_program_header() {
         assert_eq!(
             common_command_program_header(&ROOT_NODE)(b"*IDN"),
-            Ok((&b""[..], (&IDN_NODE, None)))
+            Ok((&b""[..], ((&IDN_NODE, 0), None)))
         );
 
         assert_eq!(
@@ -645,12 +824,12 @@ mod tests {
     #[test]
     pub fn test_compound_command_program_header() {
         assert_eq!(
-            compound_command_program_header(&ROOT_NODE, &ROOT_NODE)(b"SYST:ERR"),
-            Ok((&b""[..], (&ERR_NODE, Some(&SYST_NODE))))
+            compound_command_program_header(&ROOT_NODE, (&ROOT_NODE, 0))(b"SYST:ERR"),
+            Ok((&b""[..], ((&ERR_NODE, 0), Some((&SYST_NODE, 0)))))
         );
 
         assert_eq!(
-            compound_command_program_header(&ROOT_NODE, &ROOT_NODE)(b"SYST:XYZ"),
+            compound_command_program_header(&ROOT_NODE, (&ROOT_NODE, 0))(b"SYST:XYZ"),
             Err(Error::UndefinedHeader.into())
         );
     }
@@ -658,17 +837,17 @@ mod tests {
     #[test]
     pub fn test_command_program_header() {
         assert_eq!(
-            command_program_header(&ROOT_NODE, &ROOT_NODE)(b"*IDN"),
-            Ok((&b""[..], (&IDN_NODE, None)))
+            command_program_header(&ROOT_NODE, (&ROOT_NODE, 0))(b"*IDN"),
+            Ok((&b""[..], ((&IDN_NODE, 0), None)))
         );
 
         assert_eq!(
-            command_program_header(&ROOT_NODE, &ROOT_NODE)(b"SYST:ERR"),
-            Ok((&b""[..], (&ERR_NODE, Some(&SYST_NODE))))
+            command_program_header(&ROOT_NODE, (&ROOT_NODE, 0))(b"SYST:ERR"),
+            Ok((&b""[..], ((&ERR_NODE, 0), Some((&SYST_NODE, 0)))))
         );
 
         assert_eq!(
-            command_program_header(&ROOT_NODE, &ROOT_NODE)(b"*XYZ"),
+            command_program_header(&ROOT_NODE, (&ROOT_NODE, 0))(b"*XYZ"),
             Err(Error::UndefinedHeader.into())
         );
     }
@@ -686,12 +865,14 @@ mod tests {
     #[test]
     pub fn test_parse() {
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"*IDN?\n"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"*IDN?\n"),
             Ok((
                 &b""[..],
                 Some(CommandCall {
                     node: &IDN_NODE,
+                    offset: 0,
                     header: None,
+                    header_offset: 0,
                     query: true,
                     args: Vec::new(),
                     terminated: true,
@@ -700,12 +881,14 @@ mod tests {
         );
 
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"SYST:ERR 123, 456\n"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"SYST:ERR 123, 456\n"),
             Ok((
                 &b""[..],
                 Some(CommandCall {
                     node: &ERR_NODE,
+                    offset: 0,
                     header: Some(&SYST_NODE),
+                    header_offset: 0,
                     query: false,
                     args: heapless::Vec::from_slice(&[
                         Value::Decimal("123"),
@@ -718,7 +901,7 @@ mod tests {
         );
 
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"*XYZ\n"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"*XYZ\n"),
             Err(Error::UndefinedHeader.into())
         );
     }
@@ -780,12 +963,14 @@ mod tests {
     #[test]
     pub fn test_parse_with_whitespace() {
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"  *IDN?  \n"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"  *IDN?  \n"),
             Ok((
                 &b""[..],
                 Some(CommandCall {
                     node: &IDN_NODE,
+                    offset: 0,
                     header: None,
+                    header_offset: 0,
                     query: true,
                     args: Vec::new(),
                     terminated: true,
@@ -794,12 +979,14 @@ mod tests {
         );
 
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"  SYST:ERR  123,  456  \n"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"  SYST:ERR  123,  456  \n"),
             Ok((
                 &b""[..],
                 Some(CommandCall {
                     node: &ERR_NODE,
+                    offset: 0,
                     header: Some(&SYST_NODE),
+                    header_offset: 0,
                     query: false,
                     args: heapless::Vec::from_slice(&[
                         Value::Decimal("123"),
@@ -815,7 +1002,7 @@ mod tests {
     #[test]
     pub fn test_parse_incomplete() {
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"*IDN?"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"*IDN?"),
             Err(ParseError::Incomplete)
         );
     }
@@ -823,27 +1010,27 @@ mod tests {
     #[test]
     pub fn test_parse_invalid_character() {
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"*IDN?abc\n"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"*IDN?abc\n"),
             Err(Error::InvalidCharacter.into())
         );
 
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"SYST:ERR 123, 456abc\n"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"SYST:ERR 123, 456!\n"),
             Err(Error::InvalidCharacter.into())
         );
     }
 
     #[test]
     pub fn test_parse_empty() {
-        assert_eq!(parse(&ROOT_NODE, &ROOT_NODE, b"\n"), Ok((&[][..], None)));
+        assert_eq!(parse(&ROOT_NODE, &ROOT_NODE, 0, b"\n"), Ok((&[][..], None)));
 
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"\nabc"),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"\nabc"),
             Ok((&b"abc"[..], None))
         );
 
         assert_eq!(
-            parse(&ROOT_NODE, &ROOT_NODE, b"  \n "),
+            parse(&ROOT_NODE, &ROOT_NODE, 0, b"  \n "),
             Ok((&b" "[..], None))
         );
     }
@@ -0,0 +1,763 @@
+//! This module contains the status reporting registers defined by IEEE 488.2.
+
+use crate::Error;
+
+/// Status Byte Register
+///
+/// The status byte summarizes the state of the device and is read with the
+/// `*STB?` query. The bit assignment follows IEEE 488.2, 11.2 and SCPI-99,
+/// 9.1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusByte(u8);
+
+impl StatusByte {
+    /// The error/event queue is not empty.
+    pub const ERROR_EVENT_QUEUE: StatusByte = StatusByte(1 << 2);
+    /// Summary of the standard event status register (ESB).
+    pub const EVENT_STATUS: StatusByte = StatusByte(1 << 5);
+    /// Master summary status (MSS).
+    pub const MASTER_SUMMARY: StatusByte = StatusByte(1 << 6);
+    /// A response message is available in the output queue (MAV).
+    pub const MESSAGE_AVAILABLE: StatusByte = StatusByte(1 << 4);
+    /// Summary of the operation status register.
+    pub const OPERATION_STATUS: StatusByte = StatusByte(1 << 7);
+    /// Summary of the questionable status register.
+    pub const QUESTIONABLE_STATUS: StatusByte = StatusByte(1 << 3);
+
+    /// Creates a status byte from its raw value.
+    pub const fn from_bits(bits: u8) -> StatusByte {
+        StatusByte(bits)
+    }
+
+    /// Returns the raw value of the status byte.
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns whether all bits of `other` are set.
+    pub const fn contains(&self, other: StatusByte) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Sets or clears the bits of `other`.
+    pub fn set(&mut self, other: StatusByte, value: bool) {
+        if value {
+            self.0 |= other.0;
+        }
+        else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl StatusByte {
+    /// Returns the status byte with the master summary status (MSS) computed
+    /// from the bits enabled in the service request enable register.
+    ///
+    /// The MSS bit is set if any other bit is set in both the status byte and
+    /// `enable`. Bit 6 of `enable` is ignored.
+    pub fn with_master_summary(mut self, enable: u8) -> StatusByte {
+        let summary = self.0 & enable & !StatusByte::MASTER_SUMMARY.0 != 0;
+        self.set(StatusByte::MASTER_SUMMARY, summary);
+        self
+    }
+}
+
+impl core::ops::BitOr for StatusByte {
+    type Output = StatusByte;
+
+    fn bitor(self, rhs: StatusByte) -> StatusByte {
+        StatusByte(self.0 | rhs.0)
+    }
+}
+
+/// Standard Event Status Register
+///
+/// The standard event status register records events defined by IEEE 488.2,
+/// 11.5.1. It is read and cleared with the `*ESR?` query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventStatus(u8);
+
+impl EventStatus {
+    /// A command error occurred (CME).
+    pub const COMMAND_ERROR: EventStatus = EventStatus(1 << 5);
+    /// A device-dependent error occurred (DDE).
+    pub const DEVICE_DEPENDENT_ERROR: EventStatus = EventStatus(1 << 3);
+    /// An execution error occurred (EXE).
+    pub const EXECUTION_ERROR: EventStatus = EventStatus(1 << 4);
+    /// All pending operations have completed after `*OPC` (OPC).
+    pub const OPERATION_COMPLETE: EventStatus = EventStatus(1 << 0);
+    /// The device has been powered on (PON).
+    pub const POWER_ON: EventStatus = EventStatus(1 << 7);
+    /// A query error occurred (QYE).
+    pub const QUERY_ERROR: EventStatus = EventStatus(1 << 2);
+    /// The device requests control of the bus (RQC).
+    pub const REQUEST_CONTROL: EventStatus = EventStatus(1 << 1);
+    /// A user request occurred (URQ).
+    pub const USER_REQUEST: EventStatus = EventStatus(1 << 6);
+
+    /// Returns the event corresponding to the class of an error.
+    ///
+    /// Command errors (-100 to -199) map to CME, execution errors (-200 to
+    /// -299) to EXE, device-specific errors (-300 to -399) and device defined
+    /// errors (positive numbers) to DDE and query errors (-400 to -499) to
+    /// QYE.
+    pub fn from_error(error: &Error) -> EventStatus {
+        if error.is_command_error() {
+            EventStatus::COMMAND_ERROR
+        }
+        else if error.is_execution_error() {
+            EventStatus::EXECUTION_ERROR
+        }
+        else if error.is_device_specific_error() {
+            EventStatus::DEVICE_DEPENDENT_ERROR
+        }
+        else if error.is_query_error() {
+            EventStatus::QUERY_ERROR
+        }
+        else {
+            EventStatus(0)
+        }
+    }
+
+    /// Creates an event status from its raw value.
+    pub const fn from_bits(bits: u8) -> EventStatus {
+        EventStatus(bits)
+    }
+
+    /// Returns the raw value of the event status.
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns whether all bits of `other` are set.
+    pub const fn contains(&self, other: EventStatus) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Sets or clears the bits of `other`.
+    pub fn set(&mut self, other: EventStatus, value: bool) {
+        if value {
+            self.0 |= other.0;
+        }
+        else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl core::ops::BitOr for EventStatus {
+    type Output = EventStatus;
+
+    fn bitor(self, rhs: EventStatus) -> EventStatus {
+        EventStatus(self.0 | rhs.0)
+    }
+}
+
+/// SCPI Status Register
+///
+/// A 16-bit status register as defined by SCPI-99, 9.3, which is used for the
+/// operation and the questionable status. It consists of a condition register
+/// reflecting the current state, an event register latching the bits that
+/// have changed in the condition register and an enable register selecting
+/// the events summarized in the status byte. The positive and negative
+/// transition filters select whether a bit is latched when it is set or
+/// cleared in the condition register. By default, only set bits are latched.
+/// Bit 15 is not used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScpiRegister16 {
+    condition: u16,
+    event: u16,
+    enable: u16,
+    positive_transition: u16,
+    negative_transition: u16,
+}
+
+impl Default for ScpiRegister16 {
+    fn default() -> Self {
+        ScpiRegister16 {
+            condition: 0,
+            event: 0,
+            enable: 0,
+            positive_transition: Self::MASK,
+            negative_transition: 0,
+        }
+    }
+}
+
+impl ScpiRegister16 {
+    /// The bits used by the register.
+    const MASK: u16 = 0x7fff;
+
+    /// Returns the condition register.
+    pub fn condition(&self) -> u16 {
+        self.condition
+    }
+
+    /// Sets or clears the `bits` of the condition register. Bits changing
+    /// from 0 to 1 are latched in the event register if they are set in the
+    /// positive transition filter, bits changing from 1 to 0 if they are set
+    /// in the negative transition filter.
+    pub fn set_condition(&mut self, bits: u16, value: bool) {
+        let bits = bits & Self::MASK;
+        let previous = self.condition;
+        if value {
+            self.condition |= bits;
+        }
+        else {
+            self.condition &= !bits;
+        }
+
+        let rising = self.condition & !previous & self.positive_transition;
+        let falling = previous & !self.condition & self.negative_transition;
+        self.event |= rising | falling;
+    }
+
+    /// Returns the event register.
+    pub fn event(&self) -> u16 {
+        self.event
+    }
+
+    /// Returns and clears the event register.
+    pub fn take_event(&mut self) -> u16 {
+        core::mem::take(&mut self.event)
+    }
+
+    /// Returns the enable register.
+    pub fn enable(&self) -> u16 {
+        self.enable
+    }
+
+    /// Sets the enable register, which selects the events summarized in the
+    /// status byte.
+    pub fn set_enable(&mut self, enable: u16) {
+        self.enable = enable & Self::MASK;
+    }
+
+    /// Returns the positive transition filter.
+    pub fn positive_transition(&self) -> u16 {
+        self.positive_transition
+    }
+
+    /// Sets the positive transition filter, which selects the bits latched
+    /// when they are set in the condition register.
+    pub fn set_positive_transition(&mut self, filter: u16) {
+        self.positive_transition = filter & Self::MASK;
+    }
+
+    /// Returns the negative transition filter.
+    pub fn negative_transition(&self) -> u16 {
+        self.negative_transition
+    }
+
+    /// Sets the negative transition filter, which selects the bits latched
+    /// when they are cleared in the condition register.
+    pub fn set_negative_transition(&mut self, filter: u16) {
+        self.negative_transition = filter & Self::MASK;
+    }
+
+    /// Returns whether an enabled event is set.
+    pub fn summary(&self) -> bool {
+        self.event & self.enable != 0
+    }
+}
+
+/// Operation Status Register
+///
+/// The operation status register of SCPI-99, 9.4, which reports the state of
+/// normal operations, e.g. a running measurement. The associated constants
+/// are the bits of its condition register.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationStatus(ScpiRegister16);
+
+impl OperationStatus {
+    /// The device is calibrating.
+    pub const CALIBRATING: u16 = 1 << 0;
+    /// The device is applying a correction.
+    pub const CORRECTING: u16 = 1 << 7;
+    /// Summary of the instrument specific status registers.
+    pub const INSTRUMENT_SUMMARY: u16 = 1 << 13;
+    /// A measurement is in progress.
+    pub const MEASURING: u16 = 1 << 4;
+    /// A user-defined program is running.
+    pub const PROGRAM_RUNNING: u16 = 1 << 14;
+    /// The device is changing its range.
+    pub const RANGING: u16 = 1 << 2;
+    /// The device is waiting for signals to settle.
+    pub const SETTLING: u16 = 1 << 1;
+    /// A sweep is in progress.
+    pub const SWEEPING: u16 = 1 << 3;
+    /// The device is waiting for an arm event.
+    pub const WAITING_FOR_ARM: u16 = 1 << 6;
+    /// The device is waiting for a trigger.
+    pub const WAITING_FOR_TRIGGER: u16 = 1 << 5;
+}
+
+impl core::ops::Deref for OperationStatus {
+    type Target = ScpiRegister16;
+
+    fn deref(&self) -> &ScpiRegister16 {
+        &self.0
+    }
+}
+
+impl core::ops::DerefMut for OperationStatus {
+    fn deref_mut(&mut self) -> &mut ScpiRegister16 {
+        &mut self.0
+    }
+}
+
+impl From<ScpiRegister16> for OperationStatus {
+    fn from(register: ScpiRegister16) -> OperationStatus {
+        OperationStatus(register)
+    }
+}
+
+/// Questionable Status Register
+///
+/// The questionable status register of SCPI-99, 9.5, which reports the
+/// quality of the data acquired by the device. The associated constants are
+/// the bits of its condition register.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuestionableStatus(ScpiRegister16);
+
+impl QuestionableStatus {
+    /// The calibration is questionable.
+    pub const CALIBRATION: u16 = 1 << 8;
+    /// The command warning bit.
+    pub const COMMAND_WARNING: u16 = 1 << 14;
+    /// The current is questionable.
+    pub const CURRENT: u16 = 1 << 1;
+    /// The frequency is questionable.
+    pub const FREQUENCY: u16 = 1 << 5;
+    /// Summary of the instrument specific status registers.
+    pub const INSTRUMENT_SUMMARY: u16 = 1 << 13;
+    /// The modulation is questionable.
+    pub const MODULATION: u16 = 1 << 7;
+    /// The phase is questionable.
+    pub const PHASE: u16 = 1 << 6;
+    /// The power is questionable.
+    pub const POWER: u16 = 1 << 3;
+    /// The temperature is questionable.
+    pub const TEMPERATURE: u16 = 1 << 4;
+    /// The time is questionable.
+    pub const TIME: u16 = 1 << 2;
+    /// The voltage is questionable.
+    pub const VOLTAGE: u16 = 1 << 0;
+}
+
+impl core::ops::Deref for QuestionableStatus {
+    type Target = ScpiRegister16;
+
+    fn deref(&self) -> &ScpiRegister16 {
+        &self.0
+    }
+}
+
+impl core::ops::DerefMut for QuestionableStatus {
+    fn deref_mut(&mut self) -> &mut ScpiRegister16 {
+        &mut self.0
+    }
+}
+
+impl From<ScpiRegister16> for QuestionableStatus {
+    fn from(register: ScpiRegister16) -> QuestionableStatus {
+        QuestionableStatus(register)
+    }
+}
+
+/// A pending overlapped operation.
+///
+/// Returned by [StatusRegisters::begin_operation]. The token can be moved to
+/// the task performing the operation and is completed there, once the
+/// operation has finished.
+#[must_use = "the operation remains pending until the token is completed"]
+#[derive(Debug)]
+pub struct OperationToken(());
+
+impl OperationToken {
+    /// Completes the operation.
+    pub fn complete_on(self, registers: &mut StatusRegisters) {
+        registers.complete_operation();
+    }
+}
+
+/// Status Registers
+///
+/// Holds the state of the status reporting structure of an interface. The
+/// interface keeps the state up to date while processing commands.
+#[derive(Debug, Default)]
+pub struct StatusRegisters {
+    output_pending: bool,
+    pending_operations: u16,
+    operation_complete_armed: bool,
+    status_byte_enable: u8,
+    event_status: EventStatus,
+    event_status_enable: u8,
+    error_available: bool,
+    master_summary: bool,
+    operation: OperationStatus,
+    questionable: QuestionableStatus,
+}
+
+impl StatusRegisters {
+    pub fn new() -> StatusRegisters {
+        StatusRegisters::default()
+    }
+
+    /// Returns whether a response has been produced, but not yet been read by
+    /// the controller.
+    pub fn output_pending(&self) -> bool {
+        self.output_pending
+    }
+
+    /// Updates whether a response is pending.
+    ///
+    /// This is called by the interface whenever response data is produced or
+    /// written to the controller.
+    pub fn set_output_pending(&mut self, pending: bool) {
+        self.output_pending = pending;
+    }
+
+    /// Returns whether the error queue is not empty.
+    pub fn error_available(&self) -> bool {
+        self.error_available
+    }
+
+    /// Updates whether the error queue is not empty.
+    ///
+    /// This is called by the interface for interfaces with an error queue.
+    pub fn set_error_available(&mut self, available: bool) {
+        self.error_available = available;
+    }
+
+    /// Returns the operation status register.
+    pub fn operation(&self) -> &OperationStatus {
+        &self.operation
+    }
+
+    /// Returns the operation status register to update its condition.
+    pub fn operation_mut(&mut self) -> &mut OperationStatus {
+        &mut self.operation
+    }
+
+    /// Returns the questionable status register.
+    pub fn questionable(&self) -> &QuestionableStatus {
+        &self.questionable
+    }
+
+    /// Returns the questionable status register to update its condition.
+    pub fn questionable_mut(&mut self) -> &mut QuestionableStatus {
+        &mut self.questionable
+    }
+
+    /// Returns the number of overlapped operations that have not completed
+    /// yet.
+    pub fn pending_operations(&self) -> u16 {
+        self.pending_operations
+    }
+
+    /// Registers the start of an overlapped operation, which is pending until
+    /// the returned token is completed with [OperationToken::complete_on].
+    pub fn begin_operation(&mut self) -> OperationToken {
+        self.pending_operations = self.pending_operations.saturating_add(1);
+        OperationToken(())
+    }
+
+    /// Registers the completion of an overlapped operation and sets the OPC
+    /// bit if `*OPC` is waiting for the last one.
+    fn complete_operation(&mut self) {
+        self.pending_operations = self.pending_operations.saturating_sub(1);
+        if self.pending_operations == 0 && self.operation_complete_armed {
+            self.operation_complete_armed = false;
+            self.set_event(EventStatus::OPERATION_COMPLETE);
+        }
+    }
+
+    /// Returns whether the OPC bit is set when all pending operations have
+    /// completed.
+    pub fn operation_complete_armed(&self) -> bool {
+        self.operation_complete_armed
+    }
+
+    /// Sets the OPC bit once all pending operations have completed, or
+    /// immediately if no operations are pending.
+    pub fn arm_operation_complete(&mut self) {
+        if self.pending_operations == 0 {
+            self.set_event(EventStatus::OPERATION_COMPLETE);
+        }
+        else {
+            self.operation_complete_armed = true;
+        }
+    }
+
+    /// Cancels a pending `*OPC`, e.g. on `*RST` or `*CLS`.
+    pub fn disarm_operation_complete(&mut self) {
+        self.operation_complete_armed = false;
+    }
+
+    /// Returns the service request enable register.
+    pub fn status_byte_enable(&self) -> u8 {
+        self.status_byte_enable
+    }
+
+    /// Sets the service request enable register. Bit 6 is always cleared.
+    pub fn set_status_byte_enable(&mut self, enable: u8) {
+        self.status_byte_enable = enable & !StatusByte::MASTER_SUMMARY.bits();
+    }
+
+    /// Returns the standard event status register.
+    pub fn event_status(&self) -> EventStatus {
+        self.event_status
+    }
+
+    /// Records an event in the standard event status register.
+    pub fn set_event(&mut self, event: EventStatus) {
+        self.event_status.set(event, true);
+    }
+
+    /// Returns and clears the standard event status register.
+    pub fn take_event_status(&mut self) -> EventStatus {
+        core::mem::take(&mut self.event_status)
+    }
+
+    /// Returns the standard event status enable register.
+    pub fn event_status_enable(&self) -> u8 {
+        self.event_status_enable
+    }
+
+    /// Sets the standard event status enable register, which selects the
+    /// events summarized by the ESB bit of the status byte.
+    pub fn set_event_status_enable(&mut self, enable: u8) {
+        self.event_status_enable = enable;
+    }
+
+    /// Computes the current value of the status byte.
+    pub fn status_byte(&self) -> StatusByte {
+        let mut status = StatusByte::default();
+        status.set(StatusByte::MESSAGE_AVAILABLE, self.output_pending);
+        status.set(StatusByte::ERROR_EVENT_QUEUE, self.error_available);
+        status.set(StatusByte::OPERATION_STATUS, self.operation.summary());
+        status.set(StatusByte::QUESTIONABLE_STATUS, self.questionable.summary());
+        status.set(
+            StatusByte::EVENT_STATUS,
+            self.event_status.bits() & self.event_status_enable != 0,
+        );
+        status.with_master_summary(self.status_byte_enable)
+    }
+
+    /// Returns whether the MSS bit of the status byte has been set since the
+    /// last call, i.e. whether a service request has to be raised.
+    pub fn poll_service_request(&mut self) -> bool {
+        let master_summary = self.status_byte().contains(StatusByte::MASTER_SUMMARY);
+        let rising = master_summary && !self.master_summary;
+        self.master_summary = master_summary;
+        rising
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_byte_bits() {
+        let mut status = StatusByte::default();
+        assert_eq!(status.bits(), 0);
+
+        status.set(StatusByte::MESSAGE_AVAILABLE, true);
+        assert_eq!(status.bits(), 0x10);
+        assert!(status.contains(StatusByte::MESSAGE_AVAILABLE));
+
+        status.set(StatusByte::MESSAGE_AVAILABLE, false);
+        assert_eq!(status, StatusByte::from_bits(0));
+        assert_eq!(
+            (StatusByte::ERROR_EVENT_QUEUE | StatusByte::EVENT_STATUS).bits(),
+            0x24
+        );
+    }
+
+    #[test]
+    fn test_message_available() {
+        let mut registers = StatusRegisters::new();
+        assert!(!registers
+            .status_byte()
+            .contains(StatusByte::MESSAGE_AVAILABLE));
+
+        registers.set_output_pending(true);
+        assert_eq!(registers.status_byte(), StatusByte::MESSAGE_AVAILABLE);
+
+        registers.set_output_pending(false);
+        assert_eq!(registers.status_byte().bits(), 0);
+    }
+
+    #[test]
+    fn test_master_summary() {
+        let mut registers = StatusRegisters::new();
+        registers.set_output_pending(true);
+        assert_eq!(registers.status_byte(), StatusByte::MESSAGE_AVAILABLE);
+
+        registers.set_status_byte_enable(0xff);
+        assert_eq!(registers.status_byte_enable(), 0xbf);
+        assert_eq!(
+            registers.status_byte(),
+            StatusByte::MESSAGE_AVAILABLE | StatusByte::MASTER_SUMMARY
+        );
+
+        registers.set_status_byte_enable(StatusByte::ERROR_EVENT_QUEUE.bits());
+        assert_eq!(registers.status_byte(), StatusByte::MESSAGE_AVAILABLE);
+    }
+
+    #[test]
+    fn test_event_status() {
+        let mut registers = StatusRegisters::new();
+        registers.set_event(EventStatus::COMMAND_ERROR);
+        registers.set_event(EventStatus::QUERY_ERROR);
+        assert_eq!(registers.event_status().bits(), 0x24);
+
+        // Only enabled events are summarized in the status byte.
+        assert_eq!(registers.status_byte().bits(), 0);
+        registers.set_event_status_enable(EventStatus::QUERY_ERROR.bits());
+        assert_eq!(registers.status_byte(), StatusByte::EVENT_STATUS);
+
+        assert_eq!(
+            registers.take_event_status(),
+            EventStatus::COMMAND_ERROR | EventStatus::QUERY_ERROR
+        );
+        assert_eq!(registers.event_status().bits(), 0);
+        assert_eq!(registers.status_byte().bits(), 0);
+    }
+
+    #[test]
+    fn test_event_from_error() {
+        assert_eq!(
+            EventStatus::from_error(&Error::SyntaxError),
+            EventStatus::COMMAND_ERROR
+        );
+        assert_eq!(
+            EventStatus::from_error(&Error::DataOutOfRange),
+            EventStatus::EXECUTION_ERROR
+        );
+        assert_eq!(
+            EventStatus::from_error(&Error::SelfTestFailed),
+            EventStatus::DEVICE_DEPENDENT_ERROR
+        );
+        assert_eq!(
+            EventStatus::from_error(&Error::QueryInterrupted),
+            EventStatus::QUERY_ERROR
+        );
+    }
+
+    #[test]
+    fn test_service_request() {
+        let mut registers = StatusRegisters::new();
+        registers.set_error_available(true);
+        assert_eq!(registers.status_byte(), StatusByte::ERROR_EVENT_QUEUE);
+        assert!(!registers.poll_service_request());
+
+        registers.set_status_byte_enable(StatusByte::ERROR_EVENT_QUEUE.bits());
+        assert!(registers.poll_service_request());
+        assert!(!registers.poll_service_request());
+
+        registers.set_error_available(false);
+        assert!(!registers.poll_service_request());
+        registers.set_error_available(true);
+        assert!(registers.poll_service_request());
+    }
+
+    #[test]
+    fn test_scpi_register() {
+        let mut operation = ScpiRegister16::default();
+        operation.set_condition(OperationStatus::MEASURING, true);
+        operation.set_condition(OperationStatus::MEASURING, false);
+        assert_eq!(operation.condition(), 0);
+        assert_eq!(operation.event(), OperationStatus::MEASURING);
+
+        // Bits that are already set are not latched again.
+        operation.set_condition(OperationStatus::SWEEPING, true);
+        assert_eq!(operation.take_event(), 0x18);
+        operation.set_condition(OperationStatus::SWEEPING, true);
+        assert_eq!(operation.event(), 0);
+
+        operation.set_condition(0xffff, true);
+        assert_eq!(operation.condition(), 0x7fff);
+        assert!(!operation.summary());
+        operation.set_enable(OperationStatus::CALIBRATING);
+        assert!(operation.summary());
+
+        let mut registers = StatusRegisters::new();
+        *registers.operation_mut() = operation.into();
+        assert_eq!(registers.status_byte(), StatusByte::OPERATION_STATUS);
+
+        *registers.questionable_mut() = operation.into();
+        assert_eq!(
+            registers.status_byte(),
+            StatusByte::OPERATION_STATUS | StatusByte::QUESTIONABLE_STATUS
+        );
+    }
+
+    #[test]
+    fn test_transition_filters() {
+        let mut register = ScpiRegister16::default();
+        assert_eq!(register.positive_transition(), 0x7fff);
+        assert_eq!(register.negative_transition(), 0);
+
+        // Only falling edges are latched.
+        register.set_positive_transition(0);
+        register.set_negative_transition(0xffff);
+        assert_eq!(register.negative_transition(), 0x7fff);
+        register.set_condition(0x0003, true);
+        assert_eq!(register.event(), 0);
+        register.set_condition(0x0001, false);
+        assert_eq!(register.take_event(), 0x0001);
+
+        // Simultaneous edges of several bits are latched according to the
+        // filter of each bit.
+        register.set_positive_transition(0x0004);
+        register.set_negative_transition(0x0002);
+        register.set_condition(0x0006, true);
+        assert_eq!(register.take_event(), 0x0004);
+        register.set_condition(0x0006, false);
+        assert_eq!(register.take_event(), 0x0002);
+        assert_eq!(register.condition(), 0);
+    }
+
+    #[test]
+    fn test_pending_operations() {
+        let mut registers = StatusRegisters::new();
+        let first = registers.begin_operation();
+        let second = registers.begin_operation();
+        assert_eq!(registers.pending_operations(), 2);
+
+        first.complete_on(&mut registers);
+        second.complete_on(&mut registers);
+        assert_eq!(registers.pending_operations(), 0);
+    }
+
+    #[test]
+    fn test_operation_complete() {
+        let mut registers = StatusRegisters::new();
+        registers.arm_operation_complete();
+        assert_eq!(
+            registers.take_event_status(),
+            EventStatus::OPERATION_COMPLETE
+        );
+
+        let token = registers.begin_operation();
+        registers.arm_operation_complete();
+        assert!(registers.operation_complete_armed());
+        assert_eq!(registers.take_event_status(), EventStatus::default());
+        token.complete_on(&mut registers);
+        assert!(!registers.operation_complete_armed());
+        assert_eq!(
+            registers.take_event_status(),
+            EventStatus::OPERATION_COMPLETE
+        );
+
+        let token = registers.begin_operation();
+        registers.arm_operation_complete();
+        registers.disarm_operation_complete();
+        token.complete_on(&mut registers);
+        assert_eq!(registers.take_event_status(), EventStatus::default());
+    }
+}
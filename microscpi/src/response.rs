@@ -1,5 +1,9 @@
 use core::fmt::Arguments;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use core::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+use core::time::Duration;
 
+use crate::value::Bounded;
 use crate::Error;
 
 /// SCPI characters
@@ -7,11 +11,99 @@ use crate::Error;
 /// This represents a choice or predefined value in SCPI.
 pub struct Characters<'a>(pub &'a str);
 
+/// Identification
+///
+/// The response to the `*IDN?` query as defined by IEEE 488.2, 10.14. The
+/// fields are written comma-separated as arbitrary ASCII response data, i.e.
+/// without quotes, and must not contain commas themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Identity<'a> {
+    pub manufacturer: &'a str,
+    pub model: &'a str,
+    pub serial_number: &'a str,
+    pub firmware_version: &'a str,
+}
+
 /// Arbitrary data
 ///
 /// Contains arbitrary binary data.
 pub struct Arbitrary<'a>(pub &'a [u8]);
 
+/// Arbitrary data from parts
+///
+/// Contains arbitrary binary data which is split into multiple parts, e.g. the
+/// two halves of a DMA buffer. The parts are written consecutively as a single
+/// definite length block.
+pub struct ArbitraryParts<'a>(pub &'a [&'a [u8]]);
+
+/// Response from an iterator
+///
+/// Writes the items of an iterator comma-separated directly to the response
+/// without collecting them first. The iterator is cloned for each write, so
+/// it should be cheap to clone, e.g. a range or an adapter over a slice.
+#[derive(Debug, Clone)]
+pub struct ResponseIter<I>(pub I);
+
+/// Channel list
+///
+/// Emits a list of channel numbers in the compact channel list syntax, e.g.
+/// `(@1:3,7)`. Consecutive ascending channels are collapsed into ranges. An
+/// empty list is written as `(@)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelList<'a>(pub &'a [u32]);
+
+/// Boolean as ON/OFF
+///
+/// Emits a boolean as the character data `ON` or `OFF` instead of `1` or
+/// `0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OnOff(pub bool);
+
+impl From<bool> for OnOff {
+    fn from(value: bool) -> Self {
+        OnOff(value)
+    }
+}
+
+/// Hexadecimal number
+///
+/// Emits an unsigned integer as hexadecimal numeric response data, e.g.
+/// `#HFF3A`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hex<T>(pub T);
+
+/// Binary number
+///
+/// Emits an unsigned integer as binary numeric response data, e.g. `#B1011`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bin<T>(pub T);
+
+/// Octal number
+///
+/// Emits an unsigned integer as octal numeric response data, e.g. `#Q755`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oct<T>(pub T);
+
+/// NR3 float with fixed precision
+///
+/// Emits a floating point number in NR3 notation with the specified number of
+/// significant digits, e.g. `Nr3(989.0266, 7)` is written as `9.890266E+02`.
+/// The number of significant digits is clamped to the range `1..=17`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nr3<T>(pub T, pub u8);
+
+/// NR2 float with fixed decimals
+///
+/// Emits a floating point number in NR2 notation with the specified number of
+/// digits after the decimal point, e.g. `Nr2(989.0266, 2)` is written as
+/// `989.03`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nr2<T>(pub T, pub u8);
+
+/// Response writer
+///
+/// Characters and strings are written UTF-8 encoded. Responses are usually
+/// plain ASCII, but string response data may contain any character.
 pub trait Write {
     async fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error>;
     async fn write_char(&mut self, c: char) -> Result<(), Error>;
@@ -27,7 +119,8 @@ impl<const N: usize> Write for heapless::Vec<u8, N> {
     }
 
     async fn write_char(&mut self, c: char) -> Result<(), Error> {
-        self.push(c as u8).or(Err(Error::TooMuchData))?;
+        self.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+            .or(Err(Error::TooMuchData))?;
         Ok(())
     }
 
@@ -47,7 +140,7 @@ impl<const N: usize> Write for heapless::Vec<u8, N> {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(any(test, feature = "std"))]
 impl Write for std::vec::Vec<u8> {
     async fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
         self.extend_from_slice(bytes);
@@ -55,7 +148,7 @@ impl Write for std::vec::Vec<u8> {
     }
 
     async fn write_char(&mut self, c: char) -> Result<(), Error> {
-        self.push(c as u8);
+        self.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
         Ok(())
     }
 
@@ -75,6 +168,258 @@ impl Write for std::vec::Vec<u8> {
     }
 }
 
+/// Writer into a fixed byte buffer
+///
+/// Writes the response into a preallocated byte slice, e.g. a DMA buffer.
+/// Writing more data than fits into the buffer fails with
+/// [Error::TooMuchData]. Formatting does not allocate.
+///
+/// ```
+/// use microscpi::{self as scpi, Interface, SliceWriter};
+///
+/// pub struct ExampleInterface;
+///
+/// impl scpi::ErrorHandler for ExampleInterface {
+///     fn handle_error(&mut self, _error: scpi::Error) {}
+/// }
+///
+/// #[scpi::interface]
+/// impl ExampleInterface {
+///     #[scpi(cmd = "SYSTem:VALue?")]
+///     async fn system_value(&mut self) -> Result<u64, scpi::Error> {
+///         Ok(42)
+///     }
+/// }
+///
+/// #[tokio::main]
+/// pub async fn main() {
+///     let mut buf = [0u8; 256];
+///     let mut output = SliceWriter::new(&mut buf);
+///
+///     ExampleInterface.run(b"SYST:VAL?\n", &mut output).await;
+///
+///     assert_eq!(output.written(), b"42\n");
+/// }
+/// ```
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Creates a new writer into the specified buffer.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        SliceWriter { buf, len: 0 }
+    }
+
+    /// Returns the bytes written so far.
+    pub fn written(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if nothing has been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Discards the written bytes.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let end = self
+            .len
+            .checked_add(bytes.len())
+            .ok_or(Error::TooMuchData)?;
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(Error::TooMuchData)?
+            .copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+}
+
+impl core::fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.push(s.as_bytes()).or(Err(core::fmt::Error))
+    }
+}
+
+impl Write for SliceWriter<'_> {
+    async fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.push(bytes)
+    }
+
+    async fn write_char(&mut self, c: char) -> Result<(), Error> {
+        self.push(c.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+
+    async fn write_str(&mut self, s: &str) -> Result<(), Error> {
+        self.push(s.as_bytes())
+    }
+
+    async fn write_fmt(&mut self, args: core::fmt::Arguments<'_>) -> Result<(), Error> {
+        core::fmt::Write::write_fmt(self, args).or(Err(Error::TooMuchData))
+    }
+
+    async fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Writer into a [core::fmt::Write] sink
+///
+/// Allows writing responses into any [core::fmt::Write] implementation, e.g.
+/// a `heapless::String` or a logging sink. Since such sinks only accept
+/// UTF-8, [Write::write_bytes] fails with [Error::QueryError] if the bytes are
+/// not valid UTF-8, e.g. for binary block data. Errors of the underlying sink
+/// are reported as [Error::SystemError].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FmtWriter<W>(pub W);
+
+impl<W: core::fmt::Write> FmtWriter<W> {
+    /// Returns the underlying sink.
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W: core::fmt::Write> Write for FmtWriter<W> {
+    async fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let s = core::str::from_utf8(bytes).or(Err(Error::QueryError))?;
+        self.0.write_str(s).or(Err(Error::SystemError))
+    }
+
+    async fn write_char(&mut self, c: char) -> Result<(), Error> {
+        self.0.write_char(c).or(Err(Error::SystemError))
+    }
+
+    async fn write_str(&mut self, s: &str) -> Result<(), Error> {
+        self.0.write_str(s).or(Err(Error::SystemError))
+    }
+
+    async fn write_fmt(&mut self, args: core::fmt::Arguments<'_>) -> Result<(), Error> {
+        self.0.write_fmt(args).or(Err(Error::SystemError))
+    }
+
+    async fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// [core::fmt::Write] implementation for a [Write]
+///
+/// Allows using `Display` based formatting with a [Write]. As
+/// [core::fmt::Write] is synchronous, each write is polled exactly once and
+/// fails if the writer does not complete immediately. This is the case for
+/// all in-memory writers like [SliceWriter] or `heapless::Vec`.
+pub struct WriteAsFmt<'a, W: Write>(pub &'a mut W);
+
+impl<W: Write> core::fmt::Write for WriteAsFmt<'_, W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let future = core::pin::pin!(self.0.write_str(s));
+        let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+
+        match core::future::Future::poll(future, &mut cx) {
+            core::task::Poll::Ready(Ok(())) => Ok(()),
+            _ => Err(core::fmt::Error),
+        }
+    }
+}
+
+/// Object safe synchronous writer
+///
+/// In contrast to [Write], this trait can be used as a trait object. It is
+/// used by [Interface::run_dyn](crate::Interface::run_dyn) to avoid
+/// instantiating the command dispatch code for each writer type. It is
+/// implemented for the in-memory writers of this crate and can easily be
+/// implemented for custom buffers.
+pub trait SyncWrite {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Error>;
+
+    fn flush_all(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> SyncWrite for heapless::Vec<u8, N> {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.extend_from_slice(bytes).or(Err(Error::TooMuchData))
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl SyncWrite for std::vec::Vec<u8> {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl SyncWrite for SliceWriter<'_> {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.push(bytes)
+    }
+}
+
+impl<W: core::fmt::Write> SyncWrite for FmtWriter<W> {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let s = core::str::from_utf8(bytes).or(Err(Error::QueryError))?;
+        self.0.write_str(s).or(Err(Error::SystemError))
+    }
+}
+
+/// [Write] implementation for a [SyncWrite] trait object.
+pub(crate) struct DynWriter<'a> {
+    inner: &'a mut dyn SyncWrite,
+    error: Option<Error>,
+}
+
+impl<'a> DynWriter<'a> {
+    pub(crate) fn new(inner: &'a mut dyn SyncWrite) -> Self {
+        DynWriter { inner, error: None }
+    }
+}
+
+impl core::fmt::Write for DynWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            core::fmt::Error
+        })
+    }
+}
+
+impl Write for DynWriter<'_> {
+    async fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.inner.write_all(bytes)
+    }
+
+    async fn write_char(&mut self, c: char) -> Result<(), Error> {
+        self.inner.write_all(c.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+
+    async fn write_str(&mut self, s: &str) -> Result<(), Error> {
+        self.inner.write_all(s.as_bytes())
+    }
+
+    async fn write_fmt(&mut self, args: core::fmt::Arguments<'_>) -> Result<(), Error> {
+        core::fmt::Write::write_fmt(self, args)
+            .or(Err(self.error.take().unwrap_or(Error::SystemError)))
+    }
+
+    async fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush_all()
+    }
+}
+
 pub trait Response {
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error>;
 }
@@ -88,6 +433,100 @@ impl Response for bool {
     }
 }
 
+/// Writes a number as non-decimal numeric response data with the specified
+/// prefix character and radix.
+async fn write_radix(
+    f: &mut impl Write, prefix: &str, mut value: u128, radix: u128,
+) -> Result<(), Error> {
+    // Large enough for the binary representation of a 128 bit number.
+    let mut buf = [0u8; 128];
+    let mut pos = buf.len();
+
+    loop {
+        pos -= 1;
+        buf[pos] = b"0123456789ABCDEF"[(value % radix) as usize];
+        value /= radix;
+        if value == 0 {
+            break;
+        }
+    }
+
+    f.write_str(prefix).await?;
+    f.write_bytes(&buf[pos..]).await
+}
+
+impl<T: Copy + Into<u128>> Response for Hex<T> {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write_radix(f, "#H", self.0.into(), 16).await
+    }
+}
+
+impl<T: Copy + Into<u128>> Response for Bin<T> {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write_radix(f, "#B", self.0.into(), 2).await
+    }
+}
+
+impl<T: Copy + Into<u128>> Response for Oct<T> {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write_radix(f, "#Q", self.0.into(), 8).await
+    }
+}
+
+impl Response for char {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        if self.is_ascii() {
+            f.write_char(*self).await
+        }
+        else {
+            Err(Error::QueryError)
+        }
+    }
+}
+
+impl Response for OnOff {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        match self.0 {
+            true => f.write_str("ON").await,
+            false => f.write_str("OFF").await,
+        }
+    }
+}
+
+impl Response for ChannelList<'_> {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        f.write_str("(@").await?;
+
+        let mut channels = self.0.iter().copied().peekable();
+        let mut first = true;
+
+        while let Some(start) = channels.next() {
+            let mut end = start;
+            while let Some(&next) = channels.peek() {
+                if end.checked_add(1) != Some(next) {
+                    break;
+                }
+                end = next;
+                channels.next();
+            }
+
+            if !first {
+                f.write_char(',').await?;
+            }
+            first = false;
+
+            if start == end {
+                write!(f, "{start}").await?;
+            }
+            else {
+                write!(f, "{start}:{end}").await?;
+            }
+        }
+
+        f.write_char(')').await
+    }
+}
+
 impl Response for () {
     async fn write_response(&self, _f: &mut impl Write) -> Result<(), Error> {
         Ok(())
@@ -100,28 +539,80 @@ impl Response for Characters<'_> {
     }
 }
 
-impl Response for Arbitrary<'_> {
+impl Response for Identity<'_> {
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        let len = self.0.len();
-        if len > 0 {
-            let len_digits = len.ilog10() + 1;
+        write!(
+            f,
+            "{},{},{},{}",
+            self.manufacturer, self.model, self.serial_number, self.firmware_version
+        )
+        .await
+    }
+}
 
-            if len_digits > 9 {
-                return Err(Error::TooMuchData);
-            }
+/// Writes the header of a definite length arbitrary block with the specified
+/// length.
+pub(crate) async fn write_block_header(f: &mut impl Write, len: usize) -> Result<(), Error> {
+    if len > 0 {
+        let len_digits = len.ilog10() + 1;
 
-            write!(f, "#{}{}", len_digits, len).await?;
-            f.write_bytes(self.0).await
+        if len_digits > 9 {
+            return Err(Error::TooMuchData);
         }
-        else {
-            f.write_str("#10").await
+
+        write!(f, "#{}{}", len_digits, len).await
+    }
+    else {
+        f.write_str("#10").await
+    }
+}
+
+impl Response for Arbitrary<'_> {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write_block_header(f, self.0.len()).await?;
+        f.write_bytes(self.0).await
+    }
+}
+
+impl Response for ArbitraryParts<'_> {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        let len = self
+            .0
+            .iter()
+            .try_fold(0usize, |len, part| len.checked_add(part.len()))
+            .ok_or(Error::TooMuchData)?;
+
+        write_block_header(f, len).await?;
+        for part in self.0 {
+            f.write_bytes(part).await?;
         }
+        Ok(())
     }
 }
 
+/// Writes string response data enclosed in double quotes.
+///
+/// Embedded double quotes are doubled as required by IEEE 488.2, 8.7.8.
+async fn write_quoted(f: &mut impl Write, s: &str) -> Result<(), Error> {
+    f.write_char('"').await?;
+    write_escaped(f, s).await?;
+    f.write_char('"').await
+}
+
+/// Writes string response data with embedded double quotes doubled.
+async fn write_escaped(f: &mut impl Write, s: &str) -> Result<(), Error> {
+    for (i, part) in s.split('"').enumerate() {
+        if i > 0 {
+            f.write_str("\"\"").await?;
+        }
+        f.write_str(part).await?;
+    }
+    Ok(())
+}
+
 impl Response for &str {
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        write!(f, "\"{self}\"").await
+        write_quoted(f, self).await
     }
 }
 
@@ -185,6 +676,69 @@ impl Response for usize {
     }
 }
 
+impl Response for i128 {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "{self}").await
+    }
+}
+
+impl Response for u128 {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "{self}").await
+    }
+}
+
+impl Response for NonZeroU8 {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "{self}").await
+    }
+}
+
+impl Response for NonZeroU16 {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "{self}").await
+    }
+}
+
+impl Response for NonZeroU32 {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "{self}").await
+    }
+}
+
+impl Response for NonZeroU64 {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "{self}").await
+    }
+}
+
+/// Writes a finite float in its shortest representation.
+#[cfg(not(feature = "fast-float-format"))]
+async fn write_float(f: &mut impl Write, value: impl core::fmt::Display) -> Result<(), Error> {
+    write!(f, "{value}").await
+}
+
+/// Writes a finite float in its shortest representation.
+///
+/// Uses the Ryū algorithm instead of `core::fmt`. Values with a large or small
+/// magnitude are written in exponential notation, e.g. `1E-40`.
+#[cfg(feature = "fast-float-format")]
+async fn write_float(f: &mut impl Write, value: impl ryu::Float) -> Result<(), Error> {
+    let mut buf = ryu::Buffer::new();
+    let s = buf.format_finite(value);
+    // Integral values are written without a fractional part, like `core::fmt`.
+    let s = s.strip_suffix(".0").unwrap_or(s);
+
+    match s.split_once('e') {
+        Some((mantissa, exponent)) => {
+            f.write_str(mantissa).await?;
+            f.write_char('E').await?;
+            f.write_str(exponent).await
+        }
+        None => f.write_str(s).await,
+    }
+}
+
 impl Response for f32 {
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
         if self.is_nan() {
@@ -199,7 +753,7 @@ impl Response for f32 {
             }
         }
         else {
-            write!(f, "{self}").await
+            write_float(f, *self).await
         }
     }
 }
@@ -218,14 +772,97 @@ impl Response for f64 {
             }
         }
         else {
-            write!(f, "{self}").await
+            write_float(f, *self).await
+        }
+    }
+}
+
+macro_rules! impl_response_nr {
+    ($type:ty) => {
+        impl Response for Nr3<$type> {
+            async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+                let Nr3(value, digits) = *self;
+
+                if !value.is_finite() {
+                    return value.write_response(f).await;
+                }
+
+                let precision = digits.clamp(1, 17) as usize - 1;
+                // Normalize negative zero, so it is not emitted with a sign.
+                let value = if value == 0.0 { 0.0 } else { value };
+
+                let mut buf: heapless::String<32> = heapless::String::new();
+                core::fmt::Write::write_fmt(&mut buf, format_args!("{value:.precision$E}"))?;
+
+                let (mantissa, exponent) = buf.split_once('E').ok_or(Error::SystemError)?;
+                let exponent: i16 = exponent.parse().or(Err(Error::SystemError))?;
+                let sign = if exponent < 0 { '-' } else { '+' };
+
+                write!(f, "{mantissa}E{sign}{:02}", exponent.unsigned_abs()).await
+            }
         }
+
+        impl Response for Nr2<$type> {
+            async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+                let Nr2(value, decimals) = *self;
+
+                if !value.is_finite() {
+                    return value.write_response(f).await;
+                }
+
+                let precision = decimals as usize;
+                // Normalize negative zero, so it is not emitted with a sign.
+                let value = if value == 0.0 { 0.0 } else { value };
+
+                write!(f, "{value:.precision$}").await
+            }
+        }
+    };
+}
+
+impl_response_nr!(f32);
+impl_response_nr!(f64);
+
+impl<T: Response, const MIN: i64, const MAX: i64> Response for Bounded<T, MIN, MAX> {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        (**self).write_response(f).await
+    }
+}
+
+impl Response for Duration {
+    /// Writes the duration in seconds as NR3 number.
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "{:E}", self.as_secs_f64()).await
+    }
+}
+
+impl Response for Ipv4Addr {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "\"{self}\"").await
+    }
+}
+
+impl Response for Ipv6Addr {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "\"{self}\"").await
+    }
+}
+
+impl Response for IpAddr {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "\"{self}\"").await
+    }
+}
+
+impl Response for SocketAddr {
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        write!(f, "\"{self}\"").await
     }
 }
 
 impl<const N: usize> Response for heapless::String<N> {
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        write!(f, "\"{}\"", self.as_str()).await
+        write_quoted(f, self.as_str()).await
     }
 }
 
@@ -241,66 +878,82 @@ impl<const N: usize, T: Response> Response for heapless::Vec<T, N> {
     }
 }
 
-#[cfg(feature = "std")]
+#[cfg(any(test, feature = "std"))]
 impl Response for std::string::String {
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        write!(f, "\"{}\"", self.as_str()).await
+        write_quoted(f, self.as_str()).await
     }
 }
 
 impl Response for crate::Error {
+    /// Writes the error as `<code>,"<description>"`, or as
+    /// `<code>,"<description>;<info>"` if it carries device-dependent info.
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        let result: (i16, &str) = (self.number(), (*self).into());
-        result.write_response(f).await
+        write!(f, "{},\"", self.number()).await?;
+        write_escaped(f, (*self).into()).await?;
+        if let Some(info) = self.info() {
+            f.write_char(';').await?;
+            write_escaped(f, info).await?;
+        }
+        f.write_char('"').await
     }
 }
 
-impl<A, B> Response for (A, B)
-where
-    A: Response,
-    B: Response,
-{
+impl Response for Option<crate::Error> {
+    /// Writes the error, or `0,""` if there is no error.
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        self.0.write_response(f).await?;
-        f.write_char(',').await?;
-        self.1.write_response(f).await
+        match self {
+            Some(error) => error.write_response(f).await,
+            None => f.write_str("0,\"\"").await,
+        }
     }
 }
 
-impl<A, B, C> Response for (A, B, C)
-where
-    A: Response,
-    B: Response,
-    C: Response,
-{
-    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        self.0.write_response(f).await?;
-        f.write_char(',').await?;
-        self.1.write_response(f).await?;
-        f.write_char(',').await?;
-        self.2.write_response(f).await
-    }
+macro_rules! impl_response_tuple {
+    ($first:ident $(, $name:ident)*) => {
+        impl<$first: Response, $($name: Response),*> Response for ($first, $($name),*) {
+            #[allow(non_snake_case)]
+            async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+                let ($first, $($name),*) = self;
+                $first.write_response(f).await?;
+                $(
+                    f.write_char(',').await?;
+                    $name.write_response(f).await?;
+                )*
+                Ok(())
+            }
+        }
+    };
 }
 
-impl<A, B, C, D> Response for (A, B, C, D)
+impl_response_tuple!(A, B);
+impl_response_tuple!(A, B, C);
+impl_response_tuple!(A, B, C, D);
+impl_response_tuple!(A, B, C, D, E);
+impl_response_tuple!(A, B, C, D, E, F);
+impl_response_tuple!(A, B, C, D, E, F, G);
+impl_response_tuple!(A, B, C, D, E, F, G, H);
+impl_response_tuple!(A, B, C, D, E, F, G, H, I);
+impl_response_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_response_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_response_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+impl<T> Response for [T]
 where
-    A: Response,
-    B: Response,
-    C: Response,
-    D: Response,
+    T: Response,
 {
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        self.0.write_response(f).await?;
-        f.write_char(',').await?;
-        self.1.write_response(f).await?;
-        f.write_char(',').await?;
-        self.2.write_response(f).await?;
-        f.write_char(',').await?;
-        self.3.write_response(f).await
+        for (i, item) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',').await?;
+            }
+            item.write_response(f).await?;
+        }
+        Ok(())
     }
 }
 
-impl<T> Response for [T]
+impl<T> Response for &[T]
 where
     T: Response,
 {
@@ -315,12 +968,22 @@ where
     }
 }
 
-impl<T> Response for &[T]
+impl<T, const N: usize> Response for [T; N]
 where
     T: Response,
 {
     async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
-        for (i, item) in self.iter().enumerate() {
+        self.as_slice().write_response(f).await
+    }
+}
+
+impl<I> Response for ResponseIter<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Response,
+{
+    async fn write_response(&self, f: &mut impl Write) -> Result<(), Error> {
+        for (i, item) in self.0.clone().into_iter().enumerate() {
             if i > 0 {
                 f.write_char(',').await?;
             }
@@ -345,6 +1008,20 @@ mod tests {
         assert_eq!(buffer, b"0");
     }
 
+    #[tokio::test]
+    async fn test_on_off_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        OnOff(true).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"ON");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        OnOff::from(false)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"OFF");
+    }
+
     #[tokio::test]
     async fn test_label_response() {
         let mut buffer: Vec<u8> = Vec::new();
@@ -355,6 +1032,21 @@ mod tests {
         assert_eq!(buffer, b"TEST");
     }
 
+    #[tokio::test]
+    async fn test_identity_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        Identity {
+            manufacturer: "ACME",
+            model: "PSU",
+            serial_number: "0",
+            firmware_version: "1.0",
+        }
+        .write_response(&mut buffer)
+        .await
+        .unwrap();
+        assert_eq!(buffer, b"ACME,PSU,0,1.0");
+    }
+
     #[tokio::test]
     async fn test_str_response() {
         let mut buffer: Vec<u8> = Vec::new();
@@ -362,52 +1054,86 @@ mod tests {
         assert_eq!(buffer, b"\"hello\"");
     }
 
+    #[tokio::test]
+    async fn test_str_response_escaping() {
+        let mut buffer: Vec<u8> = Vec::new();
+        "say \"hi\"".write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"\"say \"\"hi\"\"\"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        "\"".write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"\"\"\"\"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        "\"\"\"".write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"\"\"\"\"\"\"\"\"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        "\"quoted\"".write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"\"\"\"quoted\"\"\"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        String::from("a\"b")
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"\"a\"\"b\"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        heapless::String::<8>::try_from("\"x\"")
+            .unwrap()
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"\"\"\"x\"\"\"");
+    }
+
     #[tokio::test]
     async fn test_i8_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (-121 as i8).write_response(&mut buffer).await.unwrap();
+        (-121_i8).write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"-121");
     }
 
     #[tokio::test]
     async fn test_u8_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (83 as u8).write_response(&mut buffer).await.unwrap();
+        83_u8.write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"83");
     }
 
     #[tokio::test]
     async fn test_i16_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (-23502 as i16).write_response(&mut buffer).await.unwrap();
+        (-23502_i16).write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"-23502");
     }
 
     #[tokio::test]
     async fn test_u16_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (54968 as u16).write_response(&mut buffer).await.unwrap();
+        54968_u16.write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"54968");
     }
 
     #[tokio::test]
     async fn test_i32_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (-3895783 as i32).write_response(&mut buffer).await.unwrap();
+        (-3895783_i32).write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"-3895783");
     }
 
     #[tokio::test]
     async fn test_u32_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (9437838 as u32).write_response(&mut buffer).await.unwrap();
+        9437838_u32.write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"9437838");
     }
 
     #[tokio::test]
     async fn test_i64_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (-128945978592 as i64)
+        (-128945978592_i64)
             .write_response(&mut buffer)
             .await
             .unwrap();
@@ -417,7 +1143,7 @@ mod tests {
     #[tokio::test]
     async fn test_u64_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (39048530499456 as u64)
+        39048530499456_u64
             .write_response(&mut buffer)
             .await
             .unwrap();
@@ -427,27 +1153,46 @@ mod tests {
     #[tokio::test]
     async fn test_isize_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (-3451512 as isize)
-            .write_response(&mut buffer)
-            .await
-            .unwrap();
+        (-3451512_isize).write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"-3451512");
     }
 
     #[tokio::test]
     async fn test_usize_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (49684793 as usize)
+        49684793_usize.write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"49684793");
+    }
+
+    #[tokio::test]
+    async fn test_i128_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        i128::MIN.write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"-170141183460469231731687303715884105728");
+    }
+
+    #[tokio::test]
+    async fn test_u128_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        u128::MAX.write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"340282366920938463463374607431768211455");
+    }
+
+    #[tokio::test]
+    async fn test_non_zero_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        NonZeroU32::new(9437838)
+            .unwrap()
             .write_response(&mut buffer)
             .await
             .unwrap();
-        assert_eq!(buffer, b"49684793");
+        assert_eq!(buffer, b"9437838");
     }
 
     #[tokio::test]
     async fn test_f32_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (1.23 as f32).write_response(&mut buffer).await.unwrap();
+        1.23_f32.write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"1.23");
 
         let mut buffer: Vec<u8> = Vec::new();
@@ -466,10 +1211,58 @@ mod tests {
         assert_eq!(buffer, b"-9.9E+37");
     }
 
+    #[tokio::test]
+    async fn test_float_formatting() {
+        let cases = [
+            (format(0.1f32).await, "0.1", "0.1"),
+            (format(-2.5f32).await, "-2.5", "-2.5"),
+            (format(3.0f32).await, "3", "3"),
+            (format(-0.0f32).await, "-0", "-0"),
+            (
+                format(1e-40f32).await,
+                "0.0000000000000000000000000000000000000001",
+                "1E-40",
+            ),
+            (
+                format(f32::MIN_POSITIVE).await,
+                "0.000000000000000000000000000000000000011754944",
+                "1.1754944E-38",
+            ),
+            (
+                format(f32::MAX).await,
+                "340282350000000000000000000000000000000",
+                "3.4028235E38",
+            ),
+            (format(0.1f64).await, "0.1", "0.1"),
+            (
+                format(1e30f64).await,
+                "1000000000000000000000000000000",
+                "1E30",
+            ),
+        ];
+
+        for (output, expected, expected_fast) in cases {
+            if cfg!(feature = "fast-float-format") {
+                assert_eq!(output, expected_fast);
+            }
+            else {
+                assert_eq!(output, expected);
+            }
+        }
+
+        // The output parses back to the same value.
+        for value in [0.1f32, 1e-40, f32::MIN_POSITIVE, f32::MAX, -1.0e-7] {
+            assert_eq!(format(value).await.parse::<f32>(), Ok(value));
+        }
+        for value in [0.1f64, 1e-300, f64::MIN_POSITIVE, f64::MAX, 123456.789] {
+            assert_eq!(format(value).await.parse::<f64>(), Ok(value));
+        }
+    }
+
     #[tokio::test]
     async fn test_f64_response() {
         let mut buffer: Vec<u8> = Vec::new();
-        (4.56 as f64).write_response(&mut buffer).await.unwrap();
+        4.56_f64.write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"4.56");
 
         let mut buffer: Vec<u8> = Vec::new();
@@ -488,6 +1281,47 @@ mod tests {
         assert_eq!(buffer, b"-9.9E+37");
     }
 
+    #[tokio::test]
+    async fn test_duration_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        Duration::from_micros(1500)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"1.5E-3");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Duration::from_secs(120)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"1.2E2");
+    }
+
+    #[tokio::test]
+    async fn test_ip_addr_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        Ipv4Addr::new(192, 168, 1, 10)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"\"192.168.1.10\"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        IpAddr::V6(Ipv6Addr::LOCALHOST)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"\"::1\"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        SocketAddr::new(Ipv6Addr::LOCALHOST.into(), 5025)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"\"[::1]:5025\"");
+    }
+
     #[tokio::test]
     async fn test_arbitrary_response() {
         let mut buffer: Vec<u8> = Vec::new();
@@ -509,6 +1343,35 @@ mod tests {
         assert_eq!(buffer, b"#10");
     }
 
+    #[tokio::test]
+    async fn test_arbitrary_parts_response() {
+        let data = b"\xb7\x54\x5d\xc8\x60\x10\xa5\x13\x33\x3c\xd0";
+
+        let mut single: Vec<u8> = Vec::new();
+        Arbitrary(data).write_response(&mut single).await.unwrap();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        ArbitraryParts(&[&data[..4], &data[4..]])
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, single);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        ArbitraryParts(&[])
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"#10");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        ArbitraryParts(&[&[], &[]])
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"#10");
+    }
+
     #[tokio::test]
     async fn test_tuple_response() {
         let mut buffer: Vec<u8> = Vec::new();
@@ -516,6 +1379,31 @@ mod tests {
         assert_eq!(buffer, b"123,\"world\"");
     }
 
+    #[tokio::test]
+    async fn test_long_tuple_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        (
+            "ACME",
+            Characters("MODEL"),
+            1.5,
+            2u8,
+            Characters("ON"),
+            "fw",
+            -3i32,
+        )
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"\"ACME\",MODEL,1.5,2,ON,\"fw\",-3");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"1,2,3,4,5,6,7,8,9,10,11,12");
+    }
+
     #[tokio::test]
     async fn test_slice_response() {
         let mut buffer: Vec<u8> = Vec::new();
@@ -552,4 +1440,294 @@ mod tests {
         test.write_response(&mut buffer).await.unwrap();
         assert_eq!(buffer, b"\"Hello World\"");
     }
+
+    #[tokio::test]
+    async fn test_char_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        'A'.write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"A");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        assert_eq!(
+            'µ'.write_response(&mut buffer).await,
+            Err(Error::QueryError)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_array_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        ['A', 'B'].write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"A,B");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let empty: [u8; 0] = [];
+        empty.write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        [[1, 2], [3, 4]].write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"1,2,3,4");
+    }
+
+    #[tokio::test]
+    async fn test_hex_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        Hex(0u8).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#H0");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Hex(u8::MAX).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#HFF");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Hex(0xff3au16).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#HFF3A");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Hex(0x00c0ffeeu32)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"#HC0FFEE");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Hex(u64::MAX).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#HFFFFFFFFFFFFFFFF");
+    }
+
+    #[tokio::test]
+    async fn test_bin_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        Bin(0u8).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#B0");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Bin(u8::MAX).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#B11111111");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Bin(0b1011u16).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#B1011");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Bin(u32::MAX).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#B11111111111111111111111111111111");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Bin(u64::MAX).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer.len(), 66);
+        assert!(buffer[2..].iter().all(|b| *b == b'1'));
+    }
+
+    #[tokio::test]
+    async fn test_oct_response() {
+        let mut buffer: Vec<u8> = Vec::new();
+        Oct(0u8).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#Q0");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Oct(u8::MAX).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#Q377");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Oct(u16::MAX).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#Q177777");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Oct(0o755u32).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#Q755");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        Oct(u64::MAX).write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, b"#Q1777777777777777777777");
+    }
+
+    async fn format(value: impl Response) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        value.write_response(&mut buffer).await.unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_nr3_response() {
+        assert_eq!(format(Nr3(989.0266f64, 7)).await, "9.890266E+02");
+        assert_eq!(format(Nr3(989.0266f32, 3)).await, "9.89E+02");
+        assert_eq!(format(Nr3(-989.0266f64, 4)).await, "-9.890E+02");
+        assert_eq!(format(Nr3(0.00123f64, 3)).await, "1.23E-03");
+        assert_eq!(format(Nr3(1.0f64, 1)).await, "1E+00");
+        assert_eq!(format(Nr3(1.0f64, 0)).await, "1E+00");
+        assert_eq!(format(Nr3(9.9996f64, 4)).await, "1.000E+01");
+        assert_eq!(format(Nr3(1.5e300f64, 2)).await, "1.5E+300");
+        assert_eq!(format(Nr3(0.0f64, 3)).await, "0.00E+00");
+        assert_eq!(format(Nr3(-0.0f64, 3)).await, "0.00E+00");
+        assert_eq!(format(Nr3(5e-324f64, 3)).await, "4.94E-324");
+        assert_eq!(format(Nr3(1e-40f32, 2)).await, "1.0E-40");
+        assert_eq!(format(Nr3(f64::NAN, 3)).await, "9.91E+37");
+        assert_eq!(format(Nr3(f32::INFINITY, 3)).await, "9.9E+37");
+        assert_eq!(format(Nr3(f64::NEG_INFINITY, 3)).await, "-9.9E+37");
+    }
+
+    #[tokio::test]
+    async fn test_nr2_response() {
+        assert_eq!(format(Nr2(989.0266f64, 2)).await, "989.03");
+        assert_eq!(format(Nr2(-989.0266f32, 1)).await, "-989.0");
+        assert_eq!(format(Nr2(1.5f64, 0)).await, "2");
+        assert_eq!(format(Nr2(0.0f64, 3)).await, "0.000");
+        assert_eq!(format(Nr2(-0.0f64, 3)).await, "0.000");
+        assert_eq!(format(Nr2(f32::NAN, 2)).await, "9.91E+37");
+        assert_eq!(format(Nr2(f64::NEG_INFINITY, 2)).await, "-9.9E+37");
+    }
+
+    #[tokio::test]
+    async fn test_response_iter() {
+        let mut buffer: Vec<u8> = Vec::new();
+        ResponseIter(0..10000u16)
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+
+        let mut count = 0;
+        for (i, item) in buffer.split(|b| *b == b',').enumerate() {
+            assert_eq!(item, i.to_string().as_bytes());
+            count += 1;
+        }
+        assert_eq!(count, 10000);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        ResponseIter(core::iter::empty::<u16>())
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"");
+
+        let mut buffer: Vec<u8> = Vec::new();
+        ResponseIter([1.5f32, 2.5].iter().map(|v| v * 2.0))
+            .write_response(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(buffer, b"3,5");
+    }
+
+    #[tokio::test]
+    async fn test_response_iter_error() {
+        let mut buffer: heapless::Vec<u8, 8> = heapless::Vec::new();
+        assert!(ResponseIter(0..100u16)
+            .write_response(&mut buffer)
+            .await
+            .is_err());
+        assert_eq!(buffer, b"0,1,2,3,");
+    }
+
+    #[tokio::test]
+    async fn test_channel_list_response() {
+        assert_eq!(format(ChannelList(&[1, 2, 3, 7])).await, "(@1:3,7)");
+        assert_eq!(format(ChannelList(&[5])).await, "(@5)");
+        assert_eq!(format(ChannelList(&[4, 2, 3])).await, "(@4,2:3)");
+        assert_eq!(format(ChannelList(&[])).await, "(@)");
+    }
+
+    #[tokio::test]
+    async fn test_slice_writer() {
+        let mut buf = [0u8; 8];
+        let mut writer = SliceWriter::new(&mut buf);
+        assert!(writer.is_empty());
+
+        writer.write_str("AB").await.unwrap();
+        writer.write_char(',').await.unwrap();
+        write!(writer, "{}", 42).await.unwrap();
+        assert_eq!(writer.written(), b"AB,42");
+
+        assert_eq!(writer.write_str("XYZW").await, Err(Error::TooMuchData));
+        assert_eq!(writer.written(), b"AB,42");
+
+        assert_eq!(write!(writer, "{}", 1234).await, Err(Error::TooMuchData));
+
+        writer.clear();
+        1.5f32.write_response(&mut writer).await.unwrap();
+        assert_eq!(writer.written(), b"1.5");
+    }
+
+    #[tokio::test]
+    async fn test_fmt_writer() {
+        let mut writer = FmtWriter(heapless::String::<8>::new());
+        writer.write_str("AB").await.unwrap();
+        writer.write_bytes(b"CD").await.unwrap();
+        write!(writer, "{}", 12).await.unwrap();
+        assert_eq!(writer.0, "ABCD12");
+
+        assert_eq!(writer.write_bytes(&[0xff]).await, Err(Error::QueryError));
+        assert_eq!(writer.write_str("XYZ").await, Err(Error::SystemError));
+    }
+
+    #[test]
+    fn test_write_as_fmt() {
+        let mut buffer: heapless::Vec<u8, 8> = heapless::Vec::new();
+        core::fmt::Write::write_fmt(&mut WriteAsFmt(&mut buffer), format_args!("{}-{}", 1, 2))
+            .unwrap();
+        assert_eq!(buffer, b"1-2");
+
+        let result =
+            core::fmt::Write::write_fmt(&mut WriteAsFmt(&mut buffer), format_args!("{}", 123456));
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dyn_writer() {
+        let mut buffer: heapless::Vec<u8, 4> = heapless::Vec::new();
+        {
+            let mut writer = DynWriter::new(&mut buffer);
+            writer.write_str("A").await.unwrap();
+            write!(writer, "{}", 12).await.unwrap();
+            assert_eq!(write!(writer, "{}", 345).await, Err(Error::TooMuchData));
+        }
+
+        assert_eq!(buffer, b"A12");
+    }
+
+    #[tokio::test]
+    async fn test_write_non_ascii_char() {
+        let mut buffer: Vec<u8> = Vec::new();
+        buffer.write_char('µ').await.unwrap();
+        buffer.write_char('€').await.unwrap();
+        assert_eq!(buffer, "µ€".as_bytes());
+
+        let mut buffer: heapless::Vec<u8, 5> = heapless::Vec::new();
+        buffer.write_char('µ').await.unwrap();
+        buffer.write_char('€').await.unwrap();
+        assert_eq!(buffer, "µ€".as_bytes());
+        assert_eq!(buffer.write_char('µ').await, Err(Error::TooMuchData));
+        assert_eq!(buffer, "µ€".as_bytes());
+
+        assert_eq!(format("25 µs, 3 €").await, "\"25 µs, 3 €\"");
+
+        let mut buffer: heapless::Vec<u8, 16> = heapless::Vec::new();
+        "°C".write_response(&mut buffer).await.unwrap();
+        assert_eq!(buffer, "\"°C\"".as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_error_response() {
+        assert_eq!(
+            format(Error::UndefinedHeader).await,
+            "-113,\"Undefined header\""
+        );
+        assert_eq!(
+            format(Error::Custom(373, "Oven cold")).await,
+            "373,\"Oven cold\""
+        );
+        assert_eq!(
+            format(Error::Custom(-300, "Lid \"A\" open")).await,
+            "-300,\"Lid \"\"A\"\" open\""
+        );
+        assert_eq!(
+            format(Error::DataOutOfRange.with_info("clipped to 10V")).await,
+            "-222,\"Data out of range;clipped to 10V\""
+        );
+        assert_eq!(
+            format(Error::Custom(-300, "Lid open").with_info("lid \"A\"")).await,
+            "-300,\"Lid open;lid \"\"A\"\"\""
+        );
+        assert_eq!(format(None::<Error>).await, "0,\"\"");
+    }
 }
@@ -0,0 +1,336 @@
+//! This module contains a wrapper to share an interface between several
+//! transports.
+
+use core::cell::RefCell;
+use core::ops::{Deref, DerefMut};
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::blocking_mutex::Mutex as BlockingMutex;
+use embassy_sync::mutex::{Mutex, MutexGuard};
+
+use crate::interface::MessageState;
+use crate::{CommandId, Error, ErrorHandler, Interface, RunReport, Value};
+
+/// The maximum number of errors and commands deferred while the interface is
+/// locked.
+const DEFERRED_CAPACITY: usize = 8;
+
+/// An error or command that is passed to the interface once it is unlocked.
+#[derive(Debug, Clone, Copy)]
+enum Operation {
+    Error(Error),
+    RecordError(Error),
+    RecordCommand(CommandId),
+}
+
+/// The operations on the interface that are applied once it is unlocked.
+///
+/// Changes of the state are coalesced, so only the errors and commands are
+/// limited by [DEFERRED_CAPACITY]. If more of them are deferred, a
+/// [Error::QueueOverflow] is reported instead of the excess ones.
+#[derive(Debug, Default)]
+struct Deferred {
+    operations: heapless::Vec<Operation, DEFERRED_CAPACITY>,
+    overflow: bool,
+    device_clear: bool,
+    output_pending: Option<bool>,
+    update_status: bool,
+}
+
+impl Deferred {
+    fn push(&mut self, operation: Operation) {
+        if self.operations.push(operation).is_err() {
+            self.overflow = true;
+        }
+    }
+
+    fn apply(self, interface: &mut impl Interface) {
+        for operation in self.operations {
+            match operation {
+                Operation::Error(error) => interface.handle_error(error),
+                Operation::RecordError(error) => interface.record_error(error),
+                Operation::RecordCommand(command_id) => interface.record_command(command_id),
+            }
+        }
+        if self.overflow {
+            interface.handle_error(Error::QueueOverflow);
+            interface.record_error(Error::QueueOverflow);
+        }
+        if self.device_clear {
+            interface.on_device_clear();
+        }
+        if let Some(pending) = self.output_pending {
+            interface.set_output_pending(pending);
+        }
+        if self.update_status {
+            interface.update_status();
+        }
+    }
+}
+
+/// Shared Interface
+///
+/// Shares an interface between several transports, e.g. a UART and a TCP
+/// connection, which are processed concurrently on a single-threaded executor.
+/// Each transport processes the interface through its own
+/// [SharedHandle]. A handle locks the interface for the execution of a
+/// complete program message, so the commands of different transports are
+/// never interleaved within a message.
+///
+/// The interface is protected by an [embassy_sync] mutex, so `M` is a
+/// [RawMutex] that suits the executor, e.g.
+/// [NoopRawMutex](embassy_sync::blocking_mutex::raw::NoopRawMutex) for
+/// transports processed on the same executor, or
+/// [CriticalSectionRawMutex](embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex)
+/// otherwise. A handle waiting for the lock is woken once the interface is
+/// unlocked. The `embassy-sync` feature enables the shared interface.
+///
+/// Errors and status changes that occur outside of a program message while
+/// the interface is locked, e.g. an input buffer overrun, are applied when
+/// the interface is unlocked. Up to eight errors and commands are deferred,
+/// further ones are reported as a single [Error::QueueOverflow].
+///
+/// ```
+/// use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+/// use microscpi::testing::MockAdapter;
+/// use microscpi::{self as scpi, Interface, SharedInterface};
+///
+/// #[derive(Default, scpi::IgnoreErrors)]
+/// pub struct ExampleInterface {
+///     value: u32,
+/// }
+///
+/// #[scpi::interface]
+/// impl ExampleInterface {
+///     #[scpi(cmd = "VALue")]
+///     fn set_value(&mut self, value: u32) -> Result<(), scpi::Error> {
+///         self.value = value;
+///         Ok(())
+///     }
+///
+///     #[scpi(cmd = "VALue?")]
+///     fn value(&mut self) -> Result<u32, scpi::Error> {
+///         Ok(self.value)
+///     }
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let shared = SharedInterface::<NoopRawMutex, _>::new(ExampleInterface::default());
+/// let mut uart = MockAdapter::new([b"VAL 42\n"]);
+/// let mut tcp = MockAdapter::new([b"VAL?\n"]).pending_reads(true);
+///
+/// let (mut uart_handle, mut tcp_handle) = (shared.handle(), shared.handle());
+/// let (uart_result, tcp_result) = tokio::join!(
+///     uart_handle.process::<64, _>(&mut uart),
+///     tcp_handle.process::<64, _>(&mut tcp),
+/// );
+/// assert!(uart_result.is_ok() && tcp_result.is_ok());
+/// assert_eq!(tcp.output(), b"42\n");
+/// assert_eq!(shared.into_inner().value, 42);
+/// # }
+/// ```
+pub struct SharedInterface<M: RawMutex, T> {
+    interface: Mutex<M, T>,
+    deferred: BlockingMutex<M, RefCell<Deferred>>,
+}
+
+impl<M: RawMutex, T: Interface> SharedInterface<M, T> {
+    pub fn new(interface: T) -> Self {
+        SharedInterface {
+            interface: Mutex::new(interface),
+            deferred: BlockingMutex::new(RefCell::new(Deferred::default())),
+        }
+    }
+
+    /// Returns a handle to process the interface on a transport.
+    pub fn handle(&self) -> SharedHandle<'_, M, T> {
+        SharedHandle { shared: self }
+    }
+
+    /// Waits until the interface is unlocked and locks it.
+    pub async fn lock(&self) -> SharedGuard<'_, M, T> {
+        self.guard(self.interface.lock().await)
+    }
+
+    /// Locks the interface if it is unlocked.
+    pub fn try_lock(&self) -> Option<SharedGuard<'_, M, T>> {
+        self.interface
+            .try_lock()
+            .ok()
+            .map(|interface| self.guard(interface))
+    }
+
+    /// Returns the interface, with the deferred operations applied.
+    pub fn into_inner(self) -> T {
+        let mut interface = self.interface.into_inner();
+        self.deferred
+            .into_inner()
+            .into_inner()
+            .apply(&mut interface);
+        interface
+    }
+
+    fn guard<'a>(&'a self, mut interface: MutexGuard<'a, M, T>) -> SharedGuard<'a, M, T> {
+        self.take_deferred().apply(&mut *interface);
+        SharedGuard {
+            shared: self,
+            interface,
+        }
+    }
+
+    fn take_deferred(&self) -> Deferred {
+        self.deferred.lock(|deferred| deferred.take())
+    }
+
+    /// Applies an operation to the interface, or defers it if the interface is
+    /// locked.
+    fn apply(&self, apply: impl FnOnce(&mut T), defer: impl FnOnce(&mut Deferred)) {
+        if let Some(mut guard) = self.try_lock() {
+            apply(&mut guard);
+        }
+        else {
+            self.deferred
+                .lock(|deferred| defer(&mut deferred.borrow_mut()));
+        }
+    }
+}
+
+/// Exclusive access to a [SharedInterface], which is unlocked when dropped.
+///
+/// The operations deferred while the interface was locked are applied before
+/// it is unlocked.
+pub struct SharedGuard<'a, M: RawMutex, T: Interface> {
+    shared: &'a SharedInterface<M, T>,
+    interface: MutexGuard<'a, M, T>,
+}
+
+impl<M: RawMutex, T: Interface> Deref for SharedGuard<'_, M, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.interface
+    }
+}
+
+impl<M: RawMutex, T: Interface> DerefMut for SharedGuard<'_, M, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.interface
+    }
+}
+
+impl<M: RawMutex, T: Interface> Drop for SharedGuard<'_, M, T> {
+    fn drop(&mut self) {
+        self.shared.take_deferred().apply(&mut *self.interface);
+    }
+}
+
+/// A handle to process a [SharedInterface] on a transport.
+pub struct SharedHandle<'a, M: RawMutex, T> {
+    shared: &'a SharedInterface<M, T>,
+}
+
+impl<M: RawMutex, T> Clone for SharedHandle<'_, M, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M: RawMutex, T> Copy for SharedHandle<'_, M, T> {}
+
+impl<M: RawMutex, T: Interface> ErrorHandler for SharedHandle<'_, M, T> {
+    fn handle_error(&mut self, error: Error) {
+        self.shared.apply(
+            |interface| interface.handle_error(error),
+            |deferred| deferred.push(Operation::Error(error)),
+        );
+    }
+}
+
+impl<M: RawMutex, T: Interface> Interface for SharedHandle<'_, M, T> {
+    type Context = T::Context;
+
+    const COMMAND_COUNT: CommandId = T::COMMAND_COUNT;
+    const RESET_COMMAND: Option<CommandId> = T::RESET_COMMAND;
+    const ROOT_NODE: &'static crate::Node = T::ROOT_NODE;
+
+    async fn execute_command<'a>(
+        &'a mut self, context: &mut Self::Context, command_id: CommandId, args: &[Value<'a>],
+        response: &mut impl crate::Write,
+    ) -> Result<(), Error> {
+        let mut guard = self.shared.lock().await;
+        guard
+            .execute_command(context, command_id, args, response)
+            .await
+    }
+
+    async fn run_report_with_context<'a>(
+        &mut self, context: &mut Self::Context, input: &'a [u8], response: &mut impl crate::Write,
+    ) -> RunReport<'a> {
+        let mut guard = self.shared.lock().await;
+        guard
+            .run_report_with_context(context, input, response)
+            .await
+    }
+
+    async fn run_message_state<'a>(
+        &mut self, context: &mut Self::Context, state: &mut MessageState, input: &'a [u8],
+        response: &mut impl crate::Write,
+    ) -> RunReport<'a> {
+        let mut guard = self.shared.lock().await;
+        guard
+            .run_message_state(context, state, input, response)
+            .await
+    }
+
+    fn timeout(duration_ms: u32) -> impl core::future::Future<Output = ()> {
+        T::timeout(duration_ms)
+    }
+
+    fn on_device_clear(&mut self) {
+        self.shared.apply(
+            |interface| interface.on_device_clear(),
+            |deferred| deferred.device_clear = true,
+        );
+    }
+
+    fn command_name(command_id: CommandId) -> Option<&'static str> {
+        T::command_name(command_id)
+    }
+
+    fn command_help(command_id: CommandId) -> Option<&'static str> {
+        T::command_help(command_id)
+    }
+
+    fn is_local_locked(command_id: CommandId) -> bool {
+        T::is_local_locked(command_id)
+    }
+
+    fn record_command(&mut self, command_id: CommandId) {
+        self.shared.apply(
+            |interface| interface.record_command(command_id),
+            |deferred| deferred.push(Operation::RecordCommand(command_id)),
+        );
+    }
+
+    fn record_error(&mut self, error: Error) {
+        self.shared.apply(
+            |interface| interface.record_error(error),
+            |deferred| deferred.push(Operation::RecordError(error)),
+        );
+    }
+
+    fn set_output_pending(&mut self, pending: bool) {
+        self.shared.apply(
+            |interface| interface.set_output_pending(pending),
+            |deferred| deferred.output_pending = Some(pending),
+        );
+    }
+
+    fn update_status(&mut self) {
+        self.shared.apply(
+            |interface| interface.update_status(),
+            |deferred| deferred.update_status = true,
+        );
+    }
+}
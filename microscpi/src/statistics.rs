@@ -0,0 +1,98 @@
+//! This module contains the command execution statistics of an interface.
+
+/// Command execution statistics of an interface
+///
+/// The statistics are updated by the interface while executing commands, if
+/// it implements [crate::StatisticsCommands]. All counters saturate at their
+/// maximum value.
+#[derive(Debug, Clone)]
+pub struct Statistics {
+    commands: u32,
+    errors: u32,
+    last_command: Option<&'static str>,
+    #[cfg(feature = "std")]
+    started: std::time::Instant,
+}
+
+impl Statistics {
+    pub fn new() -> Statistics {
+        Statistics {
+            commands: 0,
+            errors: 0,
+            last_command: None,
+            #[cfg(feature = "std")]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    /// The number of commands and queries that were executed successfully.
+    pub fn commands(&self) -> u32 {
+        self.commands
+    }
+
+    /// The number of errors that occurred while executing commands.
+    pub fn errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// The canonical name of the last command that was executed successfully,
+    /// e.g. `SYSTem:ERRor:COUNt?`.
+    pub fn last_command(&self) -> Option<&'static str> {
+        self.last_command
+    }
+
+    /// The time since the statistics were created.
+    #[cfg(feature = "std")]
+    pub fn uptime(&self) -> core::time::Duration {
+        self.started.elapsed()
+    }
+
+    /// Records the successful execution of a command.
+    pub fn record_command(&mut self, name: Option<&'static str>) {
+        self.commands = self.commands.saturating_add(1);
+        self.last_command = name;
+    }
+
+    /// Records an error.
+    pub fn record_error(&mut self) {
+        self.errors = self.errors.saturating_add(1);
+    }
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut statistics = Statistics::new();
+        assert_eq!(statistics.commands(), 0);
+        assert_eq!(statistics.last_command(), None);
+
+        statistics.record_command(Some("*IDN?"));
+        statistics.record_error();
+        assert_eq!(statistics.commands(), 1);
+        assert_eq!(statistics.errors(), 1);
+        assert_eq!(statistics.last_command(), Some("*IDN?"));
+    }
+
+    #[test]
+    fn test_saturating() {
+        let mut statistics = Statistics {
+            commands: u32::MAX,
+            errors: u32::MAX,
+            ..Statistics::new()
+        };
+
+        statistics.record_command(None);
+        statistics.record_error();
+        assert_eq!(statistics.commands(), u32::MAX);
+        assert_eq!(statistics.errors(), u32::MAX);
+    }
+}
@@ -0,0 +1,318 @@
+//! This module contains utilities for testing SCPI interfaces.
+//!
+//! The [MockAdapter] simulates a transport for [Interface::process] and the
+//! [assert_scpi](crate::assert_scpi) macro checks the response of an interface
+//! to a command.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::vec::Vec;
+
+use crate::{AdapterErrorType, AdapterRead, AdapterWrite, Interface};
+
+/// An error injected by the [MockAdapter].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MockError;
+
+/// An operation performed on a [MockAdapter].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockEvent {
+    /// The data passed to [AdapterWrite::write].
+    Write(Vec<u8>),
+    /// A call to [AdapterWrite::flush].
+    Flush,
+    /// A call to [AdapterWrite::discard_response].
+    Discard,
+}
+
+/// Adapter that returns scripted input and records the output.
+///
+/// The input is returned in the given chunks to simulate the fragmentation of
+/// a transport. A chunk larger than the read buffer is returned over several
+/// reads. Once all input has been read, the end of the input is signaled.
+///
+/// ```
+/// use microscpi::testing::MockAdapter;
+/// use microscpi::{self as scpi, Interface};
+///
+/// #[derive(scpi::IgnoreErrors)]
+/// pub struct ExampleInterface;
+///
+/// #[scpi::interface]
+/// impl ExampleInterface {
+///     #[scpi(cmd = "VALue?")]
+///     fn value(&mut self) -> Result<u32, scpi::Error> {
+///         Ok(42)
+///     }
+/// }
+///
+/// let mut adapter = MockAdapter::new([&b"VA"[..], b"L?\nVAL?\n"]);
+/// ExampleInterface.process_blocking::<64, _>(&mut adapter).unwrap();
+/// assert_eq!(adapter.output(), b"42\n42\n");
+/// assert_eq!(adapter.flushes(), 2);
+/// ```
+#[derive(Debug, Default)]
+pub struct MockAdapter {
+    input: VecDeque<Vec<u8>>,
+    output: Vec<u8>,
+    events: Vec<MockEvent>,
+    reads: usize,
+    fail_after: Option<usize>,
+    hold_output: bool,
+    pending_reads: bool,
+}
+
+impl MockAdapter {
+    /// Creates an adapter returning the input in the given chunks.
+    pub fn new<I, C>(chunks: I) -> MockAdapter
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<[u8]>,
+    {
+        MockAdapter {
+            input: chunks
+                .into_iter()
+                .map(|chunk| chunk.as_ref().to_vec())
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Appends a chunk to the input.
+    pub fn push_input(&mut self, chunk: impl AsRef<[u8]>) {
+        self.input.push_back(chunk.as_ref().to_vec());
+    }
+
+    /// Returns an error for every read after `reads` successful reads.
+    pub fn fail_after(mut self, reads: usize) -> MockAdapter {
+        self.fail_after = Some(reads);
+        self
+    }
+
+    /// Simulates a controller that does not read the responses, so the output
+    /// remains pending until it is discarded.
+    pub fn hold_output(mut self, hold: bool) -> MockAdapter {
+        self.hold_output = hold;
+        self
+    }
+
+    /// Makes each read pending once before it returns the input.
+    pub fn pending_reads(mut self, pending: bool) -> MockAdapter {
+        self.pending_reads = pending;
+        self
+    }
+
+    /// The output written since the last discarded response.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Returns the output and clears it.
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// All writes, flushes and discarded responses in the order they occurred.
+    pub fn events(&self) -> &[MockEvent] {
+        &self.events
+    }
+
+    /// The data of all writes.
+    pub fn writes(&self) -> impl Iterator<Item = &[u8]> {
+        self.events.iter().filter_map(|event| match event {
+            MockEvent::Write(data) => Some(data.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// The number of flushes.
+    pub fn flushes(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|event| **event == MockEvent::Flush)
+            .count()
+    }
+
+    /// The number of successful reads.
+    pub fn reads(&self) -> usize {
+        self.reads
+    }
+
+    /// The number of input chunks that have not been read completely.
+    pub fn remaining_input(&self) -> usize {
+        self.input.len()
+    }
+}
+
+impl AdapterErrorType for MockAdapter {
+    type Error = MockError;
+}
+
+impl AdapterRead for MockAdapter {
+    async fn read(&mut self, dst: &mut [u8]) -> Result<usize, MockError> {
+        if self.pending_reads {
+            YieldNow(false).await;
+        }
+        if self.fail_after.is_some_and(|reads| self.reads >= reads) {
+            return Err(MockError);
+        }
+
+        let Some(chunk) = self.input.front_mut()
+        else {
+            return Ok(0);
+        };
+
+        let count = chunk.len().min(dst.len());
+        dst[..count].copy_from_slice(&chunk[..count]);
+        chunk.drain(..count);
+        if chunk.is_empty() {
+            self.input.pop_front();
+        }
+
+        self.reads += 1;
+        Ok(count)
+    }
+}
+
+impl AdapterWrite for MockAdapter {
+    async fn write(&mut self, src: &[u8]) -> Result<(), MockError> {
+        self.output.extend_from_slice(src);
+        self.events.push(MockEvent::Write(src.to_vec()));
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), MockError> {
+        self.events.push(MockEvent::Flush);
+        Ok(())
+    }
+
+    fn response_pending(&self) -> bool {
+        self.hold_output && !self.output.is_empty()
+    }
+
+    async fn discard_response(&mut self) -> Result<(), MockError> {
+        self.output.clear();
+        self.events.push(MockEvent::Discard);
+        Ok(())
+    }
+}
+
+/// Future that is pending once.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        }
+        else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Replaces `\r\n` terminators by `\n` and appends a missing terminator.
+#[doc(hidden)]
+pub fn normalize(data: &[u8]) -> Vec<u8> {
+    let mut normalized = Vec::with_capacity(data.len() + 1);
+    let mut iter = data.iter().peekable();
+    while let Some(byte) = iter.next() {
+        if *byte == b'\r' && iter.peek() == Some(&&b'\n') {
+            continue;
+        }
+        normalized.push(*byte);
+    }
+    if !normalized.is_empty() && !normalized.ends_with(b"\n") {
+        normalized.push(b'\n');
+    }
+    normalized
+}
+
+/// Runs a command on an interface and returns the normalized response.
+#[doc(hidden)]
+pub fn run_command<I: Interface<Context = ()>>(interface: &mut I, command: &[u8]) -> Vec<u8> {
+    let mut response = Vec::new();
+    interface.run_blocking(&normalize(command), &mut response);
+    normalize(&response)
+}
+
+/// Asserts that an interface responds to a command with the expected
+/// response.
+///
+/// The command and the expected response may be given as strings or byte
+/// strings. A missing terminator is appended to both and `\r\n` terminators
+/// are compared as `\n`.
+///
+/// ```
+/// use microscpi::{self as scpi, assert_scpi};
+///
+/// #[derive(scpi::IgnoreErrors)]
+/// pub struct ExampleInterface;
+///
+/// #[scpi::interface]
+/// impl ExampleInterface {
+///     #[scpi(cmd = "VALue?")]
+///     fn value(&mut self) -> Result<u32, scpi::Error> {
+///         Ok(42)
+///     }
+/// }
+///
+/// let mut interface = ExampleInterface;
+/// assert_scpi!(interface, "VAL?;VAL?", "42;42");
+/// assert_scpi!(interface, b"UNKNOWN\r\n", "");
+/// ```
+#[macro_export]
+macro_rules! assert_scpi {
+    ($interface:expr, $command:expr, $expected:expr $(,)?) => {
+        match (&$command, &$expected) {
+            (command, expected) => {
+                let command: &[u8] = ::core::convert::AsRef::<[u8]>::as_ref(command);
+                let expected: &[u8] = ::core::convert::AsRef::<[u8]>::as_ref(expected);
+                let response = $crate::testing::run_command(&mut $interface, command);
+                assert_eq!(
+                    ::std::string::String::from_utf8_lossy(&response),
+                    ::std::string::String::from_utf8_lossy(&$crate::testing::normalize(expected)),
+                    "unexpected response to {:?}",
+                    ::std::string::String::from_utf8_lossy(command),
+                );
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize(b"1;2\r\n3"), b"1;2\n3\n");
+        assert_eq!(normalize(b"\r"), b"\r\n");
+        assert_eq!(normalize(b""), b"");
+    }
+
+    #[test]
+    fn test_mock_adapter_read() {
+        let mut adapter = MockAdapter::new([&b"ABCDEF"[..], b"G"]).fail_after(3);
+        let mut buf = [0; 4];
+
+        let read = |adapter: &mut MockAdapter, buf: &mut [u8]| {
+            crate::adapters::block_on(adapter.read(buf))
+        };
+
+        assert_eq!(read(&mut adapter, &mut buf), Ok(4));
+        assert_eq!(&buf, b"ABCD");
+        assert_eq!(read(&mut adapter, &mut buf), Ok(2));
+        assert_eq!(&buf[..2], b"EF");
+        assert_eq!(adapter.remaining_input(), 1);
+        assert_eq!(read(&mut adapter, &mut buf), Ok(1));
+        assert_eq!(read(&mut adapter, &mut buf), Err(MockError));
+        assert_eq!(adapter.reads(), 3);
+    }
+}
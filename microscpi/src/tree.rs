@@ -10,6 +10,19 @@ pub struct Node {
     pub children: &'static [(&'static str, &'static Node)],
     pub command: Option<CommandId>,
     pub query: Option<CommandId>,
+    /// The command tree of a nested interface mounted at this node.
+    pub subtree: Option<Subtree>,
+}
+
+/// Nested Command Tree
+///
+/// A subtree mounts the command tree of a nested interface below a node. The
+/// command ids of the nested tree are shifted by `offset` so they do not
+/// collide with the ids of the parent.
+#[derive(Debug)]
+pub struct Subtree {
+    pub root: &'static Node,
+    pub offset: CommandId,
 }
 
 impl Node {
@@ -27,6 +40,58 @@ impl Node {
         }
         None
     }
+
+    /// Searches for a path component in this node or in the subtree mounted at
+    /// this node.
+    ///
+    /// The children of this node take precedence over the children of the
+    /// subtree. The `offset` is the command id offset of this node and is
+    /// returned adjusted for the node that was found.
+    pub fn descend(&self, name: &str, offset: CommandId) -> Option<(&'static Node, CommandId)> {
+        if let Some(child) = self.child(name) {
+            return Some((child, offset));
+        }
+        let subtree = self.subtree.as_ref()?;
+        subtree.root.descend(name, offset + subtree.offset)
+    }
+
+    /// Returns whether a child of this node has the same name as a child of
+    /// the root node of a subtree mounted at this node.
+    ///
+    /// As the children of this node take precedence, the child of the subtree
+    /// would not be reachable. The interface macro uses this to reject such
+    /// subtrees at compile time.
+    #[doc(hidden)]
+    pub const fn shadows(&self, root: &Node) -> bool {
+        let mut i = 0;
+        while i < self.children.len() {
+            let mut j = 0;
+            while j < root.children.len() {
+                if eq_ignore_ascii_case(self.children[i].0, root.children[j].0) {
+                    return true;
+                }
+                j += 1;
+            }
+            i += 1;
+        }
+        false
+    }
+}
+
+/// Compares two names case-insensitively in a constant expression.
+const fn eq_ignore_ascii_case(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if !a[i].eq_ignore_ascii_case(&b[i]) {
+            return false;
+        }
+        i += 1;
+    }
+    true
 }
 
 impl PartialEq for Node {
@@ -43,12 +108,14 @@ mod tests {
         children: &[("LEAF", &LEAF_NODE)],
         command: None,
         query: None,
+        subtree: None,
     };
 
     static LEAF_NODE: Node = Node {
         children: &[],
         command: Some(1),
         query: None,
+        subtree: None,
     };
 
     #[test]
@@ -68,6 +135,64 @@ mod tests {
         assert_eq!(ROOT_NODE.child("lEaF"), Some(&LEAF_NODE));
     }
 
+    static CHILD_ROOT_NODE: Node = Node {
+        children: &[("LEAF", &CHILD_LEAF_NODE), ("NESTED", &CHILD_NESTED_NODE)],
+        command: None,
+        query: None,
+        subtree: None,
+    };
+
+    static CHILD_LEAF_NODE: Node = Node {
+        children: &[],
+        command: Some(0),
+        query: None,
+        subtree: None,
+    };
+
+    static CHILD_NESTED_NODE: Node = Node {
+        children: &[],
+        command: Some(1),
+        query: None,
+        subtree: None,
+    };
+
+    static MOUNT_NODE: Node = Node {
+        children: &[("LEAF", &LEAF_NODE)],
+        command: None,
+        query: None,
+        subtree: Some(Subtree {
+            root: &CHILD_ROOT_NODE,
+            offset: 10,
+        }),
+    };
+
+    #[test]
+    fn test_descend() {
+        assert_eq!(ROOT_NODE.descend("leaf", 0), Some((&LEAF_NODE, 0)));
+        assert_eq!(ROOT_NODE.descend("nested", 0), None);
+        assert_eq!(
+            MOUNT_NODE.descend("nested", 5),
+            Some((&CHILD_NESTED_NODE, 15))
+        );
+        // Children of the node itself shadow the children of the subtree.
+        assert_eq!(MOUNT_NODE.descend("leaf", 5), Some((&LEAF_NODE, 5)));
+    }
+
+    #[test]
+    fn test_shadows() {
+        assert!(MOUNT_NODE.shadows(&CHILD_ROOT_NODE));
+        assert!(!MOUNT_NODE.shadows(&LEAF_NODE));
+        assert!(!LEAF_NODE.shadows(&CHILD_ROOT_NODE));
+
+        static LOWER_NODE: Node = Node {
+            children: &[("nested", &LEAF_NODE)],
+            command: None,
+            query: None,
+            subtree: None,
+        };
+        assert!(LOWER_NODE.shadows(&CHILD_ROOT_NODE));
+    }
+
     #[test]
     fn test_node_equality() {
         assert_eq!(&LEAF_NODE, &LEAF_NODE);
@@ -1,4 +1,9 @@
+use core::marker::PhantomData;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use core::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+use core::ops::Deref;
 use core::str;
+use core::time::Duration;
 
 use crate::Error;
 
@@ -9,6 +14,9 @@ pub enum Value<'a> {
     ///
     /// A string that is enclosed by single or double quotes.
     /// Example: "Hello" or 'Hello'
+    ///
+    /// Quotes embedded into the string by doubling them remain doubled. They
+    /// are unescaped by the conversion into a `heapless::String`.
     String(&'a str),
     /// Characters
     ///
@@ -21,6 +29,12 @@ pub enum Value<'a> {
     /// the command that is called with this value.
     /// Example: 3953.64
     Decimal(&'a str),
+    /// Decimal number with suffix
+    ///
+    /// A decimal number followed by a suffix unit. The first element is the
+    /// number, the second element is the suffix as it was received.
+    /// Example: 10ms or 2.5 V/s
+    Suffixed(&'a str, &'a str),
     /// Hexadecimal number
     ///
     /// A number in hexadecimal format. Example `#H3A1CE96`
@@ -39,6 +53,268 @@ pub enum Value<'a> {
     Arbitrary(&'a [u8]),
 }
 
+/// Numeric value with engineering prefix
+///
+/// A number that may be followed by a metric prefix which scales the value
+/// accordingly, e.g. `1k`, `2.2M`, `470u` or `10n`. The prefixes are
+/// case-sensitive, so `m` denotes milli while `M` denotes mega. The supported
+/// prefixes are `p`, `n`, `u`, `m`, `k`, `M`, `G` and `T`. Any other suffix
+/// is rejected with [Error::InvalidSuffix].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scaled<T>(T);
+
+impl<T: Copy> Scaled<T> {
+    /// Returns the value with the prefix scale factor applied.
+    pub fn value(&self) -> T {
+        self.0
+    }
+}
+
+/// Bounded integer value
+///
+/// An integer of type `T` that is guaranteed to be within the inclusive range
+/// `MIN..=MAX`. Values outside of this range are rejected with
+/// [Error::DataOutOfRange] when converting the argument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounded<T, const MIN: i64, const MAX: i64>(T);
+
+impl<T: Copy + TryInto<i64>, const MIN: i64, const MAX: i64> Bounded<T, MIN, MAX> {
+    /// Creates a new bounded value.
+    ///
+    /// Returns [Error::DataOutOfRange] if the value is not within the range
+    /// `MIN..=MAX`.
+    pub fn new(value: T) -> Result<Self, Error> {
+        let number: i64 = value.try_into().or(Err(Error::DataOutOfRange))?;
+
+        if (MIN..=MAX).contains(&number) {
+            Ok(Bounded(value))
+        }
+        else {
+            Err(Error::DataOutOfRange)
+        }
+    }
+}
+
+impl<T, const MIN: i64, const MAX: i64> Deref for Bounded<T, MIN, MAX> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Byte order of binary block data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ByteOrder {
+    /// Least significant byte first.
+    #[default]
+    LittleEndian,
+    /// Most significant byte first.
+    BigEndian,
+}
+
+/// A numeric type that can be decoded from binary block data.
+pub trait BlockElement: Sized {
+    /// The number of bytes of a single element.
+    const SIZE: usize;
+
+    /// Decodes a single element from exactly [BlockElement::SIZE] bytes.
+    fn from_bytes(bytes: &[u8], order: ByteOrder) -> Self;
+}
+
+macro_rules! impl_block_element {
+    ($type:ty) => {
+        impl BlockElement for $type {
+            const SIZE: usize = core::mem::size_of::<$type>();
+
+            fn from_bytes(bytes: &[u8], order: ByteOrder) -> Self {
+                let mut buf = [0u8; core::mem::size_of::<$type>()];
+                buf.copy_from_slice(bytes);
+                match order {
+                    ByteOrder::LittleEndian => <$type>::from_le_bytes(buf),
+                    ByteOrder::BigEndian => <$type>::from_be_bytes(buf),
+                }
+            }
+        }
+    };
+}
+
+impl_block_element!(u8);
+impl_block_element!(i8);
+impl_block_element!(u16);
+impl_block_element!(i16);
+impl_block_element!(u32);
+impl_block_element!(i32);
+impl_block_element!(u64);
+impl_block_element!(i64);
+impl_block_element!(f32);
+impl_block_element!(f64);
+
+/// Typed view of binary block data
+///
+/// Interprets the bytes of an arbitrary block argument as a sequence of
+/// numeric values of type `T`. The data does not need to be aligned, the
+/// values are decoded on the fly while iterating. By default the data is
+/// interpreted as little-endian, use [BlockData::with_byte_order] to change
+/// this. Block data arguments of handlers are decoded in the byte order
+/// selected by `FORMat:BORDer` for interfaces with
+/// [FormatCommands](crate::FormatCommands).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockData<'a, T> {
+    data: &'a [u8],
+    order: ByteOrder,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: BlockElement> BlockData<'a, T> {
+    /// Creates a new typed view of the given bytes.
+    ///
+    /// Returns [Error::InvalidBlockData] if the length of the data is not a
+    /// multiple of the element size.
+    pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+        if !data.len().is_multiple_of(T::SIZE) {
+            return Err(Error::InvalidBlockData);
+        }
+
+        Ok(BlockData {
+            data,
+            order: ByteOrder::default(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the view with the specified byte order.
+    pub fn with_byte_order(self, order: ByteOrder) -> Self {
+        BlockData { order, ..self }
+    }
+
+    /// The byte order used for decoding the values.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.order
+    }
+
+    /// The number of values in the block.
+    pub fn len(&self) -> usize {
+        self.data.len() / T::SIZE
+    }
+
+    /// Returns `true` if the block contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the value at the specified index.
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.data
+            .chunks_exact(T::SIZE)
+            .nth(index)
+            .map(|bytes| T::from_bytes(bytes, self.order))
+    }
+
+    /// Returns an iterator over the decoded values.
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'a {
+        let order = self.order;
+        self.data
+            .chunks_exact(T::SIZE)
+            .map(move |bytes| T::from_bytes(bytes, order))
+    }
+
+    /// The raw bytes of the block.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// An argument type that is decoded in a byte order.
+///
+/// Handler arguments of a type implementing this trait are decoded in the
+/// byte order selected by `FORMat:BORDer`, if the interface implements
+/// [FormatCommands](crate::FormatCommands). Arguments of other types are
+/// passed unchanged.
+pub trait WithByteOrder {
+    /// Returns the argument with the specified byte order.
+    fn with_byte_order(self, order: ByteOrder) -> Self;
+}
+
+impl<T: BlockElement> WithByteOrder for BlockData<'_, T> {
+    fn with_byte_order(self, order: ByteOrder) -> Self {
+        BlockData::with_byte_order(self, order)
+    }
+}
+
+impl<T: WithByteOrder> WithByteOrder for Option<T> {
+    fn with_byte_order(self, order: ByteOrder) -> Self {
+        self.map(|value| value.with_byte_order(order))
+    }
+}
+
+/// A converted handler argument, to which the interface applies its byte order.
+///
+/// The inherent method is only available for arguments implementing
+/// [WithByteOrder] and takes precedence over the method of
+/// [UnorderedArgument], which passes all other arguments unchanged.
+#[doc(hidden)]
+pub struct Argument<T>(pub T);
+
+impl<T: WithByteOrder> Argument<T> {
+    pub fn apply_byte_order(self, order: Option<ByteOrder>) -> T {
+        match order {
+            Some(order) => self.0.with_byte_order(order),
+            None => self.0,
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait UnorderedArgument<T> {
+    fn apply_byte_order(self, order: Option<ByteOrder>) -> T;
+}
+
+impl<T> UnorderedArgument<T> for Argument<T> {
+    fn apply_byte_order(self, _order: Option<ByteOrder>) -> T {
+        self.0
+    }
+}
+
+/// Checks whether the input matches the long or the short form of a SCPI
+/// mnemonic.
+///
+/// The mnemonic has to be specified in the usual SCPI notation where the short
+/// form is written in uppercase letters, e.g. `ASCii`. The comparison is
+/// *case-insensitive*.
+pub(crate) fn mnemonic_matches(mnemonic: &str, input: &str) -> bool {
+    if input.eq_ignore_ascii_case(mnemonic) {
+        return true;
+    }
+
+    let mut short = mnemonic.chars().filter(|c| !c.is_ascii_lowercase());
+    let mut input = input.chars();
+
+    loop {
+        match (short.next(), input.next()) {
+            (Some(a), Some(b)) if a.eq_ignore_ascii_case(&b) => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Returns the scale factor of a metric prefix.
+fn prefix_scale(prefix: &str) -> Result<f64, Error> {
+    match prefix {
+        "p" => Ok(1e-12),
+        "n" => Ok(1e-9),
+        "u" => Ok(1e-6),
+        "m" => Ok(1e-3),
+        "k" => Ok(1e3),
+        "M" => Ok(1e6),
+        "G" => Ok(1e9),
+        "T" => Ok(1e12),
+        _ => Err(Error::InvalidSuffix),
+    }
+}
+
 impl<'a> TryInto<&'a str> for &Value<'a> {
     type Error = Error;
 
@@ -69,6 +345,116 @@ impl<'a> TryInto<&'a [u8]> for &Value<'a> {
     }
 }
 
+impl<const N: usize> TryInto<heapless::String<N>> for &Value<'_> {
+    type Error = Error;
+
+    /// Copies the text of the value. The doubled quotes of a string are
+    /// unescaped, before the length is checked.
+    fn try_into(self) -> Result<heapless::String<N>, Self::Error> {
+        match self {
+            Value::String(data) => {
+                let mut string = heapless::String::new();
+                let mut chars = data.chars().peekable();
+                while let Some(c) = chars.next() {
+                    if matches!(c, '"' | '\'') && chars.peek() == Some(&c) {
+                        chars.next();
+                    }
+                    string.push(c).or(Err(Error::TooMuchData))?;
+                }
+                Ok(string)
+            }
+            Value::Characters(data) => {
+                heapless::String::try_from(*data).or(Err(Error::TooMuchData))
+            }
+            _ => Err(Error::DataTypeError),
+        }
+    }
+}
+
+impl<const N: usize> TryInto<heapless::String<N>> for Value<'_> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<heapless::String<N>, Self::Error> {
+        (&self).try_into()
+    }
+}
+
+/// Removes the square brackets enclosing an IPv6 address, if present.
+fn strip_brackets(address: &str) -> &str {
+    address
+        .strip_prefix('[')
+        .and_then(|address| address.strip_suffix(']'))
+        .unwrap_or(address)
+}
+
+macro_rules! impl_try_into_addr {
+    ($type:ty, $strip:expr) => {
+        impl TryInto<$type> for &Value<'_> {
+            type Error = Error;
+
+            fn try_into(self) -> Result<$type, Self::Error> {
+                match self {
+                    Value::String(data) | Value::Characters(data) => {
+                        $strip(data).parse().or(Err(Error::IllegalParameterValue))
+                    }
+                    _ => Err(Error::DataTypeError),
+                }
+            }
+        }
+
+        impl TryInto<$type> for Value<'_> {
+            type Error = Error;
+
+            fn try_into(self) -> Result<$type, Self::Error> {
+                (&self).try_into()
+            }
+        }
+    };
+}
+
+impl_try_into_addr!(Ipv4Addr, core::convert::identity);
+impl_try_into_addr!(Ipv6Addr, strip_brackets);
+impl_try_into_addr!(IpAddr, strip_brackets);
+impl_try_into_addr!(SocketAddr, core::convert::identity);
+
+impl<const N: usize> TryInto<[u8; N]> for &Value<'_> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<[u8; N], Self::Error> {
+        match self {
+            Value::Arbitrary(data) => (*data).try_into().or(Err(Error::InvalidBlockData)),
+            _ => Err(Error::DataTypeError),
+        }
+    }
+}
+
+impl<const N: usize> TryInto<[u8; N]> for Value<'_> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<[u8; N], Self::Error> {
+        (&self).try_into()
+    }
+}
+
+impl<'a, T: BlockElement> TryInto<BlockData<'a, T>> for &Value<'a> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<BlockData<'a, T>, Self::Error> {
+        match self {
+            Value::Arbitrary(data) => BlockData::new(data),
+            _ => Err(Error::DataTypeError),
+        }
+    }
+}
+
+impl<'a, T: BlockElement> TryInto<BlockData<'a, T>> for Value<'a> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<BlockData<'a, T>, Self::Error> {
+        (&self).try_into()
+    }
+}
+
 macro_rules! impl_try_into_int {
     ($type:ty) => {
         impl TryInto<$type> for &Value<'_> {
@@ -88,6 +474,7 @@ macro_rules! impl_try_into_int {
                     Value::Octal(data) => {
                         <$type>::from_str_radix(data, 8).or(Err(Error::NumericDataError))
                     }
+                    Value::Suffixed(_, _) => Err(Error::SuffixNotAllowed),
                     _ => Err(Error::DataTypeError),
                 }
             }
@@ -113,6 +500,34 @@ impl_try_into_int!(u64);
 impl_try_into_int!(i64);
 impl_try_into_int!(usize);
 impl_try_into_int!(isize);
+impl_try_into_int!(u128);
+impl_try_into_int!(i128);
+
+macro_rules! impl_try_into_non_zero {
+    ($type:ty, $inner:ty) => {
+        impl TryInto<$type> for &Value<'_> {
+            type Error = Error;
+
+            fn try_into(self) -> Result<$type, Self::Error> {
+                let value: $inner = self.try_into()?;
+                <$type>::new(value).ok_or(Error::DataOutOfRange)
+            }
+        }
+
+        impl TryInto<$type> for Value<'_> {
+            type Error = Error;
+
+            fn try_into(self) -> Result<$type, Self::Error> {
+                (&self).try_into()
+            }
+        }
+    };
+}
+
+impl_try_into_non_zero!(NonZeroU8, u8);
+impl_try_into_non_zero!(NonZeroU16, u16);
+impl_try_into_non_zero!(NonZeroU32, u32);
+impl_try_into_non_zero!(NonZeroU64, u64);
 
 impl TryInto<bool> for &Value<'_> {
     type Error = Error;
@@ -144,6 +559,7 @@ impl TryInto<f32> for &Value<'_> {
     fn try_into(self) -> Result<f32, Self::Error> {
         match self {
             Value::Decimal(data) => data.parse().or(Err(Error::NumericDataError)),
+            Value::Suffixed(_, _) => Err(Error::SuffixNotAllowed),
             _ => Err(Error::DataTypeError),
         }
     }
@@ -163,6 +579,7 @@ impl TryInto<f64> for &Value<'_> {
     fn try_into(self) -> Result<f64, Self::Error> {
         match self {
             Value::Decimal(data) => data.parse().or(Err(Error::NumericDataError)),
+            Value::Suffixed(_, _) => Err(Error::SuffixNotAllowed),
             _ => Err(Error::DataTypeError),
         }
     }
@@ -176,6 +593,89 @@ impl TryInto<f64> for Value<'_> {
     }
 }
 
+impl<'a, T, const MIN: i64, const MAX: i64> TryInto<Bounded<T, MIN, MAX>> for &'a Value<'a>
+where
+    T: Copy + TryInto<i64>,
+    &'a Value<'a>: TryInto<T, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_into(self) -> Result<Bounded<T, MIN, MAX>, Self::Error> {
+        Bounded::new(TryInto::<T>::try_into(self)?)
+    }
+}
+
+macro_rules! impl_try_into_scaled {
+    ($type:ty) => {
+        impl TryInto<Scaled<$type>> for &Value<'_> {
+            type Error = Error;
+
+            fn try_into(self) -> Result<Scaled<$type>, Self::Error> {
+                match self {
+                    Value::Decimal(data) => {
+                        data.parse().map(Scaled).or(Err(Error::NumericDataError))
+                    }
+                    Value::Suffixed(data, prefix) => {
+                        let scale = prefix_scale(prefix)?;
+                        let value: f64 = data.parse().or(Err(Error::NumericDataError))?;
+                        Ok(Scaled((value * scale) as $type))
+                    }
+                    _ => Err(Error::DataTypeError),
+                }
+            }
+        }
+
+        impl TryInto<Scaled<$type>> for Value<'_> {
+            type Error = Error;
+
+            fn try_into(self) -> Result<Scaled<$type>, Self::Error> {
+                (&self).try_into()
+            }
+        }
+    };
+}
+
+impl_try_into_scaled!(f32);
+impl_try_into_scaled!(f64);
+
+impl TryInto<Duration> for &Value<'_> {
+    type Error = Error;
+
+    /// Converts a decimal number into a [Duration].
+    ///
+    /// Numbers without a suffix are interpreted as seconds. The supported
+    /// suffixes are `s`, `ms`, `us`, `ns` and `min`, matched
+    /// case-insensitively.
+    fn try_into(self) -> Result<Duration, Self::Error> {
+        let (number, scale) = match self {
+            Value::Decimal(number) => (number, 1.0),
+            Value::Suffixed(number, suffix) => {
+                let scale = match suffix {
+                    s if s.eq_ignore_ascii_case("s") => 1.0,
+                    s if s.eq_ignore_ascii_case("ms") => 1e-3,
+                    s if s.eq_ignore_ascii_case("us") => 1e-6,
+                    s if s.eq_ignore_ascii_case("ns") => 1e-9,
+                    s if s.eq_ignore_ascii_case("min") => 60.0,
+                    _ => return Err(Error::InvalidSuffix),
+                };
+                (number, scale)
+            }
+            _ => return Err(Error::DataTypeError),
+        };
+
+        let seconds: f64 = number.parse().or(Err(Error::NumericDataError))?;
+        Duration::try_from_secs_f64(seconds * scale).or(Err(Error::DataOutOfRange))
+    }
+}
+
+impl TryInto<Duration> for Value<'_> {
+    type Error = Error;
+
+    fn try_into(self) -> Result<Duration, Self::Error> {
+        (&self).try_into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,6 +776,64 @@ mod tests {
         assert_eq!(Value::Octal("173").try_into(), Ok(123i64));
     }
 
+    #[test]
+    pub fn test_u128() {
+        assert_eq!(
+            Value::Decimal("340282366920938463463374607431768211455").try_into(),
+            Ok(u128::MAX)
+        );
+        assert_eq!(
+            Value::Decimal("-1").try_into(),
+            Err::<u128, Error>(Error::NumericDataError)
+        );
+        assert_eq!(
+            Value::Hexadecimal("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF").try_into(),
+            Ok(u128::MAX)
+        );
+        assert_eq!(Value::Binary("1111011").try_into(), Ok(123u128));
+        assert_eq!(Value::Octal("173").try_into(), Ok(123u128));
+    }
+
+    #[test]
+    pub fn test_i128() {
+        assert_eq!(
+            Value::Decimal("-170141183460469231731687303715884105728").try_into(),
+            Ok(i128::MIN)
+        );
+        assert_eq!(
+            Value::Hexadecimal("7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF").try_into(),
+            Ok(i128::MAX)
+        );
+        assert_eq!(
+            Value::String("123").try_into(),
+            Err::<i128, Error>(Error::DataTypeError)
+        );
+    }
+
+    #[test]
+    pub fn test_non_zero() {
+        assert_eq!(
+            Value::Decimal("42").try_into(),
+            Ok(NonZeroU8::new(42).unwrap())
+        );
+        assert_eq!(
+            Value::Hexadecimal("FFFF").try_into(),
+            Ok(NonZeroU16::new(u16::MAX).unwrap())
+        );
+        assert_eq!(
+            Value::Decimal("0").try_into(),
+            Err::<NonZeroU32, Error>(Error::DataOutOfRange)
+        );
+        assert_eq!(
+            Value::Binary("0").try_into(),
+            Err::<NonZeroU64, Error>(Error::DataOutOfRange)
+        );
+        assert_eq!(
+            Value::Decimal("256").try_into(),
+            Err::<NonZeroU8, Error>(Error::NumericDataError)
+        );
+    }
+
     #[test]
     pub fn test_f32() {
         assert_eq!(Value::Decimal("123.45").try_into(), Ok(123.45f32));
@@ -301,4 +859,257 @@ mod tests {
             Err::<f64, Error>(Error::DataTypeError)
         );
     }
+
+    #[test]
+    pub fn test_duration() {
+        assert_eq!(Value::Decimal("2").try_into(), Ok(Duration::from_secs(2)));
+        assert_eq!(
+            Value::Suffixed("10", "ms").try_into(),
+            Ok(Duration::from_millis(10))
+        );
+        assert_eq!(
+            Value::Suffixed("1.5", "ms").try_into(),
+            Ok(Duration::from_micros(1500))
+        );
+        assert_eq!(
+            Value::Suffixed("2.5", "S").try_into(),
+            Ok(Duration::from_millis(2500))
+        );
+        assert_eq!(
+            Value::Suffixed("250", "US").try_into(),
+            Ok(Duration::from_micros(250))
+        );
+        assert_eq!(
+            Value::Suffixed("100", "ns").try_into(),
+            Ok(Duration::from_nanos(100))
+        );
+        assert_eq!(
+            Value::Suffixed("2", "min").try_into(),
+            Ok(Duration::from_secs(120))
+        );
+        assert_eq!(
+            Value::Suffixed("10", "hz").try_into(),
+            Err::<Duration, Error>(Error::InvalidSuffix)
+        );
+        assert_eq!(
+            Value::Suffixed("-1", "s").try_into(),
+            Err::<Duration, Error>(Error::DataOutOfRange)
+        );
+        assert_eq!(
+            Value::String("1s").try_into(),
+            Err::<Duration, Error>(Error::DataTypeError)
+        );
+    }
+
+    #[test]
+    pub fn test_suffix_not_allowed() {
+        assert_eq!(
+            Value::Suffixed("10", "ms").try_into(),
+            Err::<u32, Error>(Error::SuffixNotAllowed)
+        );
+        assert_eq!(
+            Value::Suffixed("1.5", "V").try_into(),
+            Err::<f64, Error>(Error::SuffixNotAllowed)
+        );
+    }
+
+    #[test]
+    pub fn test_scaled() {
+        assert_eq!(Value::Decimal("50").try_into(), Ok(Scaled(50.0f64)));
+        assert_eq!(Value::Suffixed("1", "k").try_into(), Ok(Scaled(1e3f64)));
+        assert_eq!(Value::Suffixed("2.2", "M").try_into(), Ok(Scaled(2.2e6f64)));
+        assert_eq!(
+            Value::Suffixed("470", "u").try_into(),
+            Ok(Scaled(470e-6f64))
+        );
+        assert_eq!(Value::Suffixed("10", "n").try_into(), Ok(Scaled(10e-9f64)));
+        assert_eq!(Value::Suffixed("3", "G").try_into(), Ok(Scaled(3e9f32)));
+
+        // Prefixes are case-sensitive: `m` is milli and `M` is mega.
+        assert_eq!(Value::Suffixed("5", "m").try_into(), Ok(Scaled(5e-3f64)));
+        assert_eq!(Value::Suffixed("5", "M").try_into(), Ok(Scaled(5e6f64)));
+        assert_eq!(
+            Value::Suffixed("1", "K").try_into(),
+            Err::<Scaled<f64>, Error>(Error::InvalidSuffix)
+        );
+
+        assert_eq!(
+            Value::Suffixed("1", "kHz").try_into(),
+            Err::<Scaled<f64>, Error>(Error::InvalidSuffix)
+        );
+        assert_eq!(
+            Value::String("1k").try_into(),
+            Err::<Scaled<f64>, Error>(Error::DataTypeError)
+        );
+    }
+
+    #[test]
+    pub fn test_bounded() {
+        let value = Value::Decimal("1");
+        let bounded: Result<Bounded<u16, 1, 1024>, Error> = (&value).try_into();
+        assert_eq!(bounded.map(|b| *b), Ok(1));
+
+        let value = Value::Decimal("1024");
+        let bounded: Result<Bounded<u16, 1, 1024>, Error> = (&value).try_into();
+        assert_eq!(bounded.map(|b| *b), Ok(1024));
+
+        let value = Value::Decimal("0");
+        let bounded: Result<Bounded<u16, 1, 1024>, Error> = (&value).try_into();
+        assert_eq!(bounded, Err(Error::DataOutOfRange));
+
+        let value = Value::Decimal("1025");
+        let bounded: Result<Bounded<u16, 1, 1024>, Error> = (&value).try_into();
+        assert_eq!(bounded, Err(Error::DataOutOfRange));
+
+        let value = Value::Decimal("-5");
+        let bounded: Result<Bounded<i32, -5, 5>, Error> = (&value).try_into();
+        assert_eq!(bounded.map(|b| *b), Ok(-5));
+
+        let value = Value::Decimal("18446744073709551615");
+        let bounded: Result<Bounded<u64, 0, 10>, Error> = (&value).try_into();
+        assert_eq!(bounded, Err(Error::DataOutOfRange));
+
+        let value = Value::String("5");
+        let bounded: Result<Bounded<u8, 0, 10>, Error> = (&value).try_into();
+        assert_eq!(bounded, Err(Error::DataTypeError));
+    }
+
+    #[test]
+    pub fn test_block_data() {
+        let bytes = [0x01, 0x00, 0xff, 0xff, 0x00, 0x80];
+        let block: BlockData<i16> = Value::Arbitrary(&bytes).try_into().unwrap();
+        assert_eq!(block.len(), 3);
+        assert_eq!(block.get(1), Some(-1));
+        assert_eq!(block.get(3), None);
+        assert!(block.iter().eq([1, -1, i16::MIN]));
+
+        let block = block.with_byte_order(ByteOrder::BigEndian);
+        assert!(block.iter().eq([256, -1, 128]));
+
+        // Unaligned input
+        let bytes = [0x00, 0x00, 0x00, 0xc0, 0x3f];
+        let block: BlockData<f32> = Value::Arbitrary(&bytes[1..]).try_into().unwrap();
+        assert!(block.iter().eq([1.5f32]));
+
+        let block: BlockData<f32> = Value::Arbitrary(&[]).try_into().unwrap();
+        assert!(block.is_empty());
+
+        assert_eq!(
+            Value::Arbitrary(&bytes).try_into(),
+            Err::<BlockData<f32>, Error>(Error::InvalidBlockData)
+        );
+        assert_eq!(
+            Value::Decimal("1").try_into(),
+            Err::<BlockData<f32>, Error>(Error::DataTypeError)
+        );
+    }
+
+    #[test]
+    pub fn test_byte_array() {
+        assert_eq!(
+            Value::Arbitrary(&[1, 2, 3, 4]).try_into(),
+            Ok([1u8, 2, 3, 4])
+        );
+        assert_eq!(
+            Value::Arbitrary(&[1, 2, 3]).try_into(),
+            Err::<[u8; 4], Error>(Error::InvalidBlockData)
+        );
+        assert_eq!(
+            Value::Arbitrary(&[1, 2, 3, 4, 5]).try_into(),
+            Err::<[u8; 4], Error>(Error::InvalidBlockData)
+        );
+        assert_eq!(
+            Value::String("abcd").try_into(),
+            Err::<[u8; 4], Error>(Error::DataTypeError)
+        );
+    }
+
+    #[test]
+    pub fn test_heapless_string() {
+        let label: heapless::String<5> = Value::String("Hello").try_into().unwrap();
+        assert_eq!(label, "Hello");
+
+        let label: heapless::String<5> = Value::Characters("ON").try_into().unwrap();
+        assert_eq!(label, "ON");
+
+        assert_eq!(
+            Value::String("Hello!").try_into(),
+            Err::<heapless::String<5>, Error>(Error::TooMuchData)
+        );
+        assert_eq!(
+            Value::Decimal("1").try_into(),
+            Err::<heapless::String<5>, Error>(Error::DataTypeError)
+        );
+    }
+
+    #[test]
+    pub fn test_heapless_string_unescape() {
+        let label: heapless::String<3> = Value::String("a\"\"b").try_into().unwrap();
+        assert_eq!(label, "a\"b");
+
+        let label: heapless::String<4> = Value::String("it''s").try_into().unwrap();
+        assert_eq!(label, "it's");
+
+        let label: heapless::String<2> = Value::String("\"\"\"\"").try_into().unwrap();
+        assert_eq!(label, "\"\"");
+
+        assert_eq!(
+            Value::String("a\"\"b").try_into(),
+            Err::<heapless::String<2>, Error>(Error::TooMuchData)
+        );
+    }
+
+    #[test]
+    pub fn test_ip_addr() {
+        assert_eq!(
+            Value::String("192.168.1.10").try_into(),
+            Ok(Ipv4Addr::new(192, 168, 1, 10))
+        );
+        assert_eq!(
+            Value::String("192.168.1.256").try_into(),
+            Err::<Ipv4Addr, Error>(Error::IllegalParameterValue)
+        );
+        assert_eq!(
+            Value::String("[fe80::1]").try_into(),
+            Ok(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))
+        );
+        assert_eq!(
+            Value::String("::1").try_into(),
+            Ok(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        );
+        assert_eq!(
+            Value::String("10.0.0.1").try_into(),
+            Ok(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)))
+        );
+        assert_eq!(
+            Value::Decimal("10").try_into(),
+            Err::<IpAddr, Error>(Error::DataTypeError)
+        );
+    }
+
+    #[test]
+    pub fn test_socket_addr() {
+        assert_eq!(
+            Value::String("192.168.1.10:5025").try_into(),
+            Ok(SocketAddr::new(Ipv4Addr::new(192, 168, 1, 10).into(), 5025))
+        );
+        assert_eq!(
+            Value::String("[::1]:5025").try_into(),
+            Ok(SocketAddr::new(Ipv6Addr::LOCALHOST.into(), 5025))
+        );
+        assert_eq!(
+            Value::String("192.168.1.10").try_into(),
+            Err::<SocketAddr, Error>(Error::IllegalParameterValue)
+        );
+    }
+
+    #[test]
+    pub fn test_mnemonic_matches() {
+        assert!(mnemonic_matches("ASCii", "ASCII"));
+        assert!(mnemonic_matches("ASCii", "asc"));
+        assert!(mnemonic_matches("REAL", "Real"));
+        assert!(!mnemonic_matches("ASCii", "ASCI"));
+        assert!(!mnemonic_matches("ASCii", "AS"));
+        assert!(!mnemonic_matches("INTeger", "INTEGERS"));
+    }
 }
@@ -0,0 +1,47 @@
+//! A command set defined in a crate other than the interfaces using it.
+//!
+//! The argument types of the commands are only in scope in this crate, so the
+//! interfaces in the tests have to resolve them through the trait.
+
+pub mod lan {
+    use microscpi as scpi;
+
+    pub type Port = u16;
+
+    #[derive(Default)]
+    pub struct LanConfig {
+        pub port: Port,
+        pub gateway: Option<Port>,
+        pub mac: [u8; 6],
+    }
+
+    #[scpi::command_set]
+    pub trait LanCommands {
+        fn lan_config(&mut self) -> &mut LanConfig;
+
+        #[scpi(cmd = "SYSTem:COMMunicate:LAN:PORT", range(port = "1024.."))]
+        fn set_port(&mut self, port: Port) -> Result<(), scpi::Error> {
+            self.lan_config().port = port;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SYSTem:COMMunicate:LAN:PORT?")]
+        fn port(&mut self) -> Port {
+            self.lan_config().port
+        }
+
+        #[scpi(cmd = "SYSTem:COMMunicate:LAN:GATeway")]
+        fn set_gateway(&mut self, gateway: Option<Port>) -> Result<(), scpi::Error> {
+            self.lan_config().gateway = gateway;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SYSTem:COMMunicate:LAN:MAC")]
+        fn set_mac(&mut self, mac: scpi::BlockData<'_, u8>) -> Result<(), scpi::Error> {
+            for (byte, value) in self.lan_config().mac.iter_mut().zip(mac.iter()) {
+                *byte = value;
+            }
+            Ok(())
+        }
+    }
+}
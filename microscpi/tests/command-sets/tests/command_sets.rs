@@ -0,0 +1,30 @@
+use microscpi as scpi;
+use microscpi_command_sets::lan::{LanCommands, LanConfig};
+
+#[derive(Default, scpi::IgnoreErrors)]
+pub struct Instrument {
+    pub lan: LanConfig,
+}
+
+impl LanCommands for Instrument {
+    fn lan_config(&mut self) -> &mut LanConfig {
+        &mut self.lan
+    }
+}
+
+#[scpi::interface(use(LanCommands), learn)]
+impl Instrument {}
+
+#[test]
+fn test_foreign_command_set() {
+    let mut instrument = Instrument::default();
+
+    scpi::assert_scpi!(instrument, "SYST:COMM:LAN:PORT 5025;PORT?", "5025");
+    scpi::assert_scpi!(instrument, "SYST:COMM:LAN:PORT 80;PORT?", "5025");
+    scpi::assert_scpi!(instrument, "SYST:COMM:LAN:GAT 80", "");
+    scpi::assert_scpi!(instrument, "SYST:COMM:LAN:MAC #16ABCDEF", "");
+    scpi::assert_scpi!(instrument, "*LRN?", ":SYSTEM:COMMUNICATE:LAN:PORT 5025");
+
+    assert_eq!(instrument.lan.gateway, Some(80));
+    assert_eq!(&instrument.lan.mac, b"ABCDEF");
+}
@@ -0,0 +1,57 @@
+//! Several interfaces defined in the same module.
+
+use microscpi::{self as scpi, assert_scpi, Interface};
+
+#[derive(Default, scpi::IgnoreErrors)]
+pub struct Device {
+    voltage: f64,
+}
+
+#[scpi::interface]
+impl Device {
+    #[scpi(cmd = "SOURce:VOLTage")]
+    fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+        self.voltage = voltage;
+        Ok(())
+    }
+
+    #[scpi(cmd = "SOURce:VOLTage?")]
+    fn voltage(&mut self) -> Result<f64, scpi::Error> {
+        Ok(self.voltage)
+    }
+}
+
+/// A stub of the device with a different command tree.
+#[derive(Default, scpi::IgnoreErrors)]
+pub struct DeviceStub {
+    calls: u32,
+}
+
+#[scpi::interface]
+impl DeviceStub {
+    #[scpi(cmd = "SOURce:CURRent")]
+    fn set_current(&mut self, _current: f64) -> Result<(), scpi::Error> {
+        self.calls += 1;
+        Ok(())
+    }
+
+    #[scpi(cmd = "CALLs?")]
+    fn calls(&mut self) -> Result<u32, scpi::Error> {
+        Ok(self.calls)
+    }
+}
+
+#[test]
+fn test_interfaces_in_same_module() {
+    let mut device = Device::default();
+    let mut stub = DeviceStub::default();
+
+    assert_scpi!(device, "SOUR:VOLT 1.5;VOLT?", "1.5");
+    assert_scpi!(stub, "SOUR:CURR 2;:CALL?", "1");
+    assert_scpi!(device, "SOUR:CURR 2;:CALL?", "");
+    assert_scpi!(stub, "SOUR:VOLT?", "");
+
+    assert!(device.lookup("SOUR:VOLT").is_some());
+    assert!(stub.lookup("SOUR:VOLT").is_none());
+    assert!(!core::ptr::eq(Device::ROOT_NODE, DeviceStub::ROOT_NODE));
+}
@@ -1,5 +1,12 @@
+use core::num::NonZeroU16;
+use core::time::Duration;
+
+#[cfg(feature = "embassy-sync")]
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use microscpi::testing::{MockAdapter, MockEvent};
 use microscpi::{
-    self as scpi, ErrorCommands, ErrorQueue, Interface, StandardCommands, StaticErrorQueue,
+    self as scpi, ErrorCommands, ErrorQueue, FormatCommands, Interface, StandardCommands,
+    StaticErrorQueue, StatusCommands,
 };
 
 #[derive(Debug, PartialEq)]
@@ -14,6 +21,17 @@ pub enum TestResult {
 pub struct TestInterface {
     errors: StaticErrorQueue<10>,
     result: Option<TestResult>,
+    delay: Duration,
+    frequency: f64,
+    average: u16,
+    waveform_sum: f32,
+    mac: [u8; 6],
+    label: heapless::String<8>,
+    data_format: scpi::DataFormat,
+    blob: Vec<u8>,
+    status: scpi::StatusRegisters,
+    device_clears: usize,
+    statistics: scpi::Statistics,
 }
 
 impl ErrorCommands for TestInterface {
@@ -24,8 +42,37 @@ impl ErrorCommands for TestInterface {
 
 impl StandardCommands for TestInterface {}
 
-#[scpi::interface(StandardCommands, ErrorCommands)]
+impl StatusCommands for TestInterface {
+    fn status_registers(&mut self) -> &mut scpi::StatusRegisters {
+        &mut self.status
+    }
+}
+
+impl scpi::StatisticsCommands for TestInterface {
+    fn statistics(&mut self) -> &mut scpi::Statistics {
+        &mut self.statistics
+    }
+}
+
+impl FormatCommands for TestInterface {
+    fn data_format(&mut self) -> &mut scpi::DataFormat {
+        &mut self.data_format
+    }
+}
+
+#[scpi::interface(
+    StandardCommands,
+    ErrorCommands,
+    FormatCommands,
+    StatusCommands,
+    StatisticsCommands,
+    on_device_clear = abort_operations
+)]
 impl TestInterface {
+    fn abort_operations(&mut self) {
+        self.device_clears += 1;
+    }
+
     #[scpi(cmd = "*RST")]
     pub async fn rst(&mut self) -> Result<(), scpi::Error> {
         self.result = Some(TestResult::ResetOk);
@@ -65,6 +112,101 @@ impl TestInterface {
         Ok(a * b)
     }
 
+    #[scpi(cmd = "VALue:SERial?")]
+    pub async fn value_serial(&mut self, serial: u128) -> Result<u128, scpi::Error> {
+        Ok(serial)
+    }
+
+    #[scpi(cmd = "VALue:COUNt")]
+    pub async fn value_count(&mut self, _count: NonZeroU16) -> Result<(), scpi::Error> {
+        Ok(())
+    }
+
+    #[scpi(cmd = "TRIGger:DELay")]
+    pub async fn trigger_delay(&mut self, delay: Duration) -> Result<(), scpi::Error> {
+        self.delay = delay;
+        Ok(())
+    }
+
+    #[scpi(cmd = "TRIGger:DELay?")]
+    pub async fn trigger_delay_q(&mut self) -> Result<Duration, scpi::Error> {
+        Ok(self.delay)
+    }
+
+    #[scpi(cmd = "SOURce:FREQuency")]
+    pub async fn source_frequency(&mut self, freq: scpi::Scaled<f64>) -> Result<(), scpi::Error> {
+        self.frequency = freq.value();
+        Ok(())
+    }
+
+    #[scpi(cmd = "SENSe:AVERage:COUNt")]
+    pub async fn set_avg(&mut self, count: scpi::Bounded<u16, 1, 1024>) -> Result<(), scpi::Error> {
+        self.average = *count;
+        Ok(())
+    }
+
+    #[scpi(cmd = "SENSe:AVERage:COUNt?")]
+    pub async fn avg(&mut self) -> Result<scpi::Bounded<u16, 1, 1024>, scpi::Error> {
+        scpi::Bounded::new(self.average)
+    }
+
+    #[scpi(cmd = "WAVeform:DATA")]
+    pub async fn waveform_data(
+        &mut self, data: scpi::BlockData<'_, f32>,
+    ) -> Result<(), scpi::Error> {
+        self.waveform_sum = data.iter().sum();
+        Ok(())
+    }
+
+    #[scpi(cmd = "SYSTem:MAC")]
+    pub async fn system_mac(&mut self, mac: [u8; 6]) -> Result<(), scpi::Error> {
+        self.mac = mac;
+        Ok(())
+    }
+
+    #[scpi(cmd = "SYSTem:LABel")]
+    pub async fn set_label(&mut self, label: heapless::String<8>) -> Result<(), scpi::Error> {
+        self.label = label;
+        Ok(())
+    }
+
+    #[scpi(cmd = "SYSTem:LABel?")]
+    pub async fn label(&mut self) -> Result<heapless::String<8>, scpi::Error> {
+        Ok(self.label.clone())
+    }
+
+    #[scpi(cmd = "BLOB?")]
+    pub async fn blob(&mut self) -> Result<scpi::Arbitrary<'_>, scpi::Error> {
+        Ok(scpi::Arbitrary(&self.blob))
+    }
+
+    #[scpi(cmd = "SEQuence?")]
+    pub async fn sequence(
+        &mut self,
+    ) -> Result<scpi::ResponseIter<core::ops::Range<u32>>, scpi::Error> {
+        Ok(scpi::ResponseIter(0..2000))
+    }
+
+    #[scpi(cmd = "MEMory:DUMP?")]
+    pub async fn memory_dump(
+        &mut self, len: u16, response: &mut impl scpi::Write,
+    ) -> Result<(), scpi::Error> {
+        let mut chunk = [0u8; 64];
+        for offset in (0..len as usize).step_by(chunk.len()) {
+            let count = chunk.len().min(len as usize - offset);
+            for (i, b) in chunk[..count].iter_mut().enumerate() {
+                *b = b'0' + ((offset + i) % 10) as u8;
+            }
+            response.write_bytes(&chunk[..count]).await?;
+        }
+        Ok(())
+    }
+
+    #[scpi(cmd = "WAVeform:DATA?")]
+    pub async fn waveform_data_q(&mut self) -> Result<scpi::DataArray<'_, f32>, scpi::Error> {
+        Ok(scpi::DataArray::new(&[0.5, -1.0, 2.25], self.data_format))
+    }
+
     #[scpi(cmd = "ARGument:ARBitrary")]
     pub async fn argument_arbitrary(&mut self, _value: &'_ [u8]) -> Result<(), scpi::Error> {
         self.result = Some(TestResult::Arbitrary(_value.into()));
@@ -76,6 +218,17 @@ fn setup() -> (TestInterface, Vec<u8>) {
     let interface = TestInterface {
         errors: StaticErrorQueue::new(),
         result: None,
+        delay: Duration::ZERO,
+        frequency: 0.0,
+        average: 0,
+        waveform_sum: 0.0,
+        mac: [0; 6],
+        label: heapless::String::new(),
+        data_format: scpi::DataFormat::default(),
+        blob: (0..65536).map(|i| (i % 251) as u8).collect(),
+        status: scpi::StatusRegisters::new(),
+        device_clears: 0,
+        statistics: scpi::Statistics::new(),
     };
     (interface, Vec::new())
 }
@@ -159,6 +312,35 @@ async fn test_terminators() {
     assert_eq!(interface.run(b"*IDN?\n\r", &mut output).await, &[]);
 }
 
+#[test]
+fn test_chained_queries() {
+    let (mut interface, _) = setup();
+
+    scpi::assert_scpi!(
+        interface,
+        "*RST;*IDN?;SYST:ERR:COUNT?",
+        "\"MICROSCPI,TEST,1,1.0\";0"
+    );
+    assert_eq!(interface.result, Some(TestResult::IdnOk));
+
+    // Commands between queries do not produce separators.
+    scpi::assert_scpi!(
+        interface,
+        "SYST:VERS?;*RST;:SYST:ERR:COUNT?;*RST",
+        "1999.0;0"
+    );
+
+    // Each program message is terminated separately.
+    scpi::assert_scpi!(
+        interface,
+        "SYST:VERS?\n*RST\nSYST:VERS?;VERS?\r\n",
+        "1999.0\n1999.0;1999.0\n"
+    );
+
+    // Messages without queries have no response.
+    scpi::assert_scpi!(interface, "*RST;*RST", "");
+}
+
 #[tokio::test]
 async fn test_invalid_command() {
     let (mut interface, mut output) = setup();
@@ -260,21 +442,113 @@ async fn test_invalid_arguments() {
     assert_eq!(interface.errors.pop_error(), None);
 }
 
-#[tokio::test]
-async fn test_next_error() {
-    let (mut interface, mut output) = setup();
+#[test]
+fn test_next_error() {
+    let (mut interface, _) = setup();
 
     interface.errors.push_error(scpi::Error::SystemError);
+    scpi::assert_scpi!(interface, b"SYST:ERR:NEXT?", b"-310,\"System error\"");
+    scpi::assert_scpi!(interface, b"SYST:ERR:NEXT?", b"0,\"\"");
+}
 
-    interface.run(b"SYST:ERR:NEXT?\n", &mut output).await;
+#[test]
+fn test_all_errors() {
+    let (mut interface, _) = setup();
 
-    assert_eq!(output, b"-310,\"System error\"\n");
+    scpi::assert_scpi!(interface, b"SYST:ERR:ALL?", b"0,\"No error\"");
 
-    output.clear();
+    interface.errors.push_error(scpi::Error::SystemError);
+    interface.errors.push_error(scpi::Error::DataOutOfRange);
+    interface.errors.push_error(scpi::Error::InvalidCharacter);
+    scpi::assert_scpi!(
+        interface,
+        b"SYST:ERR:ALL?",
+        b"-310,\"System error\",-222,\"Data out of range\",-101,\"Invalid character\""
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+}
 
-    interface.run(b"SYST:ERR:NEXT?\n", &mut output).await;
+mod error_info {
+    use microscpi::{self as scpi, ErrorCommands, StaticErrorQueue};
 
-    assert_eq!(output, b"0,\"\"\n");
+    #[derive(Default)]
+    pub struct PowerSupply {
+        pub voltage: f64,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for PowerSupply {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    #[scpi::interface(ErrorCommands)]
+    impl PowerSupply {
+        #[scpi(cmd = "SOURce:VOLTage")]
+        fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+            self.voltage = voltage.min(10.0);
+            if voltage > 10.0 {
+                return Err(scpi::Error::DataOutOfRange.with_info("value clipped to 10V"));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_error_info() {
+    let mut interface = error_info::PowerSupply::default();
+
+    scpi::assert_scpi!(interface, "SOUR:VOLT 12", "");
+    assert_eq!(interface.voltage, 10.0);
+    scpi::assert_scpi!(
+        interface,
+        "SYST:ERR?",
+        "-222,\"Data out of range;value clipped to 10V\""
+    );
+
+    scpi::assert_scpi!(interface, "SOUR:VOLT 12;VOLT 5;VOLT 15", "");
+    scpi::assert_scpi!(
+        interface,
+        "SYST:ERR:ALL?",
+        "-222,\"Data out of range;value clipped to 10V\",-222,\"Data out of range;value clipped \
+         to 10V\""
+    );
+}
+
+mod dropped_errors {
+    use microscpi::{self as scpi, ErrorCommands, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct Device {
+        pub errors: StaticErrorQueue<2>,
+    }
+
+    impl ErrorCommands for Device {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, dropped_errors)]
+    impl Device {}
+}
+
+#[test]
+fn test_dropped_errors() {
+    let mut interface = dropped_errors::Device::default();
+
+    scpi::assert_scpi!(interface, "SYST:ERR:DROP?", "0");
+    for command in ["A", "B", "C", "D", "E"] {
+        scpi::assert_scpi!(interface, command, "");
+    }
+    scpi::assert_scpi!(interface, "SYST:ERR:DROP?;DROP?", "4;0");
+    scpi::assert_scpi!(
+        interface,
+        "SYST:ERR:ALL?",
+        "-113,\"Undefined header\",-350,\"Queue overflow\""
+    );
 }
 
 #[tokio::test]
@@ -307,3 +581,3832 @@ async fn test_empty_input() {
     let remaining = interface.run(b"  \n  \n\n  ", &mut output).await;
     assert_eq!(remaining, &[]);
 }
+
+#[tokio::test]
+async fn test_u128_round_trip() {
+    let (mut interface, mut output) = setup();
+
+    interface
+        .run(
+            b"VAL:SER? 340282366920938463463374607431768211455\n",
+            &mut output,
+        )
+        .await;
+    assert_eq!(output, b"340282366920938463463374607431768211455\n");
+
+    output.clear();
+
+    interface
+        .run(
+            b"VAL:SER? #HFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF\n",
+            &mut output,
+        )
+        .await;
+    assert_eq!(output, b"340282366920938463463374607431768211455\n");
+}
+
+#[tokio::test]
+async fn test_non_zero_argument() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"VAL:COUN 0\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::DataOutOfRange)
+    );
+
+    interface.run(b"VAL:COUN 12\n", &mut output).await;
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[tokio::test]
+async fn test_duration_argument() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"TRIG:DEL 10ms\n", &mut output).await;
+    assert_eq!(interface.delay, Duration::from_millis(10));
+
+    interface.run(b":TRIG:DEL 2.5 S\n", &mut output).await;
+    assert_eq!(interface.delay, Duration::from_millis(2500));
+
+    interface.run(b"TRIG:DEL 1.5ms;DEL?\n", &mut output).await;
+    assert_eq!(output, b"1.5E-3\n");
+
+    interface.run(b"TRIG:DEL 3 FOO\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidSuffix)
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[tokio::test]
+async fn test_scaled_argument() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"SOUR:FREQ 2.2M\n", &mut output).await;
+    assert_eq!(interface.frequency, 2.2e6);
+
+    interface.run(b"SOUR:FREQ 1k\n", &mut output).await;
+    assert_eq!(interface.frequency, 1e3);
+
+    interface.run(b"SOUR:FREQ 1x\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidSuffix)
+    );
+    assert_eq!(interface.frequency, 1e3);
+}
+
+#[tokio::test]
+async fn test_bounded_argument() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"SENS:AVER:COUN 1\n", &mut output).await;
+    assert_eq!(interface.average, 1);
+
+    interface.run(b"SENS:AVER:COUN 1024\n", &mut output).await;
+    assert_eq!(interface.average, 1024);
+    assert_eq!(interface.errors.pop_error(), None);
+
+    interface.run(b"SENS:AVER:COUN 0\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::DataOutOfRange)
+    );
+
+    interface.run(b"SENS:AVER:COUN 1025\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::DataOutOfRange)
+    );
+    assert_eq!(interface.average, 1024);
+
+    interface.run(b"SENS:AVER:COUN?\n", &mut output).await;
+    assert_eq!(output, b"1024\n");
+}
+
+#[tokio::test]
+async fn test_block_data_argument() {
+    let (mut interface, mut output) = setup();
+
+    let mut input = b"FORM:BORD SWAP;:WAV:DATA #44000".to_vec();
+    for i in 0..1000 {
+        input.extend_from_slice(&(i as f32 * 0.5).to_le_bytes());
+    }
+    input.push(b'\n');
+
+    interface.run(&input, &mut output).await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.waveform_sum, 249750.0);
+
+    interface.run(b"WAV:DATA #15abcde\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidBlockData)
+    );
+}
+
+#[tokio::test]
+async fn test_block_data_byte_order() {
+    let (mut interface, mut output) = setup();
+
+    let mut input = b"FORM:BORD SWAP;:WAV:DATA #212".to_vec();
+    for value in [0.5f32, -1.0, 2.25] {
+        input.extend_from_slice(&value.to_le_bytes());
+    }
+    input.push(b'\n');
+
+    interface.run(&input, &mut output).await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.waveform_sum, 1.75);
+
+    let mut input = b"FORM:BORD NORM;:WAV:DATA #212".to_vec();
+    for value in [0.5f32, -1.0, 2.25] {
+        input.extend_from_slice(&value.to_be_bytes());
+    }
+    input.push(b'\n');
+
+    interface.run(&input, &mut output).await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.waveform_sum, 1.75);
+}
+
+mod uploads {
+    use microscpi::{self as scpi, DataFormat, Error, ErrorQueue, StaticErrorQueue, Value};
+
+    pub type Samples<'a> = scpi::BlockData<'a, u16>;
+
+    /// Unrelated to [scpi::BlockData], only sharing its name.
+    pub struct BlockData(pub usize);
+
+    impl TryInto<BlockData> for &Value<'_> {
+        type Error = Error;
+
+        fn try_into(self) -> Result<BlockData, Error> {
+            match self {
+                Value::Arbitrary(data) => Ok(BlockData(data.len())),
+                _ => Err(Error::DataTypeError),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Uploads {
+        pub errors: StaticErrorQueue<10>,
+        pub samples: Vec<u16>,
+    }
+
+    impl scpi::ErrorCommands for Uploads {
+        fn error_queue(&mut self) -> &mut impl ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    #[scpi::interface(ErrorCommands)]
+    impl Uploads {
+        #[scpi(cmd = "SAMPles")]
+        fn samples(&mut self, samples: Samples<'_>) -> Result<(), Error> {
+            self.samples = samples.iter().collect();
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    pub struct FormattedUploads {
+        pub errors: StaticErrorQueue<10>,
+        pub data_format: DataFormat,
+        pub samples: Vec<u16>,
+        pub length: usize,
+    }
+
+    impl scpi::ErrorCommands for FormattedUploads {
+        fn error_queue(&mut self) -> &mut impl ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl scpi::FormatCommands for FormattedUploads {
+        fn data_format(&mut self) -> &mut DataFormat {
+            &mut self.data_format
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, FormatCommands)]
+    impl FormattedUploads {
+        #[scpi(cmd = "SAMPles")]
+        fn samples(&mut self, samples: Option<Samples<'_>>) -> Result<(), Error> {
+            self.samples = samples.iter().flat_map(|samples| samples.iter()).collect();
+            Ok(())
+        }
+
+        #[scpi(cmd = "LENGth")]
+        fn length(&mut self, block: BlockData) -> Result<(), Error> {
+            self.length = block.0;
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_block_data_default_byte_order() {
+    let mut interface = uploads::Uploads::default();
+    let mut output = Vec::new();
+
+    // Without FormatCommands the block data keeps its little-endian default.
+    interface
+        .run(b"SAMP #14\x01\x00\x00\x01\n", &mut output)
+        .await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.samples, [1, 256]);
+
+    let mut interface = uploads::FormattedUploads::default();
+
+    interface
+        .run(b"SAMP #14\x01\x00\x00\x01;LENG #13abc\n", &mut output)
+        .await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.samples, [256, 1]);
+    assert_eq!(interface.length, 3);
+
+    interface
+        .run(b"FORM:BORD SWAP;:SAMP #14\x01\x00\x00\x01\n", &mut output)
+        .await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.samples, [1, 256]);
+}
+
+#[tokio::test]
+async fn test_byte_array_argument() {
+    let (mut interface, mut output) = setup();
+
+    interface
+        .run(b"SYST:MAC #16\x02\x00\x5e\x10\x20\x30\n", &mut output)
+        .await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.mac, [0x02, 0x00, 0x5e, 0x10, 0x20, 0x30]);
+
+    interface
+        .run(b"SYST:MAC #15\x02\x00\x5e\x10\x20\n", &mut output)
+        .await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidBlockData)
+    );
+
+    interface
+        .run(b"SYST:MAC #17\x02\x00\x5e\x10\x20\x30\x40\n", &mut output)
+        .await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidBlockData)
+    );
+    assert_eq!(interface.mac, [0x02, 0x00, 0x5e, 0x10, 0x20, 0x30]);
+}
+
+#[tokio::test]
+async fn test_heapless_string_argument() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"SYST:LAB \"ABCDEFGH\"\n", &mut output).await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.label, "ABCDEFGH");
+
+    interface
+        .run(b"SYST:LAB \"ABCDEFGHI\"\n", &mut output)
+        .await;
+    assert_eq!(interface.errors.pop_error(), Some(scpi::Error::TooMuchData));
+    assert_eq!(interface.label, "ABCDEFGH");
+}
+
+#[tokio::test]
+async fn test_heapless_string_round_trip() {
+    let (mut interface, mut output) = setup();
+
+    interface
+        .run(b"SYST:LAB \"A\"\"B\"\"\";LAB?\n", &mut output)
+        .await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.label, "A\"B\"");
+    assert_eq!(output, b"\"A\"\"B\"\"\"\n");
+
+    // The length is checked after unescaping the quotes.
+    output.clear();
+    interface.run(b"SYST:LAB 'ABC''DEFG'\n", &mut output).await;
+    assert_eq!(interface.errors.pop_error(), None);
+    assert_eq!(interface.label, "ABC'DEFG");
+}
+
+#[tokio::test]
+async fn test_format_data() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"FORM?\n", &mut output).await;
+    assert_eq!(output, b"ASC,0\n");
+
+    output.clear();
+    interface.run(b"WAV:DATA?\n", &mut output).await;
+    assert_eq!(output, b"0.5,-1,2.25\n");
+
+    output.clear();
+    interface.run(b"FORM:DATA REAL;DATA?\n", &mut output).await;
+    assert_eq!(output, b"REAL,32\n");
+
+    output.clear();
+    interface.run(b"WAV:DATA?\n", &mut output).await;
+    assert_eq!(
+        output,
+        b"#212\x3f\x00\x00\x00\xbf\x80\x00\x00\x40\x10\x00\x00\n"
+    );
+
+    output.clear();
+    interface
+        .run(b"FORMAT INT,8;:WAV:DATA?\n", &mut output)
+        .await;
+    assert_eq!(output, b"#13\x00\xff\x02\n");
+    assert_eq!(interface.errors.pop_error(), None);
+
+    interface.run(b"FORM REAL,16\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::IllegalParameterValue)
+    );
+
+    interface.run(b"FORM\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UnexpectedNumberOfParameters)
+    );
+
+    interface.run(b"FORM REAL,32,1\n", &mut output).await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UnexpectedNumberOfParameters)
+    );
+}
+
+#[tokio::test]
+async fn test_format_border() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"FORM:BORD?\n", &mut output).await;
+    assert_eq!(output, b"NORM\n");
+
+    output.clear();
+    interface.run(b"FORM REAL;:WAV:DATA?\n", &mut output).await;
+    let normal = output.clone();
+
+    output.clear();
+    interface
+        .run(b"FORM:BORD SWAP;BORD?;:WAV:DATA?\n", &mut output)
+        .await;
+    assert_eq!(&output[..5], b"SWAP;");
+    let swapped = &output[5..];
+
+    // The block header and the terminator are not affected.
+    assert_eq!(&swapped[..4], &normal[..4]);
+    assert_eq!(swapped.last(), normal.last());
+
+    // Each element is reversed separately.
+    for (a, b) in swapped[4..16].chunks(4).zip(normal[4..16].chunks(4)) {
+        assert!(a.iter().eq(b.iter().rev()));
+    }
+
+    output.clear();
+    interface.run(b"FORM:DATA?;BORD?\n", &mut output).await;
+    assert_eq!(output, b"REAL,32;SWAP\n");
+}
+
+#[tokio::test]
+async fn test_fmt_writer_output() {
+    let (mut interface, _) = setup();
+    let mut output = scpi::FmtWriter(heapless::String::<64>::new());
+
+    interface.run(b"SYST:VERS?\n", &mut output).await;
+    assert_eq!(output.0, "1999.0\n");
+}
+
+#[tokio::test]
+async fn test_run_dyn() {
+    let (mut interface, _) = setup();
+
+    let mut vec_output = Vec::new();
+    interface.run_dyn(b"SYST:VERS?\n", &mut vec_output).await;
+    assert_eq!(vec_output, b"1999.0\n");
+
+    let mut heapless_output: heapless::Vec<u8, 32> = heapless::Vec::new();
+    interface
+        .run_dyn(b"SYST:VERS?;:SYST:ERR?\n", &mut heapless_output)
+        .await;
+    assert_eq!(heapless_output, b"1999.0;0,\"\"\n");
+
+    let mut buf = [0u8; 4];
+    let mut slice_output = scpi::SliceWriter::new(&mut buf);
+    interface.run_dyn(b"SYST:VERS?\n", &mut slice_output).await;
+    interface.run(b"SYST:ERR?\n", &mut vec_output).await;
+    assert_eq!(vec_output, b"1999.0\n-223,\"Too much data\"\n");
+}
+
+#[tokio::test]
+async fn test_custom_error() {
+    let (mut interface, mut output) = setup();
+
+    interface
+        .errors
+        .push_error(scpi::Error::Custom(373, "Oven cold"));
+    interface.run(b"SYST:ERR:NEXT?\n", &mut output).await;
+    assert_eq!(output, b"373,\"Oven cold\"\n");
+}
+
+#[tokio::test]
+async fn test_process_streams_large_response() {
+    let (mut interface, _) = setup();
+    let mut adapter = MockAdapter::new([&b"BLO"[..], b"B?\nSYST:VERS?\n"]);
+
+    assert_eq!(interface.process::<256, _>(&mut adapter).await, Ok(()));
+
+    let mut expected = b"#565536".to_vec();
+    expected.extend_from_slice(&interface.blob);
+    expected.extend_from_slice(b"\n1999.0\n");
+
+    assert_eq!(adapter.output(), expected);
+    assert!(adapter.writes().all(|data| data.len() <= 256));
+    assert_eq!(adapter.flushes(), 2);
+}
+
+#[tokio::test]
+async fn test_process_streams_formatted_response() {
+    let (mut interface, mut output) = setup();
+    interface.run(b"SEQ?\n", &mut output).await;
+
+    let mut adapter = MockAdapter::new([b"SEQ?\n"]);
+
+    assert_eq!(interface.process::<16, _>(&mut adapter).await, Ok(()));
+    assert_eq!(adapter.output(), output);
+    assert!(adapter.writes().all(|data| data.len() <= 16));
+}
+
+#[tokio::test]
+async fn test_process_chunks_response_larger_than_buffer() {
+    let (mut interface, mut output) = setup();
+    interface
+        .run(b"MEM:DUMP? 4096\nSYST:VERS?\n", &mut output)
+        .await;
+
+    let mut adapter = MockAdapter::new([b"MEM:DUMP? 4096\nSYST:VERS?\n"]);
+
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert_eq!(adapter.output(), output);
+    assert!(adapter.writes().all(|data| data.len() <= 64));
+
+    // The adapter is flushed once at the end of each response message.
+    let mut written = 0;
+    let flushed_at: Vec<usize> = adapter
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            MockEvent::Write(data) => {
+                written += data.len();
+                None
+            }
+            MockEvent::Flush => Some(written),
+            MockEvent::Discard => None,
+        })
+        .collect();
+    assert_eq!(flushed_at, [4097, 4097 + 7]);
+}
+
+#[tokio::test]
+async fn test_process_input_buffer_overrun() {
+    let (mut interface, _) = setup();
+    let mut input = vec![b"SYSTem:A".repeat(4); 4];
+    input.push(b"A\n*IDN?\n".to_vec());
+
+    let mut adapter = MockAdapter::new(input);
+
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert_eq!(adapter.output(), b"\"MICROSCPI,TEST,1,1.0\"\n");
+
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InputBufferOverrun)
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[tokio::test]
+async fn test_process_step() {
+    let (mut interface, _) = setup();
+    let mut state = scpi::ProcessState::<64>::new();
+    let mut adapter = MockAdapter::new([&b"*ID"[..], b"N?\nSYST:VERS?\n*RST\n"]);
+
+    // An incomplete command is buffered.
+    interface
+        .process_step(&mut adapter, &mut state)
+        .await
+        .unwrap();
+    assert!(adapter.output().is_empty());
+
+    // Dropping the step while it waits for input keeps the buffered input.
+    adapter = adapter.pending_reads(true);
+    tokio::select! {
+        biased;
+        _ = interface.process_step(&mut adapter, &mut state) => panic!("Read is not pending"),
+        _ = core::future::ready(()) => {}
+    }
+    assert_eq!(adapter.remaining_input(), 1);
+
+    interface
+        .process_step(&mut adapter, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(adapter.output(), b"\"MICROSCPI,TEST,1,1.0\"\n1999.0\n");
+    assert_eq!(interface.result, Some(TestResult::ResetOk));
+
+    // The end of the input closes the state.
+    interface
+        .process_step(&mut adapter, &mut state)
+        .await
+        .unwrap();
+    assert!(state.is_closed());
+
+    // Errors of the adapter are returned.
+    let mut adapter = MockAdapter::new([b"*IDN?\n"]).fail_after(0);
+    let mut state = scpi::ProcessState::<64>::new();
+    assert_eq!(
+        interface.process_step(&mut adapter, &mut state).await,
+        Err(scpi::testing::MockError)
+    );
+}
+
+#[tokio::test]
+async fn test_process_state_reset_input() {
+    let (mut interface, _) = setup();
+    let mut state = scpi::ProcessState::<64>::new();
+    let mut adapter = MockAdapter::new([&b"*ID"[..], b"*IDN?\n"]);
+
+    interface
+        .process_step(&mut adapter, &mut state)
+        .await
+        .unwrap();
+
+    // The idle timer fires before the next input arrives.
+    adapter = adapter.pending_reads(true);
+    let timeout = core::future::ready(());
+    tokio::select! {
+        biased;
+        _ = interface.process_step(&mut adapter, &mut state) => panic!("Read is not pending"),
+        _ = timeout => {
+            assert!(state.reset_input());
+            scpi::ErrorHandler::handle_error(&mut interface, scpi::Error::InputBufferOverrun);
+        }
+    }
+    assert!(!state.reset_input());
+
+    interface
+        .process_step(&mut adapter, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(adapter.output(), b"\"MICROSCPI,TEST,1,1.0\"\n");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InputBufferOverrun)
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[tokio::test]
+async fn test_process_console() {
+    let (mut interface, _) = setup();
+    let options = scpi::ConsoleOptions {
+        echo: true,
+        prompt: "scpi> ",
+        backspace: true,
+    };
+    let mut adapter = MockAdapter::new([&b"*IDX"[..], b"\x08N?\n"]);
+
+    assert_eq!(
+        interface
+            .process_console::<64, _>(&mut adapter, options)
+            .await,
+        Ok(())
+    );
+    assert_eq!(
+        adapter.output(),
+        b"scpi> *IDX\x08 \x08N?\n\"MICROSCPI,TEST,1,1.0\"\nscpi> "
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+
+    // Without backspace handling, the backspace character is parsed.
+    let mut adapter = MockAdapter::new([&b"*IDX"[..], b"\x08N?\n"]);
+
+    assert_eq!(
+        interface
+            .process_console::<64, _>(&mut adapter, Default::default())
+            .await,
+        Ok(())
+    );
+    assert!(adapter.output().is_empty());
+    assert!(interface.errors.pop_error().is_some());
+}
+
+#[tokio::test]
+async fn test_device_clear() {
+    let (mut interface, _) = setup();
+    let mut state = scpi::ProcessState::<64>::new();
+    let mut adapter =
+        MockAdapter::new([&b"SYST:ERR:NEXT?\nVAL:"[..], b"*IDN?\n"]).hold_output(true);
+    interface.errors.push_error(scpi::Error::SystemError);
+
+    interface
+        .process_step(&mut adapter, &mut state)
+        .await
+        .unwrap();
+    assert!(!adapter.output().is_empty());
+    interface.errors.push_error(scpi::Error::SystemError);
+
+    interface
+        .device_clear(&mut adapter, &mut state)
+        .await
+        .unwrap();
+    assert!(adapter.output().is_empty());
+    assert!(!interface.output_pending());
+    assert_eq!(interface.device_clears, 1);
+
+    // The partial command is discarded and the response is not interrupted.
+    interface
+        .process_step(&mut adapter, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(adapter.output(), b"\"MICROSCPI,TEST,1,1.0\"\n");
+
+    // The error queue is not cleared.
+    assert_eq!(interface.errors.pop_error(), Some(scpi::Error::SystemError));
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+/// Receiving half of a transport.
+struct MockRx(Vec<Vec<u8>>);
+
+impl scpi::AdapterErrorType for MockRx {
+    type Error = ();
+}
+
+impl scpi::AdapterRead for MockRx {
+    async fn read(&mut self, dst: &mut [u8]) -> Result<usize, ()> {
+        let chunk = self.0.pop().unwrap_or_default();
+        dst[..chunk.len()].copy_from_slice(&chunk);
+        Ok(chunk.len())
+    }
+}
+
+/// Transmitting half of a transport.
+struct MockTx(Vec<u8>);
+
+impl scpi::AdapterErrorType for MockTx {
+    type Error = ();
+}
+
+impl scpi::AdapterWrite for MockTx {
+    async fn write(&mut self, src: &[u8]) -> Result<(), ()> {
+        self.0.extend_from_slice(src);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), ()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_process_split() {
+    let (mut interface, _) = setup();
+    let mut rx = MockRx(vec![b"LUE:STR?\n".to_vec(), b"*IDN?;:VA".to_vec()]);
+    let mut tx = MockTx(Vec::new());
+
+    assert_eq!(
+        interface.process_split::<64, _, _>(&mut rx, &mut tx).await,
+        Ok(())
+    );
+    assert_eq!(tx.0, b"\"MICROSCPI,TEST,1,1.0\";\"Hello World\"\n");
+}
+
+#[tokio::test]
+async fn test_run_report() {
+    let (mut interface, mut output) = setup();
+
+    let report = interface
+        .run_report(
+            b"*RST;*IDN?;SENS:AVER:COUN 0;:SYST:ERR:COUNT?\n",
+            &mut output,
+        )
+        .await;
+    assert_eq!(report.commands(), 3);
+    assert_eq!(report.queries(), 2);
+    assert_eq!(report.errors(), 1);
+    assert!(report.responded());
+    assert!(!report.incomplete());
+    assert_eq!(report.remaining(), b"");
+
+    output.clear();
+    let report = interface.run_report(b"*RST\nSYST:LAB ", &mut output).await;
+    assert_eq!(report.commands(), 1);
+    assert!(!report.responded());
+    assert!(report.incomplete());
+    assert_eq!(report.remaining(), b"SYST:LAB ");
+    assert_eq!(output, b"");
+}
+
+#[tokio::test]
+async fn test_run_single() {
+    let (mut interface, mut output) = setup();
+
+    let remaining = interface
+        .run_single(b"*RST;*IDN?\nSYST:VERS?\n", &mut output)
+        .await;
+    assert_eq!(remaining, Ok(Some(&b"SYST:VERS?\n"[..])));
+    assert_eq!(output, b"\"MICROSCPI,TEST,1,1.0\"\n");
+
+    // Incomplete messages are not executed.
+    output.clear();
+    interface.result = None;
+    let remaining = interface.run_single(b"*RST;*IDN?", &mut output).await;
+    assert_eq!(remaining, Ok(None));
+    assert_eq!(interface.result, None);
+    assert_eq!(output, b"");
+
+    // Empty lines before the message are skipped.
+    let remaining = interface.run_single(b"\n*RST\n", &mut output).await;
+    assert_eq!(remaining, Ok(Some(&b""[..])));
+    assert_eq!(interface.result, Some(TestResult::ResetOk));
+}
+
+#[tokio::test]
+async fn test_run_single_parse_error() {
+    let (mut interface, mut output) = setup();
+
+    // Nothing of a message that cannot be parsed is executed.
+    let remaining = interface
+        .run_single(b"*RST;*IDN?;UNKNown\n*IDN?\n", &mut output)
+        .await;
+    assert_eq!(remaining, Err(scpi::Error::UndefinedHeader));
+    assert_eq!(interface.result, None);
+    assert_eq!(output, b"");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[tokio::test]
+async fn test_writer_handler() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"MEM:DUMP? 4096\n", &mut output).await;
+    assert_eq!(output.len(), 4097);
+    assert!(output[..4096]
+        .iter()
+        .enumerate()
+        .all(|(i, b)| *b == b'0' + (i % 10) as u8));
+    assert_eq!(output.last(), Some(&b'\n'));
+
+    output.clear();
+    interface.run(b"MEM:DUMP? 3;DUMP? 2\n", &mut output).await;
+    assert_eq!(output, b"012;01\n");
+
+    output.clear();
+    interface.run(b"MEM:DUMP?\n", &mut output).await;
+    assert_eq!(output, b"");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UnexpectedNumberOfParameters)
+    );
+}
+
+#[tokio::test]
+async fn test_lookup() {
+    let (interface, _) = setup();
+
+    let (id, query) = interface.lookup("SYSTem:ERRor:NEXT?").unwrap();
+    assert!(query);
+    assert_eq!(interface.lookup("syst:err:next?"), Some((id, true)));
+    assert_eq!(interface.lookup(":SYST:ERR?"), Some((id, true)));
+
+    assert_eq!(
+        interface.lookup("TEST:A").map(|(_, query)| query),
+        Some(false)
+    );
+    assert_eq!(interface.lookup("SYSTem:TeST:A"), interface.lookup("TST:A"));
+    assert!(interface.lookup("*IDN?").is_some());
+
+    assert_eq!(interface.lookup("SYST:ERR"), None);
+    assert_eq!(interface.lookup("SYST:FOO?"), None);
+    assert_eq!(interface.lookup("SYST:ERR:NEXT:MORE?"), None);
+    assert_eq!(interface.lookup(""), None);
+}
+
+#[tokio::test]
+async fn test_execute_str() {
+    let (mut interface, mut output) = setup();
+
+    let setup = ["SOUR:FREQ 2k", "FOO:BAR 1", "SENS:AVER:COUN 16;COUN?\n"];
+    let mut results = Vec::new();
+    for line in setup {
+        results.push(interface.execute_str(line, &mut output).await);
+    }
+
+    assert_eq!(results, [Ok(()), Err(scpi::Error::UndefinedHeader), Ok(())]);
+    assert_eq!(interface.frequency, 2000.0);
+    assert_eq!(interface.average, 16);
+    assert_eq!(output, b"16");
+
+    // Errors are not passed to the error handler.
+    assert_eq!(interface.errors.pop_error(), None);
+
+    // Commands without a terminator are limited in length.
+    let command = format!("SOUR:FREQ 3k;{}", ";".repeat(scpi::MAX_EXECUTE_STR_LENGTH));
+    assert_eq!(
+        interface.execute_str(&command, &mut output).await,
+        Err(scpi::Error::InputBufferOverrun)
+    );
+    assert_eq!(interface.frequency, 2000.0);
+
+    let command = format!("SOUR:FREQ 3k{}\n", " ".repeat(scpi::MAX_EXECUTE_STR_LENGTH));
+    assert_eq!(interface.execute_str(&command, &mut output).await, Ok(()));
+    assert_eq!(interface.frequency, 3000.0);
+}
+
+#[tokio::test]
+async fn test_query_interrupted() {
+    let (mut interface, _) = setup();
+    let mut adapter = MockAdapter::new([b"*IDN?\nSYST:VERS?\n"]).hold_output(true);
+
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+
+    assert_eq!(adapter.output(), b"1999.0\n");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::QueryInterrupted)
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+
+    // Responses that are read do not cause an error.
+    let mut adapter = MockAdapter::new([b"*IDN?\nSYST:VERS?\n"]);
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[tokio::test]
+async fn test_query_unterminated() {
+    let (mut interface, _) = setup();
+    let mut adapter = MockAdapter::new([b"*IDN?\nSYST:VERS?"]);
+
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert_eq!(adapter.output(), b"\"MICROSCPI,TEST,1,1.0\"\n");
+    scpi::assert_scpi!(interface, "SYST:ERR?", "-420,\"Query UNTERMINATED\"");
+
+    // An unterminated command without a query is not reported.
+    let mut adapter = MockAdapter::new([b"*RST"]);
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert_eq!(interface.errors.pop_error(), None);
+
+    // Neither is a question mark within string data.
+    let mut adapter = MockAdapter::new([b"SYST:LAB \"A?\""]);
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[tokio::test]
+async fn test_query_deadlocked() {
+    let (mut interface, _) = setup();
+    let mut input = vec![b"*IDN?\n".to_vec()];
+    input.extend(vec![b"SYSTem:A".repeat(4); 4]);
+    input.push(b"A\nSYST:VERS?\n".to_vec());
+
+    let mut adapter = MockAdapter::new(input).hold_output(true);
+
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert_eq!(adapter.output(), b"1999.0\n");
+    assert_eq!(
+        adapter
+            .events()
+            .iter()
+            .filter(|event| **event == MockEvent::Discard)
+            .count(),
+        1
+    );
+    scpi::assert_scpi!(interface, "SYST:ERR?", "-430,\"Query DEADLOCKED\"");
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[tokio::test]
+async fn test_status_byte_message_available() {
+    let (mut interface, _) = setup();
+    let mut adapter = MockAdapter::new([&b"*STB?\n"[..], b"*IDN?;*STB?\n", b"*STB?\n"]);
+
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert_eq!(adapter.output(), b"0\n\"MICROSCPI,TEST,1,1.0\";16\n0\n");
+
+    // A response that has not been read by the controller sets MAV.
+    let mut adapter = MockAdapter::new([b"*IDN?\n"]).hold_output(true);
+
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+    assert!(interface.output_pending());
+
+    let mut output = Vec::new();
+    interface.run(b"*STB?\n", &mut output).await;
+    assert_eq!(output, b"16\n");
+    assert!(!interface.output_pending());
+}
+
+#[tokio::test]
+async fn test_service_request_enable() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"*SRE 255;*SRE?\n", &mut output).await;
+    assert_eq!(output, b"191\n");
+
+    // The MSS bit summarizes the enabled bits of the status byte.
+    interface.run(b"*SRE 16\n", &mut output).await;
+    let mut adapter = MockAdapter::new([b"*IDN?\n"]).hold_output(true);
+    assert_eq!(interface.process::<64, _>(&mut adapter).await, Ok(()));
+
+    output.clear();
+    interface.run(b"*STB?\n", &mut output).await;
+    assert_eq!(output, b"80\n");
+
+    output.clear();
+    interface.run(b"*SRE 4;*STB?\n", &mut output).await;
+    assert_eq!(output, b"0\n");
+    assert_eq!(interface.status.status_byte_enable(), 4);
+}
+
+#[tokio::test]
+async fn test_event_status() {
+    let (mut interface, mut output) = setup();
+
+    interface
+        .run(b"*ESE 36;*ESE?;*ESR?;*STB?\n", &mut output)
+        .await;
+    // The preceding responses of the message are reported by MAV.
+    assert_eq!(output, b"36;0;16\n");
+
+    interface.status.set_event(scpi::EventStatus::COMMAND_ERROR);
+
+    // The enabled event sets ESB, which is enabled for a service request.
+    output.clear();
+    interface.run(b"*SRE 32;*STB?\n", &mut output).await;
+    assert_eq!(output, b"96\n");
+
+    // Reading the event status register clears it.
+    output.clear();
+    interface.run(b"*ESR?;*ESR?;*STB?\n", &mut output).await;
+    assert_eq!(output, b"32;0;16\n");
+}
+
+#[tokio::test]
+async fn test_event_status_unmasked() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"*ESE 0\n", &mut output).await;
+    interface.status.set_event(scpi::EventStatus::COMMAND_ERROR);
+    interface.status.set_event(scpi::EventStatus::QUERY_ERROR);
+
+    // The enable register only masks ESB, not the response to *ESR?.
+    interface.run(b"*STB?\n", &mut output).await;
+    interface.run(b"*ESR?;*ESR?\n", &mut output).await;
+    assert_eq!(output, b"0\n36;0\n");
+}
+
+#[tokio::test]
+async fn test_error_events() {
+    let (mut interface, mut output) = setup();
+
+    // A syntax error sets CME, which is summarized by ESB.
+    interface
+        .run(b"*ESE 255;*CLS\nVOLT 1 2 3\n", &mut output)
+        .await;
+    interface.run(b"*STB?;*ESR?;*ESR?\n", &mut output).await;
+    assert_eq!(output, b"36;32;0\n");
+
+    output.clear();
+    interface.run(b"UNKNOWN\n", &mut output).await;
+    interface.run(b"SYST:ERR:COUN?;*ESR?\n", &mut output).await;
+    assert_eq!(output, b"2;32\n");
+
+    // *CLS clears the event status register and the error queue.
+    output.clear();
+    interface.run(b"UNKNOWN\n", &mut output).await;
+    interface.run(b"*CLS\n", &mut output).await;
+    interface
+        .run(b"*STB?;*ESR?;SYST:ERR:COUN?\n", &mut output)
+        .await;
+    assert_eq!(output, b"0;0;0\n");
+}
+
+#[test]
+fn test_blocking() {
+    let (mut interface, mut output) = setup();
+
+    interface.run_blocking(b"*IDN?;:SYST:VERS?\n", &mut output);
+    assert_eq!(output, b"\"MICROSCPI,TEST,1,1.0\";1999.0\n");
+
+    let input: &[u8] = b"MATH:OPERATION:MULT? 6,7\n*RST\nSYST:ERR:COUN?\n";
+    let mut adapter = scpi::BlockingAdapter::new(input, Vec::new());
+    interface.process_blocking::<64, _>(&mut adapter).unwrap();
+
+    let (_, output) = adapter.into_inner();
+    assert_eq!(output, b"42\n0\n");
+    assert_eq!(interface.result, Some(TestResult::ResetOk));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_tokio_adapter() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (mut interface, _) = setup();
+    let (mut client, server) = tokio::io::duplex(64);
+    let mut adapter = scpi::TokioAdapter::new(server);
+
+    let controller = async move {
+        let mut response = Vec::new();
+
+        client.write_all(b"*IDN?\n").await.unwrap();
+        while !response.ends_with(b"\n") {
+            response.push(client.read_u8().await.unwrap());
+        }
+        assert_eq!(response, b"\"MICROSCPI,TEST,1,1.0\"\n");
+
+        // The command is split across two writes.
+        response.clear();
+        client.write_all(b"SYST:").await.unwrap();
+        tokio::task::yield_now().await;
+        client.write_all(b"VERS?\n").await.unwrap();
+        while !response.ends_with(b"\n") {
+            response.push(client.read_u8().await.unwrap());
+        }
+        assert_eq!(response, b"1999.0\n");
+    };
+
+    // Closing the stream ends the processing.
+    let (result, ()) = tokio::join!(interface.process::<64, _>(&mut adapter), controller);
+    assert!(result.is_ok());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_net_serve() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+
+    async fn query(stream: &mut TcpStream, command: &[u8]) -> Vec<u8> {
+        let mut response = Vec::new();
+        stream.write_all(command).await.unwrap();
+        while !response.ends_with(b"\n") {
+            response.push(stream.read_u8().await.unwrap());
+        }
+        response
+    }
+
+    let controller = async {
+        let mut first = TcpStream::connect(address).await.unwrap();
+        let mut second = TcpStream::connect(address).await.unwrap();
+
+        first.write_all(b"UNKNOWN\n").await.unwrap();
+        assert_eq!(query(&mut first, b"SYST:ERR:COUNT?\n").await, b"1\n");
+
+        // Each connection has its own error queue.
+        assert_eq!(query(&mut second, b"SYST:ERR:COUNT?\n").await, b"0\n");
+        drop(first);
+        assert_eq!(
+            query(&mut second, b"*IDN?\n").await,
+            b"\"MICROSCPI,TEST,1,1.0\"\n"
+        );
+    };
+
+    tokio::select! {
+        result = scpi::net::serve::<64, _, _>(listener, || setup().0) => panic!("{result:?}"),
+        _ = controller => {}
+    }
+}
+
+#[tokio::test]
+async fn test_statistics() {
+    let (mut interface, mut output) = setup();
+
+    interface.run(b"*RST;*IDN?\n", &mut output).await;
+    interface.run(b"UNKNOWN\n", &mut output).await;
+    interface.run(b"MATH:OP:MULT? 2\n", &mut output).await;
+    interface.run(b"syst:err:coun?\n", &mut output).await;
+    interface.run(b"SYST:ERR:NEXT?\n", &mut output).await;
+    interface
+        .run(b"VAL:COUN 0;:SYST:VERS?\n", &mut output)
+        .await;
+    assert_eq!(interface.statistics.commands(), 5);
+    assert_eq!(interface.statistics.errors(), 3);
+
+    // The statistics queries are counted as well.
+    output.clear();
+    interface
+        .run(
+            b"SYST:STAT:COMM?;ERR?;LAST?\nSYST:STAT:LAST?\n",
+            &mut output,
+        )
+        .await;
+    assert_eq!(
+        output,
+        b"5;3;\"SYSTem:STATistics:ERRors?\"\n\"SYSTem:STATistics:LAST?\"\n"
+    );
+}
+
+mod psu {
+    use microscpi::{self as scpi, Error};
+
+    #[derive(Default)]
+    pub struct PsuInterface {
+        pub voltage: f64,
+        pub current: f64,
+    }
+
+    impl scpi::ErrorHandler for PsuInterface {
+        fn handle_error(&mut self, _error: Error) {
+            unreachable!("Errors are handled by the parent interface");
+        }
+    }
+
+    #[scpi::interface]
+    impl PsuInterface {
+        #[scpi(cmd = "VOLTage")]
+        fn set_voltage(&mut self, voltage: f64) -> Result<(), Error> {
+            if voltage > 30.0 {
+                return Err(Error::DataOutOfRange);
+            }
+            self.voltage = voltage;
+            Ok(())
+        }
+
+        #[scpi(cmd = "VOLTage?")]
+        fn voltage(&mut self) -> Result<f64, Error> {
+            Ok(self.voltage)
+        }
+
+        #[scpi(cmd = "CURRent")]
+        fn set_current(&mut self, current: f64) -> Result<(), Error> {
+            self.current = current;
+            Ok(())
+        }
+    }
+}
+
+mod dmm {
+    use microscpi::{self as scpi, Error};
+
+    #[derive(Default)]
+    pub struct DmmInterface {
+        pub range: f64,
+    }
+
+    impl scpi::ErrorHandler for DmmInterface {
+        fn handle_error(&mut self, _error: Error) {
+            unreachable!("Errors are handled by the parent interface");
+        }
+    }
+
+    #[scpi::interface]
+    impl DmmInterface {
+        #[scpi(cmd = "VOLTage?")]
+        fn voltage(&mut self) -> Result<f64, Error> {
+            Ok(1.5)
+        }
+
+        #[scpi(cmd = "VOLTage:RANGe")]
+        fn set_range(&mut self, range: f64) -> Result<(), Error> {
+            self.range = range;
+            Ok(())
+        }
+    }
+}
+
+mod instrument {
+    use microscpi::{self as scpi, Error, ErrorQueue, StaticErrorQueue};
+
+    use super::dmm::DmmInterface;
+    use super::psu::PsuInterface;
+
+    #[derive(Default)]
+    pub struct Instrument {
+        pub errors: StaticErrorQueue<10>,
+        pub psu: PsuInterface,
+        pub dmm: DmmInterface,
+        pub frequency: f64,
+    }
+
+    impl scpi::ErrorCommands for Instrument {
+        fn error_queue(&mut self) -> &mut impl ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    #[scpi::interface(ErrorCommands)]
+    impl Instrument {
+        #[scpi(subtree = "SOURce")]
+        fn source(&mut self) -> &mut PsuInterface {
+            &mut self.psu
+        }
+
+        #[scpi(subtree = "SENSe")]
+        fn sense(&mut self) -> &mut DmmInterface {
+            &mut self.dmm
+        }
+
+        #[scpi(cmd = "SOURce:FREQuency")]
+        fn set_frequency(&mut self, frequency: f64) -> Result<(), Error> {
+            self.frequency = frequency;
+            Ok(())
+        }
+
+        #[scpi(cmd = "VOLTage?")]
+        fn voltage(&mut self) -> Result<f64, Error> {
+            Ok(230.0)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_subtree() {
+    let mut interface = instrument::Instrument::default();
+    let mut output = Vec::new();
+
+    interface
+        .run(b"SOUR:VOLT 12.5;CURR 0.5;FREQ 50\n", &mut output)
+        .await;
+    assert_eq!(interface.psu.voltage, 12.5);
+    assert_eq!(interface.psu.current, 0.5);
+    assert_eq!(interface.frequency, 50.0);
+
+    interface
+        .run(b"SENSE:VOLTAGE:RANGE 10\n", &mut output)
+        .await;
+    assert_eq!(interface.dmm.range, 10.0);
+
+    // The same name resolves to a different command in each tree.
+    interface
+        .run(b"SOUR:VOLT?;:SENS:VOLT?;:VOLT?\n", &mut output)
+        .await;
+    assert_eq!(output, b"12.5;1.5;230\n");
+    assert_eq!(interface.errors.error_count(), 0);
+}
+
+#[tokio::test]
+async fn test_subtree_errors() {
+    let mut interface = instrument::Instrument::default();
+    let mut output = Vec::new();
+
+    // Errors of the nested interfaces are handled by the parent.
+    interface.run(b"SOUR:VOLT 100\n", &mut output).await;
+    interface.run(b"SENS:CURR?\n", &mut output).await;
+    interface.run(b"SENS:FREQ 50\n", &mut output).await;
+    assert_eq!(interface.psu.voltage, 0.0);
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::DataOutOfRange)
+    );
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+}
+
+#[test]
+fn test_subtree_lookup() {
+    let interface = instrument::Instrument::default();
+
+    let (source, _) = interface.lookup("SOURce:VOLTage?").unwrap();
+    let (sense, _) = interface.lookup("sens:volt?").unwrap();
+    let (own, _) = interface.lookup("VOLT?").unwrap();
+    assert_ne!(source, sense);
+    assert_ne!(source, own);
+    assert_ne!(sense, own);
+
+    assert_eq!(interface.lookup("SOUR:FREQ"), Some((0, false)));
+    assert!(interface.lookup("SENS:VOLT:RANG").is_some());
+    assert_eq!(interface.lookup("SENS:CURR"), None);
+}
+
+mod voltmeter {
+    use microscpi::{self as scpi, Error};
+
+    #[derive(Default)]
+    pub struct Voltmeter {
+        pub range: f64,
+        pub resets: usize,
+    }
+
+    impl scpi::ErrorHandler for Voltmeter {
+        fn handle_error(&mut self, _error: Error) {}
+    }
+
+    #[scpi::interface(prefix = "[SENSe]:VOLTage")]
+    impl Voltmeter {
+        #[scpi(cmd = "RANGe")]
+        fn set_range(&mut self, range: f64) -> Result<(), Error> {
+            self.range = range;
+            Ok(())
+        }
+
+        #[scpi(cmd = "*RST")]
+        fn reset(&mut self) -> Result<(), Error> {
+            self.resets += 1;
+            Ok(())
+        }
+    }
+}
+
+mod ammeter {
+    use microscpi::{self as scpi, Error};
+
+    #[derive(Default)]
+    pub struct Ammeter {
+        pub range: f64,
+    }
+
+    impl scpi::ErrorHandler for Ammeter {
+        fn handle_error(&mut self, _error: Error) {}
+    }
+
+    #[scpi::interface(prefix = "SENSe:CURRent")]
+    impl Ammeter {
+        #[scpi(cmd = "RANGe")]
+        fn set_range(&mut self, range: f64) -> Result<(), Error> {
+            self.range = range;
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_prefix() {
+    let mut voltmeter = voltmeter::Voltmeter::default();
+    let mut ammeter = ammeter::Ammeter::default();
+    let mut output = Vec::new();
+
+    voltmeter
+        .run(b"SENS:VOLT:RANG 10;*RST\n", &mut output)
+        .await;
+    voltmeter.run(b"VOLTAGE:RANGE 20\n", &mut output).await;
+    ammeter.run(b"SENSE:CURR:RANG 0.5\n", &mut output).await;
+    assert_eq!(voltmeter.range, 20.0);
+    assert_eq!(voltmeter.resets, 1);
+    assert_eq!(ammeter.range, 0.5);
+
+    assert!(voltmeter.lookup("RANG").is_none());
+    assert!(ammeter.lookup("CURR:RANG").is_none());
+    assert_eq!(
+        <voltmeter::Voltmeter as Interface>::command_name(0),
+        Some("[SENSe]:VOLTage:RANGe")
+    );
+    assert_eq!(
+        <ammeter::Ammeter as Interface>::command_name(0),
+        Some("SENSe:CURRent:RANGe")
+    );
+}
+
+/// Logger capturing the records of each thread, so tests running in parallel
+/// only see their own records.
+#[cfg(feature = "log")]
+mod capture_logger {
+    use std::sync::{Mutex, Once};
+    use std::thread::{self, ThreadId};
+
+    static RECORDS: Mutex<Vec<(ThreadId, log::Level, String)>> = Mutex::new(Vec::new());
+
+    struct CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            RECORDS.lock().unwrap().push((
+                thread::current().id(),
+                record.level(),
+                record.args().to_string(),
+            ));
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Installs the logger once and discards the records of the current
+    /// thread.
+    pub fn install() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            log::set_logger(&CaptureLogger).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+        take_records();
+    }
+
+    /// Returns and removes the records of the current thread.
+    pub fn take_records() -> Vec<(log::Level, String)> {
+        let id = thread::current().id();
+        let mut records = RECORDS.lock().unwrap();
+        let (own, other) = records.drain(..).partition(|(thread, ..)| *thread == id);
+        *records = other;
+        own.into_iter()
+            .map(|(_, level, message)| (level, message))
+            .collect()
+    }
+}
+
+#[cfg(feature = "log")]
+#[tokio::test]
+async fn test_log_errors() {
+    #[derive(scpi::LogErrors)]
+    struct LoggingInterface;
+
+    #[scpi::interface]
+    impl LoggingInterface {
+        #[scpi(cmd = "VALue?")]
+        fn value(&mut self) -> Result<u32, scpi::Error> {
+            Ok(1)
+        }
+    }
+
+    capture_logger::install();
+
+    let mut output = Vec::new();
+    LoggingInterface.run(b"UNKNOWN\n", &mut output).await;
+    let errors: Vec<_> = capture_logger::take_records()
+        .into_iter()
+        .filter(|(level, _)| *level == log::Level::Error)
+        .collect();
+    assert_eq!(errors, [(
+        log::Level::Error,
+        "SCPI error: Undefined header".to_string()
+    )]);
+}
+
+#[cfg(feature = "log")]
+#[tokio::test]
+async fn test_log_error_push() {
+    let (mut interface, mut output) = setup();
+    capture_logger::install();
+
+    interface.run(b"UNKNOWN\n", &mut output).await;
+    let records = capture_logger::take_records();
+    let warnings: Vec<_> = records
+        .iter()
+        .filter(|(level, _)| *level == log::Level::Warn)
+        .collect();
+    assert_eq!(warnings, [&(
+        log::Level::Warn,
+        "Push Error: Undefined header".to_string()
+    )]);
+    assert!(records
+        .iter()
+        .any(|(level, message)| *level == log::Level::Trace && message == "Parse error"));
+}
+
+#[tokio::test]
+async fn test_interpreter_byte_by_byte() {
+    let (mut interface, mut output) = setup();
+    let mut interpreter = scpi::Interpreter::<32>::new();
+
+    for byte in b"SENS:AVER:COUN 4;COUN?;*IDN?\nSENS:AVER:COUN?\n" {
+        interpreter
+            .feed(&mut interface, &mut (), &[*byte], &mut output)
+            .await;
+    }
+    assert_eq!(interface.average, 4);
+    assert_eq!(output, b"4;\"MICROSCPI,TEST,1,1.0\"\n4\n");
+    assert!(!interpreter.pending());
+    assert_eq!(interface.errors.error_count(), 0);
+}
+
+#[tokio::test]
+async fn test_interpreter_keeps_header() {
+    let (mut interface, mut output) = setup();
+    let mut interpreter = scpi::Interpreter::<32>::new();
+
+    // The header of the first command applies to the commands of the next call.
+    let report = interpreter
+        .feed(&mut interface, &mut (), b"TRIG:DEL 0.5;", &mut output)
+        .await;
+    assert_eq!(report.commands(), 1);
+    assert!(!interpreter.pending());
+
+    let report = interpreter
+        .feed(&mut interface, &mut (), b"DEL?\n", &mut output)
+        .await;
+    assert_eq!(report.queries(), 1);
+    assert_eq!(interface.delay, Duration::from_millis(500));
+    assert_eq!(output, b"5E-1\n");
+
+    // The header is reset by the terminator.
+    interpreter
+        .feed(&mut interface, &mut (), b"DEL?\n", &mut output)
+        .await;
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+}
+
+#[tokio::test]
+async fn test_interpreter_errors() {
+    let (mut interface, mut output) = setup();
+    let mut interpreter = scpi::Interpreter::<24>::new();
+
+    // The rest of a program message is discarded after a parse error.
+    interpreter
+        .feed(&mut interface, &mut (), b"UNKNOWN;*RST", &mut output)
+        .await;
+    interpreter
+        .feed(
+            &mut interface,
+            &mut (),
+            b"\nSENS:AVER:COUN 3\n",
+            &mut output,
+        )
+        .await;
+    assert_eq!(interface.result, None);
+    assert_eq!(interface.average, 3);
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+
+    // A command exceeding the buffer is discarded up to its terminator.
+    interpreter
+        .feed(
+            &mut interface,
+            &mut (),
+            b"VAL:STR? \"0123456789",
+            &mut output,
+        )
+        .await;
+    interpreter
+        .feed(
+            &mut interface,
+            &mut (),
+            b"0123456789\";*RST\n*IDN?\n",
+            &mut output,
+        )
+        .await;
+    assert_eq!(interface.result, Some(TestResult::IdnOk));
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InputBufferOverrun)
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+    assert!(!interpreter.pending());
+}
+
+mod timeouts {
+    use core::time::Duration;
+
+    use microscpi::{self as scpi, Error, ErrorQueue, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct SlowInterface {
+        pub errors: StaticErrorQueue<10>,
+        pub completed: usize,
+    }
+
+    impl scpi::ErrorCommands for SlowInterface {
+        fn error_queue(&mut self) -> &mut impl ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, timeout = sleep)]
+    impl SlowInterface {
+        async fn sleep(duration_ms: u32) {
+            tokio::time::sleep(Duration::from_millis(duration_ms.into())).await;
+        }
+
+        #[scpi(cmd = "BUS:READ?", timeout_ms = 20)]
+        async fn bus_read(&mut self, delay_ms: u64) -> Result<u32, Error> {
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+            self.completed += 1;
+            Ok(7)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_command_timeout() {
+    let mut interface = timeouts::SlowInterface::default();
+    let mut output = Vec::new();
+
+    interface.run(b"BUS:READ? 1\n", &mut output).await;
+    assert_eq!(output, b"7\n");
+    assert_eq!(interface.completed, 1);
+
+    output.clear();
+    interface.run(b"BUS:READ? 5000\n", &mut output).await;
+    assert_eq!(interface.completed, 1);
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::TimeoutError)
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+#[cfg(feature = "embassy-sync")]
+mod recorder {
+    use microscpi::{self as scpi, Error, ErrorQueue, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct Recorder {
+        pub errors: StaticErrorQueue<10>,
+        pub log: Vec<u32>,
+    }
+
+    impl scpi::ErrorCommands for Recorder {
+        fn error_queue(&mut self) -> &mut impl ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    #[scpi::interface(ErrorCommands)]
+    impl Recorder {
+        #[scpi(cmd = "LOG")]
+        async fn log(&mut self, value: u32) -> Result<(), Error> {
+            // Give the other transport a chance to run.
+            tokio::task::yield_now().await;
+            self.log.push(value);
+            Ok(())
+        }
+
+        #[scpi(cmd = "LOG:COUNt?")]
+        async fn log_count(&mut self) -> Result<usize, Error> {
+            tokio::task::yield_now().await;
+            Ok(self.log.len())
+        }
+    }
+}
+
+#[cfg(feature = "embassy-sync")]
+#[tokio::test]
+async fn test_shared_interface() {
+    let shared = scpi::SharedInterface::<NoopRawMutex, _>::new(recorder::Recorder::default());
+    let mut uart = MockAdapter::new([&b"LOG 1;LOG 2;"[..], b"LOG 3;LOG:COUN?\n", b"UNKNOWN\n"])
+        .pending_reads(true);
+    let mut tcp = MockAdapter::new([
+        &b"LOG 10;LOG 20;LOG 30;LOG:COUN?\n"[..],
+        b"SYST:ERR:COUN?\n",
+    ])
+    .pending_reads(true);
+
+    let (mut uart_handle, mut tcp_handle) = (shared.handle(), shared.handle());
+    let (uart_result, tcp_result) = tokio::join!(
+        uart_handle.process::<64, _>(&mut uart),
+        tcp_handle.process::<64, _>(&mut tcp),
+    );
+    assert_eq!(uart_result, Ok(()));
+    assert_eq!(tcp_result, Ok(()));
+
+    // The commands of a program message are not interleaved with the commands of
+    // the other transport.
+    let mut recorder = shared.into_inner();
+    let (first, second) = recorder.log.split_at(3);
+    assert!(
+        (first == [1, 2, 3] && second == [10, 20, 30])
+            || (first == [10, 20, 30] && second == [1, 2, 3])
+    );
+
+    // Each transport receives the response to its own program message.
+    let mut counts = [uart.output()[0], tcp.output()[0]];
+    counts.sort();
+    assert_eq!(counts, [b'3', b'6']);
+    assert_eq!(uart.output().len(), 2);
+    assert!(tcp.output().ends_with(b"\n0\n") || tcp.output().ends_with(b"\n1\n"));
+
+    // Errors are passed to the shared interface.
+    assert_eq!(
+        recorder.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+}
+
+#[cfg(feature = "embassy-sync")]
+#[test]
+fn test_shared_interface_deferred() {
+    let shared = scpi::SharedInterface::<NoopRawMutex, _>::new(recorder::Recorder::default());
+    let mut handle = shared.handle();
+
+    let guard = shared.try_lock().unwrap();
+    assert!(shared.try_lock().is_none());
+    for _ in 0..9 {
+        scpi::ErrorHandler::handle_error(&mut handle, scpi::Error::InputBufferOverrun);
+    }
+    // The errors are recorded once the interface is unlocked.
+    assert_eq!(guard.errors.error_count(), 0);
+    drop(guard);
+
+    let mut recorder = shared.into_inner();
+    for _ in 0..8 {
+        assert_eq!(
+            recorder.errors.pop_error(),
+            Some(scpi::Error::InputBufferOverrun)
+        );
+    }
+    assert_eq!(
+        recorder.errors.pop_error(),
+        Some(scpi::Error::QueueOverflow)
+    );
+    assert_eq!(recorder.errors.pop_error(), None);
+}
+
+mod standard {
+    use microscpi::{self as scpi, StandardCommands};
+
+    #[derive(scpi::IgnoreErrors)]
+    pub struct PowerSupply;
+
+    impl StandardCommands for PowerSupply {
+        const SCPI_VERSION: &'static str = "1999.0-rev2";
+
+        fn capability(&self) -> &str {
+            "DCSUPPLY WITH MEASURE"
+        }
+    }
+
+    #[scpi::interface(StandardCommands)]
+    impl PowerSupply {}
+}
+
+#[test]
+fn test_system_capability() {
+    let (mut interface, _) = setup();
+    scpi::assert_scpi!(interface, "SYST:CAP?", "\"SCPI-99\"");
+
+    let mut interface = standard::PowerSupply;
+    scpi::assert_scpi!(interface, "SYSTEM:CAPABILITY?", "\"DCSUPPLY WITH MEASURE\"");
+}
+
+#[test]
+fn test_system_version() {
+    let (mut interface, _) = setup();
+    scpi::assert_scpi!(interface, "SYST:VERS?", "1999.0");
+
+    let mut interface = standard::PowerSupply;
+    scpi::assert_scpi!(interface, "SYST:VERS?", "1999.0-rev2");
+}
+
+mod help {
+    use microscpi::{self as scpi, ErrorCommands, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct PowerSupply {
+        pub voltage: f64,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for PowerSupply {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, help)]
+    impl PowerSupply {
+        /// Sets the output voltage.
+        ///
+        /// The voltage is given in volts.
+        #[scpi(cmd = "SOURce:VOLTage")]
+        fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            Ok(())
+        }
+
+        /// Returns the output voltage.
+        #[scpi(cmd = "SOURce:VOLTage?")]
+        fn voltage(&mut self) -> Result<f64, scpi::Error> {
+            Ok(self.voltage)
+        }
+
+        /// Measures the voltage in the given range.
+        #[scpi(cmd = "MEASure:VOLTage?")]
+        fn measure_voltage(&mut self, _range: Option<u8>) -> Result<f64, scpi::Error> {
+            Ok(self.voltage)
+        }
+
+        #[scpi(cmd = "OUTPut")]
+        fn set_output(&mut self, _label: &str, _state: bool) -> Result<(), scpi::Error> {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_help_syntax() {
+    use scpi::ErrorQueue;
+
+    fn block(data: &str) -> String {
+        format!("#{}{}{}", data.len().to_string().len(), data.len(), data)
+    }
+
+    let mut interface = help::PowerSupply::default();
+
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"MEASure:VOLTage\"",
+        block("MEASure:VOLTage? [<u8>] - Measures the voltage in the given range.")
+    );
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"sour:volt\"",
+        block(
+            "SOURce:VOLTage <f64> - Sets the output voltage.\nSOURce:VOLTage? - Returns the \
+             output voltage."
+        )
+    );
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \":SOURCE:VOLTAGE?\"",
+        block("SOURce:VOLTage? - Returns the output voltage.")
+    );
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"OUTP\"",
+        block("OUTPut <str>,<bool>")
+    );
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"SYST:ERR:COUN\"",
+        block("SYSTem:ERRor:COUNt?")
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+
+    // Unknown headers respond with an empty block.
+    scpi::assert_scpi!(interface, "SYST:HELP:SYNT? \"SOUR:CURR\"", "#10");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+}
+
+mod ranges {
+    use microscpi as scpi;
+
+    #[derive(Default)]
+    pub struct PowerSupply {
+        pub voltage: f64,
+        pub channel: u8,
+        pub calls: usize,
+        pub errors: Vec<scpi::Error>,
+    }
+
+    impl scpi::ErrorHandler for PowerSupply {
+        fn handle_error(&mut self, error: scpi::Error) {
+            self.errors.push(error);
+        }
+    }
+
+    #[scpi::interface(help)]
+    impl PowerSupply {
+        /// Sets the output voltage.
+        #[scpi(cmd = "SOURce:VOLTage", range(voltage = "0.0..=30.0"))]
+        fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            self.calls += 1;
+            Ok(())
+        }
+
+        #[scpi(cmd = "INSTrument:NSELect", range(channel = "1..=4", delay = "..1000"))]
+        fn select(&mut self, channel: u8, delay: Option<u32>) -> Result<(), scpi::Error> {
+            self.channel = channel;
+            self.calls += 1;
+            let _ = delay;
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_parameter_ranges() {
+    let mut interface = ranges::PowerSupply::default();
+
+    scpi::assert_scpi!(interface, "SOUR:VOLT 0", "");
+    assert_eq!(interface.calls, 1);
+    scpi::assert_scpi!(interface, "SOUR:VOLT 30", "");
+    assert_eq!(interface.voltage, 30.0);
+    scpi::assert_scpi!(interface, "SOUR:VOLT 30.01", "");
+    scpi::assert_scpi!(interface, "SOUR:VOLT -0.1", "");
+    assert_eq!(interface.voltage, 30.0);
+    assert_eq!(interface.calls, 2);
+    assert_eq!(interface.errors, [scpi::Error::DataOutOfRange; 2]);
+
+    scpi::assert_scpi!(interface, "INST:NSEL 4", "");
+    scpi::assert_scpi!(interface, "INST:NSEL 1,999", "");
+    assert_eq!(interface.channel, 1);
+    scpi::assert_scpi!(interface, "INST:NSEL 5", "");
+    scpi::assert_scpi!(interface, "INST:NSEL 0", "");
+    scpi::assert_scpi!(interface, "INST:NSEL 2,1000", "");
+    assert_eq!(interface.channel, 1);
+    assert_eq!(interface.calls, 4);
+    assert_eq!(interface.errors, [scpi::Error::DataOutOfRange; 5]);
+    let info: Vec<_> = interface.errors.iter().map(scpi::Error::info).collect();
+    assert_eq!(info, [
+        Some("parameter 1 (voltage)"),
+        Some("parameter 1 (voltage)"),
+        Some("parameter 1 (channel)"),
+        Some("parameter 1 (channel)"),
+        Some("parameter 2 (delay)"),
+    ]);
+
+    let help = "INSTrument:NSELect <u8 1..=4>,[<u32 ..1000>]";
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"INST:NSEL\"",
+        format!("#2{}{help}", help.len())
+    );
+    let help = "SOURce:VOLTage <f64 0.0..=30.0> - Sets the output voltage.";
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"SOUR:VOLT\"",
+        format!("#2{}{help}", help.len())
+    );
+}
+
+#[test]
+fn test_parameter_errors() {
+    let mut interface = ranges::PowerSupply::default();
+
+    // A failed conversion reports the position and name of the parameter.
+    scpi::assert_scpi!(interface, "INST:NSEL 2,\"A\"", "");
+    scpi::assert_scpi!(interface, "INST:NSEL 300", "");
+    assert_eq!(interface.errors, [
+        scpi::Error::DataTypeError,
+        scpi::Error::NumericDataError
+    ]);
+    assert_eq!(
+        interface.errors[0].to_string(),
+        "Data type error;parameter 2 (delay)"
+    );
+    assert_eq!(interface.errors[1].info(), Some("parameter 1 (channel)"));
+
+    // The variant of the error is kept.
+    assert!(matches!(
+        interface.errors[0].kind(),
+        scpi::Error::DataTypeError
+    ));
+    assert_eq!(interface.calls, 0);
+}
+
+mod aliases {
+    use microscpi as scpi;
+
+    #[derive(Default, scpi::IgnoreErrors)]
+    pub struct Generator {
+        pub voltage: f64,
+    }
+
+    #[scpi::interface(help)]
+    impl Generator {
+        /// Sets the amplitude.
+        #[scpi(cmd = "SOURce:VOLTage:[LEVel]")]
+        #[scpi(cmd = "SOURce:AMPLitude")]
+        fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:VOLTage:[LEVel]?")]
+        #[scpi(cmd = "SOURce:AMPLitude?")]
+        fn voltage(&mut self) -> Result<f64, scpi::Error> {
+            Ok(self.voltage)
+        }
+    }
+}
+
+#[test]
+fn test_command_aliases() {
+    let mut interface = aliases::Generator::default();
+
+    scpi::assert_scpi!(interface, "SOUR:VOLT:LEV 5;LEV?", "5");
+    scpi::assert_scpi!(interface, "SOUR:AMPL 7;AMPL?;:SOUR:VOLT?", "7;7");
+    assert_eq!(interface.voltage, 7.0);
+
+    // An alias shares the command id of its handler.
+    assert_eq!(<aliases::Generator as Interface>::COMMAND_COUNT, 3);
+    assert_eq!(
+        <aliases::Generator as Interface>::command_name(0),
+        Some("SOURce:VOLTage:[LEVel]")
+    );
+
+    let help = "SOURce:VOLTage:[LEVel] <f64> (alias SOURce:AMPLitude) - Sets the \
+                amplitude.\nSOURce:VOLTage:[LEVel]? (alias SOURce:AMPLitude?)";
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"SOUR:AMPL\"",
+        format!("#3{}{help}", help.len())
+    );
+}
+
+mod feature_gated {
+    use microscpi as scpi;
+
+    #[derive(Default, scpi::IgnoreErrors)]
+    pub struct Board {
+        pub dac: u16,
+        pub gain: u8,
+    }
+
+    #[scpi::interface(learn)]
+    impl Board {
+        #[cfg(test)]
+        #[scpi(cmd = "SOURce:DAC")]
+        fn set_dac(&mut self, value: u16) -> Result<(), scpi::Error> {
+            self.dac = value;
+            Ok(())
+        }
+
+        #[cfg(test)]
+        #[scpi(cmd = "SOURce:DAC?")]
+        fn dac(&mut self) -> Result<u16, scpi::Error> {
+            Ok(self.dac)
+        }
+
+        #[cfg(any())]
+        #[scpi(cmd = "SOURce:DAC:RAW")]
+        fn set_raw(&mut self, value: u16) -> Result<(), scpi::Error> {
+            self.dac = value;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:GAIN")]
+        fn set_gain(&mut self, gain: u8) -> Result<(), scpi::Error> {
+            self.gain = gain;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:GAIN?")]
+        fn gain(&mut self) -> Result<u8, scpi::Error> {
+            Ok(self.gain)
+        }
+    }
+}
+
+#[test]
+fn test_feature_gated_commands() {
+    let mut interface = feature_gated::Board::default();
+
+    scpi::assert_scpi!(interface, "SOUR:DAC:RAW 7", "");
+    assert_eq!(interface.dac, 0);
+
+    scpi::assert_scpi!(interface, "SOUR:GAIN 2;DAC 100", "");
+    assert_eq!((interface.gain, interface.dac), (2, 100));
+    scpi::assert_scpi!(interface, "SOUR:DAC?", "100");
+    scpi::assert_scpi!(interface, "*LRN?", ":SOURCE:DAC 100;:SOURCE:GAIN 2");
+
+    // The compiled out command has no name.
+    let names: Vec<_> = (0..feature_gated::Board::COMMAND_COUNT)
+        .filter_map(feature_gated::Board::command_name)
+        .collect();
+    assert!(names.contains(&"SOURce:DAC"));
+    assert!(!names.contains(&"SOURce:DAC:RAW"));
+}
+
+mod plain_values {
+    use microscpi as scpi;
+
+    /// A plain value named like a result.
+    pub mod status {
+        pub type Result = u8;
+    }
+
+    #[derive(Default, scpi::IgnoreErrors)]
+    pub struct Counter {
+        pub count: u64,
+    }
+
+    #[scpi::interface(learn)]
+    impl Counter {
+        #[scpi(cmd = "COUNt")]
+        fn set_count(&mut self, count: u64) -> Result<(), scpi::Error> {
+            self.count = count;
+            Ok(())
+        }
+
+        #[scpi(cmd = "COUNt?")]
+        fn count(&mut self) -> u64 {
+            self.count
+        }
+
+        #[scpi(cmd = "COUNt:INCRement")]
+        fn increment(&mut self) {
+            self.count += 1;
+        }
+
+        #[scpi(cmd = "COUNt:DOUBle?")]
+        async fn double(&mut self) -> u64 {
+            self.count * 2
+        }
+
+        #[scpi(cmd = "COUNt:HALF?")]
+        async fn half(&mut self) -> scpi::Result<u64> {
+            if self.count.is_multiple_of(2) {
+                Ok(self.count / 2)
+            }
+            else {
+                Err(scpi::Error::ExecutionError)
+            }
+        }
+
+        #[scpi(cmd = "STATus?", infallible)]
+        fn status(&mut self) -> status::Result {
+            1
+        }
+    }
+}
+
+#[test]
+fn test_plain_values() {
+    let mut interface = plain_values::Counter::default();
+
+    scpi::assert_scpi!(interface, "COUN 4;COUN?;COUN:DOUB?", "4;8");
+    scpi::assert_scpi!(interface, "COUN:INCR;:COUN?", "5");
+    // The error of a fallible handler is still reported.
+    scpi::assert_scpi!(interface, "COUN:HALF?", "");
+    scpi::assert_scpi!(interface, "COUN:INCR;:COUN:HALF?;:STAT?", "3;1");
+    scpi::assert_scpi!(interface, "*LRN?", ":COUNT 6");
+}
+
+mod parts {
+    use microscpi as scpi;
+
+    #[derive(Default)]
+    pub struct Instrument {
+        pub voltage: f64,
+        pub output: bool,
+        pub resets: usize,
+        pub errors: Vec<scpi::Error>,
+    }
+
+    impl scpi::ErrorHandler for Instrument {
+        fn handle_error(&mut self, error: scpi::Error) {
+            self.errors.push(error);
+        }
+    }
+
+    pub mod source {
+        use microscpi as scpi;
+
+        #[scpi::interface_part(Source, prefix = "SOURce")]
+        impl super::Instrument {
+            /// Sets the output voltage.
+            #[scpi(cmd = "VOLTage", range(voltage = "0.0..=30.0"))]
+            fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+                self.voltage = voltage;
+                Ok(())
+            }
+
+            #[scpi(cmd = "VOLTage?")]
+            async fn voltage(&mut self) -> Result<f64, scpi::Error> {
+                Ok(self.voltage)
+            }
+        }
+    }
+
+    pub mod output {
+        use microscpi as scpi;
+
+        #[scpi::interface_part(Output)]
+        impl super::Instrument {
+            #[scpi(cmd = "OUTPut:[STATe]")]
+            fn set_output(&mut self, output: bool) -> Result<(), scpi::Error> {
+                self.output = output;
+                Ok(())
+            }
+
+            #[scpi(cmd = "OUTPut:[STATe]?")]
+            fn output(&mut self) -> bool {
+                self.output
+            }
+        }
+    }
+
+    #[scpi::interface(parts(source::Source, output::Output), learn, help)]
+    impl Instrument {
+        #[scpi(cmd = "*RST")]
+        fn reset(&mut self) -> Result<(), scpi::Error> {
+            self.voltage = 0.0;
+            self.output = false;
+            self.resets += 1;
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_interface_parts() {
+    let mut interface = parts::Instrument::default();
+
+    scpi::assert_scpi!(interface, "SOUR:VOLT 12.5;:OUTP ON", "");
+    scpi::assert_scpi!(interface, "SOUR:VOLT?;:OUTP?;:OUTP:STAT?", "12.5;1;1");
+    scpi::assert_scpi!(interface, "*LRN?", ":SOURCE:VOLTAGE 12.5;:OUTPUT:STATE 1");
+    scpi::assert_scpi!(interface, "*RST;:OUTP?", "0");
+    assert_eq!(interface.resets, 1);
+    assert_eq!(interface.voltage, 0.0);
+
+    // The errors of the parts are handled by the interface.
+    scpi::assert_scpi!(interface, "SOUR:VOLT 31", "");
+    assert_eq!(interface.errors, [scpi::Error::DataOutOfRange]);
+
+    let help = "SOURce:VOLTage <f64 0.0..=30.0> - Sets the output voltage.\nSOURce:VOLTage?";
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"SOUR:VOLT\"",
+        format!("#2{}{help}", help.len())
+    );
+    // The commands of the parts follow the own commands, i.e. `*RST`, `*LRN?`
+    // and `SYSTem:HELP:SYNTax?`.
+    assert_eq!(<parts::Instrument as Interface>::COMMAND_COUNT, 7);
+    assert_eq!(
+        <parts::Instrument as Interface>::command_name(3),
+        Some("SOURce:VOLTage")
+    );
+    assert_eq!(
+        <parts::Instrument as Interface>::command_name(6),
+        Some("OUTPut:[STATe]?")
+    );
+}
+
+mod command_sets {
+    pub mod lan {
+        use microscpi as scpi;
+
+        #[derive(Default)]
+        pub struct LanConfig {
+            pub hostname: &'static str,
+            pub dhcp: bool,
+        }
+
+        #[scpi::command_set]
+        pub trait LanCommands {
+            fn lan_config(&mut self) -> &mut LanConfig;
+
+            #[scpi(cmd = "SYSTem:COMMunicate:LAN:DHCP")]
+            fn set_dhcp(&mut self, dhcp: bool) -> Result<(), scpi::Error> {
+                self.lan_config().dhcp = dhcp;
+                Ok(())
+            }
+
+            #[scpi(cmd = "SYSTem:COMMunicate:LAN:DHCP?")]
+            fn dhcp(&mut self) -> bool {
+                self.lan_config().dhcp
+            }
+
+            #[scpi(cmd = "SYSTem:COMMunicate:LAN:HOSTname?")]
+            async fn hostname(&mut self) -> Result<&'static str, scpi::Error> {
+                Ok(self.lan_config().hostname)
+            }
+        }
+    }
+
+    pub mod power_supply {
+        use microscpi as scpi;
+
+        use super::lan;
+
+        #[derive(Default, scpi::IgnoreErrors)]
+        pub struct PowerSupply {
+            pub lan: lan::LanConfig,
+            pub voltage: f64,
+        }
+
+        impl lan::LanCommands for PowerSupply {
+            fn lan_config(&mut self) -> &mut lan::LanConfig {
+                &mut self.lan
+            }
+        }
+
+        #[scpi::interface(use(lan::LanCommands), learn)]
+        impl PowerSupply {
+            #[scpi(cmd = "SOURce:VOLTage")]
+            fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+                self.voltage = voltage;
+                Ok(())
+            }
+        }
+    }
+
+    pub mod multimeter {
+        use microscpi as scpi;
+
+        use super::lan::{LanCommands, LanConfig};
+
+        #[derive(Default, scpi::IgnoreErrors)]
+        pub struct Multimeter {
+            pub lan: LanConfig,
+        }
+
+        impl LanCommands for Multimeter {
+            fn lan_config(&mut self) -> &mut LanConfig {
+                &mut self.lan
+            }
+        }
+
+        #[scpi::interface(use(LanCommands), prefix = "SENSe")]
+        impl Multimeter {
+            #[scpi(cmd = "VOLTage?")]
+            fn voltage(&mut self) -> f64 {
+                1.5
+            }
+        }
+    }
+}
+
+#[test]
+fn test_command_sets() {
+    let mut supply = command_sets::power_supply::PowerSupply::default();
+    supply.lan.hostname = "psu";
+
+    scpi::assert_scpi!(supply, "SOUR:VOLT 5;:SYST:COMM:LAN:DHCP ON;DHCP?", "1");
+    scpi::assert_scpi!(supply, "SYST:COMM:LAN:HOST?", "\"psu\"");
+    scpi::assert_scpi!(supply, "*LRN?", ":SYSTEM:COMMUNICATE:LAN:DHCP 1");
+    assert_eq!(supply.voltage, 5.0);
+
+    // The command set is registered independently of the prefix.
+    let mut multimeter = command_sets::multimeter::Multimeter::default();
+    scpi::assert_scpi!(multimeter, "SYST:COMM:LAN:DHCP?;:SENS:VOLT?", "0;1.5");
+}
+
+mod clock {
+    use microscpi::{self as scpi, ClockCommands, ErrorCommands, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct Instrument {
+        pub date: (u16, u8, u8),
+        pub time: (u8, u8, u8),
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for Instrument {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl ClockCommands for Instrument {
+        fn get_datetime(&mut self) -> Result<(u16, u8, u8, u8, u8, u8), scpi::Error> {
+            let ((year, month, day), (hour, minute, second)) = (self.date, self.time);
+            Ok((year, month, day, hour, minute, second))
+        }
+
+        fn set_date(&mut self, year: u16, month: u8, day: u8) -> Result<(), scpi::Error> {
+            self.date = (year, month, day);
+            Ok(())
+        }
+
+        fn set_time(&mut self, hour: u8, minute: u8, second: u8) -> Result<(), scpi::Error> {
+            self.time = (hour, minute, second);
+            Ok(())
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, ClockCommands)]
+    impl Instrument {}
+}
+
+#[test]
+fn test_clock_commands() {
+    use scpi::ErrorQueue;
+
+    let mut interface = clock::Instrument::default();
+
+    scpi::assert_scpi!(
+        interface,
+        "SYST:DATE 2024,2,29;TIME 23,59,58;:SYST:DATE?;TIME?",
+        "2024,2,29;23,59,58"
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+
+    // February 29th only exists in leap years.
+    for date in [
+        "2023,2,29",
+        "1900,2,29",
+        "2024,2,30",
+        "2024,13,1",
+        "2024,4,31",
+        "2024,1,0",
+    ] {
+        scpi::assert_scpi!(interface, format!("SYST:DATE {date}"), "");
+        assert_eq!(
+            interface.errors.pop_error(),
+            Some(scpi::Error::DataOutOfRange)
+        );
+    }
+    scpi::assert_scpi!(interface, "SYST:DATE 2000,2,29;DATE?", "2000,2,29");
+
+    for time in ["24,0,0", "0,60,0", "0,0,60"] {
+        scpi::assert_scpi!(interface, format!("SYST:TIME {time}"), "");
+        assert_eq!(
+            interface.errors.pop_error(),
+            Some(scpi::Error::DataOutOfRange)
+        );
+    }
+    scpi::assert_scpi!(interface, "SYST:TIME?", "23,59,58");
+    assert_eq!(interface.errors.error_count(), 0);
+}
+
+mod remote {
+    use microscpi::{self as scpi, ErrorCommands, RemoteCommands, RemoteState, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct PowerSupply {
+        pub voltage: u32,
+        pub state: RemoteState,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for PowerSupply {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl RemoteCommands for PowerSupply {
+        fn remote_state(&mut self) -> &mut RemoteState {
+            &mut self.state
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, RemoteCommands)]
+    impl PowerSupply {
+        #[scpi(cmd = "SOURce:VOLTage", local_locked)]
+        fn set_voltage(&mut self, voltage: u32) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:VOLTage?")]
+        fn voltage(&mut self) -> Result<u32, scpi::Error> {
+            Ok(self.voltage)
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Rack {
+        pub channel: PowerSupply,
+        pub state: RemoteState,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for Rack {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl RemoteCommands for Rack {
+        fn remote_state(&mut self) -> &mut RemoteState {
+            &mut self.state
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, RemoteCommands)]
+    impl Rack {
+        #[scpi(subtree = "CHANnel")]
+        fn channel(&mut self) -> &mut PowerSupply {
+            &mut self.channel
+        }
+    }
+}
+
+#[test]
+fn test_remote_commands() {
+    use scpi::{ErrorQueue, RemoteState};
+
+    let mut interface = remote::PowerSupply::default();
+
+    // In local operation, only the query is executed.
+    scpi::assert_scpi!(interface, "SOUR:VOLT 5;VOLT?", "0");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidWhileInLocal)
+    );
+
+    scpi::assert_scpi!(interface, "SYST:REM;:SOUR:VOLT 5;VOLT?", "5");
+    assert_eq!(interface.state, RemoteState::Remote);
+
+    scpi::assert_scpi!(interface, "SYST:RWL;:SOUR:VOLT 7;VOLT?", "7");
+    assert_eq!(interface.state, RemoteState::RemoteWithLockout);
+    assert_eq!(interface.errors.error_count(), 0);
+
+    scpi::assert_scpi!(interface, "SYST:LOC;:SOUR:VOLT 9;VOLT?", "7");
+    assert_eq!(interface.state, RemoteState::Local);
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidWhileInLocal)
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+}
+
+#[test]
+fn test_remote_commands_nested() {
+    use scpi::{ErrorQueue, RemoteState};
+
+    let mut interface = remote::Rack::default();
+    interface.channel.state = RemoteState::Remote;
+
+    // The local operation of the rack locks the commands of the channel.
+    scpi::assert_scpi!(interface, "CHAN:SOUR:VOLT 5;VOLT?", "0");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidWhileInLocal)
+    );
+
+    scpi::assert_scpi!(interface, "SYST:REM;:CHAN:SOUR:VOLT 5;VOLT?", "5");
+    assert_eq!(interface.errors.error_count(), 0);
+
+    // So does the local operation of the channel itself.
+    interface.channel.state = RemoteState::Local;
+    scpi::assert_scpi!(interface, "CHAN:SOUR:VOLT 7;VOLT?", "5");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::InvalidWhileInLocal)
+    );
+}
+
+#[cfg(feature = "command-macros")]
+mod macros {
+    use microscpi::{
+        self as scpi, ErrorCommands, MacroCommands, StaticErrorQueue, StaticMacroStore,
+    };
+
+    #[derive(Default)]
+    pub struct PowerSupply {
+        pub voltage: u32,
+        pub current: u32,
+        pub macros: StaticMacroStore<8, 32>,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for PowerSupply {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl MacroCommands for PowerSupply {
+        fn macro_store(&mut self) -> &mut impl scpi::MacroStore {
+            &mut self.macros
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, MacroCommands)]
+    impl PowerSupply {
+        #[scpi(cmd = "SOURce:VOLTage")]
+        fn set_voltage(&mut self, voltage: u32) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:VOLTage?")]
+        fn voltage(&mut self) -> Result<u32, scpi::Error> {
+            Ok(self.voltage)
+        }
+
+        #[scpi(cmd = "SOURce:CURRent")]
+        fn set_current(&mut self, current: u32) -> Result<(), scpi::Error> {
+            self.current = current;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:CURRent?")]
+        fn current(&mut self) -> Result<u32, scpi::Error> {
+            Ok(self.current)
+        }
+    }
+}
+
+#[cfg(feature = "command-macros")]
+#[test]
+fn test_macro_commands() {
+    use scpi::ErrorQueue;
+
+    let mut interface = macros::PowerSupply::default();
+
+    scpi::assert_scpi!(interface, "*LMC?;*EMC?", "\"\";0");
+    scpi::assert_scpi!(
+        interface,
+        "*DMC \"LIMITS\",#218SOUR:VOLT 5;CURR 2;*DMC \"READ?\",#216SOUR:VOLT?;CURR?",
+        ""
+    );
+    scpi::assert_scpi!(
+        interface,
+        "*LMC?;*GMC? \"limits\"",
+        "\"LIMITS\",\"READ?\";#218SOUR:VOLT 5;CURR 2"
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+
+    // Macros are not expanded before they are enabled.
+    scpi::assert_scpi!(interface, "LIMITS", "");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+
+    scpi::assert_scpi!(interface, "*EMC 1;LIMITS;READ?;*EMC?", "5;2;1");
+    assert_eq!((interface.voltage, interface.current), (5, 2));
+
+    // The commands of a macro do not depend on the header of the preceding
+    // commands and vice versa.
+    scpi::assert_scpi!(interface, "SOUR:VOLT 3;READ?;CURR 4;CURR?", "3;2;4");
+
+    // Macros are expanded within other macros.
+    scpi::assert_scpi!(interface, "*DMC \"SETUP\",#212LIMITS;READ?;SETUP", "5;2");
+
+    scpi::assert_scpi!(interface, "LIMITS 1", "");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::MacroParameterError)
+    );
+    scpi::assert_scpi!(interface, "*DMC \"LOOP\",#14LOOP;LOOP;*IDN?", "");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::MacroRecursionError)
+    );
+
+    // Only the nesting of macros is limited, not the number of expansions.
+    scpi::assert_scpi!(
+        interface,
+        "*DMC \"V\",#210SOUR:VOLT?;*DMC \"NINE\",#217V;V;V;V;V;V;V;V;V;NINE",
+        "5;5;5;5;5;5;5;5;5"
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+
+    scpi::assert_scpi!(interface, "*DMC \"*RST\",#15*IDN?", "");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::IllegalMacroLabel)
+    );
+    scpi::assert_scpi!(interface, "*DMC \"LIMITS\",#10", "");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::MacroRedefinitionNotAllowed)
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+
+    scpi::assert_scpi!(interface, "*EMC 0;READ?", "");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::UndefinedHeader)
+    );
+
+    scpi::assert_scpi!(interface, "*PMC;*EMC 1;*LMC?;*GMC? \"LIMITS\"", "\"\";");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::MacroHeaderNotFound)
+    );
+}
+
+#[cfg(feature = "command-macros")]
+#[tokio::test]
+async fn test_run_single_macro() {
+    let mut interface = macros::PowerSupply::default();
+    let mut output = Vec::new();
+
+    interface
+        .run(
+            b"*DMC \"LIMITS\",#218SOUR:VOLT 5;CURR 2;*EMC 1\n",
+            &mut output,
+        )
+        .await;
+    let remaining = interface
+        .run_single(b"LIMITS;SOUR:VOLT?\nSOUR:CURR?\n", &mut output)
+        .await;
+    assert_eq!(remaining, Ok(Some(&b"SOUR:CURR?\n"[..])));
+    assert_eq!(output, b"5\n");
+    assert_eq!(interface.current, 2);
+}
+
+mod protected {
+    use microscpi::{self as scpi, ErrorCommands, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct PowerSupply {
+        pub gain: f64,
+        pub unlocked: bool,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for PowerSupply {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl PowerSupply {
+        fn calibration_unlocked(&self) -> bool {
+            self.unlocked
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, help, protection_unlocked = calibration_unlocked)]
+    impl PowerSupply {
+        #[scpi(cmd = "CALibration:SECure:STATe")]
+        fn set_secure(&mut self, state: bool, password: Option<&str>) -> Result<(), scpi::Error> {
+            match (state, password) {
+                (true, _) => self.unlocked = false,
+                (false, Some("SECRET")) => self.unlocked = true,
+                (false, _) => return Err(scpi::Error::IllegalParameterValue),
+            }
+            Ok(())
+        }
+
+        /// Sets the gain of the output.
+        #[scpi(cmd = "CALibration:GAIN", protected(query = false))]
+        fn set_gain(&mut self, gain: f64) -> Result<(), scpi::Error> {
+            self.gain = gain;
+            Ok(())
+        }
+
+        #[scpi(cmd = "CALibration:GAIN?", protected(query = false))]
+        fn gain(&mut self) -> Result<f64, scpi::Error> {
+            Ok(self.gain)
+        }
+
+        #[scpi(cmd = "CALibration:COUNt?", protected)]
+        fn count(&mut self) -> Result<u32, scpi::Error> {
+            Ok(1)
+        }
+    }
+}
+
+#[test]
+fn test_protected_commands() {
+    use scpi::ErrorQueue;
+
+    let mut interface = protected::PowerSupply::default();
+
+    scpi::assert_scpi!(interface, "CAL:GAIN 2;GAIN?;COUN?", "0;");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::CommandProtected)
+    );
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::CommandProtected)
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+
+    scpi::assert_scpi!(interface, "CAL:SEC:STAT OFF,\"WRONG\"", "");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::IllegalParameterValue)
+    );
+
+    scpi::assert_scpi!(
+        interface,
+        "CAL:SEC:STAT OFF,\"SECRET\";:CAL:GAIN 2;GAIN?;COUN?",
+        "2;1"
+    );
+    assert_eq!(interface.errors.error_count(), 0);
+
+    scpi::assert_scpi!(interface, "CAL:SEC:STAT ON;:CAL:GAIN 3;GAIN?", "2");
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::CommandProtected)
+    );
+
+    scpi::assert_scpi!(
+        interface,
+        "SYST:HELP:SYNT? \"CAL:GAIN\"",
+        "#283CALibration:GAIN <f64> (protected) - Sets the gain of the output.\nCALibration:GAIN?"
+    );
+}
+
+mod ieee4882 {
+    use microscpi::{self as scpi, ErrorQueue, StaticErrorQueue, StatusRegisters};
+
+    #[derive(Default)]
+    pub struct Device {
+        pub voltage: u32,
+        pub errors: StaticErrorQueue<10>,
+        pub status: StatusRegisters,
+    }
+
+    impl scpi::ErrorCommands for Device {
+        fn error_queue(&mut self) -> &mut impl ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl scpi::StandardCommands for Device {}
+
+    impl scpi::IdentityCommands for Device {
+        fn manufacturer(&self) -> &str {
+            "MICROSCPI"
+        }
+
+        fn model(&self) -> &str {
+            "DEVICE"
+        }
+
+        fn serial_number(&self) -> &str {
+            "42"
+        }
+
+        fn firmware_version(&self) -> &str {
+            "1.0"
+        }
+    }
+
+    impl scpi::ResetCommands for Device {
+        fn reset(&mut self) -> Result<(), scpi::Error> {
+            self.voltage = 0;
+            Ok(())
+        }
+    }
+
+    impl scpi::SelfTestCommands for Device {
+        async fn self_test(&mut self) -> Result<i16, scpi::Error> {
+            Ok(0)
+        }
+    }
+
+    impl scpi::StatusCommands for Device {
+        fn status_registers(&mut self) -> &mut StatusRegisters {
+            &mut self.status
+        }
+    }
+
+    #[scpi::interface(Ieee4882Device)]
+    impl Device {
+        #[scpi(cmd = "SOURce:VOLTage")]
+        fn set_voltage(&mut self, voltage: u32) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_ieee4882_device() {
+    use scpi::ErrorQueue;
+
+    let mut interface = ieee4882::Device::default();
+
+    scpi::assert_scpi!(interface, "*IDN?", "MICROSCPI,DEVICE,42,1.0");
+    scpi::assert_scpi!(interface, "*TST?", "0");
+
+    scpi::assert_scpi!(interface, "SOUR:VOLT 5;*RST", "");
+    assert_eq!(interface.voltage, 0);
+
+    scpi::assert_scpi!(interface, "*OPC;*WAI;*OPC?;*ESR?", "1;1");
+    scpi::assert_scpi!(interface, "*ESE 32;*ESE?;*SRE 32;*SRE?", "32;32");
+
+    // A command error is summarized by ESB and requests service.
+    scpi::assert_scpi!(interface, "UNKNOWN", "");
+    scpi::assert_scpi!(interface, "*STB?", "100");
+    scpi::assert_scpi!(interface, "*CLS;*STB?;*ESR?", "0;0");
+    assert_eq!(interface.errors.error_count(), 0);
+
+    scpi::assert_scpi!(interface, "SYST:ERR?;:SYST:VERS?", "0,\"\";1999.0");
+}
+
+mod identity {
+    use microscpi::{self as scpi, IdentityCommands};
+
+    #[derive(scpi::IgnoreErrors)]
+    pub struct Instrument {
+        pub serial: heapless::String<16>,
+    }
+
+    impl IdentityCommands for Instrument {
+        fn manufacturer(&self) -> &str {
+            "MICROSCPI"
+        }
+
+        fn model(&self) -> &str {
+            "DMM-1"
+        }
+
+        fn serial_number(&self) -> &str {
+            &self.serial
+        }
+
+        fn firmware_version(&self) -> &str {
+            "1.2.3"
+        }
+    }
+
+    #[scpi::interface(IdentityCommands)]
+    impl Instrument {
+        #[scpi(cmd = "VALue?")]
+        fn value(&mut self) -> Result<u32, scpi::Error> {
+            Ok(1)
+        }
+    }
+}
+
+#[test]
+fn test_identity_commands() {
+    let mut interface = identity::Instrument {
+        serial: "SN0042".try_into().unwrap(),
+    };
+
+    let response = scpi::testing::run_command(&mut interface, b"*IDN?");
+    assert_eq!(response, b"MICROSCPI,DMM-1,SN0042,1.2.3\n");
+    assert!(!response.contains(&b'"'));
+    assert_eq!(response.iter().filter(|b| **b == b',').count(), 3);
+
+    scpi::assert_scpi!(interface, "*IDN?;VAL?", "MICROSCPI,DMM-1,SN0042,1.2.3;1");
+}
+
+mod reset {
+    use microscpi::{self as scpi, ErrorCommands, ResetCommands, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct Instrument {
+        pub voltage: f64,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for Instrument {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl ResetCommands for Instrument {
+        fn reset(&mut self) -> Result<(), scpi::Error> {
+            self.voltage = 0.0;
+            Ok(())
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, ResetCommands)]
+    impl Instrument {
+        #[scpi(cmd = "SOURce:VOLTage")]
+        fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:VOLTage?")]
+        fn voltage(&mut self) -> Result<f64, scpi::Error> {
+            Ok(self.voltage)
+        }
+
+        #[scpi(cmd = "VOLTage?")]
+        fn measure_voltage(&mut self) -> Result<f64, scpi::Error> {
+            Ok(-1.0)
+        }
+    }
+}
+
+#[test]
+fn test_reset_commands() {
+    let mut interface = reset::Instrument::default();
+
+    scpi::assert_scpi!(interface, "SOUR:VOLT 5;VOLT?", "5");
+    scpi::assert_scpi!(interface, "UNKNOWN", "");
+
+    // The reset restores the settings, but keeps the error queue.
+    scpi::assert_scpi!(interface, "*RST;SOUR:VOLT?", "0");
+    assert_eq!(interface.errors.error_count(), 1);
+    scpi::assert_scpi!(interface, "SYST:ERR?", "-113,\"Undefined header\"");
+
+    // The header is reset to the root of the command tree.
+    scpi::assert_scpi!(interface, "SOUR:VOLT 5;*RST;VOLT?", "-1");
+}
+
+mod self_test {
+    use core::time::Duration;
+
+    use microscpi::{self as scpi, ErrorCommands, SelfTestCommands, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct Instrument {
+        pub result: i16,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for Instrument {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl SelfTestCommands for Instrument {
+        async fn self_test(&mut self) -> Result<i16, scpi::Error> {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(self.result)
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, SelfTestCommands)]
+    impl Instrument {}
+}
+
+#[tokio::test]
+async fn test_self_test_commands() {
+    let mut interface = self_test::Instrument::default();
+    let mut output = Vec::new();
+
+    let start = std::time::Instant::now();
+    interface.run(b"*TST?\n", &mut output).await;
+    assert!(start.elapsed() >= Duration::from_millis(10));
+    assert_eq!(output, b"0\n");
+    assert_eq!(interface.errors.error_count(), 0);
+
+    interface.result = 3;
+    output.clear();
+    interface.run(b"*TST?;SYST:ERR?\n", &mut output).await;
+    assert_eq!(output, b"3;-330,\"Self test failed\"\n");
+    assert_eq!(interface.errors.error_count(), 0);
+}
+
+mod overlapped {
+    use core::time::Duration;
+
+    use microscpi::{self as scpi, OperationToken, ResetCommands, StatusCommands, StatusRegisters};
+    use tokio::sync::mpsc;
+
+    #[derive(scpi::IgnoreErrors)]
+    pub struct Instrument {
+        pub registers: StatusRegisters,
+        completed: mpsc::UnboundedSender<OperationToken>,
+        completions: mpsc::UnboundedReceiver<OperationToken>,
+    }
+
+    impl Instrument {
+        pub fn new() -> Instrument {
+            let (completed, completions) = mpsc::unbounded_channel();
+            Instrument {
+                registers: StatusRegisters::new(),
+                completed,
+                completions,
+            }
+        }
+    }
+
+    impl StatusCommands for Instrument {
+        fn status_registers(&mut self) -> &mut StatusRegisters {
+            &mut self.registers
+        }
+
+        async fn wait_operations(&mut self) {
+            if let Some(token) = self.completions.recv().await {
+                token.complete_on(&mut self.registers);
+            }
+        }
+    }
+
+    impl ResetCommands for Instrument {
+        fn reset(&mut self) -> Result<(), scpi::Error> {
+            Ok(())
+        }
+    }
+
+    #[scpi::interface(StatusCommands, ResetCommands)]
+    impl Instrument {
+        /// Starts a sweep, which completes in the background.
+        #[scpi(cmd = "INITiate")]
+        fn initiate(&mut self) -> Result<(), scpi::Error> {
+            let token = self.registers.begin_operation();
+            let completed = self.completed.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                let _ = completed.send(token);
+            });
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_wait_pending_operations() {
+    let mut interface = overlapped::Instrument::new();
+    let mut output = Vec::new();
+
+    // Without pending operations, *WAI and *OPC? complete immediately.
+    interface.run(b"*WAI;*OPC?\n", &mut output).await;
+    assert_eq!(output, b"1\n");
+
+    output.clear();
+    let start = tokio::time::Instant::now();
+    interface.run(b"INIT;*OPC?\n", &mut output).await;
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(output, b"1\n");
+    assert_eq!(interface.registers.pending_operations(), 0);
+
+    output.clear();
+    interface.run(b"INIT\n", &mut output).await;
+    assert_eq!(interface.registers.pending_operations(), 1);
+    let run = interface.run(b"*WAI;*OPC?\n", &mut output);
+    assert!(tokio::time::timeout(Duration::from_millis(5), run)
+        .await
+        .is_err());
+    assert!(output.is_empty());
+    interface.run(b"*WAI;*OPC?\n", &mut output).await;
+    assert_eq!(output, b"1\n");
+}
+
+#[tokio::test]
+async fn test_operation_complete() {
+    let mut interface = overlapped::Instrument::new();
+    let mut output = Vec::new();
+
+    // Without pending operations, *OPC sets the OPC bit immediately.
+    interface.run(b"*OPC;*ESR?\n", &mut output).await;
+    assert_eq!(output, b"1\n");
+
+    // With a pending operation, the OPC bit is set once it has completed.
+    output.clear();
+    interface.run(b"INIT;*OPC;*ESR?\n", &mut output).await;
+    assert_eq!(output, b"0\n");
+    assert!(interface.registers.operation_complete_armed());
+    output.clear();
+    interface.run(b"*WAI;*ESR?\n", &mut output).await;
+    assert_eq!(output, b"1\n");
+
+    // *CLS cancels the pending *OPC.
+    output.clear();
+    interface.run(b"INIT;*OPC;*CLS\n", &mut output).await;
+    assert!(!interface.registers.operation_complete_armed());
+    interface.run(b"*WAI;*ESR?\n", &mut output).await;
+    assert_eq!(output, b"0\n");
+
+    // *RST cancels the pending *OPC.
+    output.clear();
+    interface.run(b"INIT;*OPC;*RST\n", &mut output).await;
+    assert!(!interface.registers.operation_complete_armed());
+    interface.run(b"*WAI;*ESR?\n", &mut output).await;
+    assert_eq!(output, b"0\n");
+    assert_eq!(interface.registers.pending_operations(), 0);
+}
+
+mod trigger {
+    use microscpi::{self as scpi, ErrorCommands, StaticErrorQueue, TriggerCommands};
+
+    #[derive(Default)]
+    pub struct Instrument {
+        pub armed: bool,
+        pub triggers: usize,
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for Instrument {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl TriggerCommands for Instrument {
+        async fn trigger(&mut self) -> Result<(), scpi::Error> {
+            self.triggers += 1;
+            self.armed = false;
+            Ok(())
+        }
+
+        fn armed(&mut self) -> bool {
+            self.armed
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, TriggerCommands)]
+    impl Instrument {
+        #[scpi(cmd = "ARM")]
+        fn arm(&mut self) -> Result<(), scpi::Error> {
+            self.armed = true;
+            Ok(())
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_trigger_commands() {
+    let mut interface = trigger::Instrument::default();
+    let mut output = Vec::new();
+
+    interface.run(b"ARM;*TRG\n", &mut output).await;
+    assert_eq!(interface.triggers, 1);
+    assert_eq!(interface.errors.error_count(), 0);
+
+    // The trigger disarmed the device.
+    interface.run(b"*TRG\n", &mut output).await;
+    assert_eq!(interface.triggers, 1);
+    assert_eq!(interface.errors.error_count(), 1);
+
+    interface.run(b"SYST:ERR?;:SYST:ERR?\n", &mut output).await;
+    assert_eq!(output, b"-210,\"Trigger error\";0,\"\"\n");
+}
+
+mod state {
+    use microscpi::{self as scpi, ErrorCommands, StateCommands, StaticErrorQueue};
+
+    #[derive(Default)]
+    pub struct Instrument {
+        pub voltage: u32,
+        pub slots: [u32; 4],
+        pub errors: StaticErrorQueue<10>,
+    }
+
+    impl ErrorCommands for Instrument {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl StateCommands for Instrument {
+        const MAX_SLOTS: u8 = 4;
+
+        fn save_state(&mut self, slot: u8) -> Result<(), scpi::Error> {
+            self.slots[slot as usize] = self.voltage;
+            Ok(())
+        }
+
+        fn recall_state(&mut self, slot: u8) -> Result<(), scpi::Error> {
+            self.voltage = self.slots[slot as usize];
+            Ok(())
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, StateCommands)]
+    impl Instrument {
+        #[scpi(cmd = "VOLTage")]
+        fn set_voltage(&mut self, voltage: u32) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            Ok(())
+        }
+
+        #[scpi(cmd = "VOLTage?")]
+        fn voltage(&mut self) -> Result<u32, scpi::Error> {
+            Ok(self.voltage)
+        }
+    }
+}
+
+#[test]
+fn test_state_commands() {
+    let mut interface = state::Instrument::default();
+
+    scpi::assert_scpi!(interface, "VOLT 5;*SAV 0", "");
+    scpi::assert_scpi!(interface, "VOLT 7;*SAV 3", "");
+    assert_eq!(interface.slots, [5, 0, 0, 7]);
+
+    scpi::assert_scpi!(interface, "*RCL 0;VOLT?", "5");
+    scpi::assert_scpi!(interface, "*RCL 3;VOLT?", "7");
+    assert_eq!(interface.errors.error_count(), 0);
+
+    // One past the last slot is rejected without calling the handlers.
+    scpi::assert_scpi!(interface, "VOLT 9;*SAV 4", "");
+    scpi::assert_scpi!(interface, "*RCL 4;VOLT?", "9");
+    assert_eq!(interface.slots, [5, 0, 0, 7]);
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::DataOutOfRange)
+    );
+    assert_eq!(
+        interface.errors.pop_error(),
+        Some(scpi::Error::DataOutOfRange)
+    );
+    assert_eq!(interface.errors.pop_error(), None);
+}
+
+mod learn {
+    use microscpi as scpi;
+
+    #[derive(Default, scpi::IgnoreErrors)]
+    pub struct Instrument {
+        pub voltage: f64,
+        pub current: f64,
+        pub output: bool,
+        pub brightness: u8,
+    }
+
+    #[scpi::interface(learn)]
+    impl Instrument {
+        #[scpi(cmd = "SOURce:VOLTage")]
+        fn set_voltage(&mut self, voltage: f64) -> Result<(), scpi::Error> {
+            self.voltage = voltage;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:VOLTage?")]
+        fn voltage(&mut self) -> Result<f64, scpi::Error> {
+            Ok(self.voltage)
+        }
+
+        #[scpi(cmd = "SOURce:CURRent")]
+        fn set_current(&mut self, current: f64) -> Result<(), scpi::Error> {
+            self.current = current;
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:CURRent?")]
+        async fn current(&mut self) -> Result<f64, scpi::Error> {
+            Ok(self.current)
+        }
+
+        #[scpi(cmd = "OUTPut:[STATe]")]
+        fn set_output(&mut self, output: bool) -> Result<(), scpi::Error> {
+            self.output = output;
+            Ok(())
+        }
+
+        #[scpi(cmd = "OUTPut:[STATe]?")]
+        fn output(&mut self) -> Result<bool, scpi::Error> {
+            Ok(self.output)
+        }
+
+        #[scpi(cmd = "DISPlay:BRIGhtness", no_learn)]
+        fn set_brightness(&mut self, brightness: u8) -> Result<(), scpi::Error> {
+            self.brightness = brightness;
+            Ok(())
+        }
+
+        #[scpi(cmd = "DISPlay:BRIGhtness?")]
+        fn brightness(&mut self) -> Result<u8, scpi::Error> {
+            Ok(self.brightness)
+        }
+
+        #[scpi(cmd = "MEASure:VOLTage?")]
+        fn measure_voltage(&mut self) -> Result<f64, scpi::Error> {
+            Ok(self.voltage)
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_learn() {
+    let mut interface = learn::Instrument::default();
+    let mut output = Vec::new();
+
+    interface
+        .run(
+            b"SOUR:VOLT 2.5;CURR 0.5;:OUTP ON;:DISP:BRIG 3\n",
+            &mut output,
+        )
+        .await;
+    interface.run(b"*LRN?\n", &mut output).await;
+    assert_eq!(
+        output,
+        b":SOURCE:VOLTAGE 2.5;:SOURCE:CURRENT 0.5;:OUTPUT:STATE 1\n"
+    );
+
+    let mut restored = learn::Instrument::default();
+    let report = restored.run_report(&output, &mut Vec::new()).await;
+    assert_eq!(report.errors(), 0);
+    assert_eq!(report.commands(), 3);
+    assert_eq!(restored.voltage, 2.5);
+    assert_eq!(restored.current, 0.5);
+    assert!(restored.output);
+    assert_eq!(restored.brightness, 0);
+}
+
+mod service_request {
+    use microscpi::{
+        self as scpi, ErrorCommands, StaticErrorQueue, StatusCommands, StatusRegisters,
+    };
+
+    #[derive(Default)]
+    pub struct Instrument {
+        pub registers: StatusRegisters,
+        pub errors: StaticErrorQueue<10>,
+        pub service_requests: usize,
+    }
+
+    impl ErrorCommands for Instrument {
+        fn error_queue(&mut self) -> &mut impl scpi::ErrorQueue {
+            &mut self.errors
+        }
+    }
+
+    impl StatusCommands for Instrument {
+        fn status_registers(&mut self) -> &mut StatusRegisters {
+            &mut self.registers
+        }
+
+        fn on_service_request(&mut self) {
+            self.service_requests += 1;
+        }
+    }
+
+    #[scpi::interface(ErrorCommands, StatusCommands)]
+    impl Instrument {}
+}
+
+#[test]
+fn test_service_request() {
+    let mut interface = service_request::Instrument::default();
+
+    // The error queue is not enabled for a service request.
+    scpi::assert_scpi!(interface, "UNKNOWN", "");
+    assert_eq!(interface.service_requests, 0);
+    scpi::assert_scpi!(interface, "SYST:ERR?", "-113,\"Undefined header\"");
+
+    scpi::assert_scpi!(interface, "*SRE 4", "");
+    scpi::assert_scpi!(interface, "UNKNOWN", "");
+    assert_eq!(interface.service_requests, 1);
+
+    // The MSS bit remains set while the error queue is not empty.
+    scpi::assert_scpi!(interface, "UNKNOWN", "");
+    scpi::assert_scpi!(interface, "*STB?", "68");
+    assert_eq!(interface.service_requests, 1);
+
+    scpi::assert_scpi!(interface, "*CLS;*STB?", "0");
+    scpi::assert_scpi!(interface, "UNKNOWN", "");
+    assert_eq!(interface.service_requests, 2);
+}
+
+#[tokio::test]
+async fn test_clear_status_discards_output() {
+    let (mut interface, _) = setup();
+    let mut adapter = MockAdapter::new([&b"*IDN?\n"[..], b"*CLS\n", b"*STB?\n"]).hold_output(true);
+
+    interface.process::<64, _>(&mut adapter).await.unwrap();
+    assert_eq!(adapter.output(), b"0\n");
+    // The unread response has been discarded, before *CLS was executed.
+    assert_eq!(adapter.events()[..3], [
+        scpi::testing::MockEvent::Write(b"\"MICROSCPI,TEST,1,1.0\"\n".to_vec()),
+        scpi::testing::MockEvent::Flush,
+        scpi::testing::MockEvent::Discard
+    ]);
+    assert_eq!(interface.errors.pop_error(), None);
+
+    // The MAV bit of a response within the same message is cleared as well.
+    scpi::assert_scpi!(
+        interface,
+        "*IDN?;*STB?;*CLS;*STB?",
+        "\"MICROSCPI,TEST,1,1.0\";16;0"
+    );
+}
+
+mod status_registers {
+    use microscpi::{
+        self as scpi, OperationStatusCommands, QuestionableStatusCommands, StatusCommands,
+        StatusRegisters,
+    };
+
+    #[derive(Default, scpi::IgnoreErrors)]
+    pub struct Instrument {
+        pub registers: StatusRegisters,
+    }
+
+    impl StatusCommands for Instrument {
+        fn status_registers(&mut self) -> &mut StatusRegisters {
+            &mut self.registers
+        }
+    }
+
+    impl OperationStatusCommands for Instrument {}
+
+    impl QuestionableStatusCommands for Instrument {}
+
+    #[scpi::interface(StatusCommands, OperationStatusCommands, QuestionableStatusCommands)]
+    impl Instrument {}
+}
+
+#[test]
+fn test_operation_status() {
+    use scpi::OperationStatus;
+
+    let mut interface = status_registers::Instrument::default();
+    let operation = interface.registers.operation_mut();
+    operation.set_condition(OperationStatus::MEASURING, true);
+    operation.set_condition(OperationStatus::SWEEPING, true);
+    operation.set_condition(OperationStatus::SWEEPING, false);
+
+    // The condition is not cleared by reading, but the event is.
+    scpi::assert_scpi!(interface, "STAT:OPER:COND?", "16");
+    scpi::assert_scpi!(interface, "STAT:OPER:COND?", "16");
+    scpi::assert_scpi!(interface, "STAT:OPER?", "24");
+    scpi::assert_scpi!(interface, "STAT:OPER:EVEN?", "0");
+
+    // Only enabled events are summarized in the status byte.
+    interface
+        .registers
+        .operation_mut()
+        .set_condition(OperationStatus::CALIBRATING, true);
+    scpi::assert_scpi!(interface, "*STB?", "0");
+    scpi::assert_scpi!(interface, "STAT:OPER:ENAB 1;ENAB?", "1");
+    scpi::assert_scpi!(interface, "*STB?", "128");
+    scpi::assert_scpi!(interface, "STAT:OPER?;*STB?", "1;16");
+}
+
+#[test]
+fn test_questionable_status() {
+    use scpi::{OperationStatus, QuestionableStatus};
+
+    let mut interface = status_registers::Instrument::default();
+    let questionable = interface.registers.questionable_mut();
+    questionable.set_condition(QuestionableStatus::VOLTAGE, true);
+    questionable.set_condition(QuestionableStatus::TEMPERATURE, true);
+    questionable.set_condition(QuestionableStatus::TEMPERATURE, false);
+
+    scpi::assert_scpi!(interface, "STAT:QUES:COND?", "1");
+    scpi::assert_scpi!(interface, "STAT:QUES:COND?", "1");
+    scpi::assert_scpi!(interface, "STAT:QUES?", "17");
+    scpi::assert_scpi!(interface, "STAT:QUES:EVEN?", "0");
+
+    interface
+        .registers
+        .questionable_mut()
+        .set_condition(QuestionableStatus::CURRENT, true);
+    scpi::assert_scpi!(interface, "*STB?", "0");
+    scpi::assert_scpi!(interface, "STAT:QUES:ENAB 2;ENAB?", "2");
+    scpi::assert_scpi!(interface, "*STB?", "8");
+
+    // Both summaries are reported independently.
+    interface
+        .registers
+        .operation_mut()
+        .set_condition(OperationStatus::MEASURING, true);
+    scpi::assert_scpi!(interface, "STAT:OPER:ENAB 16;*STB?", "136");
+    scpi::assert_scpi!(interface, "STAT:QUES?;*STB?", "2;144");
+    scpi::assert_scpi!(interface, "STAT:OPER?;*STB?", "16;16");
+}
+
+#[test]
+fn test_clear_status_registers() {
+    use scpi::{OperationStatus, QuestionableStatus};
+
+    let mut interface = status_registers::Instrument::default();
+    interface
+        .registers
+        .operation_mut()
+        .set_condition(OperationStatus::MEASURING, true);
+    interface
+        .registers
+        .questionable_mut()
+        .set_condition(QuestionableStatus::VOLTAGE, true);
+    scpi::assert_scpi!(
+        interface,
+        "STAT:OPER:ENAB 16;:STAT:QUES:ENAB 1;*STB?",
+        "136"
+    );
+
+    // The events are cleared, but not the conditions and enable masks.
+    scpi::assert_scpi!(interface, "*CLS;*STB?", "0");
+    scpi::assert_scpi!(interface, "STAT:OPER?;:STAT:QUES?", "0;0");
+    scpi::assert_scpi!(interface, "STAT:OPER:COND?;:STAT:QUES:COND?", "16;1");
+    scpi::assert_scpi!(interface, "STAT:OPER:ENAB?;:STAT:QUES:ENAB?", "16;1");
+}
+
+#[test]
+fn test_transition_filters() {
+    use scpi::{OperationStatus, QuestionableStatus};
+
+    let mut interface = status_registers::Instrument::default();
+    scpi::assert_scpi!(interface, "STAT:OPER:PTR?;NTR?", "32767;0");
+
+    // Latch only the end of a measurement.
+    scpi::assert_scpi!(interface, "STAT:OPER:PTR 0;NTR 16;PTR?;NTR?", "0;16");
+    let operation = interface.registers.operation_mut();
+    operation.set_condition(OperationStatus::MEASURING, true);
+    scpi::assert_scpi!(interface, "STAT:OPER?", "0");
+    interface
+        .registers
+        .operation_mut()
+        .set_condition(OperationStatus::MEASURING, false);
+    scpi::assert_scpi!(interface, "STAT:OPER?", "16");
+
+    // Simultaneous edges of several bits.
+    scpi::assert_scpi!(interface, "STAT:QUES:PTR 1;NTR 2", "");
+    let questionable = interface.registers.questionable_mut();
+    questionable.set_condition(QuestionableStatus::CURRENT, true);
+    questionable.set_condition(
+        QuestionableStatus::VOLTAGE | QuestionableStatus::CURRENT,
+        true,
+    );
+    questionable.set_condition(
+        QuestionableStatus::VOLTAGE | QuestionableStatus::CURRENT,
+        false,
+    );
+    scpi::assert_scpi!(interface, "STAT:QUES:EVEN?;COND?", "3;0");
+}
+
+mod generic {
+    use microscpi::{self as scpi, Error};
+
+    /// A digital-to-analog converter of a power supply.
+    pub trait Dac {
+        type Error;
+        type Code: Copy;
+
+        fn write(&mut self, channel: usize, code: Self::Code) -> Result<(), Self::Error>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct DacFault;
+
+    impl From<DacFault> for Error {
+        fn from(_: DacFault) -> Error {
+            Error::HardwareError
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Dac12 {
+        pub codes: Vec<(usize, u16)>,
+    }
+
+    impl Dac for Dac12 {
+        type Code = u16;
+        type Error = DacFault;
+
+        fn write(&mut self, channel: usize, code: u16) -> Result<(), DacFault> {
+            if code >= 4096 {
+                return Err(DacFault);
+            }
+            self.codes.push((channel, code));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    pub struct Dac8 {
+        pub codes: Vec<(usize, u8)>,
+    }
+
+    impl Dac for Dac8 {
+        type Code = u8;
+        type Error = Error;
+
+        fn write(&mut self, channel: usize, code: u8) -> Result<(), Error> {
+            self.codes.push((channel, code));
+            Ok(())
+        }
+    }
+
+    pub struct Psu<D: Dac, const CH: usize> {
+        pub dac: D,
+        pub codes: [Option<D::Code>; CH],
+        pub channel: usize,
+        pub errors: Vec<Error>,
+    }
+
+    impl<D: Dac + Default, const CH: usize> Default for Psu<D, CH> {
+        fn default() -> Self {
+            Psu {
+                dac: D::default(),
+                codes: [None; CH],
+                channel: 0,
+                errors: Vec::new(),
+            }
+        }
+    }
+
+    impl<D: Dac, const CH: usize> scpi::ErrorHandler for Psu<D, CH> {
+        fn handle_error(&mut self, error: Error) {
+            self.errors.push(error);
+        }
+    }
+
+    #[scpi::interface]
+    impl<D: Dac, const CH: usize> Psu<D, CH>
+    where
+        D::Error: Into<Error>,
+    {
+        #[scpi(cmd = "INSTrument:NSELect")]
+        fn select(&mut self, channel: usize) -> Result<(), Error> {
+            if !(1..=CH).contains(&channel) {
+                return Err(Error::DataOutOfRange);
+            }
+            self.channel = channel - 1;
+            Ok(())
+        }
+
+        #[scpi(cmd = "INSTrument:NSELect?")]
+        fn selected(&mut self) -> usize {
+            self.channel + 1
+        }
+
+        #[scpi(cmd = "SOURce:CODE")]
+        fn set_code(&mut self, code: D::Code) -> Result<(), Error> {
+            self.dac.write(self.channel, code).map_err(Into::into)?;
+            self.codes[self.channel] = Some(code);
+            Ok(())
+        }
+
+        #[scpi(cmd = "SOURce:CODE?")]
+        async fn code(&mut self) -> Result<D::Code, Error> {
+            self.codes[self.channel].ok_or(Error::SettingsConflict)
+        }
+    }
+}
+
+#[test]
+fn test_generic_interface() {
+    use generic::{Dac12, Dac8, Psu};
+
+    let mut psu = Psu::<Dac12, 2>::default();
+    scpi::assert_scpi!(psu, "SOUR:CODE 1000;CODE?", "1000");
+    scpi::assert_scpi!(psu, "INST:NSEL 2;NSEL?", "2");
+    scpi::assert_scpi!(psu, "SOUR:CODE 5000", "");
+    scpi::assert_scpi!(psu, "INST:NSEL 3", "");
+    assert_eq!(psu.dac.codes, [(0, 1000)]);
+    assert_eq!(psu.errors, [
+        scpi::Error::HardwareError,
+        scpi::Error::DataOutOfRange
+    ]);
+
+    let mut psu = Psu::<Dac8, 4>::default();
+    scpi::assert_scpi!(psu, "INST:NSEL 4;:SOUR:CODE 200;CODE?", "200");
+    scpi::assert_scpi!(psu, "SOUR:CODE 300", "");
+    assert_eq!(psu.dac.codes, [(3, 200)]);
+    assert_eq!(psu.errors, [scpi::Error::NumericDataError]);
+    assert_eq!(
+        <Psu<Dac8, 4> as Interface>::command_name(2),
+        Some("SOURce:CODE")
+    );
+}
+
+mod generic_parts {
+    use microscpi::{self as scpi, Error};
+
+    use super::generic::Dac;
+
+    pub struct Calibrator<D: Dac> {
+        pub dac: D,
+        pub offset: D::Code,
+        pub errors: Vec<Error>,
+    }
+
+    impl<D: Dac> scpi::ErrorHandler for Calibrator<D> {
+        fn handle_error(&mut self, error: Error) {
+            self.errors.push(error);
+        }
+    }
+
+    pub mod offset {
+        use microscpi::{self as scpi, Error};
+
+        use super::Dac;
+
+        #[scpi::interface_part(Offset, prefix = "CALibration")]
+        impl<D: Dac> super::Calibrator<D>
+        where
+            D::Error: Into<Error>,
+        {
+            #[scpi(cmd = "OFFSet")]
+            fn set_offset(&mut self, offset: D::Code) -> Result<(), Error> {
+                self.dac.write(0, offset).map_err(Into::into)?;
+                self.offset = offset;
+                Ok(())
+            }
+
+            #[scpi(cmd = "OFFSet?")]
+            fn offset(&mut self) -> D::Code {
+                self.offset
+            }
+        }
+    }
+
+    #[scpi::interface(parts(offset::Offset), learn)]
+    impl<D: Dac> Calibrator<D>
+    where
+        D::Error: Into<Error>,
+    {
+        #[scpi(cmd = "CALibration:ERRors?")]
+        fn error_count(&mut self) -> usize {
+            self.errors.len()
+        }
+    }
+}
+
+#[test]
+fn test_generic_interface_parts() {
+    use generic::{Dac12, Dac8};
+    use generic_parts::Calibrator;
+
+    let mut calibrator = Calibrator {
+        dac: Dac12::default(),
+        offset: 0,
+        errors: Vec::new(),
+    };
+    scpi::assert_scpi!(calibrator, "CAL:OFFS 100;OFFS?", "100");
+    scpi::assert_scpi!(calibrator, "*LRN?", ":CALIBRATION:OFFSET 100");
+    scpi::assert_scpi!(calibrator, "CAL:OFFS 5000;ERR?", "1");
+    assert_eq!(calibrator.dac.codes, [(0, 100)]);
+    assert_eq!(calibrator.errors, [scpi::Error::HardwareError]);
+
+    let mut calibrator = Calibrator {
+        dac: Dac8::default(),
+        offset: 0,
+        errors: Vec::new(),
+    };
+    scpi::assert_scpi!(calibrator, "CAL:OFFS 200;OFFS?", "200");
+    scpi::assert_scpi!(calibrator, "CAL:OFFS 300", "");
+    assert_eq!(calibrator.errors, [scpi::Error::NumericDataError]);
+}
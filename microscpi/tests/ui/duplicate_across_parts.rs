@@ -0,0 +1,33 @@
+use microscpi as scpi;
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Interface;
+
+mod source {
+    use microscpi as scpi;
+
+    #[scpi::interface_part(Source, prefix = "SOURce")]
+    impl super::Interface {
+        #[scpi(cmd = "VOLTage")]
+        fn set_voltage(&mut self, _voltage: f64) -> Result<(), scpi::Error> {
+            Ok(())
+        }
+    }
+}
+
+mod output {
+    use microscpi as scpi;
+
+    #[scpi::interface_part(Output)]
+    impl super::Interface {
+        #[scpi(cmd = "SOURce:VOLTage")]
+        fn set_output_voltage(&mut self, _voltage: f64) -> Result<(), scpi::Error> {
+            Ok(())
+        }
+    }
+}
+
+#[scpi::interface(parts(source::Source, output::Output))]
+impl Interface {}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use microscpi as scpi;
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Interface;
+
+#[scpi::interface]
+impl Interface {
+    #[scpi(cmd = "[SYSTem]:TEST")]
+    fn system_test(&mut self) -> Result<(), scpi::Error> {
+        Ok(())
+    }
+
+    #[scpi(cmd = "TEST")]
+    fn test(&mut self) -> Result<(), scpi::Error> {
+        Ok(())
+    }
+}
+
+fn main() {}
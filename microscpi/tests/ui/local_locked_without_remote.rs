@@ -0,0 +1,14 @@
+use microscpi as scpi;
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Interface;
+
+#[scpi::interface]
+impl Interface {
+    #[scpi(cmd = "OUTPut", local_locked)]
+    fn set_output(&mut self, _enabled: bool) -> Result<(), scpi::Error> {
+        Ok(())
+    }
+}
+
+fn main() {}
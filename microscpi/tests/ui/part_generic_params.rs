@@ -0,0 +1,23 @@
+use microscpi as scpi;
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Interface<T> {
+    value: T,
+}
+
+mod source {
+    use microscpi as scpi;
+
+    #[scpi::interface_part(Source, prefix = "SOURce")]
+    impl<U: Copy> super::Interface<U> {
+        #[scpi(cmd = "VALue?")]
+        fn value(&mut self) -> U {
+            self.value
+        }
+    }
+}
+
+#[scpi::interface(parts(source::Source))]
+impl<T: Copy> Interface<T> {}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use microscpi as scpi;
+
+pub struct Settings {
+    pub limit: f64,
+}
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Interface;
+
+#[scpi::interface(context = Settings)]
+impl Interface {
+    #[scpi(cmd = "MEASure?", timeout_ms = 500)]
+    async fn measure(&mut self) -> Result<f64, scpi::Error> {
+        Ok(1.0)
+    }
+
+    #[scpi(cmd = "SOURce:VOLTage?")]
+    fn voltage(&mut self) -> f64 {
+        5.0
+    }
+}
+
+fn main() {
+    let mut interface = Interface;
+    let _ = interface.voltage();
+    let _ = interface.measure();
+    let _: <Interface as scpi::Interface>::Context = Settings { limit: 1.0 };
+}
@@ -0,0 +1,32 @@
+use microscpi as scpi;
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Channel;
+
+#[scpi::interface]
+impl Channel {
+    #[scpi(cmd = "VOLTage?")]
+    fn voltage(&mut self) -> Result<f64, scpi::Error> {
+        Ok(1.0)
+    }
+}
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Instrument {
+    channel: Channel,
+}
+
+#[scpi::interface]
+impl Instrument {
+    #[scpi(subtree = "CHANnel")]
+    fn channel(&mut self) -> &mut Channel {
+        &mut self.channel
+    }
+
+    #[scpi(cmd = "CHANnel:VOLTage:RANGe")]
+    fn set_range(&mut self, _range: f64) -> Result<(), scpi::Error> {
+        Ok(())
+    }
+}
+
+fn main() {}
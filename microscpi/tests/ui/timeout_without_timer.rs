@@ -0,0 +1,14 @@
+use microscpi as scpi;
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Interface;
+
+#[scpi::interface]
+impl Interface {
+    #[scpi(cmd = "MEASure?", timeout_ms = 500)]
+    async fn measure(&mut self) -> Result<f64, scpi::Error> {
+        Ok(1.0)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use microscpi as scpi;
+
+#[derive(scpi::IgnoreErrors)]
+pub struct Interface;
+
+#[scpi::interface]
+impl Interface {
+    #[scpi(cmd = "CONFigure")]
+    #[allow(clippy::too_many_arguments)]
+    fn configure(
+        &mut self, _a: u8, _b: u8, _c: u8, _d: u8, _e: u8, _f: u8, _g: u8, _h: u8, _i: u8, _j: u8,
+        _k: u8, _l: u8,
+    ) -> Result<(), scpi::Error> {
+        Ok(())
+    }
+}
+
+fn main() {}